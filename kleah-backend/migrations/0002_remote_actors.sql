@@ -0,0 +1,6 @@
+alter table actors drop constraint actors_pkey;
+alter table actors add primary key (username, host);
+alter table actors add column ap_id text;
+alter table actors add column key_id text;
+alter table actors add column shared_inbox text;
+create index actors_key_id_idx on actors(key_id);
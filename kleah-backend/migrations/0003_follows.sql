@@ -0,0 +1,17 @@
+alter table users add column is_locked boolean not null default false;
+
+create table follows(
+    follow_id text not null primary key,
+    follower_username text not null,
+    follower_host text not null,
+    followee_username text not null,
+    followee_host text not null,
+    accepted boolean not null,
+    created_at timestamptz not null default now(),
+    unique (follower_username, follower_host, followee_username, followee_host)
+);
+
+create index follows_followee_idx on follows(followee_username, followee_host, created_at, follow_id);
+create index follows_follower_idx on follows(follower_username, follower_host, created_at, follow_id);
+
+create index actors_ap_id_idx on actors(ap_id);
@@ -0,0 +1,15 @@
+create table deliveries(
+    delivery_id text not null primary key,
+    activity_id text not null,
+    sender_username text not null,
+    inbox text not null,
+    payload text not null,
+    attempts integer not null default 0,
+    next_attempt_at timestamptz not null default now(),
+    last_error text,
+    failed_at timestamptz,
+    created_at timestamptz not null default now(),
+    unique (activity_id, inbox)
+);
+
+create index deliveries_due_idx on deliveries(next_attempt_at) where failed_at is null;
@@ -0,0 +1,3 @@
+alter table users add column last_active timestamptz;
+
+create index users_last_active_idx on users(last_active);
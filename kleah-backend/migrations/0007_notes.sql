@@ -0,0 +1,14 @@
+create table notes(
+    note_id text not null primary key,
+    ap_id text unique,
+    author_username text not null,
+    author_host text not null,
+    content text not null,
+    in_reply_to text,
+    visibility text not null,
+    created_at timestamptz not null default now(),
+    updated_at timestamptz
+);
+
+create index notes_author_idx on notes(author_username, author_host, created_at, note_id);
+create index notes_in_reply_to_idx on notes(in_reply_to);
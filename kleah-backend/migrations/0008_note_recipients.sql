@@ -0,0 +1,8 @@
+create table note_recipients(
+    note_id text not null references notes(note_id) on delete cascade,
+    actor_username text not null,
+    actor_host text not null,
+    primary key (note_id, actor_username, actor_host)
+);
+
+create index note_recipients_actor_idx on note_recipients(actor_username, actor_host);
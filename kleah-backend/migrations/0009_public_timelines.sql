@@ -0,0 +1,5 @@
+alter table instance_information add column local_timeline text not null default 'public';
+alter table instance_information add column federated_timeline text not null default 'public';
+
+create index notes_public_idx on notes(created_at, note_id) where visibility = 'public';
+create index notes_public_host_idx on notes(author_host, created_at, note_id) where visibility = 'public';
@@ -0,0 +1,11 @@
+create table notifications(
+    notification_id text not null primary key,
+    username text not null,
+    kind text not null,
+    actor_username text not null,
+    actor_host text not null,
+    note_id text references notes(note_id) on delete cascade,
+    created_at timestamptz not null default now()
+);
+
+create index notifications_username_idx on notifications(username, created_at, notification_id);
@@ -0,0 +1,12 @@
+alter table notes add column like_count bigint not null default 0;
+
+create table likes(
+    like_id text not null primary key,
+    note_id text not null references notes(note_id) on delete cascade,
+    actor_username text not null,
+    actor_host text not null,
+    created_at timestamptz not null default now(),
+    unique (note_id, actor_username, actor_host)
+);
+
+create index likes_actor_idx on likes(actor_username, actor_host, created_at, like_id);
@@ -0,0 +1,12 @@
+alter table notes add column boost_count bigint not null default 0;
+
+create table boosts(
+    boost_id text not null primary key,
+    note_id text not null references notes(note_id) on delete cascade,
+    actor_username text not null,
+    actor_host text not null,
+    created_at timestamptz not null default now(),
+    unique (note_id, actor_username, actor_host)
+);
+
+create index boosts_actor_idx on boosts(actor_username, actor_host, created_at, boost_id);
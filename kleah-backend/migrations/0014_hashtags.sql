@@ -0,0 +1,8 @@
+create table hashtags(
+    note_id text not null references notes(note_id) on delete cascade,
+    name text not null,
+    created_at timestamptz not null,
+    primary key (note_id, name)
+);
+
+create index hashtags_name_idx on hashtags(name, created_at, note_id);
@@ -0,0 +1,10 @@
+create table note_edits(
+    edit_id text not null primary key,
+    note_id text not null references notes(note_id) on delete cascade,
+    content text not null,
+    summary text,
+    sensitive boolean not null default false,
+    created_at timestamptz not null
+);
+
+create index note_edits_note_idx on note_edits(note_id, created_at);
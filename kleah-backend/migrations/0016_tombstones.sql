@@ -0,0 +1,4 @@
+create table tombstones(
+    note_id text not null primary key,
+    deleted_at timestamptz not null default now()
+);
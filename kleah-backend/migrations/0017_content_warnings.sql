@@ -0,0 +1,3 @@
+alter table notes add column summary text;
+alter table notes add column sensitive boolean not null default false;
+alter table users add column expand_spoilers boolean not null default false;
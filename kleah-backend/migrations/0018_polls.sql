@@ -0,0 +1,31 @@
+alter table notes add column has_poll boolean not null default false;
+
+create table polls(
+    note_id text not null primary key references notes(note_id) on delete cascade,
+    multiple boolean not null default false,
+    expires_at timestamptz not null,
+    closed_at timestamptz,
+    voters_count bigint not null default 0
+);
+
+create index polls_open_idx on polls(expires_at) where closed_at is null;
+
+create table poll_options(
+    note_id text not null references polls(note_id) on delete cascade,
+    position integer not null,
+    title text not null,
+    votes_count bigint not null default 0,
+    primary key (note_id, position)
+);
+
+create table poll_votes(
+    vote_id text not null primary key,
+    note_id text not null references polls(note_id) on delete cascade,
+    position integer not null,
+    actor_username text not null,
+    actor_host text not null,
+    created_at timestamptz not null default now(),
+    unique (note_id, position, actor_username, actor_host)
+);
+
+create index poll_votes_actor_idx on poll_votes(note_id, actor_username, actor_host);
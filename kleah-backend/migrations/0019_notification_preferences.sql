@@ -0,0 +1,9 @@
+alter table notifications add column is_read boolean not null default false;
+
+create index notifications_unread_idx on notifications(username) where not is_read;
+
+create table notification_mutes(
+    username text not null references users(username) on delete cascade,
+    kind text not null,
+    primary key (username, kind)
+);
@@ -0,0 +1,12 @@
+create table reports(
+    report_id text not null primary key,
+    reporter_username text not null,
+    reporter_host text not null,
+    target_username text not null,
+    target_host text not null,
+    note_id text references notes(note_id) on delete set null,
+    comment text not null default '',
+    created_at timestamptz not null default now()
+);
+
+alter table notifications add column report_id text references reports(report_id) on delete cascade;
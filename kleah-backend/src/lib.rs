@@ -46,3 +46,90 @@ pub use modules::models::*;
 /// for accepting JSON
 /// payloads.
 pub use modules::payloads::*;
+
+/// Re-exporting the module
+/// containing structures
+/// modelling ActivityPub
+/// objects.
+pub use modules::apub::*;
+
+/// Re-exporting the module
+/// containing functions
+/// for signing and verifying
+/// HTTP requests.
+pub use modules::signatures::*;
+
+/// Re-exporting the module
+/// containing functions
+/// for communicating with
+/// other ActivityPub servers.
+pub use modules::federation::*;
+
+/// Re-exporting the module
+/// containing functions
+/// for queueing and sending
+/// activities to other servers.
+pub use modules::queue::*;
+
+/// Re-exporting the module
+/// containing functions
+/// for describing this
+/// instance with NodeInfo.
+pub use modules::nodeinfo::*;
+
+/// Re-exporting the module
+/// containing functions
+/// for publishing and 
+/// managing notes.
+pub use modules::notes::*;
+
+/// Re-exporting the module
+/// containing functions
+/// for reading timelines.
+pub use modules::timelines::*;
+
+/// Re-exporting the module
+/// containing functions
+/// for notifying local
+/// users.
+pub use modules::notifications::*;
+
+/// Re-exporting the module
+/// containing functions
+/// for liking notes.
+pub use modules::likes::*;
+
+/// Re-exporting the module
+/// containing functions
+/// for boosting notes.
+pub use modules::boosts::*;
+
+/// Re-exporting the module
+/// containing functions
+/// for threads of notes.
+pub use modules::threads::*;
+
+/// Re-exporting the module
+/// containing functions
+/// for mentioning actors.
+pub use modules::mentions::*;
+
+/// Re-exporting the module
+/// containing functions
+/// for hashtags.
+pub use modules::hashtags::*;
+
+/// Re-exporting the module
+/// containing functions
+/// for editing notes.
+pub use modules::edits::*;
+
+/// Re-exporting the module
+/// containing functions
+/// for polls.
+pub use modules::polls::*;
+
+/// Re-exporting the module
+/// containing functions
+/// for reports.
+pub use modules::reports::*;
@@ -7,11 +7,21 @@ Licensed under the FSL v1.
 /// to verify a hashed string.
 use bcrypt::verify;
 
+/// Importing the function
+/// macro for making "GET"
+/// requests.
+use actix_web::get;
+
 /// Importing the function
 /// macro for making "POST"
 /// requests.
 use actix_web::post;
 
+/// Importing the structure
+/// representing an arbitrary
+/// JSON value.
+use serde_json::Value;
+
 /// Importing the structure
 /// for catching and handling
 /// errors.
@@ -27,12 +37,27 @@ use actix_web::web::Json;
 /// state of data at app runtime.
 use actix_web::web::Data;
 
+/// Importing the structure to
+/// accept path segments as an
+/// argument for a service function.
+use actix_web::web::Path;
+
 /// Importing the data structure
 /// containing the app-wide
 /// pool of connections to the
 /// database.
 use super::units::AppData;
 
+/// Importing the structure to
+/// accept query strings as an
+/// argument for a service function.
+use actix_web::web::Query;
+
+/// Importing the structure
+/// modelling an ActivityPub
+/// note.
+use super::apub::ApubNote;
+
 /// Importing the function
 /// to check whether a user
 /// exists or not.
@@ -44,6 +69,30 @@ use super::db::user_exists;
 /// actor.
 use super::db::update_name;
 
+/// Importing the function to
+/// build the base URL of an
+/// instance from its host.
+use super::utils::base_url;
+
+/// Importing the structure
+/// modelling an ActivityPub
+/// actor document.
+use super::apub::ApubActor;
+
+/// Importing the function
+/// to count the notes an
+/// actor has liked.
+use super::db::count_liked;
+
+/// Importing the function
+/// to build the URL of a note.
+use super::utils::note_url;
+
+/// Importing the structure
+/// representing an HTTP
+/// request.
+use actix_web::HttpRequest;
+
 /// Importing the function to
 /// update the email of a
 /// Kleah user.
@@ -54,6 +103,19 @@ use super::db::update_email;
 /// as HTTP responses.
 use actix_web::HttpResponse;
 
+/// Importing the function
+/// to like a note.
+use super::likes::like_note;
+
+/// Importing the function
+/// to edit a note.
+use super::edits::edit_note;
+
+/// Importing the function
+/// to read the poll of
+/// a note.
+use super::polls::read_poll;
+
 /// Importing the data structure
 /// modelling data about a Kleah user
 /// in the database.
@@ -64,6 +126,25 @@ use super::models::KleahUser;
 /// the database.
 use super::db::destroy_token;
 
+/// Importing the enumeration
+/// describing the types of
+/// errors that can occur.
+use super::err::KleahErrType;
+
+/// Importing the function to
+/// change whether follow requests
+/// for a user are approved manually.
+use super::db::update_locked;
+
+/// Importing the data structure
+/// modelling a note in
+/// the database.
+use super::models::KleahNote;
+
+/// Importing the structure
+/// modelling a poll.
+use super::models::KleahPoll;
+
 /// Importing the data structure
 /// modelling data about an ActivityPub
 /// actor on a Kleah instance in the 
@@ -86,6 +167,29 @@ use super::db::get_user_by_id;
 /// valid email address.
 use super::utils::check_email;
 
+/// Importing the function
+/// to delete a note.
+use super::notes::remove_note;
+
+/// Importing the structure
+/// modelling an ActivityPub
+/// activity.
+use super::apub::ApubActivity;
+
+/// Importing the function
+/// to retrieve a page of
+/// the notes an actor has liked.
+use super::db::get_liked_page;
+
+/// Importing the function
+/// to take back the like
+/// of a note.
+use super::likes::unlike_note;
+
+/// Importing the function
+/// to boost a note.
+use super::boosts::boost_note;
+
 /// Importing the function to
 /// create a record for a new
 /// Kleah user in the database.
@@ -96,6 +200,64 @@ use super::db::create_new_user;
 /// Kleah user.
 use super::db::update_password;
 
+/// Importing the function to
+/// retrieve the record of an
+/// actor in the database given
+/// their username and host.
+use super::db::get_actor_by_id;
+
+/// Importing the structure
+/// modelling the public key
+/// of an ActivityPub actor.
+use super::apub::ApubPublicKey;
+
+/// Importing the media type
+/// for ActivityPub documents.
+use super::apub::ACTIVITY_JSON;
+
+/// Importing the data structure
+/// modelling a follow relationship
+/// in the database.
+use super::models::KleahFollow;
+
+/// Importing the function
+/// to count the followers
+/// of an actor.
+use super::db::count_followers;
+
+/// Importing the function
+/// to count the actors
+/// an actor follows.
+use super::db::count_following;
+
+/// Importing the function
+/// to publish a new note.
+use super::notes::publish_note;
+
+/// Importing the structure
+/// containing data about the
+/// tombstone of a deleted note.
+use super::apub::ApubTombstone;
+
+/// Importing the function
+/// to vote in the poll
+/// of a note.
+use super::polls::vote_in_poll;
+
+/// Importing the structure
+/// modelling a report.
+use super::models::KleahReport;
+
+/// Importing the function
+/// to read a cursor from
+/// a query string.
+use super::utils::parse_cursor;
+
+/// Importing the function
+/// to read the API token
+/// of a request.
+use super::utils::bearer_token;
+
 /// Importing the function to
 /// create a record for a new
 /// Kleah ActivityPub actor
@@ -107,16 +269,30 @@ use super::db::create_new_actor;
 /// for a Kleah user in the database.
 use super::db::create_api_token;
 
-/// Importing the function to retrieve
-/// the record of a created invite code
-/// from the database.
-use super::db::get_code_by_code;
-
 /// Importing the data structure
 /// modelling a user's API token
 /// in the database.
 use super::models::UserAPIToken;
 
+/// Importing the function
+/// to take back the boost
+/// of a note.
+use super::boosts::unboost_note;
+
+/// Importing the function
+/// to retrieve the poll of
+/// a note.
+use super::polls::get_note_poll;
+
+/// Importing the function
+/// to read a report.
+use super::reports::read_report;
+
+/// Importing the function
+/// to build the cursor
+/// pointing at an item.
+use super::utils::format_cursor;
+
 /// Importing the function for
 /// retrieving information about
 /// the current Kleah instance.
@@ -137,6 +313,20 @@ use super::utils::check_username;
 /// valid password.
 use super::utils::check_password;
 
+/// Importing the extractor for
+/// activities whose signatures
+/// have been verified.
+use super::units::SignedActivity;
+
+/// Importing the function
+/// to build the URL of
+/// a hashtag.
+use super::hashtags::hashtag_url;
+
+/// Importing the function
+/// to report an actor.
+use super::reports::report_actor;
+
 /// Importing the function to create a new
 /// record for a new invite code in the
 /// database.
@@ -159,15 +349,166 @@ use super::db::update_description;
 /// use invite codes or not.
 use super::db::edit_invite_system;
 
+/// Importing the URI of the
+/// JSON-LD context for public
+/// keys.
+use super::apub::SECURITY_CONTEXT;
+
+/// Importing the function to build
+/// the URL of a local actor.
+use super::utils::local_actor_url;
+
+/// Importing the function
+/// to retrieve a page of
+/// the followers of an actor.
+use super::db::get_followers_page;
+
+/// Importing the function
+/// to retrieve a page of
+/// the actors an actor follows.
+use super::db::get_following_page;
+
+/// Importing the data structure
+/// modelling a single item of an
+/// ActivityPub collection.
+use super::models::CollectionItem;
+
+/// Importing the function
+/// to count the items in
+/// the outbox of an actor.
+use super::db::count_outbox_items;
+
 /// Importing the enumeration describing
 /// the types of Kleah users that can
 /// exist.
 use super::payloads::KleahUserType;
 
-/// Importing the function to remove
-/// the record of an invite code from
-/// the database.
-use super::db::destroy_invite_code;
+/// Importing the function to use
+/// up the record of an invite code
+/// in the database.
+use super::db::consume_invite_code;
+
+/// Importing the structure representing
+/// a JSON payload containing data to
+/// follow or unfollow an actor.
+use super::payloads::FollowPayload;
+
+/// Importing the function
+/// to build the document
+/// describing a collection.
+use super::utils::build_collection;
+
+/// Importing the structure
+/// modelling a link to a
+/// NodeInfo document.
+use super::responses::NodeInfoLink;
+
+/// Importing the function
+/// to read a note a user
+/// is allowed to see.
+use super::notes::get_visible_note;
+
+/// Importing the structure
+/// to send data about a note.
+use super::responses::NoteResponse;
+
+/// Importing the structure
+/// modelling the query string
+/// for reading a timeline.
+use super::payloads::TimelineQuery;
+
+/// Importing the function
+/// to check the name
+/// of a hashtag.
+use super::hashtags::check_hashtag;
+
+/// Importing the structure
+/// modelling an option of
+/// a poll.
+use super::models::KleahPollOption;
+
+/// Importing the structure
+/// containing a poll.
+use super::responses::PollResponse;
+
+/// Importing the structure
+/// modelling the payload for
+/// reporting an actor.
+use super::payloads::ReportPayload;
+
+/// Importing the function
+/// to serve a paginated
+/// collection of an actor.
+use super::utils::serve_collection;
+
+/// Importing the function
+/// to build the items
+/// of a collection page.
+use super::utils::collection_items;
+
+/// Importing the structure representing
+/// the query string of a WebFinger
+/// lookup.
+use super::payloads::WebFingerQuery;
+
+/// Importing the structure for serializing
+/// a single link in a WebFinger response
+/// into a JSON string.
+use super::responses::WebFingerLink;
+
+/// Importing the function to
+/// make a user follow an actor.
+use super::federation::follow_actor;
+
+/// Importing the structure
+/// modelling a single page of
+/// an ActivityPub collection.
+use super::apub::ApubCollectionPage;
+
+/// Importing the structure
+/// modelling the links to the
+/// NodeInfo documents.
+use super::responses::NodeInfoLinks;
+
+/// Importing the structure
+/// modelling the usage of
+/// this instance.
+use super::responses::NodeInfoUsage;
+
+/// Importing the function
+/// to retrieve the usage
+/// statistics of this instance.
+use super::nodeinfo::get_node_usage;
+
+/// Importing the function
+/// to build the NodeInfo
+/// document of this instance.
+use super::nodeinfo::build_nodeinfo;
+
+/// Importing the function
+/// to build the ActivityPub
+/// object of a note.
+use super::notes::build_note_object;
+
+/// Importing the function
+/// to edit who may read
+/// the public timelines.
+use super::db::edit_timeline_access;
+
+/// Importing the function
+/// to count the public
+/// replies to a note.
+use super::db::count_public_replies;
+
+/// Importing the function
+/// to read the revisions
+/// of a note.
+use super::edits::read_note_history;
+
+/// Importing the function
+/// to read a page of
+/// the outbox of an actor.
+use super::notes::read_outbox_items;
 
 /// Importing the structure for serializing 
 /// a Rust data structure containing data
@@ -176,6 +517,74 @@ use super::db::destroy_invite_code;
 /// a JSON string.
 use super::responses::StatusResponse;
 
+/// Importing the structure for serializing
+/// data on a created follow relationship
+/// into a JSON string.
+use super::responses::FollowResponse;
+
+/// Importing the structure
+/// modelling the query string
+/// for collection pages.
+use super::payloads::CollectionQuery;
+
+/// Importing the URI of
+/// the NodeInfo schema.
+use super::nodeinfo::NODEINFO_SCHEMA;
+
+/// Importing the structure
+/// modelling the payload for
+/// editing a note.
+use super::payloads::NoteEditPayload;
+
+/// Importing the function
+/// to retrieve the tombstone
+/// of a deleted note.
+use super::notes::get_note_tombstone;
+
+/// Importing the structure
+/// modelling the payload for
+/// voting in a poll.
+use super::payloads::PollVotePayload;
+
+/// Importing the structure
+/// containing a report.
+use super::responses::ReportResponse;
+
+/// Importing the function
+/// to check whether an instance
+/// accepts new users.
+use super::utils::registrations_open;
+
+/// Importing the function to make
+/// a user stop following an actor.
+use super::federation::unfollow_actor;
+
+/// Importing the number of
+/// items on a single page
+/// of a collection.
+use super::apub::COLLECTION_PAGE_SIZE;
+
+/// Importing the function
+/// to build the response
+/// describing a note.
+use super::notes::build_note_response;
+
+/// Importing the function
+/// to read the thread
+/// around a note.
+use super::threads::read_note_context;
+
+/// Importing the function
+/// to retrieve a page of
+/// the local notes with
+/// a hashtag.
+use super::db::get_local_hashtag_page;
+
+/// Importing the function
+/// to edit whether notes with a
+/// content warning are expanded.
+use super::db::update_expand_spoilers;
+
 /// Importing the data structure
 /// modelling data about the current
 /// Kleah instance in the database.
@@ -192,11 +601,83 @@ use super::payloads::UserCreatePayload;
 /// in the database.
 use super::payloads::UserChangePayload;
 
+/// Importing the function to build
+/// the ActivityPub document of a
+/// local actor.
+use super::utils::build_actor_document;
+
+/// Importing the structure representing
+/// a JSON payload containing data to
+/// reflect whether follow requests have
+/// to be approved manually.
+use super::payloads::EditLockedPayload;
+
+/// Importing the structure
+/// modelling the NodeInfo
+/// document of this instance.
+use super::responses::NodeInfoResponse;
+
+/// Importing the structure
+/// modelling the payload for
+/// publishing notes.
+use super::payloads::NoteCreatePayload;
+
+/// Importing the structure
+/// modelling the payload for
+/// deleting notes.
+use super::payloads::NoteDeletePayload;
+
+/// Importing the structure
+/// containing a page of a
+/// timeline.
+use super::responses::TimelineResponse;
+
+/// Importing the function
+/// to retrieve a page of
+/// the public replies to
+/// a note.
+use super::db::get_public_replies_page;
+
+/// Importing the function
+/// to normalize the name
+/// of a hashtag.
+use super::hashtags::normalize_hashtag;
+
+/// Importing the structure
+/// modelling the query string
+/// for reading notifications.
+use super::payloads::NotificationQuery;
+
 /// Importing the structure representing
 /// a JSON payload containing data for 
 /// creating a new API token for a Kleah user.
 use super::payloads::CreateTokenPayload;
 
+/// Importing the structure for serializing
+/// a Rust data structure containing data
+/// on a Kleah actor in the JRD format
+/// into a JSON string.
+use super::responses::WebFingerResponse;
+
+/// Importing the function to process
+/// activities received in inboxes.
+use super::federation::process_activity;
+
+/// Importing the function
+/// to build the document
+/// for a page of a collection.
+use super::utils::build_collection_page;
+
+/// Importing the function
+/// to build the activity
+/// publishing a note.
+use super::notes::build_create_activity;
+
+/// Importing the function
+/// to retrieve a local note
+/// that may be served publicly.
+use super::notes::get_public_local_note;
+
 /// Importing the structure for serializing 
 /// a Rust data structure containing data
 /// on a created Kleah user and actor into
@@ -208,18 +689,94 @@ use super::responses::UserCreateResponse;
 /// a new invite code.
 use super::payloads::InviteCreatePayload;
 
+/// Importing the function to split
+/// a WebFinger resource into a username
+/// and a host.
+use super::utils::parse_account_resource;
+
+/// Importing the URI of the
+/// JSON-LD context for
+/// ActivityStreams documents.
+use super::apub::ACTIVITYSTREAMS_CONTEXT;
+
+/// Importing the function
+/// to read a page of a
+/// home timeline.
+use super::timelines::read_home_timeline;
+
+/// Importing the function
+/// to count the local notes
+/// with a hashtag.
+use super::db::count_local_hashtag_notes;
+
+/// Importing the structure
+/// modelling the payload for
+/// editing whether notes with a
+/// content warning are expanded.
+use super::payloads::EditSpoilersPayload;
+
+/// Importing the function
+/// to find out whether a reader
+/// expands content warnings.
+use super::notes::get_spoiler_preference;
+
 /// Importing the structure for serializing 
 /// a Rust data structure containing data
 /// on a created API token for a Kleah user
 /// into a JSON string.
 use super::responses::CreateTokenResponse;
 
+/// Importing the structure representing
+/// a JSON payload containing data to
+/// answer a follow request.
+use super::payloads::FollowRequestPayload;
+
+/// Importing the function
+/// to read a page of the
+/// local timeline.
+use super::timelines::read_local_timeline;
+
+/// Importing the structure
+/// containing the thread
+/// around a note.
+use super::responses::NoteContextResponse;
+
+/// Importing the structure
+/// containing all revisions
+/// of a note.
+use super::responses::NoteHistoryResponse;
+
+/// Importing the function
+/// to describe a report for
+/// the API.
+use super::reports::build_report_response;
+
 /// Importing the structure for serializing 
 /// a Rust data structure containing data
 /// on a created invite code into a JSON
 /// string.
 use super::responses::InviteCreateResponse;
 
+/// Importing the function
+/// to read the timeline
+/// of a hashtag.
+use super::hashtags::read_hashtag_timeline;
+
+/// Importing the function
+/// to check a level of access
+/// to a timeline.
+use super::timelines::check_timeline_access;
+
+/// Importing the structure
+/// modelling the payload for
+/// interacting with a note.
+use super::payloads::NoteInteractionPayload;
+
+/// Importing the structure
+/// containing a page of
+/// notifications.
+use super::responses::NotificationsResponse;
+
 /// Importing the data structure representing
 /// a JSON payload containing data to make a
 /// signifcant change to the record(s) of a user
@@ -232,6 +789,69 @@ use super::payloads::SecureUserChangePayload;
 /// or not.
 use super::payloads::EditInviteSystemPayload;
 
+/// Importing the function to approve
+/// or deny follow requests.
+use super::federation::answer_follow_request;
+
+/// Importing the structure
+/// modelling the payload for
+/// marking notifications as read.
+use super::payloads::NotificationReadPayload;
+
+/// Importing the structure
+/// modelling the payload for
+/// muting notifications.
+use super::payloads::NotificationMutePayload;
+
+/// Importing the function
+/// to read the notifications
+/// of a user.
+use super::notifications::read_notifications;
+
+/// Importing the function
+/// to mute a kind of
+/// notifications.
+use super::notifications::mute_notifications;
+
+/// Importing the function
+/// to read a page of the
+/// federated timeline.
+use super::timelines::read_federated_timeline;
+
+/// Importing the function
+/// to send profile changes
+/// to followers on other servers.
+use super::federation::federate_profile_update;
+
+/// Importing the structure
+/// modelling the payload for
+/// editing timeline access.
+use super::payloads::EditTimelineAccessPayload;
+
+/// Importing the function
+/// to dismiss a notification.
+use super::notifications::dismiss_notification;
+
+/// Importing the structure
+/// modelling the payload for
+/// dismissing a notification.
+use super::payloads::NotificationDismissPayload;
+
+/// Importing the structure
+/// containing the muted kinds
+/// of notifications.
+use super::responses::NotificationMutesResponse;
+
+/// Importing the function
+/// to mark notifications
+/// as read.
+use super::notifications::mark_notifications_read;
+
+/// Importing the function
+/// to read the muted kinds
+/// of notifications.
+use super::notifications::read_notification_mutes;
+
 /// A service function that accepts a JSON
 /// payload for creating a new Kleah user
 /// and a new Kleah ActivityPub actor. If both
@@ -250,20 +870,6 @@ pub async fn create_user_service(
        check_email(&payload.email_addr) &&
        !u_e
     {
-        let kleah_user: KleahUser = match create_new_user(
-            &payload.name,
-            &payload.password,
-            &payload.username,
-            &payload.email_addr,
-            &payload.description,
-            &false,
-            &data.pool
-        ).await {
-            Ok(kleah_user) => kleah_user,
-            Err(e) => return Err::<HttpResponse, KleahErr>(
-                KleahErr::new(&e.to_string())
-            )
-        };
         let instance: InstanceInformation = match get_instance_info(
             &data.pool
         ).await{
@@ -272,35 +878,50 @@ pub async fn create_user_service(
                 KleahErr::new(&e.to_string())
             )
         };
-        if !instance.uses_invites{
+        let invite_code: Option<InviteCode> = if registrations_open(&instance){
+            None
+        }
+        else {
             match &payload.invite_code{
-                Some(code) => {
-                    let invite_exists: bool = get_code_by_code(
-                        code, 
-                        &data.pool
-                    ).await.is_ok();
-                    if !invite_exists{
-                        return Err::<HttpResponse, KleahErr>(
-                            KleahErr::new("The supplied invite code is not valid.")
-                        )
-                    }
-                    else {
-                        let _del_op: () = match destroy_invite_code(
-                            &code, 
-                            &data.pool
-                        ).await {
-                            Ok(_f) => {},
-                            Err(e) => return Err::<HttpResponse, KleahErr>(
-                                KleahErr::new(&e.to_string())
-                            )
-                        };
-                    }
+                Some(code) => match consume_invite_code(
+                    code, 
+                    &data.pool
+                ).await {
+                    Ok(Some(invite_code)) => Some(invite_code),
+                    Ok(None) => return Err::<HttpResponse, KleahErr>(
+                        KleahErr::new("The supplied invite code is not valid.")
+                    ),
+                    Err(e) => return Err::<HttpResponse, KleahErr>(
+                        KleahErr::new(&e.to_string())
+                    )
                 },
                 None => return Err::<HttpResponse, KleahErr>(
                     KleahErr::new("An invite code is required for this instance.")
                 )
-            };
-        }
+            }
+        };
+        let kleah_user: KleahUser = match create_new_user(
+            &payload.name,
+            &payload.password,
+            &payload.username,
+            &payload.email_addr,
+            &payload.description,
+            &false,
+            &data.pool
+        ).await {
+            Ok(kleah_user) => kleah_user,
+            Err(e) => {
+                if let Some(invite_code) = invite_code{
+                    let _restored = create_invite_code(
+                        &invite_code.code,
+                        &data.pool
+                    ).await;
+                }
+                return Err::<HttpResponse, KleahErr>(
+                    KleahErr::new(&e.to_string())
+                )
+            }
+        };
 
         let actor_type: String = match payload.user_type{
             KleahUserType::Person => "Person".to_string(),
@@ -347,7 +968,8 @@ pub async fn create_user_service(
 
 /// This function is the service function
 /// so that users can edit their profile display name
-/// for their account. If the operation is successful,
+/// for their account. Followers on other servers are
+/// sent the updated profile. If the operation is successful,
 /// an HTTP response is returned containing the status
 /// of the operation. If the operation is unsuccessful,
 /// an error is returned.
@@ -373,6 +995,12 @@ pub async fn update_name_service(
         Ok(_f) => true,
         Err(_e) => false
     };
+    if status {
+        let _federated: Result<(), KleahErr> = federate_profile_update(
+            &user.username,
+            &data.pool
+        ).await;
+    }
     let resp: StatusResponse = StatusResponse{
         status: status
     };
@@ -381,7 +1009,8 @@ pub async fn update_name_service(
 
 /// This function is the service function
 /// so that users can edit their profile description
-/// for their account. If the operation is successful,
+/// for their account. Followers on other servers are
+/// sent the updated profile. If the operation is successful,
 /// an HTTP response is returned containing the status
 /// of the operation. If the operation is unsuccessful,
 /// an error is returned.
@@ -407,6 +1036,12 @@ pub async fn update_description_service(
         Ok(_f) => true,
         Err(_e) => false
     };
+    if status {
+        let _federated: Result<(), KleahErr> = federate_profile_update(
+            &user.username,
+            &data.pool
+        ).await;
+    }
     let resp: StatusResponse = StatusResponse{
         status: status
     };
@@ -702,3 +1337,1547 @@ pub async fn edit_invite_system_service(
     }
 }
 
+/// This function is the service function
+/// so that administrators can edit who may read
+/// the local and the federated timeline. Each 
+/// timeline can be open to everyone, only to
+/// logged-in users, or disabled. If the operation 
+/// is successful, an HTTP response is returned 
+/// containing the status of the operation. If the 
+/// operation is unsuccessful, an error is returned.
+#[post("/api/admin/edit/timelines")]
+pub async fn edit_timeline_access_service(
+    payload: Json<EditTimelineAccessPayload>,
+    data: Data<AppData>
+) -> Result<HttpResponse, KleahErr>{
+    if !check_timeline_access(&payload.local_timeline) || 
+        !check_timeline_access(&payload.federated_timeline)
+    {
+        return Err::<HttpResponse, KleahErr>(
+            KleahErr::with_type(
+                "The requested timeline access is not supported.",
+                KleahErrType::BadRequest
+            )
+        );
+    }
+    let user: KleahUser = match get_user_by_token(
+        &payload.api_token,
+        &data.pool
+    ).await {
+        Ok(user) => user,
+        Err(e) => return Err::<HttpResponse, KleahErr>(
+            KleahErr::with_type(&e.to_string(), KleahErrType::Unauthorized)
+        )
+    };
+    if user.is_admin {
+        let status: bool = edit_timeline_access(
+            &payload.local_timeline,
+            &payload.federated_timeline,
+            &data.pool
+        ).await.is_ok();
+        let resp: StatusResponse = StatusResponse{
+            status
+        };
+        Ok(HttpResponse::Ok().json(resp))
+    }
+    else{
+        Err::<HttpResponse, KleahErr>(
+            KleahErr::with_type(
+                "The requesting user is not an administrator.",
+                KleahErrType::Unauthorized
+            )
+        )
+    }
+}
+
+/// This function is the service function
+/// for WebFinger lookups of Kleah actors on
+/// the current Kleah instance. Resources of
+/// the form `acct:username@host` are resolved
+/// and a JRD document pointing to the actor
+/// is returned. If the host is not the host of 
+/// the current instance or the actor does not
+/// exist, an error is returned.
+#[get("/.well-known/webfinger")]
+pub async fn webfinger_service(
+    query: Query<WebFingerQuery>,
+    data: Data<AppData>
+) -> Result<HttpResponse, KleahErr>{
+    let (username, host): (String, String) = match parse_account_resource(
+        &query.resource
+    ){
+        Some(account) => account,
+        None => return Err::<HttpResponse, KleahErr>(
+            KleahErr::with_type(
+                "The supplied resource is of the wrong format.",
+                KleahErrType::BadRequest
+            )
+        )
+    };
+    let instance: InstanceInformation = match get_instance_info(
+        &data.pool
+    ).await{
+        Ok(instance) => instance,
+        Err(e) => return Err::<HttpResponse, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    if host != instance.host.to_lowercase(){
+        return Err::<HttpResponse, KleahErr>(
+            KleahErr::with_type(
+                "The supplied host is not served by this instance.",
+                KleahErrType::NotFound
+            )
+        )
+    }
+    let actor: KleahActor = match get_actor_by_id(
+        &username,
+        &instance.host,
+        &data.pool
+    ).await {
+        Ok(actor) => actor,
+        Err(_e) => return Err::<HttpResponse, KleahErr>(
+            KleahErr::with_type(
+                "The requested actor does not exist.",
+                KleahErrType::NotFound
+            )
+        )
+    };
+    let base: String = base_url(&instance.host);
+    let actor_url: String = local_actor_url(&instance.host, &actor.username);
+    let profile_url: String = format!("{}/@{}", &base, &actor.username);
+    let resp: WebFingerResponse = WebFingerResponse{
+        subject: format!("acct:{}@{}", &actor.username, &instance.host),
+        aliases: vec![actor_url.clone(), profile_url.clone()],
+        links: vec![
+            WebFingerLink{
+                rel: "self".to_string(),
+                link_type: "application/activity+json".to_string(),
+                href: actor_url
+            },
+            WebFingerLink{
+                rel: "http://webfinger.net/rel/profile-page".to_string(),
+                link_type: "text/html".to_string(),
+                href: profile_url
+            }
+        ]
+    };
+    Ok(
+        HttpResponse::Ok()
+            .content_type("application/jrd+json")
+            .json(resp)
+    )
+}
+
+/// This function is the service function
+/// to serve the ActivityPub actor document
+/// of a local Kleah actor to other servers.
+/// If the operation is successful, an HTTP
+/// response is returned containing the actor
+/// as a JSON-LD document. If the actor does
+/// not exist, an error is returned.
+#[get("/apub/{username}")]
+pub async fn actor_service(
+    username: Path<String>,
+    data: Data<AppData>
+) -> Result<HttpResponse, KleahErr>{
+    let instance: InstanceInformation = match get_instance_info(
+        &data.pool
+    ).await{
+        Ok(instance) => instance,
+        Err(e) => return Err::<HttpResponse, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    let actor: KleahActor = match get_actor_by_id(
+        &username,
+        &instance.host,
+        &data.pool
+    ).await {
+        Ok(actor) => actor,
+        Err(_e) => return Err::<HttpResponse, KleahErr>(
+            KleahErr::with_type(
+                "The requested actor does not exist.",
+                KleahErrType::NotFound
+            )
+        )
+    };
+    let user: KleahUser = match get_user_by_id(
+        &actor.username,
+        &data.pool
+    ).await {
+        Ok(user) => user,
+        Err(e) => return Err::<HttpResponse, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    let resp: ApubActor = build_actor_document(
+        &actor, 
+        &user, 
+        &instance.host
+    );
+    Ok(
+        HttpResponse::Ok()
+            .content_type(ACTIVITY_JSON)
+            .json(resp)
+    )
+}
+
+/// This function is the service function
+/// to serve the public key of a local Kleah
+/// actor to other servers verifying signed
+/// requests. If the operation is successful, 
+/// an HTTP response is returned containing the 
+/// key as a JSON-LD document. If the actor does
+/// not exist, an error is returned.
+#[get("/apub/{username}/pubkey")]
+pub async fn public_key_service(
+    username: Path<String>,
+    data: Data<AppData>
+) -> Result<HttpResponse, KleahErr>{
+    let instance: InstanceInformation = match get_instance_info(
+        &data.pool
+    ).await{
+        Ok(instance) => instance,
+        Err(e) => return Err::<HttpResponse, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    let actor: KleahActor = match get_actor_by_id(
+        &username,
+        &instance.host,
+        &data.pool
+    ).await {
+        Ok(actor) => actor,
+        Err(_e) => return Err::<HttpResponse, KleahErr>(
+            KleahErr::with_type(
+                "The requested actor does not exist.",
+                KleahErrType::NotFound
+            )
+        )
+    };
+    let user: KleahUser = match get_user_by_id(
+        &actor.username,
+        &data.pool
+    ).await {
+        Ok(user) => user,
+        Err(e) => return Err::<HttpResponse, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    let document: ApubActor = build_actor_document(
+        &actor, 
+        &user, 
+        &instance.host
+    );
+    let resp: ApubPublicKey = ApubPublicKey{
+        context: Some(SECURITY_CONTEXT.to_string()),
+        ..document.public_key
+    };
+    Ok(
+        HttpResponse::Ok()
+            .content_type(ACTIVITY_JSON)
+            .json(resp)
+    )
+}
+
+/// This function is the service function
+/// for the inbox of a local Kleah actor. Only
+/// activities with a verified signature are
+/// accepted. If the operation is successful,
+/// an empty HTTP response with the status
+/// "202 Accepted" is returned. If the actor 
+/// does not exist or the activity cannot be
+/// processed, an error is returned.
+#[post("/apub/{username}/inbox")]
+pub async fn inbox_service(
+    username: Path<String>,
+    signed: SignedActivity,
+    data: Data<AppData>
+) -> Result<HttpResponse, KleahErr>{
+    let instance: InstanceInformation = match get_instance_info(
+        &data.pool
+    ).await{
+        Ok(instance) => instance,
+        Err(e) => return Err::<HttpResponse, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    if get_actor_by_id(&username, &instance.host, &data.pool).await.is_err(){
+        return Err::<HttpResponse, KleahErr>(
+            KleahErr::with_type(
+                "The requested actor does not exist.",
+                KleahErrType::NotFound
+            )
+        )
+    }
+    match process_activity(
+        &signed.actor, 
+        &signed.activity, 
+        &data.pool
+    ).await {
+        Ok(_f) => Ok(HttpResponse::Accepted().finish()),
+        Err(e) => Err::<HttpResponse, KleahErr>(e)
+    }
+}
+
+/// This function is the service function
+/// for the shared inbox of the current Kleah
+/// instance. Only activities with a verified
+/// signature are accepted. If the operation is
+/// successful, an empty HTTP response with the 
+/// status "202 Accepted" is returned. If the 
+/// activity cannot be processed, an error is 
+/// returned.
+#[post("/inbox")]
+pub async fn shared_inbox_service(
+    signed: SignedActivity,
+    data: Data<AppData>
+) -> Result<HttpResponse, KleahErr>{
+    match process_activity(
+        &signed.actor, 
+        &signed.activity, 
+        &data.pool
+    ).await {
+        Ok(_f) => Ok(HttpResponse::Accepted().finish()),
+        Err(e) => Err::<HttpResponse, KleahErr>(e)
+    }
+}
+
+/// This function is the service function
+/// so that users can follow other actors.
+/// If the operation is successful, an HTTP 
+/// response is returned containing the ID
+/// of the follow and whether it was accepted
+/// already. If the operation fails, an error
+/// is returned.
+#[post("/api/user/follow")]
+pub async fn follow_service(
+    payload: Json<FollowPayload>,
+    data: Data<AppData>
+) -> Result<HttpResponse, KleahErr>{
+    let user: KleahUser = match get_user_by_token(
+        &payload.api_token,
+        &data.pool
+    ).await {
+        Ok(user) => user,
+        Err(e) => return Err::<HttpResponse, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    let follow: KleahFollow = match follow_actor(
+        &user.username,
+        &payload.target,
+        &data.pool
+    ).await {
+        Ok(follow) => follow,
+        Err(e) => return Err::<HttpResponse, KleahErr>(e)
+    };
+    let resp: FollowResponse = FollowResponse{
+        follow_id: follow.follow_id,
+        accepted: follow.accepted
+    };
+    Ok(HttpResponse::Ok().json(resp))
+}
+
+/// This function is the service function
+/// so that users can stop following other 
+/// actors. If the operation is successful,
+/// an HTTP response is returned containing 
+/// the status of the operation. If the operation
+/// fails, an error is returned.
+#[post("/api/user/unfollow")]
+pub async fn unfollow_service(
+    payload: Json<FollowPayload>,
+    data: Data<AppData>
+) -> Result<HttpResponse, KleahErr>{
+    let user: KleahUser = match get_user_by_token(
+        &payload.api_token,
+        &data.pool
+    ).await {
+        Ok(user) => user,
+        Err(e) => return Err::<HttpResponse, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    let status: bool = unfollow_actor(
+        &user.username,
+        &payload.target,
+        &data.pool
+    ).await.is_ok();
+    let resp: StatusResponse = StatusResponse{
+        status
+    };
+    Ok(HttpResponse::Ok().json(resp))
+}
+
+/// This function is the service function
+/// so that users can approve pending follow
+/// requests. If the operation is successful,
+/// an HTTP response is returned containing 
+/// the status of the operation. If the operation
+/// fails, an error is returned.
+#[post("/api/user/follow/accept")]
+pub async fn accept_follow_service(
+    payload: Json<FollowRequestPayload>,
+    data: Data<AppData>
+) -> Result<HttpResponse, KleahErr>{
+    let user: KleahUser = match get_user_by_token(
+        &payload.api_token,
+        &data.pool
+    ).await {
+        Ok(user) => user,
+        Err(e) => return Err::<HttpResponse, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    let status: bool = answer_follow_request(
+        &user.username,
+        &payload.follow_id,
+        true,
+        &data.pool
+    ).await.is_ok();
+    let resp: StatusResponse = StatusResponse{
+        status
+    };
+    Ok(HttpResponse::Ok().json(resp))
+}
+
+/// This function is the service function
+/// so that users can deny pending follow
+/// requests or remove existing followers. 
+/// If the operation is successful, an HTTP 
+/// response is returned containing the status
+/// of the operation. If the operation fails, 
+/// an error is returned.
+#[post("/api/user/follow/reject")]
+pub async fn reject_follow_service(
+    payload: Json<FollowRequestPayload>,
+    data: Data<AppData>
+) -> Result<HttpResponse, KleahErr>{
+    let user: KleahUser = match get_user_by_token(
+        &payload.api_token,
+        &data.pool
+    ).await {
+        Ok(user) => user,
+        Err(e) => return Err::<HttpResponse, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    let status: bool = answer_follow_request(
+        &user.username,
+        &payload.follow_id,
+        false,
+        &data.pool
+    ).await.is_ok();
+    let resp: StatusResponse = StatusResponse{
+        status
+    };
+    Ok(HttpResponse::Ok().json(resp))
+}
+
+/// This function is the service function
+/// so that users can edit whether follow 
+/// requests have to be approved manually.
+/// If the operation is successful, an HTTP
+/// response is returned containing the status
+/// of the operation. If the operation is 
+/// unsuccessful, an error is returned.
+#[post("/api/user/edit/locked")]
+pub async fn update_locked_service(
+    payload: Json<EditLockedPayload>,
+    data: Data<AppData>
+) -> Result<HttpResponse, KleahErr>{
+    let user: KleahUser = match get_user_by_token(
+        &payload.api_token,
+        &data.pool
+    ).await {
+        Ok(user) => user,
+        Err(e) => return Err::<HttpResponse, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    let status: bool = update_locked(
+        &user.username,
+        &payload.is_locked,
+        &data.pool
+    ).await.is_ok();
+    let resp: StatusResponse = StatusResponse{
+        status
+    };
+    Ok(HttpResponse::Ok().json(resp))
+}
+
+/// This function is the service function
+/// so that users can edit whether notes with
+/// a content warning are expanded for them by
+/// default. If the operation is successful, an 
+/// HTTP response is returned containing the status
+/// of the operation. If the operation is 
+/// unsuccessful, an error is returned.
+#[post("/api/user/edit/spoilers")]
+pub async fn update_spoilers_service(
+    payload: Json<EditSpoilersPayload>,
+    data: Data<AppData>
+) -> Result<HttpResponse, KleahErr>{
+    let user: KleahUser = match get_user_by_token(
+        &payload.api_token,
+        &data.pool
+    ).await {
+        Ok(user) => user,
+        Err(e) => return Err::<HttpResponse, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    let status: bool = update_expand_spoilers(
+        &user.username,
+        &payload.expand_spoilers,
+        &data.pool
+    ).await.is_ok();
+    let resp: StatusResponse = StatusResponse{
+        status
+    };
+    Ok(HttpResponse::Ok().json(resp))
+}
+
+/// This function is the service function
+/// to serve the activities of a local Kleah actor
+/// as a paginated ActivityPub collection. If
+/// the query string requests a page, that page
+/// is served. If the operation is successful, 
+/// an HTTP response is returned containing the 
+/// collection or page as a JSON-LD document. If 
+/// the actor does not exist, an error is returned.
+#[get("/apub/{username}/outbox")]
+pub async fn outbox_service(
+    username: Path<String>,
+    query: Query<CollectionQuery>,
+    data: Data<AppData>
+) -> Result<HttpResponse, KleahErr>{
+    serve_collection(
+        &username,
+        &query,
+        |actor| actor.outbox.as_str(),
+        async |actor| count_outbox_items(actor, &data.pool).await,
+        async |actor, _base, max_id, min_id| read_outbox_items(actor, max_id, min_id, &data.pool).await,
+        &data.pool
+    ).await
+}
+
+/// This function is the service function
+/// to serve the followers of a local Kleah actor
+/// as a paginated ActivityPub collection. If
+/// the query string requests a page, that page
+/// is served. If the operation is successful, 
+/// an HTTP response is returned containing the 
+/// collection or page as a JSON-LD document. If 
+/// the actor does not exist, an error is returned.
+#[get("/apub/{username}/followers")]
+pub async fn followers_service(
+    username: Path<String>,
+    query: Query<CollectionQuery>,
+    data: Data<AppData>
+) -> Result<HttpResponse, KleahErr>{
+    serve_collection(
+        &username,
+        &query,
+        |actor| actor.followers.as_str(),
+        async |actor| count_followers(actor, &data.pool).await,
+        async |actor, base, max_id, min_id| get_followers_page(
+            actor,
+            base,
+            max_id,
+            min_id,
+            COLLECTION_PAGE_SIZE,
+            &data.pool
+        ).await.map(collection_items),
+        &data.pool
+    ).await
+}
+
+/// This function is the service function
+/// to serve the followed actors of a local Kleah actor
+/// as a paginated ActivityPub collection. If
+/// the query string requests a page, that page
+/// is served. If the operation is successful, 
+/// an HTTP response is returned containing the 
+/// collection or page as a JSON-LD document. If 
+/// the actor does not exist, an error is returned.
+#[get("/apub/{username}/following")]
+pub async fn following_service(
+    username: Path<String>,
+    query: Query<CollectionQuery>,
+    data: Data<AppData>
+) -> Result<HttpResponse, KleahErr>{
+    serve_collection(
+        &username,
+        &query,
+        |actor| actor.following.as_str(),
+        async |actor| count_following(actor, &data.pool).await,
+        async |actor, base, max_id, min_id| get_following_page(
+            actor,
+            base,
+            max_id,
+            min_id,
+            COLLECTION_PAGE_SIZE,
+            &data.pool
+        ).await.map(collection_items),
+        &data.pool
+    ).await
+}
+
+/// This function is the service function
+/// to serve the liked objects of a local Kleah actor
+/// as a paginated ActivityPub collection. If
+/// the query string requests a page, that page
+/// is served. If the operation is successful, 
+/// an HTTP response is returned containing the 
+/// collection or page as a JSON-LD document. If 
+/// the actor does not exist, an error is returned.
+#[get("/apub/{username}/liked")]
+pub async fn liked_service(
+    username: Path<String>,
+    query: Query<CollectionQuery>,
+    data: Data<AppData>
+) -> Result<HttpResponse, KleahErr>{
+    serve_collection(
+        &username,
+        &query,
+        |actor| actor.liked.as_str(),
+        async |actor| count_liked(actor, &data.pool).await,
+        async |actor, base, max_id, min_id| get_liked_page(
+            actor,
+            base,
+            max_id,
+            min_id,
+            COLLECTION_PAGE_SIZE,
+            &data.pool
+        ).await.map(collection_items),
+        &data.pool
+    ).await
+}
+
+/// This function is the service function
+/// to let other servers discover the NodeInfo
+/// documents of this instance. If the operation 
+/// is successful, an HTTP response is returned 
+/// containing links to the documents. If the 
+/// operation fails, an error is returned.
+#[get("/.well-known/nodeinfo")]
+pub async fn nodeinfo_links_service(
+    data: Data<AppData>
+) -> Result<HttpResponse, KleahErr>{
+    let instance: InstanceInformation = match get_instance_info(
+        &data.pool
+    ).await{
+        Ok(instance) => instance,
+        Err(e) => return Err::<HttpResponse, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    let resp: NodeInfoLinks = NodeInfoLinks{
+        links: vec![
+            NodeInfoLink{
+                rel: NODEINFO_SCHEMA.to_string(),
+                href: format!("{}/nodeinfo/2.1", base_url(&instance.host))
+            }
+        ]
+    };
+    Ok(HttpResponse::Ok().json(resp))
+}
+
+/// This function is the service function
+/// to serve the NodeInfo document of this
+/// instance. If the operation is successful, 
+/// an HTTP response is returned containing the
+/// document. If the operation fails, an error
+/// is returned.
+#[get("/nodeinfo/2.1")]
+pub async fn nodeinfo_service(
+    data: Data<AppData>
+) -> Result<HttpResponse, KleahErr>{
+    let instance: InstanceInformation = match get_instance_info(
+        &data.pool
+    ).await{
+        Ok(instance) => instance,
+        Err(e) => return Err::<HttpResponse, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    let usage: NodeInfoUsage = match get_node_usage(&data.pool).await {
+        Ok(usage) => usage,
+        Err(e) => return Err::<HttpResponse, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    let resp: NodeInfoResponse = build_nodeinfo(&instance, usage);
+    Ok(
+        HttpResponse::Ok()
+            .content_type(format!("application/json; profile=\"{}#\"", NODEINFO_SCHEMA))
+            .json(resp)
+    )
+}
+
+/// This function is the service function
+/// so that users can publish a new note. If 
+/// the operation is successful, an HTTP response
+/// is returned containing the created note. If
+/// the operation is unsuccessful, an error is
+/// returned.
+#[post("/api/post/create")]
+pub async fn create_note_service(
+    payload: Json<NoteCreatePayload>,
+    data: Data<AppData>
+) -> Result<HttpResponse, KleahErr>{
+    let user: KleahUser = match get_user_by_token(
+        &payload.api_token,
+        &data.pool
+    ).await {
+        Ok(user) => user,
+        Err(e) => return Err::<HttpResponse, KleahErr>(
+            KleahErr::with_type(&e.to_string(), KleahErrType::Unauthorized)
+        )
+    };
+    let note: KleahNote = match publish_note(
+        &user.username,
+        &payload.content,
+        payload.in_reply_to.as_deref(),
+        payload.visibility.as_deref(),
+        payload.summary.as_deref(),
+        &payload.sensitive.unwrap_or(false),
+        payload.poll.as_ref(),
+        &data.pool
+    ).await {
+        Ok(note) => note,
+        Err(e) => return Err::<HttpResponse, KleahErr>(e)
+    };
+    let resp: NoteResponse = build_note_response(&note, user.expand_spoilers);
+    Ok(HttpResponse::Ok().json(resp))
+}
+
+/// This function is the service function
+/// to read a single note. Notes that are not
+/// public can only be read with the API token
+/// of a user allowed to see them, given as a
+/// bearer token. If the operation is successful,
+/// an HTTP response is returned containing the
+/// note. If the note does not exist, an error
+/// is returned.
+#[get("/api/post/{note_id}")]
+pub async fn get_note_service(
+    req: HttpRequest,
+    note_id: Path<String>,
+    data: Data<AppData>
+) -> Result<HttpResponse, KleahErr>{
+    let api_token: Option<String> = bearer_token(&req);
+    let note: KleahNote = match get_visible_note(
+        &note_id,
+        api_token.as_deref(),
+        &data.pool
+    ).await {
+        Ok(note) => note,
+        Err(e) => return Err::<HttpResponse, KleahErr>(e)
+    };
+    let expand_spoilers: bool = get_spoiler_preference(
+        api_token.as_deref(),
+        &data.pool
+    ).await;
+    let resp: NoteResponse = build_note_response(&note, expand_spoilers);
+    Ok(HttpResponse::Ok().json(resp))
+}
+
+/// This function is the service function
+/// so that users can delete one of their
+/// notes. If the operation is successful, 
+/// an HTTP response is returned containing 
+/// the status of the operation. If the operation 
+/// is unsuccessful, an error is returned.
+#[post("/api/post/delete")]
+pub async fn delete_note_service(
+    payload: Json<NoteDeletePayload>,
+    data: Data<AppData>
+) -> Result<HttpResponse, KleahErr>{
+    let user: KleahUser = match get_user_by_token(
+        &payload.api_token,
+        &data.pool
+    ).await {
+        Ok(user) => user,
+        Err(e) => return Err::<HttpResponse, KleahErr>(
+            KleahErr::with_type(&e.to_string(), KleahErrType::Unauthorized)
+        )
+    };
+    let status: bool = match remove_note(
+        &user.username,
+        &payload.note_id,
+        &data.pool
+    ).await {
+        Ok(_note) => true,
+        Err(e) => return Err::<HttpResponse, KleahErr>(e)
+    };
+    let resp: StatusResponse = StatusResponse{
+        status
+    };
+    Ok(HttpResponse::Ok().json(resp))
+}
+
+/// This function is the service function
+/// to serve a note written by a local Kleah
+/// actor to other servers. Only public and
+/// unlisted notes are served. Deleted notes 
+/// are served as tombstones with the status 
+/// "410 Gone". If the operation is successful, 
+/// an HTTP response is returned containing the
+/// note as a JSON-LD document. If the note does 
+/// not exist, an error is returned.
+#[get("/apub/notes/{note_id}")]
+pub async fn apub_note_service(
+    note_id: Path<String>,
+    data: Data<AppData>
+) -> Result<HttpResponse, KleahErr>{
+    let (note, author, recipients): (KleahNote, KleahActor, Vec<KleahActor>) = match get_public_local_note(
+        &note_id,
+        &data.pool
+    ).await {
+        Ok(found) => found,
+        Err(e) => return match get_note_tombstone(&note_id, &data.pool).await {
+            Ok(tombstone) => Ok(
+                HttpResponse::Gone()
+                    .content_type(ACTIVITY_JSON)
+                    .json(
+                        ApubTombstone{
+                            context: Some(ACTIVITYSTREAMS_CONTEXT.to_string()),
+                            ..tombstone
+                        }
+                    )
+            ),
+            Err(_e) => Err::<HttpResponse, KleahErr>(e)
+        }
+    };
+    let poll: Option<(KleahPoll, Vec<KleahPollOption>)> = match get_note_poll(&note, &data.pool).await {
+        Ok(poll) => poll,
+        Err(e) => return Err::<HttpResponse, KleahErr>(e)
+    };
+    let resp: ApubNote = ApubNote{
+        context: Some(ACTIVITYSTREAMS_CONTEXT.to_string()),
+        ..build_note_object(&note, &author, &recipients, poll.as_ref())
+    };
+    Ok(
+        HttpResponse::Ok()
+            .content_type(ACTIVITY_JSON)
+            .json(resp)
+    )
+}
+
+/// This function is the service function
+/// to serve the "Create" activity that
+/// published a note written by a local Kleah
+/// actor. Only public and unlisted notes are 
+/// served. If the operation is successful, an 
+/// HTTP response is returned containing the 
+/// activity as a JSON-LD document. If the note 
+/// does not exist, an error is returned.
+#[get("/apub/notes/{note_id}/activity")]
+pub async fn apub_note_activity_service(
+    note_id: Path<String>,
+    data: Data<AppData>
+) -> Result<HttpResponse, KleahErr>{
+    let (note, author, recipients): (KleahNote, KleahActor, Vec<KleahActor>) = match get_public_local_note(
+        &note_id,
+        &data.pool
+    ).await {
+        Ok(found) => found,
+        Err(e) => return Err::<HttpResponse, KleahErr>(e)
+    };
+    let poll: Option<(KleahPoll, Vec<KleahPollOption>)> = match get_note_poll(&note, &data.pool).await {
+        Ok(poll) => poll,
+        Err(e) => return Err::<HttpResponse, KleahErr>(e)
+    };
+    let resp: ApubActivity = match build_create_activity(&note, &author, &recipients, poll.as_ref()){
+        Ok(resp) => resp,
+        Err(e) => return Err::<HttpResponse, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    Ok(
+        HttpResponse::Ok()
+            .content_type(ACTIVITY_JSON)
+            .json(resp)
+    )
+}
+
+/// This function is the service function
+/// to read a page of the home timeline of the
+/// user with the API token given as a bearer
+/// token. The cursors for the next pages are
+/// part of the response. If the operation is
+/// successful, an HTTP response is returned
+/// containing the page. If the operation is
+/// unsuccessful, an error is returned.
+#[get("/api/timeline/home")]
+pub async fn home_timeline_service(
+    req: HttpRequest,
+    query: Query<TimelineQuery>,
+    data: Data<AppData>
+) -> Result<HttpResponse, KleahErr>{
+    let resp: TimelineResponse = match read_home_timeline(
+        bearer_token(&req).as_deref(),
+        &query,
+        &data.pool
+    ).await {
+        Ok(resp) => resp,
+        Err(e) => return Err::<HttpResponse, KleahErr>(e)
+    };
+    Ok(HttpResponse::Ok().json(resp))
+}
+
+/// This function is the service function
+/// to read a page of the local timeline, which
+/// holds the public notes of the users of this
+/// instance. The cursors for the next pages are
+/// part of the response. Readers identify
+/// themselves with a bearer token. If the
+/// operation is successful, an HTTP response
+/// is returned containing the page. If the
+/// timeline is not open to the reader, an
+/// error is returned.
+#[get("/api/timeline/local")]
+pub async fn local_timeline_service(
+    req: HttpRequest,
+    query: Query<TimelineQuery>,
+    data: Data<AppData>
+) -> Result<HttpResponse, KleahErr>{
+    let resp: TimelineResponse = match read_local_timeline(
+        bearer_token(&req).as_deref(),
+        &query,
+        &data.pool
+    ).await {
+        Ok(resp) => resp,
+        Err(e) => return Err::<HttpResponse, KleahErr>(e)
+    };
+    Ok(HttpResponse::Ok().json(resp))
+}
+
+/// This function is the service function
+/// to read a page of the federated timeline, 
+/// which holds all public notes known to this
+/// instance. The cursors for the next pages are
+/// part of the response. Readers identify
+/// themselves with a bearer token. If the
+/// operation is successful, an HTTP response
+/// is returned containing the page. If the
+/// timeline is not open to the reader, an
+/// error is returned.
+#[get("/api/timeline/federated")]
+pub async fn federated_timeline_service(
+    req: HttpRequest,
+    query: Query<TimelineQuery>,
+    data: Data<AppData>
+) -> Result<HttpResponse, KleahErr>{
+    let resp: TimelineResponse = match read_federated_timeline(
+        bearer_token(&req).as_deref(),
+        &query,
+        &data.pool
+    ).await {
+        Ok(resp) => resp,
+        Err(e) => return Err::<HttpResponse, KleahErr>(e)
+    };
+    Ok(HttpResponse::Ok().json(resp))
+}
+
+/// This function is the service function
+/// so that users can like a note they can see.
+/// If the operation is successful, an HTTP 
+/// response is returned containing the liked
+/// note. If the operation is unsuccessful, an 
+/// error is returned.
+#[post("/api/post/like")]
+pub async fn like_note_service(
+    payload: Json<NoteInteractionPayload>,
+    data: Data<AppData>
+) -> Result<HttpResponse, KleahErr>{
+    let note: KleahNote = match like_note(
+        &payload.api_token,
+        &payload.note_id,
+        &data.pool
+    ).await {
+        Ok(note) => note,
+        Err(e) => return Err::<HttpResponse, KleahErr>(e)
+    };
+    let expand_spoilers: bool = get_spoiler_preference(
+        Some(&payload.api_token),
+        &data.pool
+    ).await;
+    let resp: NoteResponse = build_note_response(&note, expand_spoilers);
+    Ok(HttpResponse::Ok().json(resp))
+}
+
+/// This function is the service function
+/// so that users can take back the like of
+/// a note. If the operation is successful, an 
+/// HTTP response is returned containing the 
+/// note. If the operation is unsuccessful, an 
+/// error is returned.
+#[post("/api/post/unlike")]
+pub async fn unlike_note_service(
+    payload: Json<NoteInteractionPayload>,
+    data: Data<AppData>
+) -> Result<HttpResponse, KleahErr>{
+    let note: KleahNote = match unlike_note(
+        &payload.api_token,
+        &payload.note_id,
+        &data.pool
+    ).await {
+        Ok(note) => note,
+        Err(e) => return Err::<HttpResponse, KleahErr>(e)
+    };
+    let expand_spoilers: bool = get_spoiler_preference(
+        Some(&payload.api_token),
+        &data.pool
+    ).await;
+    let resp: NoteResponse = build_note_response(&note, expand_spoilers);
+    Ok(HttpResponse::Ok().json(resp))
+}
+
+/// This function is the service function
+/// so that users can boost a public or unlisted
+/// note they can see. If the operation is 
+/// successful, an HTTP response is returned 
+/// containing the boosted note. If the operation
+/// is unsuccessful, an error is returned.
+#[post("/api/post/boost")]
+pub async fn boost_note_service(
+    payload: Json<NoteInteractionPayload>,
+    data: Data<AppData>
+) -> Result<HttpResponse, KleahErr>{
+    let note: KleahNote = match boost_note(
+        &payload.api_token,
+        &payload.note_id,
+        &data.pool
+    ).await {
+        Ok(note) => note,
+        Err(e) => return Err::<HttpResponse, KleahErr>(e)
+    };
+    let expand_spoilers: bool = get_spoiler_preference(
+        Some(&payload.api_token),
+        &data.pool
+    ).await;
+    let resp: NoteResponse = build_note_response(&note, expand_spoilers);
+    Ok(HttpResponse::Ok().json(resp))
+}
+
+/// This function is the service function
+/// so that users can take back the boost of
+/// a note. If the operation is successful, an 
+/// HTTP response is returned containing the 
+/// note. If the operation is unsuccessful, an 
+/// error is returned.
+#[post("/api/post/unboost")]
+pub async fn unboost_note_service(
+    payload: Json<NoteInteractionPayload>,
+    data: Data<AppData>
+) -> Result<HttpResponse, KleahErr>{
+    let note: KleahNote = match unboost_note(
+        &payload.api_token,
+        &payload.note_id,
+        &data.pool
+    ).await {
+        Ok(note) => note,
+        Err(e) => return Err::<HttpResponse, KleahErr>(e)
+    };
+    let expand_spoilers: bool = get_spoiler_preference(
+        Some(&payload.api_token),
+        &data.pool
+    ).await;
+    let resp: NoteResponse = build_note_response(&note, expand_spoilers);
+    Ok(HttpResponse::Ok().json(resp))
+}
+
+/// This function is the service function
+/// to read the thread around a note: the notes
+/// it replies to and the replies it received.
+/// Notes the reader may not see are left out.
+/// Readers identify themselves with a bearer
+/// token. If the operation is successful, an
+/// HTTP response is returned containing the
+/// thread. If the note does not exist, an
+/// error is returned.
+#[get("/api/post/{note_id}/context")]
+pub async fn note_context_service(
+    req: HttpRequest,
+    note_id: Path<String>,
+    data: Data<AppData>
+) -> Result<HttpResponse, KleahErr>{
+    let resp: NoteContextResponse = match read_note_context(
+        &note_id,
+        bearer_token(&req).as_deref(),
+        &data.pool
+    ).await {
+        Ok(resp) => resp,
+        Err(e) => return Err::<HttpResponse, KleahErr>(e)
+    };
+    Ok(HttpResponse::Ok().json(resp))
+}
+
+/// This function is the service function
+/// to serve the public and unlisted replies to
+/// a note written by a local Kleah actor as a
+/// paginated ActivityPub collection. If the query
+/// string requests a page, that page is served.
+/// If the operation is successful, an HTTP response
+/// is returned containing the collection or page 
+/// as a JSON-LD document. If the note does not 
+/// exist, an error is returned.
+#[get("/apub/notes/{note_id}/replies")]
+pub async fn apub_note_replies_service(
+    note_id: Path<String>,
+    query: Query<CollectionQuery>,
+    data: Data<AppData>
+) -> Result<HttpResponse, KleahErr>{
+    let (note, _author, _recipients): (KleahNote, KleahActor, Vec<KleahActor>) = match get_public_local_note(
+        &note_id,
+        &data.pool
+    ).await {
+        Ok(found) => found,
+        Err(e) => return Err::<HttpResponse, KleahErr>(e)
+    };
+    let url: String = format!("{}/replies", note_url(&note));
+    let total_items: i64 = match count_public_replies(
+        &note_url(&note),
+        &data.pool
+    ).await {
+        Ok(total_items) => total_items,
+        Err(e) => return Err::<HttpResponse, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    if !query.page.unwrap_or(false) && query.max_id.is_none() && query.min_id.is_none() {
+        return Ok(
+            HttpResponse::Ok()
+                .content_type(ACTIVITY_JSON)
+                .json(build_collection(&url, total_items))
+        );
+    }
+    let items: Vec<CollectionItem> = match get_public_replies_page(
+        &note_url(&note),
+        &base_url(&note.author_host),
+        parse_cursor(query.max_id.as_deref()).as_ref(),
+        parse_cursor(query.min_id.as_deref()).as_ref(),
+        COLLECTION_PAGE_SIZE,
+        &data.pool
+    ).await {
+        Ok(items) => items,
+        Err(e) => return Err::<HttpResponse, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    let resp: ApubCollectionPage = build_collection_page(
+        &url,
+        total_items,
+        &query,
+        items.into_iter()
+            .map(|item| (Value::String(item.item_id), format_cursor(&item.created_at, &item.cursor_id)))
+            .collect()
+    );
+    Ok(
+        HttpResponse::Ok()
+            .content_type(ACTIVITY_JSON)
+            .json(resp)
+    )
+}
+
+/// This function is the service function
+/// to read a page of the timeline of a hashtag,
+/// which holds the public notes known to this
+/// instance that carry the hashtag. The cursors
+/// for the next pages are part of the response.
+/// Readers identify themselves with a bearer
+/// token. If the operation is successful, an
+/// HTTP response is returned containing the
+/// page. If the timeline is not open to the
+/// reader, an error is returned.
+#[get("/api/timeline/tag/{name}")]
+pub async fn hashtag_timeline_service(
+    req: HttpRequest,
+    name: Path<String>,
+    query: Query<TimelineQuery>,
+    data: Data<AppData>
+) -> Result<HttpResponse, KleahErr>{
+    if !check_hashtag(&normalize_hashtag(&name)) {
+        return Err::<HttpResponse, KleahErr>(
+            KleahErr::with_type(
+                "The name of the hashtag is not valid.",
+                KleahErrType::BadRequest
+            )
+        )
+    }
+    let resp: TimelineResponse = match read_hashtag_timeline(
+        &name,
+        bearer_token(&req).as_deref(),
+        &query,
+        &data.pool
+    ).await {
+        Ok(resp) => resp,
+        Err(e) => return Err::<HttpResponse, KleahErr>(e)
+    };
+    Ok(HttpResponse::Ok().json(resp))
+}
+
+/// This function is the service function
+/// to serve the public notes written on this 
+/// instance that carry a hashtag as a paginated
+/// ActivityPub collection. If the query string 
+/// requests a page, that page is served. If the
+/// operation is successful, an HTTP response is
+/// returned containing the collection or page 
+/// as a JSON-LD document. If the name of the 
+/// hashtag is not valid, an error is returned.
+#[get("/tags/{name}")]
+pub async fn hashtag_collection_service(
+    name: Path<String>,
+    query: Query<CollectionQuery>,
+    data: Data<AppData>
+) -> Result<HttpResponse, KleahErr>{
+    let name: String = normalize_hashtag(&name);
+    if !check_hashtag(&name) {
+        return Err::<HttpResponse, KleahErr>(
+            KleahErr::with_type(
+                "The requested hashtag does not exist.",
+                KleahErrType::NotFound
+            )
+        )
+    }
+    let instance: InstanceInformation = match get_instance_info(
+        &data.pool
+    ).await{
+        Ok(instance) => instance,
+        Err(e) => return Err::<HttpResponse, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    let url: String = hashtag_url(&instance.host, &name);
+    let total_items: i64 = match count_local_hashtag_notes(
+        &name,
+        &instance.host,
+        &data.pool
+    ).await {
+        Ok(total_items) => total_items,
+        Err(e) => return Err::<HttpResponse, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    if !query.page.unwrap_or(false) && query.max_id.is_none() && query.min_id.is_none() {
+        return Ok(
+            HttpResponse::Ok()
+                .content_type(ACTIVITY_JSON)
+                .json(build_collection(&url, total_items))
+        );
+    }
+    let items: Vec<CollectionItem> = match get_local_hashtag_page(
+        &name,
+        &instance.host,
+        &base_url(&instance.host),
+        parse_cursor(query.max_id.as_deref()).as_ref(),
+        parse_cursor(query.min_id.as_deref()).as_ref(),
+        COLLECTION_PAGE_SIZE,
+        &data.pool
+    ).await {
+        Ok(items) => items,
+        Err(e) => return Err::<HttpResponse, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    let resp: ApubCollectionPage = build_collection_page(
+        &url,
+        total_items,
+        &query,
+        items.into_iter()
+            .map(|item| (Value::String(item.item_id), format_cursor(&item.created_at, &item.cursor_id)))
+            .collect()
+    );
+    Ok(
+        HttpResponse::Ok()
+            .content_type(ACTIVITY_JSON)
+            .json(resp)
+    )
+}
+
+/// This function is the service function
+/// so that users can edit one of their notes.
+/// The prior revision is kept in the note's 
+/// history. If the operation is successful, an
+/// HTTP response is returned containing the 
+/// edited note. If the operation is unsuccessful,
+/// an error is returned.
+#[post("/api/post/edit")]
+pub async fn edit_note_service(
+    payload: Json<NoteEditPayload>,
+    data: Data<AppData>
+) -> Result<HttpResponse, KleahErr>{
+    let user: KleahUser = match get_user_by_token(
+        &payload.api_token,
+        &data.pool
+    ).await {
+        Ok(user) => user,
+        Err(e) => return Err::<HttpResponse, KleahErr>(
+            KleahErr::with_type(&e.to_string(), KleahErrType::Unauthorized)
+        )
+    };
+    let note: KleahNote = match edit_note(
+        &user.username,
+        &payload.note_id,
+        &payload.content,
+        payload.summary.as_deref(),
+        payload.sensitive.as_ref(),
+        &data.pool
+    ).await {
+        Ok(note) => note,
+        Err(e) => return Err::<HttpResponse, KleahErr>(e)
+    };
+    let resp: NoteResponse = build_note_response(&note, user.expand_spoilers);
+    Ok(HttpResponse::Ok().json(resp))
+}
+
+/// This function is the service function
+/// to read all revisions of a note, oldest
+/// first. Notes that are not public can only
+/// be read with the API token of a user allowed
+/// to see them, given as a bearer token. If the
+/// operation is successful, an HTTP response is
+/// returned containing the revisions. If the note
+/// does not exist, an error is returned.
+#[get("/api/post/{note_id}/history")]
+pub async fn note_history_service(
+    req: HttpRequest,
+    note_id: Path<String>,
+    data: Data<AppData>
+) -> Result<HttpResponse, KleahErr>{
+    let resp: NoteHistoryResponse = match read_note_history(
+        &note_id,
+        bearer_token(&req).as_deref(),
+        &data.pool
+    ).await {
+        Ok(resp) => resp,
+        Err(e) => return Err::<HttpResponse, KleahErr>(e)
+    };
+    Ok(HttpResponse::Ok().json(resp))
+}
+
+/// This function is the service function
+/// so that users can vote in the poll of a
+/// note they can see. Choices are given by
+/// the positions of the poll's options. If the
+/// operation is successful, an HTTP response is
+/// returned containing the poll. If the operation
+/// is unsuccessful, an error is returned.
+#[post("/api/post/vote")]
+pub async fn vote_poll_service(
+    payload: Json<PollVotePayload>,
+    data: Data<AppData>
+) -> Result<HttpResponse, KleahErr>{
+    let resp: PollResponse = match vote_in_poll(
+        &payload.api_token,
+        &payload.note_id,
+        &payload.choices,
+        &data.pool
+    ).await {
+        Ok(resp) => resp,
+        Err(e) => return Err::<HttpResponse, KleahErr>(e)
+    };
+    Ok(HttpResponse::Ok().json(resp))
+}
+
+/// This function is the service function
+/// to read the poll of a note with its
+/// tallies. With an API token given as a bearer
+/// token, the response tells which options the
+/// user voted for.
+/// If the operation is successful, an HTTP
+/// response is returned containing the poll.
+/// If the note or its poll does not exist,
+/// an error is returned.
+#[get("/api/post/{note_id}/poll")]
+pub async fn poll_service(
+    req: HttpRequest,
+    note_id: Path<String>,
+    data: Data<AppData>
+) -> Result<HttpResponse, KleahErr>{
+    let resp: PollResponse = match read_poll(
+        &note_id,
+        bearer_token(&req).as_deref(),
+        &data.pool
+    ).await {
+        Ok(resp) => resp,
+        Err(e) => return Err::<HttpResponse, KleahErr>(e)
+    };
+    Ok(HttpResponse::Ok().json(resp))
+}
+
+/// This function is the service function
+/// to read a page of the notifications of
+/// the user with the given API token, newest
+/// first. The number of unread notifications
+/// and the cursors for the next pages are part
+/// of the response. If the operation is 
+/// successful, an HTTP response is returned 
+/// containing the notifications. If the operation
+/// is unsuccessful, an error is returned.
+#[get("/api/notifications")]
+pub async fn notifications_service(
+    req: HttpRequest,
+    query: Query<NotificationQuery>,
+    data: Data<AppData>
+) -> Result<HttpResponse, KleahErr>{
+    let resp: NotificationsResponse = match read_notifications(
+        &bearer_token(&req).unwrap_or_default(),
+        &query,
+        &data.pool
+    ).await {
+        Ok(resp) => resp,
+        Err(e) => return Err::<HttpResponse, KleahErr>(e)
+    };
+    Ok(HttpResponse::Ok().json(resp))
+}
+
+/// This function is the service function
+/// so that users can mark one or all of their
+/// notifications as read. If the operation is 
+/// successful, an HTTP response is returned 
+/// containing the status of the operation. If
+/// the operation is unsuccessful, an error is
+/// returned.
+#[post("/api/notifications/read")]
+pub async fn read_notifications_service(
+    payload: Json<NotificationReadPayload>,
+    data: Data<AppData>
+) -> Result<HttpResponse, KleahErr>{
+    let status: bool = match mark_notifications_read(
+        &payload.api_token,
+        payload.notification_id.as_deref(),
+        &data.pool
+    ).await {
+        Ok(_marked) => true,
+        Err(e) => return Err::<HttpResponse, KleahErr>(e)
+    };
+    let resp: StatusResponse = StatusResponse{
+        status
+    };
+    Ok(HttpResponse::Ok().json(resp))
+}
+
+/// This function is the service function
+/// so that users can dismiss one of their
+/// notifications. If the operation is 
+/// successful, an HTTP response is returned 
+/// containing the status of the operation. If
+/// the operation is unsuccessful, an error is
+/// returned.
+#[post("/api/notifications/dismiss")]
+pub async fn dismiss_notification_service(
+    payload: Json<NotificationDismissPayload>,
+    data: Data<AppData>
+) -> Result<HttpResponse, KleahErr>{
+    let status: bool = match dismiss_notification(
+        &payload.api_token,
+        &payload.notification_id,
+        &data.pool
+    ).await {
+        Ok(_f) => true,
+        Err(e) => return Err::<HttpResponse, KleahErr>(e)
+    };
+    let resp: StatusResponse = StatusResponse{
+        status
+    };
+    Ok(HttpResponse::Ok().json(resp))
+}
+
+/// This function is the service function
+/// to read the kinds of notifications the
+/// user with the given API token muted. If
+/// the operation is successful, an HTTP 
+/// response is returned containing the muted
+/// kinds. If the operation is unsuccessful,
+/// an error is returned.
+#[get("/api/notifications/mutes")]
+pub async fn notification_mutes_service(
+    req: HttpRequest,
+    data: Data<AppData>
+) -> Result<HttpResponse, KleahErr>{
+    let resp: NotificationMutesResponse = match read_notification_mutes(
+        &bearer_token(&req).unwrap_or_default(),
+        &data.pool
+    ).await {
+        Ok(resp) => resp,
+        Err(e) => return Err::<HttpResponse, KleahErr>(e)
+    };
+    Ok(HttpResponse::Ok().json(resp))
+}
+
+/// This function is the service function
+/// so that users can mute or unmute a kind
+/// of notifications. If the operation is 
+/// successful, an HTTP response is returned 
+/// containing the muted kinds. If the operation
+/// is unsuccessful, an error is returned.
+#[post("/api/notifications/mute")]
+pub async fn mute_notifications_service(
+    payload: Json<NotificationMutePayload>,
+    data: Data<AppData>
+) -> Result<HttpResponse, KleahErr>{
+    let resp: NotificationMutesResponse = match mute_notifications(
+        &payload.api_token,
+        &payload.kind,
+        &payload.muted,
+        &data.pool
+    ).await {
+        Ok(resp) => resp,
+        Err(e) => return Err::<HttpResponse, KleahErr>(e)
+    };
+    Ok(HttpResponse::Ok().json(resp))
+}
+
+/// This function is the service function
+/// so that users can report an actor and
+/// optionally one of their notes to the
+/// administrators of this instance. If the
+/// operation is successful, an HTTP response
+/// is returned containing the report. If the
+/// operation is unsuccessful, an error is 
+/// returned.
+#[post("/api/report")]
+pub async fn report_service(
+    payload: Json<ReportPayload>,
+    data: Data<AppData>
+) -> Result<HttpResponse, KleahErr>{
+    let report: KleahReport = match report_actor(
+        &payload,
+        &data.pool
+    ).await {
+        Ok(report) => report,
+        Err(e) => return Err::<HttpResponse, KleahErr>(e)
+    };
+    let resp: ReportResponse = build_report_response(&report);
+    Ok(HttpResponse::Ok().json(resp))
+}
+
+/// This function is the service function
+/// so that administrators can read a report.
+/// If the operation is successful, an HTTP 
+/// response is returned containing the report.
+/// If the operation is unsuccessful, an error
+/// is returned.
+#[get("/api/report/{report_id}")]
+pub async fn get_report_service(
+    report_id: Path<String>,
+    req: HttpRequest,
+    data: Data<AppData>
+) -> Result<HttpResponse, KleahErr>{
+    let resp: ReportResponse = match read_report(
+        &report_id,
+        &bearer_token(&req).unwrap_or_default(),
+        &data.pool
+    ).await {
+        Ok(resp) => resp,
+        Err(e) => return Err::<HttpResponse, KleahErr>(e)
+    };
+    Ok(HttpResponse::Ok().json(resp))
+}
@@ -0,0 +1,297 @@
+/*
+Kleah by Alyx Shang.
+Licensed under the FSL v1.
+*/
+
+/// Importing the macro
+/// to serialize Rust
+/// data structures
+/// into a JSON
+/// string.
+use serde::Serialize;
+
+/// Importing the macro
+/// to deserialize JSON 
+/// strings into Rust 
+/// data structures.
+use serde::Deserialize;
+
+/// Importing the structure
+/// representing an arbitrary
+/// JSON value.
+use serde_json::Value;
+
+/// The URI of the JSON-LD
+/// context for ActivityStreams
+/// documents.
+pub const ACTIVITYSTREAMS_CONTEXT: &str = "https://www.w3.org/ns/activitystreams";
+
+/// The URI of the JSON-LD
+/// context for documents
+/// containing public keys.
+pub const SECURITY_CONTEXT: &str = "https://w3id.org/security/v1";
+
+/// The media type for
+/// ActivityPub documents.
+pub const ACTIVITY_JSON: &str = "application/activity+json";
+
+/// The URI addressing a
+/// public ActivityPub object
+/// to everyone.
+pub const PUBLIC_COLLECTION: &str = "https://www.w3.org/ns/activitystreams#Public";
+
+/// The number of items on
+/// a single page of an
+/// ActivityPub collection.
+pub const COLLECTION_PAGE_SIZE: i64 = 20;
+
+/// A structure to contain data
+/// about the public key of an
+/// ActivityPub actor and serialize
+/// this data into a JSON string.
+#[derive(Serialize, Deserialize)]
+pub struct ApubPublicKey{
+    #[serde(rename = "@context", skip_serializing_if = "Option::is_none")]
+    pub context: Option<String>,
+    pub id: String,
+    pub owner: String,
+    #[serde(rename = "publicKeyPem")]
+    pub public_key_pem: String
+}
+
+/// A structure to contain data
+/// about a Kleah actor and serialize
+/// this data into an ActivityPub
+/// actor document.
+#[derive(Serialize)]
+pub struct ApubActor{
+    #[serde(rename = "@context")]
+    pub context: Vec<String>,
+    pub id: String,
+    #[serde(rename = "type")]
+    pub actor_type: String,
+    #[serde(rename = "preferredUsername")]
+    pub preferred_username: String,
+    pub name: String,
+    pub summary: String,
+    pub url: String,
+    pub inbox: String,
+    pub outbox: String,
+    pub followers: String,
+    pub following: String,
+    pub liked: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub endpoints: Option<ApubEndpoints>,
+    #[serde(rename = "publicKey")]
+    pub public_key: ApubPublicKey
+}
+
+/// A structure to contain data
+/// about the additional endpoints
+/// of an ActivityPub actor.
+#[derive(Serialize, Deserialize)]
+pub struct ApubEndpoints{
+    #[serde(rename = "sharedInbox", skip_serializing_if = "Option::is_none")]
+    pub shared_inbox: Option<String>
+}
+
+/// A structure to contain data
+/// about an ActivityPub actor on
+/// another server deserialized from
+/// the actor's JSON-LD document.
+/// Properties that are optional in
+/// the ActivityPub specification are
+/// optional here as well.
+#[derive(Deserialize)]
+pub struct ApubRemoteActor{
+    pub id: String,
+    #[serde(rename = "type")]
+    pub actor_type: String,
+    #[serde(rename = "preferredUsername")]
+    pub preferred_username: String,
+    pub name: Option<String>,
+    pub summary: Option<String>,
+    pub inbox: String,
+    pub outbox: Option<String>,
+    pub followers: Option<String>,
+    pub following: Option<String>,
+    pub liked: Option<String>,
+    pub endpoints: Option<ApubEndpoints>,
+    #[serde(rename = "publicKey")]
+    pub public_key: ApubPublicKey
+}
+
+/// A structure to contain data
+/// about an ActivityPub activity
+/// sent by a Kleah actor and serialize
+/// this data into a JSON string.
+#[derive(Serialize)]
+pub struct ApubActivity{
+    #[serde(rename = "@context")]
+    pub context: String,
+    pub id: String,
+    #[serde(rename = "type")]
+    pub activity_type: String,
+    pub actor: String,
+    pub object: Value,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub to: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub cc: Vec<String>
+}
+
+/// A structure to contain data
+/// about an ActivityPub collection
+/// and serialize this data into
+/// a JSON string. The items of the
+/// collection are served on pages.
+#[derive(Serialize)]
+pub struct ApubCollection{
+    #[serde(rename = "@context")]
+    pub context: String,
+    pub id: String,
+    #[serde(rename = "type")]
+    pub collection_type: String,
+    #[serde(rename = "totalItems")]
+    pub total_items: i64,
+    pub first: String,
+    pub last: String
+}
+
+/// A structure to contain data
+/// about a single page of an
+/// ActivityPub collection and
+/// serialize this data into a
+/// JSON string.
+#[derive(Serialize)]
+pub struct ApubCollectionPage{
+    #[serde(rename = "@context")]
+    pub context: String,
+    pub id: String,
+    #[serde(rename = "type")]
+    pub page_type: String,
+    #[serde(rename = "partOf")]
+    pub part_of: String,
+    #[serde(rename = "totalItems")]
+    pub total_items: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prev: Option<String>,
+    #[serde(rename = "orderedItems")]
+    pub ordered_items: Vec<Value>
+}
+
+/// A structure to contain data
+/// about a note and serialize this
+/// data into a JSON string.
+#[derive(Serialize)]
+pub struct ApubNote{
+    #[serde(rename = "@context", skip_serializing_if = "Option::is_none")]
+    pub context: Option<String>,
+    pub id: String,
+    #[serde(rename = "type")]
+    pub note_type: String,
+    #[serde(rename = "attributedTo")]
+    pub attributed_to: String,
+    pub content: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub summary: Option<String>,
+    pub sensitive: bool,
+    pub published: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub updated: Option<String>,
+    #[serde(rename = "inReplyTo")]
+    pub in_reply_to: Option<String>,
+    #[serde(rename = "context", skip_serializing_if = "Option::is_none")]
+    pub thread_context: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub conversation: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub replies: Option<String>,
+    pub url: String,
+    pub to: Vec<String>,
+    pub cc: Vec<String>,
+    pub tag: Vec<ApubTag>,
+    #[serde(rename = "oneOf", skip_serializing_if = "Option::is_none")]
+    pub one_of: Option<Vec<ApubPollOption>>,
+    #[serde(rename = "anyOf", skip_serializing_if = "Option::is_none")]
+    pub any_of: Option<Vec<ApubPollOption>>,
+    #[serde(rename = "endTime", skip_serializing_if = "Option::is_none")]
+    pub end_time: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub closed: Option<String>,
+    #[serde(rename = "votersCount", skip_serializing_if = "Option::is_none")]
+    pub voters_count: Option<i64>
+}
+
+/// A structure to contain data
+/// about a tag of a note, such as
+/// an actor mentioned in it, and 
+/// serialize this data into a JSON 
+/// string.
+#[derive(Serialize)]
+pub struct ApubTag{
+    #[serde(rename = "type")]
+    pub tag_type: String,
+    pub href: String,
+    pub name: String
+}
+
+/// A structure to contain data
+/// about the tombstone of a deleted
+/// note and serialize this data into
+/// a JSON string.
+#[derive(Serialize)]
+pub struct ApubTombstone{
+    #[serde(rename = "@context", skip_serializing_if = "Option::is_none")]
+    pub context: Option<String>,
+    pub id: String,
+    #[serde(rename = "type")]
+    pub tombstone_type: String,
+    #[serde(rename = "formerType")]
+    pub former_type: String,
+    pub deleted: String
+}
+
+/// A structure to contain data
+/// about an option of a poll and
+/// serialize this data into a JSON
+/// string.
+#[derive(Serialize)]
+pub struct ApubPollOption{
+    #[serde(rename = "type")]
+    pub option_type: String,
+    pub name: String,
+    pub replies: ApubPollReplies
+}
+
+/// A structure to contain data
+/// about the votes for an option
+/// of a poll and serialize this
+/// data into a JSON string.
+#[derive(Serialize)]
+pub struct ApubPollReplies{
+    #[serde(rename = "type")]
+    pub replies_type: String,
+    #[serde(rename = "totalItems")]
+    pub total_items: i64
+}
+
+/// A structure to contain data
+/// about a vote for an option of
+/// a poll on another server and 
+/// serialize this data into a JSON
+/// string.
+#[derive(Serialize)]
+pub struct ApubPollVote{
+    pub id: String,
+    #[serde(rename = "type")]
+    pub vote_type: String,
+    pub name: String,
+    #[serde(rename = "attributedTo")]
+    pub attributed_to: String,
+    #[serde(rename = "inReplyTo")]
+    pub in_reply_to: String,
+    pub to: Vec<String>
+}
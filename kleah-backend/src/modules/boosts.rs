@@ -0,0 +1,444 @@
+/*
+Kleah by Alyx Shang.
+Licensed under the FSL v1.
+*/
+
+/// Importing the structure
+/// representing a pool of
+/// database connections.
+use sqlx::Pool;
+
+/// Importing the structure
+/// representing the UTC
+/// time zone.
+use chrono::Utc;
+
+/// Importing the macro
+/// to build JSON values.
+use serde_json::json;
+
+/// Importing the structure
+/// representing an arbitrary
+/// JSON value.
+use serde_json::Value;
+
+/// Importing the structure
+/// for catching and handling
+/// errors.
+use super::err::KleahErr;
+
+/// Importing the function
+/// to build the URL of a note.
+use super::utils::note_url;
+
+/// Importing the function
+/// to save a new boost.
+use super::db::create_boost;
+
+/// Importing the function
+/// to build the URL of an actor.
+use super::utils::actor_url;
+
+/// Importing the function
+/// to delete a boost.
+use super::db::destroy_boost;
+
+/// Importing the enum
+/// to describe the kind
+/// of error that occurred.
+use super::err::KleahErrType;
+
+/// Importing the structure
+/// representing a Postgres
+/// database.
+use sqlx::postgres::Postgres;
+
+/// Importing the structure
+/// modelling a note.
+use super::models::KleahNote;
+
+/// Importing the structure
+/// modelling a boost.
+use super::models::KleahBoost;
+
+/// Importing the function
+/// to retrieve a note by
+/// its ID.
+use super::db::get_note_by_id;
+
+/// Importing the structure
+/// modelling an actor.
+use super::models::KleahActor;
+
+/// Importing the structure
+/// modelling an ActivityPub
+/// activity.
+use super::apub::ApubActivity;
+
+/// Importing the function
+/// to retrieve a boost by
+/// its ID.
+use super::db::get_boost_by_id;
+
+/// Importing the function
+/// to retrieve an actor by
+/// their username and host.
+use super::db::get_actor_by_id;
+
+/// Importing the function
+/// to retrieve the boost of
+/// a note by an actor.
+use super::db::get_boost_between;
+
+/// Importing the function
+/// to retrieve the ID of an
+/// ActivityPub object.
+use super::federation::object_id;
+
+/// Importing the function
+/// to retrieve a note by
+/// its URL.
+use super::notes::get_note_by_url;
+
+/// Importing the constant
+/// containing the URI of the
+/// public collection.
+use super::apub::PUBLIC_COLLECTION;
+
+/// Importing the function
+/// to queue activities for
+/// delivery.
+use super::queue::deliver_activity;
+
+/// Importing the function
+/// to fetch and store notes
+/// from other servers.
+use super::notes::fetch_remote_note;
+
+/// Importing the function
+/// to retrieve the remote
+/// followers of an actor.
+use super::db::get_remote_followers;
+
+/// Importing the function
+/// to notify a local user.
+use super::notifications::notify_user;
+
+/// Importing the function
+/// to build a new activity.
+use super::federation::build_activity;
+
+/// Importing the function
+/// to check whether a note
+/// can be read by anyone.
+use super::notes::is_publicly_visible;
+
+/// Importing the function
+/// to build the URL of an
+/// actor's followers.
+use super::utils::actor_followers_url;
+
+/// Importing the function
+/// to retrieve the actor and
+/// note of an interaction.
+use super::notes::get_note_interaction;
+
+/// Importing the constant
+/// containing the URL of the
+/// ActivityStreams context.
+use super::apub::ACTIVITYSTREAMS_CONTEXT;
+
+/// A function to build the "Announce"
+/// activity of a stored boost so that it
+/// can be sent, served in the booster's
+/// outbox, or referenced in an "Undo"
+/// activity. Boosts share the visibility
+/// of the boosted note and are addressed
+/// to its author as well. The activity
+/// is returned.
+pub fn build_announce_activity(
+    boost: &KleahBoost,
+    booster: &KleahActor,
+    note: &KleahNote,
+    author: &KleahActor
+) -> ApubActivity {
+    let followers: String = actor_followers_url(booster);
+    let (to, cc): (Vec<String>, Vec<String>) = match note.visibility.as_str() {
+        "public" => (
+            vec![PUBLIC_COLLECTION.to_string()],
+            vec![followers, actor_url(author)]
+        ),
+        _ => (
+            vec![followers],
+            vec![PUBLIC_COLLECTION.to_string(), actor_url(author)]
+        )
+    };
+    ApubActivity{
+        context: ACTIVITYSTREAMS_CONTEXT.to_string(),
+        id: boost.boost_id.clone(),
+        activity_type: "Announce".to_string(),
+        actor: actor_url(booster),
+        object: Value::String(note_url(note)),
+        to,
+        cc
+    }
+}
+
+/// A function that attempts to gather
+/// the actors on other servers a boost
+/// is sent to. These are the booster's
+/// followers and the author of the boosted
+/// note. If the operation is successful, the
+/// actors are returned. If the operation fails,
+/// an error is returned.
+pub async fn get_boost_audience(
+    booster: &KleahActor,
+    author: &KleahActor,
+    pool: &Pool<Postgres>
+) -> Result<Vec<KleahActor>, KleahErr> {
+    let mut audience: Vec<KleahActor> = match get_remote_followers(booster, pool).await {
+        Ok(followers) => followers,
+        Err(e) => return Err::<Vec<KleahActor>, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    if author.ap_id.is_some() {
+        audience.push(author.clone());
+    }
+    Ok(audience)
+}
+
+/// A function that attempts to make the
+/// user with the given API token boost a note.
+/// Only public and unlisted notes can be boosted.
+/// Local authors are notified and the "Announce"
+/// activity is sent to the booster's followers and
+/// the author on other servers. Boosting a note
+/// twice has no effect. If the operation is
+/// successful, the boosted note is returned. If
+/// the operation fails, an error is returned.
+pub async fn boost_note(
+    api_token: &str,
+    note_id: &str,
+    pool: &Pool<Postgres>
+) -> Result<KleahNote, KleahErr> {
+    let (viewer, note, author): (KleahActor, KleahNote, KleahActor) = match get_note_interaction(
+        api_token,
+        note_id,
+        pool
+    ).await {
+        Ok(interaction) => interaction,
+        Err(e) => return Err::<KleahNote, KleahErr>(e)
+    };
+    if !is_publicly_visible(&note) {
+        return Err::<KleahNote, KleahErr>(
+            KleahErr::with_type(
+                "Only public and unlisted notes can be boosted.",
+                KleahErrType::BadRequest
+            )
+        )
+    }
+    let boost: KleahBoost = KleahBoost{
+        boost_id: build_activity(&viewer, "Announce", Value::String(note_url(&note))).id,
+        note_id: note.note_id.clone(),
+        actor_username: viewer.username.clone(),
+        actor_host: viewer.host.clone(),
+        created_at: Utc::now()
+    };
+    let is_new: bool = match create_boost(&boost, pool).await {
+        Ok(is_new) => is_new,
+        Err(e) => return Err::<KleahNote, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    if is_new {
+        match notify_user(&author, "boost", &viewer, Some(&note.note_id), pool).await {
+            Ok(_f) => {},
+            Err(e) => return Err::<KleahNote, KleahErr>(
+                KleahErr::new(&e.to_string())
+            )
+        };
+        let audience: Vec<KleahActor> = match get_boost_audience(&viewer, &author, pool).await {
+            Ok(audience) => audience,
+            Err(e) => return Err::<KleahNote, KleahErr>(e)
+        };
+        let announce: ApubActivity = build_announce_activity(&boost, &viewer, &note, &author);
+        match deliver_activity(&viewer.username, &audience, &announce, pool).await {
+            Ok(_f) => {},
+            Err(e) => return Err::<KleahNote, KleahErr>(
+                KleahErr::new(&e.to_string())
+            )
+        };
+    }
+    match get_note_by_id(&note.note_id, pool).await {
+        Ok(note) => Ok(note),
+        Err(e) => Err::<KleahNote, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    }
+}
+
+/// A function that attempts to remove the
+/// boost of a note by the user with the given
+/// API token. The booster's followers and the
+/// author on other servers are sent an "Undo"
+/// activity for the original "Announce" activity.
+/// If the operation is successful, the note is
+/// returned. If the note was not boosted or the
+/// operation fails, an error is returned.
+pub async fn unboost_note(
+    api_token: &str,
+    note_id: &str,
+    pool: &Pool<Postgres>
+) -> Result<KleahNote, KleahErr> {
+    let (viewer, note, author): (KleahActor, KleahNote, KleahActor) = match get_note_interaction(
+        api_token,
+        note_id,
+        pool
+    ).await {
+        Ok(interaction) => interaction,
+        Err(e) => return Err::<KleahNote, KleahErr>(e)
+    };
+    let boost: KleahBoost = match get_boost_between(&note.note_id, &viewer, pool).await {
+        Ok(boost) => boost,
+        Err(_e) => return Err::<KleahNote, KleahErr>(
+            KleahErr::with_type(
+                "The note has not been boosted.",
+                KleahErrType::NotFound
+            )
+        )
+    };
+    match destroy_boost(&boost.boost_id, pool).await {
+        Ok(_f) => {},
+        Err(e) => return Err::<KleahNote, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    let audience: Vec<KleahActor> = match get_boost_audience(&viewer, &author, pool).await {
+        Ok(audience) => audience,
+        Err(e) => return Err::<KleahNote, KleahErr>(e)
+    };
+    let announce: ApubActivity = build_announce_activity(&boost, &viewer, &note, &author);
+    let undo: ApubActivity = ApubActivity{
+        to: announce.to.clone(),
+        cc: announce.cc.clone(),
+        ..build_activity(&viewer, "Undo", json!(announce))
+    };
+    match deliver_activity(&viewer.username, &audience, &undo, pool).await {
+        Ok(_f) => {},
+        Err(e) => return Err::<KleahNote, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    match get_note_by_id(&note.note_id, pool).await {
+        Ok(note) => Ok(note),
+        Err(e) => Err::<KleahNote, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    }
+}
+
+/// A function that attempts to handle a
+/// received "Announce" activity. Boosted notes
+/// from other servers that are not known yet are
+/// fetched and stored. Boosts of notes that are
+/// neither public nor unlisted are ignored. Local
+/// authors are notified. If the operation fails,
+/// an error is returned.
+pub async fn handle_announce(
+    actor: &KleahActor,
+    activity: &Value,
+    pool: &Pool<Postgres>
+) -> Result<(), KleahErr> {
+    let boost_id: String = match object_id(activity){
+        Some(boost_id) => boost_id,
+        None => return Err::<(), KleahErr>(
+            KleahErr::with_type(
+                "The activity has no ID.",
+                KleahErrType::BadRequest
+            )
+        )
+    };
+    let target: String = match activity.get("object").and_then(object_id){
+        Some(target) => target,
+        None => return Err::<(), KleahErr>(
+            KleahErr::with_type(
+                "The activity has no object.",
+                KleahErrType::BadRequest
+            )
+        )
+    };
+    let note: KleahNote = match fetch_remote_note(&target, pool).await {
+        Ok(note) => note,
+        Err(e) => return Err::<(), KleahErr>(e)
+    };
+    if !is_publicly_visible(&note) {
+        return Ok(());
+    }
+    let boost: KleahBoost = KleahBoost{
+        boost_id,
+        note_id: note.note_id.clone(),
+        actor_username: actor.username.clone(),
+        actor_host: actor.host.clone(),
+        created_at: Utc::now()
+    };
+    let is_new: bool = match create_boost(&boost, pool).await {
+        Ok(is_new) => is_new,
+        Err(e) => return Err::<(), KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    if !is_new || note.ap_id.is_some() {
+        return Ok(());
+    }
+    let author: KleahActor = match get_actor_by_id(
+        &note.author_username,
+        &note.author_host,
+        pool
+    ).await {
+        Ok(author) => author,
+        Err(e) => return Err::<(), KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    notify_user(&author, "boost", actor, Some(&note.note_id), pool).await
+}
+
+/// A function that attempts to handle a
+/// received "Undo" activity for an "Announce"
+/// activity. The boost is looked up by its ID
+/// first and by the boosted note second, and it
+/// must belong to the given actor. Unknown boosts
+/// are ignored. If the operation fails, an error
+/// is returned.
+pub async fn handle_undo_announce(
+    actor: &KleahActor,
+    object: &Value,
+    pool: &Pool<Postgres>
+) -> Result<(), KleahErr> {
+    let by_id: Option<KleahBoost> = match object_id(object){
+        Some(boost_id) => get_boost_by_id(&boost_id, pool).await.ok(),
+        None => None
+    };
+    let boost: KleahBoost = match by_id {
+        Some(boost) => boost,
+        None => {
+            let target: String = match object.get("object").and_then(object_id){
+                Some(target) => target,
+                None => return Ok(())
+            };
+            let note: KleahNote = match get_note_by_url(&target, pool).await {
+                Ok(note) => note,
+                Err(_e) => return Ok(())
+            };
+            match get_boost_between(&note.note_id, actor, pool).await {
+                Ok(boost) => boost,
+                Err(_e) => return Ok(())
+            }
+        }
+    };
+    if boost.actor_username != actor.username || boost.actor_host != actor.host {
+        return Ok(());
+    }
+    destroy_boost(&boost.boost_id, pool).await
+}
@@ -13,6 +13,11 @@ use sqlx::Pool;
 /// that return nothing.
 use sqlx::query;
 
+/// Importing the structure
+/// representing the UTC
+/// time zone.
+use chrono::Utc;
+
 /// Importing the function
 /// from the "bcrypt" library
 /// to hash and salt a piece of
@@ -24,6 +29,15 @@ use bcrypt::hash;
 /// that return an object.
 use sqlx::query_as;
 
+/// Importing this structure
+/// to store points in time.
+use chrono::DateTime;
+
+/// Importing the structure
+/// representing a span
+/// of time.
+use chrono::TimeDelta;
+
 /// Importing the structure
 /// for catching and handling
 /// errors.
@@ -56,6 +70,19 @@ use sqlx::postgres::Postgres;
 /// in the database.
 use super::models::KleahUser;
 
+/// Importing the data structure
+/// modelling a note in
+/// the database.
+use super::models::KleahNote;
+
+/// Importing the structure
+/// modelling a like.
+use super::models::KleahLike;
+
+/// Importing the structure
+/// modelling a poll.
+use super::models::KleahPoll;
+
 /// Importing the data structure
 /// modelling data about an ActivityPub
 /// actor on a Kleah instance in the 
@@ -73,20 +100,93 @@ use super::models::InviteCode;
 /// string.
 use super::utils::hash_string;
 
+/// Importing the structure
+/// modelling a boost.
+use super::models::KleahBoost;
+
+/// Importing the data structure
+/// modelling a follow relationship
+/// in the database.
+use super::models::KleahFollow;
+
+/// Importing the structure
+/// modelling a report.
+use super::models::KleahReport;
+
+/// Importing the trait for
+/// anything that can run
+/// queries against a Postgres
+/// database.
+use sqlx::postgres::PgExecutor;
+
+/// Importing the structure
+/// modelling the position
+/// of an item on a page.
+use super::models::KleahCursor;
+
 /// Importing the data structure
 /// modelling a user's API token
 /// in the database.
 use super::models::UserAPIToken;
 
+/// Importing the data structure
+/// modelling an activity waiting
+/// to be delivered.
+use super::models::KleahDelivery;
+
+/// Importing the structure
+/// modelling a prior revision
+/// of a note.
+use super::models::KleahNoteEdit;
+
+/// Importing the structure
+/// modelling a vote
+/// in a poll.
+use super::models::KleahPollVote;
+
+/// Importing the data structure
+/// modelling a single item of an
+/// ActivityPub collection.
+use super::models::CollectionItem;
+
+/// Importing the structure
+/// modelling the tombstone
+/// of a deleted note.
+use super::models::KleahTombstone;
+
 /// Importing the function for generating
 /// an RSA keypair for a user.
 use super::utils::generate_keypair;
 
+/// Importing the data structure
+/// modelling the number of
+/// users on this instance.
+use super::models::KleahUserCounts;
+
+/// Importing the structure
+/// modelling an option
+/// of a poll.
+use super::models::KleahPollOption;
+
+/// Importing the structure
+/// modelling a notification.
+use super::models::KleahNotification;
+
+/// Importing the structure
+/// modelling an entry of
+/// a timeline.
+use super::models::KleahTimelineEntry;
+
 /// Importing the data structure
 /// modelling data about the current
 /// Kleah instance in the database.
 use super::models::InstanceInformation;
 
+/// Importing the structure
+/// modelling a muted kind
+/// of notifications.
+use super::models::KleahNotificationMute;
+
 /// A function to create a new record
 /// for a new Kleah user in the database.
 /// If the operation is successful, an 
@@ -134,7 +234,10 @@ pub async fn create_new_user(
         public_key: pair.public_key,
         description: description.to_string(),
         private_key: pair.private_key,
-        is_admin: *is_admin
+        is_admin: *is_admin,
+        is_locked: false,
+        last_active: None,
+        expand_spoilers: false
     };
     let _insert_op = match query!(
         "INSERT INTO users (name, username, password, email_addr, public_key, description, private_key, is_admin) VALUES ($1, $2, $3, $4, $5, $6, $7, $8)",
@@ -199,7 +302,11 @@ pub async fn create_new_actor(
         followers: followers_endpoint.to_string(), 
         username: username.to_string(), 
         description: description.to_string(), 
-        public_key: public_key_endpoint.to_string()
+        public_key: public_key_endpoint.to_string(),
+        ap_id: None,
+        key_id: None,
+        shared_inbox: None,
+        fetched_at: None
     };
     let _insert_op = match query!(
         "INSERT INTO actors (name, actor_type, host, liked, inbox, outbox, following, followers, username, description, public_key) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)",
@@ -305,7 +412,9 @@ pub async fn create_instance_info(
 ) -> Result<InstanceInformation, KleahErr>{
     let obj: InstanceInformation = InstanceInformation{ 
         host: host.to_string(),
-        uses_invites: *uses_invites
+        uses_invites: *uses_invites,
+        local_timeline: "public".to_string(),
+        federated_timeline: "public".to_string()
     };
     let _insert_op = match query!(
         "INSERT INTO instance_information (uses_invites, host) VALUES ($1, $2)",
@@ -321,7 +430,7 @@ pub async fn create_instance_info(
         )
     };
     let fetched: InstanceInformation = match get_instance_info(
-        &pool
+        pool
     ).await {
         Ok(fetched) => fetched,
         Err(e) => return Err::<InstanceInformation, KleahErr>(
@@ -343,14 +452,14 @@ pub async fn create_instance_info(
 /// successful, an instance of the `InstanceInformation` 
 /// structure is returned. If the operation 
 /// fails, an error is returned.
-pub async fn get_instance_info(
-    pool: &Pool<Postgres>
+pub async fn get_instance_info<'c>(
+    executor: impl PgExecutor<'c>
 ) -> Result<InstanceInformation, KleahErr>{
     let object: InstanceInformation = match query_as!(
         InstanceInformation,
         "SELECT * FROM instance_information",
     )
-        .fetch_one(pool)
+        .fetch_one(executor)
         .await 
     {
         Ok(object) => object,
@@ -461,8 +570,10 @@ pub async fn user_exists(
 /// from the database given one of their API
 /// tokens. If the operation is successful,
 /// an instance of the `KleahUser` structure
-/// is returned. If the operation fails, an 
-/// error is returned.
+/// is returned and the user is marked as
+/// active if they were last marked more
+/// than a day ago. If the operation fails,
+/// an error is returned.
 pub async fn get_user_by_token(
     token: &str,
     pool: &Pool<Postgres>
@@ -476,7 +587,7 @@ pub async fn get_user_by_token(
             KleahErr::new(&e.to_string())
         )
     };
-    let fetched: KleahUser = match get_user_by_id(
+    let mut fetched: KleahUser = match get_user_by_id(
         &fetched_token.username,
         pool
     ).await {
@@ -485,6 +596,21 @@ pub async fn get_user_by_token(
             KleahErr::new(&e.to_string())
         )
     };
+    let now: DateTime<Utc> = Utc::now();
+    let is_stale: bool = fetched.last_active
+        .is_none_or(|last_active| now - last_active >= TimeDelta::days(1));
+    if is_stale {
+        match update_last_active(
+            &fetched_token.username,
+            pool
+        ).await {
+            Ok(_f) => {},
+            Err(e) => return Err::<KleahUser, KleahErr>(
+                KleahErr::new(&e.to_string())
+            )
+        };
+        fetched.last_active = Some(now);
+    }
     Ok(fetched)
 }
 
@@ -622,9 +748,10 @@ pub async fn update_description(
         )
     };
     let update_op: () = match query!(
-        "UPDATE actors SET description = $1 WHERE username = $2",
+        "UPDATE actors SET description = $1 WHERE username = $2 AND host = $3",
         new_description,
-        fetched_actor.username
+        fetched_actor.username,
+        fetched_actor.host
     )
         .execute(pool)
         .await
@@ -690,9 +817,10 @@ pub async fn update_name(
         )
     };
     let update_op: () = match query!(
-        "UPDATE actors SET name = $1 WHERE username = $2",
+        "UPDATE actors SET name = $1 WHERE username = $2 AND host = $3",
         new_name,
-        fetched_actor.username
+        fetched_actor.username,
+        fetched_actor.host
     )
         .execute(pool)
         .await
@@ -849,6 +977,33 @@ pub async fn destroy_invite_code(
     Ok(del_op)
 }
 
+/// A function that attempts to delete
+/// the record for an invite code and to
+/// return it in one statement, so that a
+/// code can only be used once. If the
+/// operation is successful, the deleted
+/// code is returned or nothing if the code
+/// does not exist. If the operation fails,
+/// an error is returned.
+pub async fn consume_invite_code(
+    code: &str,
+    pool: &Pool<Postgres>
+) -> Result<Option<InviteCode>, KleahErr>{
+    match query_as!(
+        InviteCode,
+        "DELETE FROM invite_codes WHERE code = $1 RETURNING *",
+        code
+    )
+        .fetch_optional(pool)
+        .await 
+    {
+        Ok(object) => Ok(object),
+        Err(e) => Err::<Option<InviteCode>, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    }
+}
+
 /// Attempts to update the column of the instance 
 /// record in the database about whether an instance 
 /// uses invite codes or not. If the operation is 
@@ -881,3 +1036,2400 @@ pub async fn edit_invite_system(
     };
     Ok(update_op)
 }
+
+/// Attempts to update the columns of the instance 
+/// record in the database about who may read the
+/// local and the federated timeline. If the operation 
+/// is successful, nothing is returned. If the operation 
+/// fails, an error is returned.
+pub async fn edit_timeline_access(
+    local_timeline: &str,
+    federated_timeline: &str,
+    pool: &Pool<Postgres>
+) -> Result<(), KleahErr>{
+    let instance: InstanceInformation = match get_instance_info(
+        pool
+    ).await {
+        Ok(instance) => instance,
+        Err(e) => return Err::<(), KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    match query!(
+        "UPDATE instance_information SET local_timeline = $1, federated_timeline = $2 WHERE host = $3",
+        local_timeline,
+        federated_timeline,
+        instance.host
+    )
+        .execute(pool)
+        .await
+    {
+        Ok(_f) => Ok(()),
+        Err(e) => Err::<(), KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    }
+}
+
+/// A function to create or refresh the
+/// cached record of an ActivityPub actor
+/// from another server in the database.
+/// Records are keyed by the actor's username 
+/// and host and remember when they were last
+/// fetched. If the operation is successful, 
+/// an instance of the `KleahActor` structure 
+/// is returned. If the operation fails,
+/// an error is returned.
+pub async fn upsert_remote_actor(
+    actor: &KleahActor,
+    pool: &Pool<Postgres>
+) -> Result<KleahActor, KleahErr>{
+    match query!(
+        "INSERT INTO actors (name, actor_type, host, liked, inbox, outbox, following, followers, username, description, public_key, ap_id, key_id, shared_inbox, fetched_at) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, now()) ON CONFLICT (username, host) DO UPDATE SET name = EXCLUDED.name, actor_type = EXCLUDED.actor_type, liked = EXCLUDED.liked, inbox = EXCLUDED.inbox, outbox = EXCLUDED.outbox, following = EXCLUDED.following, followers = EXCLUDED.followers, description = EXCLUDED.description, public_key = EXCLUDED.public_key, ap_id = EXCLUDED.ap_id, key_id = EXCLUDED.key_id, shared_inbox = EXCLUDED.shared_inbox, fetched_at = EXCLUDED.fetched_at",
+        actor.name,
+        actor.actor_type,
+        actor.host,
+        actor.liked,
+        actor.inbox,
+        actor.outbox,
+        actor.following,
+        actor.followers,
+        actor.username,
+        actor.description,
+        actor.public_key,
+        actor.ap_id,
+        actor.key_id,
+        actor.shared_inbox
+    )
+        .execute(pool)
+        .await
+    {
+        Ok(_feedback) => {},
+        Err(e) => return Err::<KleahActor, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    let fetched: KleahActor = match get_actor_by_id(
+        &actor.username,
+        &actor.host,
+        pool
+    ).await {
+        Ok(fetched) => fetched,
+        Err(e) => return Err::<KleahActor, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    Ok(fetched)
+}
+
+/// A function that attempts to retrieve the
+/// cached record of an ActivityPub actor from
+/// another server given the ID of the actor's
+/// public key. If the operation is successful,
+/// an instance of the `KleahActor` structure 
+/// is returned. If the operation fails, an 
+/// error is returned.
+pub async fn get_actor_by_key_id(
+    key_id: &str,
+    pool: &Pool<Postgres>
+) -> Result<KleahActor, KleahErr>{
+    let object: KleahActor = match query_as!(
+        KleahActor,
+        "SELECT * FROM actors WHERE key_id = $1",
+        key_id
+    )
+        .fetch_one(pool)
+        .await 
+    {
+        Ok(object) => object,
+        Err(e) => return Err::<KleahActor, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    Ok(object)
+}
+
+/// A function to create a new record
+/// for a follow relationship between two
+/// actors in the database. If the two 
+/// actors already have a relationship, the 
+/// existing record is replaced. If the 
+/// operation is successful, an instance of 
+/// the `KleahFollow` structure is returned.
+/// If the operation fails, an error is 
+/// returned.
+pub async fn create_follow(
+    follow_id: &str,
+    follower: &KleahActor,
+    followee: &KleahActor,
+    accepted: &bool,
+    pool: &Pool<Postgres>
+) -> Result<KleahFollow, KleahErr>{
+    match query!(
+        "INSERT INTO follows (follow_id, follower_username, follower_host, followee_username, followee_host, accepted) VALUES ($1, $2, $3, $4, $5, $6) ON CONFLICT (follower_username, follower_host, followee_username, followee_host) DO UPDATE SET follow_id = EXCLUDED.follow_id, accepted = EXCLUDED.accepted",
+        follow_id,
+        follower.username,
+        follower.host,
+        followee.username,
+        followee.host,
+        *accepted
+    )
+        .execute(pool)
+        .await
+    {
+        Ok(_feedback) => {},
+        Err(e) => return Err::<KleahFollow, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    let fetched: KleahFollow = match get_follow_by_id(
+        follow_id,
+        pool
+    ).await {
+        Ok(fetched) => fetched,
+        Err(e) => return Err::<KleahFollow, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    Ok(fetched)
+}
+
+/// A function that attempts to retrieve a record
+/// about a follow relationship given the ID of the
+/// "Follow" activity that created it. If the operation 
+/// is successful, an instance of the `KleahFollow` 
+/// structure is returned. If the operation fails, 
+/// an error is returned.
+pub async fn get_follow_by_id(
+    follow_id: &str,
+    pool: &Pool<Postgres>
+) -> Result<KleahFollow, KleahErr>{
+    let object: KleahFollow = match query_as!(
+        KleahFollow,
+        "SELECT * FROM follows WHERE follow_id = $1",
+        follow_id
+    )
+        .fetch_one(pool)
+        .await 
+    {
+        Ok(object) => object,
+        Err(e) => return Err::<KleahFollow, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    Ok(object)
+}
+
+/// A function that attempts to retrieve a record
+/// about a follow relationship given the actors
+/// following and being followed. If the operation 
+/// is successful, an instance of the `KleahFollow` 
+/// structure is returned. If the operation fails, 
+/// an error is returned.
+pub async fn get_follow_between(
+    follower: &KleahActor,
+    followee: &KleahActor,
+    pool: &Pool<Postgres>
+) -> Result<KleahFollow, KleahErr>{
+    let object: KleahFollow = match query_as!(
+        KleahFollow,
+        "SELECT * FROM follows WHERE follower_username = $1 AND follower_host = $2 AND followee_username = $3 AND followee_host = $4",
+        follower.username,
+        follower.host,
+        followee.username,
+        followee.host
+    )
+        .fetch_one(pool)
+        .await 
+    {
+        Ok(object) => object,
+        Err(e) => return Err::<KleahFollow, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    Ok(object)
+}
+
+/// Attempts to mark the follow relationship
+/// with the given ID as accepted in the
+/// database. If the operation is successful, 
+/// nothing is returned. If the operation 
+/// fails, an error is returned.
+pub async fn accept_follow(
+    follow_id: &str,
+    pool: &Pool<Postgres>
+) -> Result<(), KleahErr>{
+    match query!(
+        "UPDATE follows SET accepted = true WHERE follow_id = $1",
+        follow_id
+    )
+        .execute(pool)
+        .await
+    {
+        Ok(_f) => Ok(()),
+        Err(e) => Err::<(), KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    }
+}
+
+/// A function that attempts to delete
+/// the record of the follow relationship
+/// with the given ID. If the operation fails, 
+/// an error is returned. If the operation is 
+/// successful, nothing is returned.
+pub async fn destroy_follow(
+    follow_id: &str,
+    pool: &Pool<Postgres>
+) -> Result<(), KleahErr>{
+    match query!(
+        "DELETE FROM follows WHERE follow_id = $1",
+        follow_id
+    )
+        .execute(pool)
+        .await 
+    {
+        Ok(_f) => Ok(()),
+        Err(e) => Err::<(), KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    }
+}
+
+/// Attempts to update the column of a user's
+/// record in the database about whether follow
+/// requests for the user have to be approved 
+/// manually. If the operation is successful, 
+/// nothing is returned. If the operation fails,
+/// an error is returned.
+pub async fn update_locked(
+    username: &str,
+    is_locked: &bool,
+    pool: &Pool<Postgres>
+) -> Result<(), KleahErr>{
+    match query!(
+        "UPDATE users SET is_locked = $1 WHERE username = $2",
+        *is_locked,
+        username
+    )
+        .execute(pool)
+        .await
+    {
+        Ok(_f) => Ok(()),
+        Err(e) => Err::<(), KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    }
+}
+
+/// Attempts to update the column of a user's
+/// record in the database about whether notes 
+/// with a content warning are expanded for the
+/// user by default. If the operation is successful,
+/// nothing is returned. If the operation fails,
+/// an error is returned.
+pub async fn update_expand_spoilers(
+    username: &str,
+    expand_spoilers: &bool,
+    pool: &Pool<Postgres>
+) -> Result<(), KleahErr>{
+    match query!(
+        "UPDATE users SET expand_spoilers = $1 WHERE username = $2",
+        *expand_spoilers,
+        username
+    )
+        .execute(pool)
+        .await
+    {
+        Ok(_f) => Ok(()),
+        Err(e) => Err::<(), KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    }
+}
+
+/// A function that attempts to retrieve the
+/// cached record of an ActivityPub actor from
+/// another server given the actor's ID. If the 
+/// operation is successful, an instance of the 
+/// `KleahActor` structure is returned. If the 
+/// operation fails, an error is returned.
+pub async fn get_actor_by_ap_id(
+    ap_id: &str,
+    pool: &Pool<Postgres>
+) -> Result<KleahActor, KleahErr>{
+    let object: KleahActor = match query_as!(
+        KleahActor,
+        "SELECT * FROM actors WHERE ap_id = $1",
+        ap_id
+    )
+        .fetch_one(pool)
+        .await 
+    {
+        Ok(object) => object,
+        Err(e) => return Err::<KleahActor, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    Ok(object)
+}
+
+/// A function that attempts to count the
+/// accepted followers of an actor. If the 
+/// operation is successful, the number of 
+/// followers is returned. If the operation 
+/// fails, an error is returned.
+pub async fn count_followers(
+    actor: &KleahActor,
+    pool: &Pool<Postgres>
+) -> Result<i64, KleahErr>{
+    match query!(
+        "SELECT COUNT(*) AS \"count!\" FROM follows WHERE followee_username = $1 AND followee_host = $2 AND accepted",
+        actor.username,
+        actor.host
+    )
+        .fetch_one(pool)
+        .await
+    {
+        Ok(row) => Ok(row.count),
+        Err(e) => Err::<i64, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    }
+}
+
+/// A function that attempts to count the
+/// actors an actor follows. If the operation
+/// is successful, the number of followed 
+/// actors is returned. If the operation 
+/// fails, an error is returned.
+pub async fn count_following(
+    actor: &KleahActor,
+    pool: &Pool<Postgres>
+) -> Result<i64, KleahErr>{
+    match query!(
+        "SELECT COUNT(*) AS \"count!\" FROM follows WHERE follower_username = $1 AND follower_host = $2 AND accepted",
+        actor.username,
+        actor.host
+    )
+        .fetch_one(pool)
+        .await
+    {
+        Ok(row) => Ok(row.count),
+        Err(e) => Err::<i64, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    }
+}
+
+/// A function that attempts to retrieve
+/// one page of the URLs of an actor's accepted 
+/// followers, newest first. Only followers 
+/// before `max_id` or after `min_id`
+/// are returned if these are supplied. Local 
+/// actors' URLs are built from `base`. If the 
+/// operation is successful, the items are returned.
+/// If the operation fails, an error is returned.
+pub async fn get_followers_page(
+    actor: &KleahActor,
+    base: &str,
+    max_id: Option<&KleahCursor>,
+    min_id: Option<&KleahCursor>,
+    limit: i64,
+    pool: &Pool<Postgres>
+) -> Result<Vec<CollectionItem>, KleahErr>{
+    let fetched: Result<Vec<CollectionItem>, sqlx::Error> = match min_id {
+        Some(min_id) => query_as!(
+            CollectionItem,
+            "SELECT COALESCE(actors.ap_id, $3 || '/apub/' || actors.username) AS \"item_id!\", follows.follow_id AS cursor_id, follows.created_at FROM follows JOIN actors ON actors.username = follows.follower_username AND actors.host = follows.follower_host WHERE follows.followee_username = $1 AND follows.followee_host = $2 AND follows.accepted AND (follows.created_at, follows.follow_id) > ($4, $5) ORDER BY follows.created_at ASC, follows.follow_id ASC LIMIT $6",
+            actor.username,
+            actor.host,
+            base,
+            min_id.created_at,
+            min_id.id,
+            limit
+        )
+            .fetch_all(pool)
+            .await
+            .map(|mut items| { items.reverse(); items }),
+        None => query_as!(
+            CollectionItem,
+            "SELECT COALESCE(actors.ap_id, $3 || '/apub/' || actors.username) AS \"item_id!\", follows.follow_id AS cursor_id, follows.created_at FROM follows JOIN actors ON actors.username = follows.follower_username AND actors.host = follows.follower_host WHERE follows.followee_username = $1 AND follows.followee_host = $2 AND follows.accepted AND ($4::timestamptz IS NULL OR (follows.created_at, follows.follow_id) < ($4, $5::text)) ORDER BY follows.created_at DESC, follows.follow_id DESC LIMIT $6",
+            actor.username,
+            actor.host,
+            base,
+            max_id.map(|max_id| max_id.created_at),
+            max_id.map(|max_id| max_id.id.as_str()),
+            limit
+        )
+            .fetch_all(pool)
+            .await
+    };
+    match fetched {
+        Ok(items) => Ok(items),
+        Err(e) => Err::<Vec<CollectionItem>, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    }
+}
+
+/// A function that attempts to retrieve
+/// one page of the URLs of the actors an 
+/// actor follows, newest first. Only follows 
+/// before `max_id` or after `min_id`
+/// are returned if these are supplied. Local 
+/// actors' URLs are built from `base`. If the 
+/// operation is successful, the items are returned.
+/// If the operation fails, an error is returned.
+pub async fn get_following_page(
+    actor: &KleahActor,
+    base: &str,
+    max_id: Option<&KleahCursor>,
+    min_id: Option<&KleahCursor>,
+    limit: i64,
+    pool: &Pool<Postgres>
+) -> Result<Vec<CollectionItem>, KleahErr>{
+    let fetched: Result<Vec<CollectionItem>, sqlx::Error> = match min_id {
+        Some(min_id) => query_as!(
+            CollectionItem,
+            "SELECT COALESCE(actors.ap_id, $3 || '/apub/' || actors.username) AS \"item_id!\", follows.follow_id AS cursor_id, follows.created_at FROM follows JOIN actors ON actors.username = follows.followee_username AND actors.host = follows.followee_host WHERE follows.follower_username = $1 AND follows.follower_host = $2 AND follows.accepted AND (follows.created_at, follows.follow_id) > ($4, $5) ORDER BY follows.created_at ASC, follows.follow_id ASC LIMIT $6",
+            actor.username,
+            actor.host,
+            base,
+            min_id.created_at,
+            min_id.id,
+            limit
+        )
+            .fetch_all(pool)
+            .await
+            .map(|mut items| { items.reverse(); items }),
+        None => query_as!(
+            CollectionItem,
+            "SELECT COALESCE(actors.ap_id, $3 || '/apub/' || actors.username) AS \"item_id!\", follows.follow_id AS cursor_id, follows.created_at FROM follows JOIN actors ON actors.username = follows.followee_username AND actors.host = follows.followee_host WHERE follows.follower_username = $1 AND follows.follower_host = $2 AND follows.accepted AND ($4::timestamptz IS NULL OR (follows.created_at, follows.follow_id) < ($4, $5::text)) ORDER BY follows.created_at DESC, follows.follow_id DESC LIMIT $6",
+            actor.username,
+            actor.host,
+            base,
+            max_id.map(|max_id| max_id.created_at),
+            max_id.map(|max_id| max_id.id.as_str()),
+            limit
+        )
+            .fetch_all(pool)
+            .await
+    };
+    match fetched {
+        Ok(items) => Ok(items),
+        Err(e) => Err::<Vec<CollectionItem>, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    }
+}
+
+/// A function that attempts to add an 
+/// activity to the queue of deliveries
+/// for the given inbox. If the activity 
+/// is already queued for this inbox, nothing
+/// is added. If the operation is successful, 
+/// an empty function is returned. If the
+/// operation fails, an error is returned.
+pub async fn create_delivery<'c>(
+    delivery_id: &str,
+    activity_id: &str,
+    sender_username: &str,
+    inbox: &str,
+    payload: &str,
+    executor: impl PgExecutor<'c>
+) -> Result<(), KleahErr>{
+    match query!(
+        "INSERT INTO deliveries (delivery_id, activity_id, sender_username, inbox, payload) VALUES ($1, $2, $3, $4, $5) ON CONFLICT (activity_id, inbox) DO NOTHING",
+        delivery_id,
+        activity_id,
+        sender_username,
+        inbox,
+        payload
+    )
+        .execute(executor)
+        .await 
+    {
+        Ok(_f) => Ok(()),
+        Err(e) => Err::<(), KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    }
+}
+
+/// A function that attempts to claim
+/// deliveries that are due for sending.
+/// Claimed deliveries are not handed out
+/// again until the given lease in seconds 
+/// has expired, so that deliveries of a
+/// worker that stopped are picked up later. 
+/// If the operation is successful, the claimed 
+/// deliveries are returned. If the operation 
+/// fails, an error is returned.
+pub async fn claim_deliveries(
+    limit: i64,
+    lease: f64,
+    pool: &Pool<Postgres>
+) -> Result<Vec<KleahDelivery>, KleahErr>{
+    let objects: Vec<KleahDelivery> = match query_as!(
+        KleahDelivery,
+        "UPDATE deliveries SET next_attempt_at = now() + make_interval(secs => $2) WHERE delivery_id IN (SELECT delivery_id FROM deliveries WHERE failed_at IS NULL AND next_attempt_at <= now() ORDER BY next_attempt_at LIMIT $1 FOR UPDATE SKIP LOCKED) RETURNING *",
+        limit,
+        lease
+    )
+        .fetch_all(pool)
+        .await 
+    {
+        Ok(objects) => objects,
+        Err(e) => return Err::<Vec<KleahDelivery>, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    Ok(objects)
+}
+
+/// A function that attempts to record a
+/// failed attempt to send a delivery. The
+/// delivery is retried after the given 
+/// delay in seconds or marked as failed
+/// if `give_up` is true. If the operation 
+/// is successful, an empty function is 
+/// returned. If the operation fails, an 
+/// error is returned.
+pub async fn record_delivery_failure(
+    delivery_id: &str,
+    error: &str,
+    delay: f64,
+    give_up: &bool,
+    pool: &Pool<Postgres>
+) -> Result<(), KleahErr>{
+    match query!(
+        "UPDATE deliveries SET attempts = attempts + 1, last_error = $2, next_attempt_at = now() + make_interval(secs => $3), failed_at = CASE WHEN $4 THEN now() ELSE NULL END WHERE delivery_id = $1",
+        delivery_id,
+        error,
+        delay,
+        give_up
+    )
+        .execute(pool)
+        .await 
+    {
+        Ok(_f) => Ok(()),
+        Err(e) => Err::<(), KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    }
+}
+
+/// A function that attempts to remove a
+/// delivery that was sent successfully
+/// from the queue. If the operation is 
+/// successful, an empty function is returned. 
+/// If the operation fails, an error is 
+/// returned.
+pub async fn destroy_delivery(
+    delivery_id: &str,
+    pool: &Pool<Postgres>
+) -> Result<(), KleahErr>{
+    match query!(
+        "DELETE FROM deliveries WHERE delivery_id = $1",
+        delivery_id
+    )
+        .execute(pool)
+        .await 
+    {
+        Ok(_f) => Ok(()),
+        Err(e) => Err::<(), KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    }
+}
+
+/// A function that attempts to retrieve
+/// all queued deliveries of an activity.
+/// If the operation is successful, the 
+/// deliveries are returned. If the operation 
+/// fails, an error is returned.
+pub async fn get_deliveries_for_activity(
+    activity_id: &str,
+    pool: &Pool<Postgres>
+) -> Result<Vec<KleahDelivery>, KleahErr>{
+    let objects: Vec<KleahDelivery> = match query_as!(
+        KleahDelivery,
+        "SELECT * FROM deliveries WHERE activity_id = $1 ORDER BY inbox",
+        activity_id
+    )
+        .fetch_all(pool)
+        .await 
+    {
+        Ok(objects) => objects,
+        Err(e) => return Err::<Vec<KleahDelivery>, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    Ok(objects)
+}
+
+/// A function that attempts to record that
+/// a user was active. The time is only
+/// updated once per day to spare the
+/// database writes. If the operation is
+/// successful, an empty function is returned.
+/// If the operation fails, an error is returned.
+pub async fn update_last_active(
+    username: &str,
+    pool: &Pool<Postgres>
+) -> Result<(), KleahErr>{
+    match query!(
+        "UPDATE users SET last_active = now() WHERE username = $1 AND (last_active IS NULL OR last_active < now() - interval '1 day')",
+        username
+    )
+        .execute(pool)
+        .await 
+    {
+        Ok(_f) => Ok(()),
+        Err(e) => Err::<(), KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    }
+}
+
+/// A function that attempts to count the
+/// users on this instance in total and those
+/// active in the last month and half-year. If 
+/// the operation is successful, the counts are
+/// returned. If the operation fails, an error 
+/// is returned.
+pub async fn count_users(
+    pool: &Pool<Postgres>
+) -> Result<KleahUserCounts, KleahErr>{
+    let counts: KleahUserCounts = match query_as!(
+        KleahUserCounts,
+        "SELECT COUNT(*) AS \"total!\", COUNT(*) FILTER (WHERE last_active > now() - interval '30 days') AS \"active_month!\", COUNT(*) FILTER (WHERE last_active > now() - interval '180 days') AS \"active_halfyear!\" FROM users"
+    )
+        .fetch_one(pool)
+        .await 
+    {
+        Ok(counts) => counts,
+        Err(e) => return Err::<KleahUserCounts, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    Ok(counts)
+}
+
+/// A function that attempts to retrieve
+/// all accepted followers of an actor that
+/// live on other servers. If the operation 
+/// is successful, the followers are returned. 
+/// If the operation fails, an error is returned.
+pub async fn get_remote_followers<'c>(
+    actor: &KleahActor,
+    executor: impl PgExecutor<'c>
+) -> Result<Vec<KleahActor>, KleahErr>{
+    let objects: Vec<KleahActor> = match query_as!(
+        KleahActor,
+        "SELECT actors.* FROM actors JOIN follows ON actors.username = follows.follower_username AND actors.host = follows.follower_host WHERE follows.followee_username = $1 AND follows.followee_host = $2 AND follows.accepted AND actors.ap_id IS NOT NULL",
+        actor.username,
+        actor.host
+    )
+        .fetch_all(executor)
+        .await 
+    {
+        Ok(objects) => objects,
+        Err(e) => return Err::<Vec<KleahActor>, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    Ok(objects)
+}
+
+/// A function that attempts to save a
+/// new note. Notes from other servers carry 
+/// their ActivityPub ID and the time they were
+/// published. If the operation is successful, 
+/// an instance of the `KleahNote` structure is 
+/// returned. If the operation fails, an error 
+/// is returned.
+pub async fn create_note<'c>(
+    note: &KleahNote,
+    executor: impl PgExecutor<'c>
+) -> Result<KleahNote, KleahErr>{
+    let object: KleahNote = match query_as!(
+        KleahNote,
+        "INSERT INTO notes (note_id, ap_id, author_username, author_host, content, in_reply_to, visibility, created_at, updated_at, conversation, summary, sensitive, has_poll) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13) RETURNING *",
+        note.note_id,
+        note.ap_id,
+        note.author_username,
+        note.author_host,
+        note.content,
+        note.in_reply_to,
+        note.visibility,
+        note.created_at,
+        note.updated_at,
+        note.conversation,
+        note.summary,
+        note.sensitive,
+        note.has_poll
+    )
+        .fetch_one(executor)
+        .await 
+    {
+        Ok(object) => object,
+        Err(e) => return Err::<KleahNote, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    Ok(object)
+}
+
+/// A function that attempts to retrieve
+/// a note by its ID. If the operation is 
+/// successful, an instance of the `KleahNote`
+/// structure is returned. If the operation
+/// fails, an error is returned.
+pub async fn get_note_by_id(
+    note_id: &str,
+    pool: &Pool<Postgres>
+) -> Result<KleahNote, KleahErr>{
+    let object: KleahNote = match query_as!(
+        KleahNote,
+        "SELECT * FROM notes WHERE note_id = $1",
+        note_id
+    )
+        .fetch_one(pool)
+        .await 
+    {
+        Ok(object) => object,
+        Err(e) => return Err::<KleahNote, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    Ok(object)
+}
+
+/// A function that attempts to retrieve
+/// a note from another server by its 
+/// ActivityPub ID. If the operation is 
+/// successful, an instance of the `KleahNote`
+/// structure is returned. If the operation
+/// fails, an error is returned.
+pub async fn get_note_by_ap_id(
+    ap_id: &str,
+    pool: &Pool<Postgres>
+) -> Result<KleahNote, KleahErr>{
+    let object: KleahNote = match query_as!(
+        KleahNote,
+        "SELECT * FROM notes WHERE ap_id = $1",
+        ap_id
+    )
+        .fetch_one(pool)
+        .await 
+    {
+        Ok(object) => object,
+        Err(e) => return Err::<KleahNote, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    Ok(object)
+}
+
+/// A function that attempts to delete
+/// a note. If the operation is successful, 
+/// an empty function is returned. If the 
+/// operation fails, an error is returned.
+pub async fn destroy_note(
+    note_id: &str,
+    pool: &Pool<Postgres>
+) -> Result<(), KleahErr>{
+    match query!(
+        "DELETE FROM notes WHERE note_id = $1",
+        note_id
+    )
+        .execute(pool)
+        .await 
+    {
+        Ok(_f) => Ok(()),
+        Err(e) => Err::<(), KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    }
+}
+
+/// A function that attempts to count the
+/// notes written by actors of the given
+/// host. If the operation is successful, 
+/// the number of notes is returned. If the 
+/// operation fails, an error is returned.
+pub async fn count_notes_by_host(
+    host: &str,
+    pool: &Pool<Postgres>
+) -> Result<i64, KleahErr>{
+    match query!(
+        "SELECT COUNT(*) AS \"count!\" FROM notes WHERE author_host = $1",
+        host
+    )
+        .fetch_one(pool)
+        .await
+    {
+        Ok(row) => Ok(row.count),
+        Err(e) => Err::<i64, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    }
+}
+
+/// A function that attempts to count the
+/// notes and boosts of an actor that are shown
+/// in the actor's outbox. If the operation is 
+/// successful, the number of items is returned.
+/// If the operation fails, an error is returned.
+pub async fn count_outbox_items(
+    actor: &KleahActor,
+    pool: &Pool<Postgres>
+) -> Result<i64, KleahErr>{
+    match query!(
+        "SELECT (SELECT COUNT(*) FROM notes WHERE author_username = $1 AND author_host = $2 AND visibility IN ('public', 'unlisted')) + (SELECT COUNT(*) FROM boosts WHERE actor_username = $1 AND actor_host = $2) AS \"count!\"",
+        actor.username,
+        actor.host
+    )
+        .fetch_one(pool)
+        .await
+    {
+        Ok(row) => Ok(row.count),
+        Err(e) => Err::<i64, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    }
+}
+
+/// A function that attempts to retrieve
+/// one page of the notes and boosts shown in
+/// an actor's outbox, newest first. Only items 
+/// before `max_id` or after `min_id` 
+/// are returned if these are supplied. If the 
+/// operation is successful, the entries are 
+/// returned. If the operation fails, an error 
+/// is returned.
+pub async fn get_outbox_page(
+    actor: &KleahActor,
+    max_id: Option<&KleahCursor>,
+    min_id: Option<&KleahCursor>,
+    limit: i64,
+    pool: &Pool<Postgres>
+) -> Result<Vec<KleahTimelineEntry>, KleahErr>{
+    let fetched: Result<Vec<KleahTimelineEntry>, sqlx::Error> = match min_id {
+        Some(min_id) => query_as!(
+            KleahTimelineEntry,
+            "SELECT outbox.note_id AS \"note_id!\", outbox.boost_id, outbox.booster_username, outbox.booster_host, outbox.created_at AS \"created_at!\" FROM ((SELECT note_id, NULL::text AS boost_id, NULL::text AS booster_username, NULL::text AS booster_host, created_at FROM notes WHERE author_username = $1 AND author_host = $2 AND visibility IN ('public', 'unlisted') AND (created_at, note_id) > ($3, $4) ORDER BY created_at ASC, note_id ASC LIMIT $5) UNION ALL (SELECT note_id, boost_id, actor_username, actor_host, created_at FROM boosts WHERE actor_username = $1 AND actor_host = $2 AND (created_at, boost_id) > ($3, $4) ORDER BY created_at ASC, boost_id ASC LIMIT $5)) AS outbox ORDER BY outbox.created_at ASC, COALESCE(outbox.boost_id, outbox.note_id) ASC LIMIT $5",
+            actor.username,
+            actor.host,
+            min_id.created_at,
+            min_id.id,
+            limit
+        )
+            .fetch_all(pool)
+            .await
+            .map(|mut entries| { entries.reverse(); entries }),
+        None => query_as!(
+            KleahTimelineEntry,
+            "SELECT outbox.note_id AS \"note_id!\", outbox.boost_id, outbox.booster_username, outbox.booster_host, outbox.created_at AS \"created_at!\" FROM ((SELECT note_id, NULL::text AS boost_id, NULL::text AS booster_username, NULL::text AS booster_host, created_at FROM notes WHERE author_username = $1 AND author_host = $2 AND visibility IN ('public', 'unlisted') AND ($3::timestamptz IS NULL OR (created_at, note_id) < ($3, $4::text)) ORDER BY created_at DESC, note_id DESC LIMIT $5) UNION ALL (SELECT note_id, boost_id, actor_username, actor_host, created_at FROM boosts WHERE actor_username = $1 AND actor_host = $2 AND ($3::timestamptz IS NULL OR (created_at, boost_id) < ($3, $4::text)) ORDER BY created_at DESC, boost_id DESC LIMIT $5)) AS outbox ORDER BY outbox.created_at DESC, COALESCE(outbox.boost_id, outbox.note_id) DESC LIMIT $5",
+            actor.username,
+            actor.host,
+            max_id.map(|max_id| max_id.created_at),
+            max_id.map(|max_id| max_id.id.as_str()),
+            limit
+        )
+            .fetch_all(pool)
+            .await
+    };
+    match fetched {
+        Ok(entries) => Ok(entries),
+        Err(e) => Err::<Vec<KleahTimelineEntry>, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    }
+}
+
+/// A function that attempts to record that
+/// a note is explicitly addressed to an actor.
+/// If the operation is successful, an empty 
+/// function is returned. If the operation fails,
+/// an error is returned.
+pub async fn add_note_recipient<'c>(
+    note_id: &str,
+    actor: &KleahActor,
+    executor: impl PgExecutor<'c>
+) -> Result<(), KleahErr>{
+    match query!(
+        "INSERT INTO note_recipients (note_id, actor_username, actor_host) VALUES ($1, $2, $3) ON CONFLICT DO NOTHING",
+        note_id,
+        actor.username,
+        actor.host
+    )
+        .execute(executor)
+        .await 
+    {
+        Ok(_f) => Ok(()),
+        Err(e) => Err::<(), KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    }
+}
+
+/// A function that attempts to retrieve
+/// all actors a note is explicitly addressed
+/// to. If the operation is successful, the 
+/// actors are returned. If the operation fails, 
+/// an error is returned.
+pub async fn get_note_recipients<'c>(
+    note_id: &str,
+    executor: impl PgExecutor<'c>
+) -> Result<Vec<KleahActor>, KleahErr>{
+    let objects: Vec<KleahActor> = match query_as!(
+        KleahActor,
+        "SELECT actors.* FROM actors JOIN note_recipients ON actors.username = note_recipients.actor_username AND actors.host = note_recipients.actor_host WHERE note_recipients.note_id = $1 ORDER BY actors.host, actors.username",
+        note_id
+    )
+        .fetch_all(executor)
+        .await 
+    {
+        Ok(objects) => objects,
+        Err(e) => return Err::<Vec<KleahActor>, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    Ok(objects)
+}
+
+/// A function that attempts to check whether
+/// an actor may see a note that is not public.
+/// This is the case if the actor is the note's
+/// author, is addressed by the note or follows 
+/// the author of a followers-only note. If the 
+/// operation is successful, a boolean reflecting 
+/// this is returned. If the operation fails, an 
+/// error is returned.
+pub async fn note_is_visible_to(
+    note: &KleahNote,
+    actor: &KleahActor,
+    pool: &Pool<Postgres>
+) -> Result<bool, KleahErr>{
+    match query!(
+        "SELECT ($3 = $5 AND $4 = $6) OR EXISTS (SELECT 1 FROM note_recipients WHERE note_id = $1 AND actor_username = $5 AND actor_host = $6) OR ($2 = 'followers' AND EXISTS (SELECT 1 FROM follows WHERE follower_username = $5 AND follower_host = $6 AND followee_username = $3 AND followee_host = $4 AND accepted)) AS \"visible!\"",
+        note.note_id,
+        note.visibility,
+        note.author_username,
+        note.author_host,
+        actor.username,
+        actor.host
+    )
+        .fetch_one(pool)
+        .await
+    {
+        Ok(row) => Ok(row.visible),
+        Err(e) => Err::<bool, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    }
+}
+
+/// A function that attempts to retrieve
+/// one page of an actor's home timeline, newest 
+/// first. Entries created at the same time are
+/// ordered by their ID. The timeline holds the
+/// actor's own notes and boosts, the notes of the
+/// actors they follow that are not direct, the
+/// boosts of these actors, and the notes addressed
+/// to them. Every followed actor's notes and boosts
+/// are read through their own index so that no page
+/// needs more rows than its size from any one actor.
+/// Only entries before `max_id` or after `min_id`
+/// are returned if these are supplied. If the 
+/// operation is successful, the entries are returned.
+/// If the operation fails, an error is returned.
+pub async fn get_home_timeline(
+    actor: &KleahActor,
+    max_id: Option<&KleahCursor>,
+    min_id: Option<&KleahCursor>,
+    limit: i64,
+    pool: &Pool<Postgres>
+) -> Result<Vec<KleahTimelineEntry>, KleahErr>{
+    let fetched: Result<Vec<KleahTimelineEntry>, sqlx::Error> = match min_id {
+        Some(min_id) => query_as!(
+            KleahTimelineEntry,
+            "WITH sources AS (SELECT follows.followee_username AS username, follows.followee_host AS host FROM follows WHERE follows.follower_username = $1 AND follows.follower_host = $2 AND follows.accepted UNION SELECT $1::text, $2::text) SELECT timeline.note_id AS \"note_id!\", timeline.boost_id, timeline.booster_username, timeline.booster_host, timeline.created_at AS \"created_at!\" FROM (SELECT authored.note_id, NULL::text AS boost_id, NULL::text AS booster_username, NULL::text AS booster_host, authored.created_at FROM sources CROSS JOIN LATERAL (SELECT notes.note_id, notes.created_at FROM notes WHERE notes.author_username = sources.username AND notes.author_host = sources.host AND (notes.visibility <> 'direct' OR (notes.author_username = $1 AND notes.author_host = $2)) AND (notes.created_at, notes.note_id) > ($3, $4) ORDER BY notes.created_at ASC, notes.note_id ASC LIMIT $5) AS authored UNION SELECT boosted.note_id, boosted.boost_id, boosted.actor_username, boosted.actor_host, boosted.created_at FROM sources CROSS JOIN LATERAL (SELECT boosts.* FROM boosts JOIN notes ON notes.note_id = boosts.note_id WHERE boosts.actor_username = sources.username AND boosts.actor_host = sources.host AND notes.visibility IN ('public', 'unlisted') AND (boosts.created_at, boosts.boost_id) > ($3, $4) ORDER BY boosts.created_at ASC, boosts.boost_id ASC LIMIT $5) AS boosted UNION SELECT notes.note_id, NULL::text, NULL::text, NULL::text, notes.created_at FROM note_recipients JOIN notes ON notes.note_id = note_recipients.note_id WHERE note_recipients.actor_username = $1 AND note_recipients.actor_host = $2 AND (notes.created_at, notes.note_id) > ($3, $4)) AS timeline ORDER BY timeline.created_at ASC, COALESCE(timeline.boost_id, timeline.note_id) ASC LIMIT $5",
+            actor.username,
+            actor.host,
+            min_id.created_at,
+            min_id.id,
+            limit
+        )
+            .fetch_all(pool)
+            .await
+            .map(|mut entries| { entries.reverse(); entries }),
+        None => query_as!(
+            KleahTimelineEntry,
+            "WITH sources AS (SELECT follows.followee_username AS username, follows.followee_host AS host FROM follows WHERE follows.follower_username = $1 AND follows.follower_host = $2 AND follows.accepted UNION SELECT $1::text, $2::text) SELECT timeline.note_id AS \"note_id!\", timeline.boost_id, timeline.booster_username, timeline.booster_host, timeline.created_at AS \"created_at!\" FROM (SELECT authored.note_id, NULL::text AS boost_id, NULL::text AS booster_username, NULL::text AS booster_host, authored.created_at FROM sources CROSS JOIN LATERAL (SELECT notes.note_id, notes.created_at FROM notes WHERE notes.author_username = sources.username AND notes.author_host = sources.host AND (notes.visibility <> 'direct' OR (notes.author_username = $1 AND notes.author_host = $2)) AND ($3::timestamptz IS NULL OR (notes.created_at, notes.note_id) < ($3, $4::text)) ORDER BY notes.created_at DESC, notes.note_id DESC LIMIT $5) AS authored UNION SELECT boosted.note_id, boosted.boost_id, boosted.actor_username, boosted.actor_host, boosted.created_at FROM sources CROSS JOIN LATERAL (SELECT boosts.* FROM boosts JOIN notes ON notes.note_id = boosts.note_id WHERE boosts.actor_username = sources.username AND boosts.actor_host = sources.host AND notes.visibility IN ('public', 'unlisted') AND ($3::timestamptz IS NULL OR (boosts.created_at, boosts.boost_id) < ($3, $4::text)) ORDER BY boosts.created_at DESC, boosts.boost_id DESC LIMIT $5) AS boosted UNION SELECT notes.note_id, NULL::text, NULL::text, NULL::text, notes.created_at FROM note_recipients JOIN notes ON notes.note_id = note_recipients.note_id WHERE note_recipients.actor_username = $1 AND note_recipients.actor_host = $2 AND ($3::timestamptz IS NULL OR (notes.created_at, notes.note_id) < ($3, $4::text))) AS timeline ORDER BY timeline.created_at DESC, COALESCE(timeline.boost_id, timeline.note_id) DESC LIMIT $5",
+            actor.username,
+            actor.host,
+            max_id.map(|max_id| max_id.created_at),
+            max_id.map(|max_id| max_id.id.as_str()),
+            limit
+        )
+            .fetch_all(pool)
+            .await
+    };
+    match fetched {
+        Ok(entries) => Ok(entries),
+        Err(e) => Err::<Vec<KleahTimelineEntry>, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    }
+}
+
+/// A function that attempts to retrieve
+/// one page of the public notes written by
+/// actors from the given host, newest first.
+/// Only notes before `max_id` or after
+/// `min_id` are returned if these are supplied.
+/// If the operation is successful, the notes are
+/// returned. If the operation fails, an error is
+/// returned.
+pub async fn get_local_timeline(
+    host: &str,
+    max_id: Option<&KleahCursor>,
+    min_id: Option<&KleahCursor>,
+    limit: i64,
+    pool: &Pool<Postgres>
+) -> Result<Vec<KleahNote>, KleahErr>{
+    let fetched: Result<Vec<KleahNote>, sqlx::Error> = match min_id {
+        Some(min_id) => query_as!(
+            KleahNote,
+            "SELECT * FROM notes WHERE visibility = 'public' AND author_host = $1 AND (created_at, note_id) > ($2, $3) ORDER BY created_at ASC, note_id ASC LIMIT $4",
+            host,
+            min_id.created_at,
+            min_id.id,
+            limit
+        )
+            .fetch_all(pool)
+            .await
+            .map(|mut notes| { notes.reverse(); notes }),
+        None => query_as!(
+            KleahNote,
+            "SELECT * FROM notes WHERE visibility = 'public' AND author_host = $1 AND ($2::timestamptz IS NULL OR (created_at, note_id) < ($2, $3::text)) ORDER BY created_at DESC, note_id DESC LIMIT $4",
+            host,
+            max_id.map(|max_id| max_id.created_at),
+            max_id.map(|max_id| max_id.id.as_str()),
+            limit
+        )
+            .fetch_all(pool)
+            .await
+    };
+    match fetched {
+        Ok(notes) => Ok(notes),
+        Err(e) => Err::<Vec<KleahNote>, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    }
+}
+
+/// A function that attempts to retrieve
+/// one page of all public notes known to
+/// this instance, newest first. Only notes 
+/// before `max_id` or after `min_id`
+/// are returned if these are supplied. If the
+/// operation is successful, the notes are returned.
+/// If the operation fails, an error is returned.
+pub async fn get_federated_timeline(
+    max_id: Option<&KleahCursor>,
+    min_id: Option<&KleahCursor>,
+    limit: i64,
+    pool: &Pool<Postgres>
+) -> Result<Vec<KleahNote>, KleahErr>{
+    let fetched: Result<Vec<KleahNote>, sqlx::Error> = match min_id {
+        Some(min_id) => query_as!(
+            KleahNote,
+            "SELECT * FROM notes WHERE visibility = 'public' AND (created_at, note_id) > ($1, $2) ORDER BY created_at ASC, note_id ASC LIMIT $3",
+            min_id.created_at,
+            min_id.id,
+            limit
+        )
+            .fetch_all(pool)
+            .await
+            .map(|mut notes| { notes.reverse(); notes }),
+        None => query_as!(
+            KleahNote,
+            "SELECT * FROM notes WHERE visibility = 'public' AND ($1::timestamptz IS NULL OR (created_at, note_id) < ($1, $2::text)) ORDER BY created_at DESC, note_id DESC LIMIT $3",
+            max_id.map(|max_id| max_id.created_at),
+            max_id.map(|max_id| max_id.id.as_str()),
+            limit
+        )
+            .fetch_all(pool)
+            .await
+    };
+    match fetched {
+        Ok(notes) => Ok(notes),
+        Err(e) => Err::<Vec<KleahNote>, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    }
+}
+
+/// A function that attempts to save a
+/// new notification for a local user. 
+/// Notifications of a kind the user muted
+/// are not saved. If the operation is 
+/// successful, an empty function is returned.
+/// If the operation fails, an error is returned.
+pub async fn create_notification<'c>(
+    notification: &KleahNotification,
+    executor: impl PgExecutor<'c>
+) -> Result<(), KleahErr>{
+    match query!(
+        "INSERT INTO notifications (notification_id, username, kind, actor_username, actor_host, note_id, created_at, is_read, report_id) SELECT $1, $2, $3, $4, $5, $6, $7, $8, $9 WHERE NOT EXISTS (SELECT 1 FROM notification_mutes WHERE username = $2 AND kind = $3) ON CONFLICT DO NOTHING",
+        notification.notification_id,
+        notification.username,
+        notification.kind,
+        notification.actor_username,
+        notification.actor_host,
+        notification.note_id,
+        notification.created_at,
+        notification.is_read,
+        notification.report_id
+    )
+        .execute(executor)
+        .await
+    {
+        Ok(_f) => Ok(()),
+        Err(e) => Err::<(), KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    }
+}
+
+/// A function that attempts to save a
+/// like of a note and to count it on the
+/// note. Likes that are already stored are
+/// ignored. If the operation is successful,
+/// a boolean reflecting whether the like
+/// is new is returned. If the operation 
+/// fails, an error is returned.
+pub async fn create_like(
+    like: &KleahLike,
+    pool: &Pool<Postgres>
+) -> Result<bool, KleahErr>{
+    match query!(
+        "WITH inserted AS (INSERT INTO likes (like_id, note_id, actor_username, actor_host, created_at) VALUES ($1, $2, $3, $4, $5) ON CONFLICT DO NOTHING RETURNING note_id) UPDATE notes SET like_count = like_count + 1 WHERE note_id IN (SELECT note_id FROM inserted)",
+        like.like_id,
+        like.note_id,
+        like.actor_username,
+        like.actor_host,
+        like.created_at
+    )
+        .execute(pool)
+        .await
+    {
+        Ok(result) => Ok(result.rows_affected() > 0),
+        Err(e) => Err::<bool, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    }
+}
+
+/// A function that attempts to retrieve
+/// a like by its ID. If the operation is 
+/// successful, an instance of the `KleahLike`
+/// structure is returned. If the operation 
+/// fails, an error is returned.
+pub async fn get_like_by_id(
+    like_id: &str,
+    pool: &Pool<Postgres>
+) -> Result<KleahLike, KleahErr>{
+    match query_as!(
+        KleahLike,
+        "SELECT * FROM likes WHERE like_id = $1",
+        like_id
+    )
+        .fetch_one(pool)
+        .await
+    {
+        Ok(like) => Ok(like),
+        Err(e) => Err::<KleahLike, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    }
+}
+
+/// A function that attempts to retrieve
+/// the like of a note by an actor. If the 
+/// operation is successful, an instance of 
+/// the `KleahLike` structure is returned. If 
+/// the operation fails, an error is returned.
+pub async fn get_like_between(
+    note_id: &str,
+    actor: &KleahActor,
+    pool: &Pool<Postgres>
+) -> Result<KleahLike, KleahErr>{
+    match query_as!(
+        KleahLike,
+        "SELECT * FROM likes WHERE note_id = $1 AND actor_username = $2 AND actor_host = $3",
+        note_id,
+        actor.username,
+        actor.host
+    )
+        .fetch_one(pool)
+        .await
+    {
+        Ok(like) => Ok(like),
+        Err(e) => Err::<KleahLike, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    }
+}
+
+/// A function that attempts to delete
+/// a like and to no longer count it on 
+/// the liked note. If the operation is 
+/// successful, an empty function is returned.
+/// If the operation fails, an error is returned.
+pub async fn destroy_like(
+    like_id: &str,
+    pool: &Pool<Postgres>
+) -> Result<(), KleahErr>{
+    match query!(
+        "WITH deleted AS (DELETE FROM likes WHERE like_id = $1 RETURNING note_id) UPDATE notes SET like_count = like_count - 1 WHERE note_id IN (SELECT note_id FROM deleted)",
+        like_id
+    )
+        .execute(pool)
+        .await
+    {
+        Ok(_f) => Ok(()),
+        Err(e) => Err::<(), KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    }
+}
+
+/// A function that attempts to count the
+/// public and unlisted notes an actor has 
+/// liked. If the operation is successful, the
+/// number of notes is returned. If the operation
+/// fails, an error is returned.
+pub async fn count_liked(
+    actor: &KleahActor,
+    pool: &Pool<Postgres>
+) -> Result<i64, KleahErr>{
+    match query!(
+        "SELECT COUNT(*) AS \"count!\" FROM likes JOIN notes ON notes.note_id = likes.note_id WHERE likes.actor_username = $1 AND likes.actor_host = $2 AND notes.visibility IN ('public', 'unlisted')",
+        actor.username,
+        actor.host
+    )
+        .fetch_one(pool)
+        .await
+    {
+        Ok(row) => Ok(row.count),
+        Err(e) => Err::<i64, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    }
+}
+
+/// A function that attempts to retrieve
+/// one page of the URLs of the public and
+/// unlisted notes an actor has liked, newest
+/// first. Only likes before `max_id` or
+/// after `min_id` are returned if these
+/// are supplied. Local notes' URLs are built
+/// from `base`. If the operation is successful,
+/// the items are returned. If the operation 
+/// fails, an error is returned.
+pub async fn get_liked_page(
+    actor: &KleahActor,
+    base: &str,
+    max_id: Option<&KleahCursor>,
+    min_id: Option<&KleahCursor>,
+    limit: i64,
+    pool: &Pool<Postgres>
+) -> Result<Vec<CollectionItem>, KleahErr>{
+    let fetched: Result<Vec<CollectionItem>, sqlx::Error> = match min_id {
+        Some(min_id) => query_as!(
+            CollectionItem,
+            "SELECT COALESCE(notes.ap_id, $3 || '/apub/notes/' || notes.note_id) AS \"item_id!\", likes.like_id AS cursor_id, likes.created_at FROM likes JOIN notes ON notes.note_id = likes.note_id WHERE likes.actor_username = $1 AND likes.actor_host = $2 AND notes.visibility IN ('public', 'unlisted') AND (likes.created_at, likes.like_id) > ($4, $5) ORDER BY likes.created_at ASC, likes.like_id ASC LIMIT $6",
+            actor.username,
+            actor.host,
+            base,
+            min_id.created_at,
+            min_id.id,
+            limit
+        )
+            .fetch_all(pool)
+            .await
+            .map(|mut items| { items.reverse(); items }),
+        None => query_as!(
+            CollectionItem,
+            "SELECT COALESCE(notes.ap_id, $3 || '/apub/notes/' || notes.note_id) AS \"item_id!\", likes.like_id AS cursor_id, likes.created_at FROM likes JOIN notes ON notes.note_id = likes.note_id WHERE likes.actor_username = $1 AND likes.actor_host = $2 AND notes.visibility IN ('public', 'unlisted') AND ($4::timestamptz IS NULL OR (likes.created_at, likes.like_id) < ($4, $5::text)) ORDER BY likes.created_at DESC, likes.like_id DESC LIMIT $6",
+            actor.username,
+            actor.host,
+            base,
+            max_id.map(|max_id| max_id.created_at),
+            max_id.map(|max_id| max_id.id.as_str()),
+            limit
+        )
+            .fetch_all(pool)
+            .await
+    };
+    match fetched {
+        Ok(items) => Ok(items),
+        Err(e) => Err::<Vec<CollectionItem>, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    }
+}
+
+/// A function that attempts to retrieve
+/// the notes with the given IDs. Notes that 
+/// do not exist are skipped. If the operation 
+/// is successful, the notes are returned. If
+/// the operation fails, an error is returned.
+pub async fn get_notes_by_ids(
+    note_ids: &[String],
+    pool: &Pool<Postgres>
+) -> Result<Vec<KleahNote>, KleahErr>{
+    match query_as!(
+        KleahNote,
+        "SELECT * FROM notes WHERE note_id = ANY($1)",
+        note_ids
+    )
+        .fetch_all(pool)
+        .await
+    {
+        Ok(notes) => Ok(notes),
+        Err(e) => Err::<Vec<KleahNote>, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    }
+}
+
+/// A function that attempts to save a
+/// boost of a note and to count it on the
+/// note. Boosts that are already stored are
+/// ignored. If the operation is successful,
+/// a boolean reflecting whether the boost
+/// is new is returned. If the operation 
+/// fails, an error is returned.
+pub async fn create_boost(
+    boost: &KleahBoost,
+    pool: &Pool<Postgres>
+) -> Result<bool, KleahErr>{
+    match query!(
+        "WITH inserted AS (INSERT INTO boosts (boost_id, note_id, actor_username, actor_host, created_at) VALUES ($1, $2, $3, $4, $5) ON CONFLICT DO NOTHING RETURNING note_id) UPDATE notes SET boost_count = boost_count + 1 WHERE note_id IN (SELECT note_id FROM inserted)",
+        boost.boost_id,
+        boost.note_id,
+        boost.actor_username,
+        boost.actor_host,
+        boost.created_at
+    )
+        .execute(pool)
+        .await
+    {
+        Ok(result) => Ok(result.rows_affected() > 0),
+        Err(e) => Err::<bool, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    }
+}
+
+/// A function that attempts to retrieve
+/// a boost by its ID. If the operation is 
+/// successful, an instance of the `KleahBoost`
+/// structure is returned. If the operation 
+/// fails, an error is returned.
+pub async fn get_boost_by_id(
+    boost_id: &str,
+    pool: &Pool<Postgres>
+) -> Result<KleahBoost, KleahErr>{
+    match query_as!(
+        KleahBoost,
+        "SELECT * FROM boosts WHERE boost_id = $1",
+        boost_id
+    )
+        .fetch_one(pool)
+        .await
+    {
+        Ok(boost) => Ok(boost),
+        Err(e) => Err::<KleahBoost, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    }
+}
+
+/// A function that attempts to retrieve
+/// the boost of a note by an actor. If the 
+/// operation is successful, an instance of 
+/// the `KleahBoost` structure is returned. If 
+/// the operation fails, an error is returned.
+pub async fn get_boost_between(
+    note_id: &str,
+    actor: &KleahActor,
+    pool: &Pool<Postgres>
+) -> Result<KleahBoost, KleahErr>{
+    match query_as!(
+        KleahBoost,
+        "SELECT * FROM boosts WHERE note_id = $1 AND actor_username = $2 AND actor_host = $3",
+        note_id,
+        actor.username,
+        actor.host
+    )
+        .fetch_one(pool)
+        .await
+    {
+        Ok(boost) => Ok(boost),
+        Err(e) => Err::<KleahBoost, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    }
+}
+
+/// A function that attempts to delete
+/// a boost and to no longer count it on 
+/// the boosted note. If the operation is 
+/// successful, an empty function is returned.
+/// If the operation fails, an error is returned.
+pub async fn destroy_boost(
+    boost_id: &str,
+    pool: &Pool<Postgres>
+) -> Result<(), KleahErr>{
+    match query!(
+        "WITH deleted AS (DELETE FROM boosts WHERE boost_id = $1 RETURNING note_id) UPDATE notes SET boost_count = boost_count - 1 WHERE note_id IN (SELECT note_id FROM deleted)",
+        boost_id
+    )
+        .execute(pool)
+        .await
+    {
+        Ok(_f) => Ok(()),
+        Err(e) => Err::<(), KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    }
+}
+
+/// A function that attempts to retrieve
+/// the replies to the note with the given 
+/// URL and the replies to those replies, 
+/// oldest first. Replies are followed up 
+/// to `max_depth` levels deep and at most
+/// `limit` notes are returned. Local notes' 
+/// URLs are built from `base`. If the operation 
+/// is successful, the notes are returned. If 
+/// the operation fails, an error is returned.
+pub async fn get_note_descendants(
+    url: &str,
+    base: &str,
+    max_depth: i32,
+    limit: i64,
+    pool: &Pool<Postgres>
+) -> Result<Vec<KleahNote>, KleahErr>{
+    match query_as!(
+        KleahNote,
+        "WITH RECURSIVE thread(note_id, url, depth) AS (SELECT note_id, COALESCE(ap_id, $2::text || '/apub/notes/' || note_id), 1 FROM notes WHERE in_reply_to = $1 UNION ALL SELECT notes.note_id, COALESCE(notes.ap_id, $2::text || '/apub/notes/' || notes.note_id), thread.depth + 1 FROM notes JOIN thread ON notes.in_reply_to = thread.url WHERE thread.depth < $3) SELECT * FROM notes WHERE note_id IN (SELECT note_id FROM thread) ORDER BY created_at ASC LIMIT $4",
+        url,
+        base,
+        max_depth,
+        limit
+    )
+        .fetch_all(pool)
+        .await
+    {
+        Ok(notes) => Ok(notes),
+        Err(e) => Err::<Vec<KleahNote>, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    }
+}
+
+/// A function that attempts to count
+/// the public and unlisted replies to the
+/// note with the given URL. If the operation
+/// is successful, the number of replies is
+/// returned. If the operation fails, an error
+/// is returned.
+pub async fn count_public_replies(
+    url: &str,
+    pool: &Pool<Postgres>
+) -> Result<i64, KleahErr>{
+    match query!(
+        "SELECT COUNT(*) AS \"count!\" FROM notes WHERE in_reply_to = $1 AND visibility IN ('public', 'unlisted')",
+        url
+    )
+        .fetch_one(pool)
+        .await
+    {
+        Ok(row) => Ok(row.count),
+        Err(e) => Err::<i64, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    }
+}
+
+/// A function that attempts to retrieve
+/// one page of the URLs of the public and
+/// unlisted replies to the note with the
+/// given URL, newest first. Only replies 
+/// before `max_id` or after `min_id`
+/// are returned if these are supplied. Local 
+/// notes' URLs are built from `base`. If the 
+/// operation is successful, the URLs and the 
+/// times the replies were written are returned.
+/// If the operation fails, an error is returned.
+pub async fn get_public_replies_page(
+    url: &str,
+    base: &str,
+    max_id: Option<&KleahCursor>,
+    min_id: Option<&KleahCursor>,
+    limit: i64,
+    pool: &Pool<Postgres>
+) -> Result<Vec<CollectionItem>, KleahErr>{
+    let fetched: Result<Vec<CollectionItem>, sqlx::Error> = match min_id {
+        Some(min_id) => query_as!(
+            CollectionItem,
+            "SELECT COALESCE(ap_id, $2 || '/apub/notes/' || note_id) AS \"item_id!\", note_id AS cursor_id, created_at FROM notes WHERE in_reply_to = $1 AND visibility IN ('public', 'unlisted') AND (created_at, note_id) > ($3, $4) ORDER BY created_at ASC, note_id ASC LIMIT $5",
+            url,
+            base,
+            min_id.created_at,
+            min_id.id,
+            limit
+        )
+            .fetch_all(pool)
+            .await
+            .map(|mut items| { items.reverse(); items }),
+        None => query_as!(
+            CollectionItem,
+            "SELECT COALESCE(ap_id, $2 || '/apub/notes/' || note_id) AS \"item_id!\", note_id AS cursor_id, created_at FROM notes WHERE in_reply_to = $1 AND visibility IN ('public', 'unlisted') AND ($3::timestamptz IS NULL OR (created_at, note_id) < ($3, $4::text)) ORDER BY created_at DESC, note_id DESC LIMIT $5",
+            url,
+            base,
+            max_id.map(|max_id| max_id.created_at),
+            max_id.map(|max_id| max_id.id.as_str()),
+            limit
+        )
+            .fetch_all(pool)
+            .await
+    };
+    match fetched {
+        Ok(items) => Ok(items),
+        Err(e) => Err::<Vec<CollectionItem>, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    }
+}
+
+/// A function that attempts to save the
+/// normalized hashtags of a note. Hashtags
+/// that are already stored are ignored. If 
+/// the operation is successful, an empty 
+/// function is returned. If the operation
+/// fails, an error is returned.
+pub async fn add_note_hashtags<'c>(
+    note: &KleahNote,
+    names: &[String],
+    executor: impl PgExecutor<'c>
+) -> Result<(), KleahErr>{
+    match query!(
+        "INSERT INTO hashtags (note_id, name, created_at) SELECT $1, UNNEST($2::text[]), $3 ON CONFLICT DO NOTHING",
+        note.note_id,
+        names,
+        note.created_at
+    )
+        .execute(executor)
+        .await
+    {
+        Ok(_f) => Ok(()),
+        Err(e) => Err::<(), KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    }
+}
+
+/// A function that attempts to retrieve
+/// one page of the public notes known to
+/// this instance that carry the given 
+/// hashtag, newest first. Only notes before
+/// `max_id` or after `min_id` are
+/// returned if these are supplied. If the
+/// operation is successful, the notes are 
+/// returned. If the operation fails, an error
+/// is returned.
+pub async fn get_hashtag_timeline(
+    name: &str,
+    max_id: Option<&KleahCursor>,
+    min_id: Option<&KleahCursor>,
+    limit: i64,
+    pool: &Pool<Postgres>
+) -> Result<Vec<KleahNote>, KleahErr>{
+    let fetched: Result<Vec<KleahNote>, sqlx::Error> = match min_id {
+        Some(min_id) => query_as!(
+            KleahNote,
+            "SELECT notes.* FROM hashtags JOIN notes ON notes.note_id = hashtags.note_id WHERE hashtags.name = $1 AND notes.visibility = 'public' AND (hashtags.created_at, hashtags.note_id) > ($2, $3) ORDER BY hashtags.created_at ASC, hashtags.note_id ASC LIMIT $4",
+            name,
+            min_id.created_at,
+            min_id.id,
+            limit
+        )
+            .fetch_all(pool)
+            .await
+            .map(|mut notes| { notes.reverse(); notes }),
+        None => query_as!(
+            KleahNote,
+            "SELECT notes.* FROM hashtags JOIN notes ON notes.note_id = hashtags.note_id WHERE hashtags.name = $1 AND notes.visibility = 'public' AND ($2::timestamptz IS NULL OR (hashtags.created_at, hashtags.note_id) < ($2, $3::text)) ORDER BY hashtags.created_at DESC, hashtags.note_id DESC LIMIT $4",
+            name,
+            max_id.map(|max_id| max_id.created_at),
+            max_id.map(|max_id| max_id.id.as_str()),
+            limit
+        )
+            .fetch_all(pool)
+            .await
+    };
+    match fetched {
+        Ok(notes) => Ok(notes),
+        Err(e) => Err::<Vec<KleahNote>, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    }
+}
+
+/// A function that attempts to count
+/// the public notes written on this instance
+/// that carry the given hashtag. If the 
+/// operation is successful, the number of 
+/// notes is returned. If the operation fails,
+/// an error is returned.
+pub async fn count_local_hashtag_notes(
+    name: &str,
+    host: &str,
+    pool: &Pool<Postgres>
+) -> Result<i64, KleahErr>{
+    match query!(
+        "SELECT COUNT(*) AS \"count!\" FROM hashtags JOIN notes ON notes.note_id = hashtags.note_id WHERE hashtags.name = $1 AND notes.author_host = $2 AND notes.visibility = 'public'",
+        name,
+        host
+    )
+        .fetch_one(pool)
+        .await
+    {
+        Ok(row) => Ok(row.count),
+        Err(e) => Err::<i64, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    }
+}
+
+/// A function that attempts to retrieve
+/// one page of the URLs of the public notes
+/// written on this instance that carry the
+/// given hashtag, newest first. Only notes
+/// before `max_id` or after `min_id`
+/// are returned if these are supplied. The 
+/// URLs are built from `base`. If the operation
+/// is successful, the URLs and the times the 
+/// notes were written are returned. If the 
+/// operation fails, an error is returned.
+pub async fn get_local_hashtag_page(
+    name: &str,
+    host: &str,
+    base: &str,
+    max_id: Option<&KleahCursor>,
+    min_id: Option<&KleahCursor>,
+    limit: i64,
+    pool: &Pool<Postgres>
+) -> Result<Vec<CollectionItem>, KleahErr>{
+    let fetched: Result<Vec<CollectionItem>, sqlx::Error> = match min_id {
+        Some(min_id) => query_as!(
+            CollectionItem,
+            "SELECT $3 || '/apub/notes/' || notes.note_id AS \"item_id!\", hashtags.note_id AS cursor_id, hashtags.created_at FROM hashtags JOIN notes ON notes.note_id = hashtags.note_id WHERE hashtags.name = $1 AND notes.author_host = $2 AND notes.visibility = 'public' AND (hashtags.created_at, hashtags.note_id) > ($4, $5) ORDER BY hashtags.created_at ASC, hashtags.note_id ASC LIMIT $6",
+            name,
+            host,
+            base,
+            min_id.created_at,
+            min_id.id,
+            limit
+        )
+            .fetch_all(pool)
+            .await
+            .map(|mut items| { items.reverse(); items }),
+        None => query_as!(
+            CollectionItem,
+            "SELECT $3 || '/apub/notes/' || notes.note_id AS \"item_id!\", hashtags.note_id AS cursor_id, hashtags.created_at FROM hashtags JOIN notes ON notes.note_id = hashtags.note_id WHERE hashtags.name = $1 AND notes.author_host = $2 AND notes.visibility = 'public' AND ($4::timestamptz IS NULL OR (hashtags.created_at, hashtags.note_id) < ($4, $5::text)) ORDER BY hashtags.created_at DESC, hashtags.note_id DESC LIMIT $6",
+            name,
+            host,
+            base,
+            max_id.map(|max_id| max_id.created_at),
+            max_id.map(|max_id| max_id.id.as_str()),
+            limit
+        )
+            .fetch_all(pool)
+            .await
+    };
+    match fetched {
+        Ok(items) => Ok(items),
+        Err(e) => Err::<Vec<CollectionItem>, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    }
+}
+
+/// A function that attempts to replace
+/// the content and the content warning of a
+/// note and to keep the prior revision with its
+/// content warning in the note's edit history.
+/// The revision is saved with the time it was
+/// written. If the operation is successful, 
+/// the edited note is returned. If the operation
+/// fails, an error is returned.
+pub async fn update_note_content<'c>(
+    note_id: &str,
+    edit_id: &str,
+    content: &str,
+    summary: Option<&str>,
+    sensitive: &bool,
+    updated_at: &DateTime<Utc>,
+    executor: impl PgExecutor<'c>
+) -> Result<KleahNote, KleahErr>{
+    match query_as!(
+        KleahNote,
+        "WITH previous AS (INSERT INTO note_edits (edit_id, note_id, content, summary, sensitive, created_at) SELECT $2, note_id, content, summary, sensitive, COALESCE(updated_at, created_at) FROM notes WHERE note_id = $1) UPDATE notes SET content = $3, summary = $4, sensitive = $5, updated_at = $6 WHERE note_id = $1 RETURNING *",
+        note_id,
+        edit_id,
+        content,
+        summary,
+        sensitive,
+        updated_at
+    )
+        .fetch_one(executor)
+        .await
+    {
+        Ok(note) => Ok(note),
+        Err(e) => Err::<KleahNote, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    }
+}
+
+/// A function that attempts to retrieve
+/// the prior revisions of a note, oldest
+/// first. If the operation is successful, 
+/// the revisions are returned. If the operation
+/// fails, an error is returned.
+pub async fn get_note_edits(
+    note_id: &str,
+    pool: &Pool<Postgres>
+) -> Result<Vec<KleahNoteEdit>, KleahErr>{
+    match query_as!(
+        KleahNoteEdit,
+        "SELECT * FROM note_edits WHERE note_id = $1 ORDER BY created_at ASC",
+        note_id
+    )
+        .fetch_all(pool)
+        .await
+    {
+        Ok(edits) => Ok(edits),
+        Err(e) => Err::<Vec<KleahNoteEdit>, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    }
+}
+
+/// A function that attempts to delete
+/// all stored hashtags of a note. If the 
+/// operation is successful, an empty function
+/// is returned. If the operation fails, an 
+/// error is returned.
+pub async fn destroy_note_hashtags<'c>(
+    note_id: &str,
+    executor: impl PgExecutor<'c>
+) -> Result<(), KleahErr>{
+    match query!(
+        "DELETE FROM hashtags WHERE note_id = $1",
+        note_id
+    )
+        .execute(executor)
+        .await
+    {
+        Ok(_f) => Ok(()),
+        Err(e) => Err::<(), KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    }
+}
+
+/// A function that attempts to leave a
+/// tombstone for a deleted local note. If 
+/// the operation is successful, an instance 
+/// of the `KleahTombstone` structure is returned.
+/// If the operation fails, an error is returned.
+pub async fn create_tombstone(
+    note_id: &str,
+    deleted_at: &DateTime<Utc>,
+    pool: &Pool<Postgres>
+) -> Result<KleahTombstone, KleahErr>{
+    match query_as!(
+        KleahTombstone,
+        "INSERT INTO tombstones (note_id, deleted_at) VALUES ($1, $2) ON CONFLICT (note_id) DO UPDATE SET deleted_at = EXCLUDED.deleted_at RETURNING *",
+        note_id,
+        deleted_at
+    )
+        .fetch_one(pool)
+        .await
+    {
+        Ok(tombstone) => Ok(tombstone),
+        Err(e) => Err::<KleahTombstone, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    }
+}
+
+/// A function that attempts to retrieve
+/// the tombstone of a deleted local note. If 
+/// the operation is successful, an instance 
+/// of the `KleahTombstone` structure is returned.
+/// If the operation fails, an error is returned.
+pub async fn get_tombstone(
+    note_id: &str,
+    pool: &Pool<Postgres>
+) -> Result<KleahTombstone, KleahErr>{
+    match query_as!(
+        KleahTombstone,
+        "SELECT * FROM tombstones WHERE note_id = $1",
+        note_id
+    )
+        .fetch_one(pool)
+        .await
+    {
+        Ok(tombstone) => Ok(tombstone),
+        Err(e) => Err::<KleahTombstone, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    }
+}
+
+/// A function that attempts to save a
+/// poll attached to a note together with
+/// its options, in order. Polls from other
+/// servers are saved with the tallies they
+/// were sent with. If the operation is 
+/// successful, an empty function is returned.
+/// If the operation fails, an error is returned.
+pub async fn create_poll<'c>(
+    poll: &KleahPoll,
+    titles: &[String],
+    tallies: &[i64],
+    executor: impl PgExecutor<'c>
+) -> Result<(), KleahErr>{
+    match query!(
+        "WITH created AS (INSERT INTO polls (note_id, multiple, expires_at, closed_at, voters_count) VALUES ($1, $2, $3, $4, $5) RETURNING note_id) INSERT INTO poll_options (note_id, position, title, votes_count) SELECT created.note_id, options.position - 1, options.title, options.votes_count FROM created, UNNEST($6::text[], $7::int8[]) WITH ORDINALITY AS options(title, votes_count, position)",
+        poll.note_id,
+        poll.multiple,
+        poll.expires_at,
+        poll.closed_at,
+        poll.voters_count,
+        titles,
+        tallies
+    )
+        .execute(executor)
+        .await
+    {
+        Ok(_f) => Ok(()),
+        Err(e) => Err::<(), KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    }
+}
+
+/// A function that attempts to retrieve
+/// the poll attached to a note. If the 
+/// operation is successful, an instance of
+/// the `KleahPoll` structure is returned. If
+/// the operation fails, an error is returned.
+pub async fn get_poll(
+    note_id: &str,
+    pool: &Pool<Postgres>
+) -> Result<KleahPoll, KleahErr>{
+    match query_as!(
+        KleahPoll,
+        "SELECT * FROM polls WHERE note_id = $1",
+        note_id
+    )
+        .fetch_one(pool)
+        .await
+    {
+        Ok(poll) => Ok(poll),
+        Err(e) => Err::<KleahPoll, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    }
+}
+
+/// A function that attempts to retrieve
+/// the options of the poll attached to a
+/// note, in order. If the operation is 
+/// successful, the options are returned. If
+/// the operation fails, an error is returned.
+pub async fn get_poll_options(
+    note_id: &str,
+    pool: &Pool<Postgres>
+) -> Result<Vec<KleahPollOption>, KleahErr>{
+    match query_as!(
+        KleahPollOption,
+        "SELECT * FROM poll_options WHERE note_id = $1 ORDER BY position ASC",
+        note_id
+    )
+        .fetch_all(pool)
+        .await
+    {
+        Ok(options) => Ok(options),
+        Err(e) => Err::<Vec<KleahPollOption>, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    }
+}
+
+/// A function that attempts to save the
+/// votes of an actor for options of a poll
+/// and to count them in the poll's tallies.
+/// Votes the actor already cast are ignored.
+/// If the operation is successful, an empty 
+/// function is returned. If the operation 
+/// fails, an error is returned.
+pub async fn create_poll_votes(
+    note_id: &str,
+    vote_ids: &[String],
+    positions: &[i32],
+    actor: &KleahActor,
+    pool: &Pool<Postgres>
+) -> Result<(), KleahErr>{
+    match query!(
+        "WITH voter AS (SELECT NOT EXISTS (SELECT 1 FROM poll_votes WHERE note_id = $1 AND actor_username = $4 AND actor_host = $5) AS is_new), inserted AS (INSERT INTO poll_votes (vote_id, note_id, position, actor_username, actor_host) SELECT votes.vote_id, $1, votes.position, $4, $5 FROM UNNEST($2::text[], $3::int4[]) AS votes(vote_id, position) ON CONFLICT DO NOTHING RETURNING position), counted AS (UPDATE poll_options SET votes_count = votes_count + 1 WHERE note_id = $1 AND position IN (SELECT inserted.position FROM inserted) RETURNING position) UPDATE polls SET voters_count = voters_count + 1 WHERE note_id = $1 AND (SELECT is_new FROM voter) AND EXISTS (SELECT 1 FROM inserted)",
+        note_id,
+        vote_ids,
+        positions,
+        actor.username,
+        actor.host
+    )
+        .execute(pool)
+        .await
+    {
+        Ok(_f) => Ok(()),
+        Err(e) => Err::<(), KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    }
+}
+
+/// A function that attempts to retrieve
+/// the votes an actor cast in a poll, in
+/// the order of the options. If the operation
+/// is successful, the votes are returned. If
+/// the operation fails, an error is returned.
+pub async fn get_actor_poll_votes(
+    note_id: &str,
+    actor: &KleahActor,
+    pool: &Pool<Postgres>
+) -> Result<Vec<KleahPollVote>, KleahErr>{
+    match query_as!(
+        KleahPollVote,
+        "SELECT * FROM poll_votes WHERE note_id = $1 AND actor_username = $2 AND actor_host = $3 ORDER BY position ASC",
+        note_id,
+        actor.username,
+        actor.host
+    )
+        .fetch_all(pool)
+        .await
+    {
+        Ok(votes) => Ok(votes),
+        Err(e) => Err::<Vec<KleahPollVote>, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    }
+}
+
+/// A function that attempts to replace
+/// the tallies of a poll from another server
+/// with the tallies it last sent. A poll that
+/// was closed stays closed. If the operation 
+/// is successful, an empty function is returned.
+/// If the operation fails, an error is returned.
+pub async fn update_poll_tallies(
+    note_id: &str,
+    tallies: &[i64],
+    voters_count: i64,
+    closed_at: Option<DateTime<Utc>>,
+    pool: &Pool<Postgres>
+) -> Result<(), KleahErr>{
+    match query!(
+        "WITH counted AS (UPDATE poll_options SET votes_count = tallies.votes_count FROM UNNEST($2::int8[]) WITH ORDINALITY AS tallies(votes_count, position) WHERE poll_options.note_id = $1 AND poll_options.position = tallies.position - 1 RETURNING poll_options.position) UPDATE polls SET voters_count = $3, closed_at = COALESCE(closed_at, $4) WHERE note_id = $1",
+        note_id,
+        tallies,
+        voters_count,
+        closed_at
+    )
+        .execute(pool)
+        .await
+    {
+        Ok(_f) => Ok(()),
+        Err(e) => Err::<(), KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    }
+}
+
+/// A function that attempts to retrieve
+/// all polls that have ended but are still
+/// open. If the operation is successful, the
+/// polls are returned. If the operation fails,
+/// an error is returned.
+pub async fn get_expired_polls(
+    pool: &Pool<Postgres>
+) -> Result<Vec<KleahPoll>, KleahErr>{
+    match query_as!(
+        KleahPoll,
+        "SELECT * FROM polls WHERE closed_at IS NULL AND expires_at <= now()"
+    )
+        .fetch_all(pool)
+        .await
+    {
+        Ok(polls) => Ok(polls),
+        Err(e) => Err::<Vec<KleahPoll>, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    }
+}
+
+/// A function that attempts to close a
+/// poll. Polls that are already closed are
+/// left as they are. If the operation is 
+/// successful, the poll is returned if it was
+/// closed by this function. If the operation
+/// fails, an error is returned.
+pub async fn close_poll<'c>(
+    note_id: &str,
+    executor: impl PgExecutor<'c>
+) -> Result<Option<KleahPoll>, KleahErr>{
+    match query_as!(
+        KleahPoll,
+        "UPDATE polls SET closed_at = now() WHERE note_id = $1 AND closed_at IS NULL RETURNING *",
+        note_id
+    )
+        .fetch_optional(executor)
+        .await
+    {
+        Ok(poll) => Ok(poll),
+        Err(e) => Err::<Option<KleahPoll>, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    }
+}
+
+/// A function that attempts to retrieve
+/// the actors that voted in a poll. If the
+/// operation is successful, the actors are
+/// returned. If the operation fails, an error
+/// is returned.
+pub async fn get_poll_voters(
+    note_id: &str,
+    pool: &Pool<Postgres>
+) -> Result<Vec<KleahActor>, KleahErr>{
+    match query_as!(
+        KleahActor,
+        "SELECT * FROM actors WHERE (username, host) IN (SELECT actor_username, actor_host FROM poll_votes WHERE note_id = $1) ORDER BY host, username",
+        note_id
+    )
+        .fetch_all(pool)
+        .await
+    {
+        Ok(voters) => Ok(voters),
+        Err(e) => Err::<Vec<KleahActor>, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    }
+}
+
+/// A function that attempts to retrieve
+/// one page of the notifications of a local
+/// user, newest first. Only notifications of
+/// the given kind or unread notifications are
+/// returned if this is requested. Only 
+/// notifications before `max_id` or after
+/// `min_id` are returned if these are 
+/// supplied. If the operation is successful,
+/// the notifications are returned. If the 
+/// operation fails, an error is returned.
+pub async fn get_notifications(
+    username: &str,
+    kind: Option<&str>,
+    unread_only: &bool,
+    max_id: Option<&KleahCursor>,
+    min_id: Option<&KleahCursor>,
+    limit: i64,
+    pool: &Pool<Postgres>
+) -> Result<Vec<KleahNotification>, KleahErr>{
+    let fetched: Result<Vec<KleahNotification>, sqlx::Error> = match min_id {
+        Some(min_id) => query_as!(
+            KleahNotification,
+            "SELECT * FROM notifications WHERE username = $1 AND ($2::text IS NULL OR kind = $2) AND (NOT $3 OR NOT is_read) AND (created_at, notification_id) > ($4, $5) ORDER BY created_at ASC, notification_id ASC LIMIT $6",
+            username,
+            kind,
+            unread_only,
+            min_id.created_at,
+            min_id.id,
+            limit
+        )
+            .fetch_all(pool)
+            .await
+            .map(|mut notifications| { notifications.reverse(); notifications }),
+        None => query_as!(
+            KleahNotification,
+            "SELECT * FROM notifications WHERE username = $1 AND ($2::text IS NULL OR kind = $2) AND (NOT $3 OR NOT is_read) AND ($4::timestamptz IS NULL OR (created_at, notification_id) < ($4, $5::text)) ORDER BY created_at DESC, notification_id DESC LIMIT $6",
+            username,
+            kind,
+            unread_only,
+            max_id.map(|max_id| max_id.created_at),
+            max_id.map(|max_id| max_id.id.as_str()),
+            limit
+        )
+            .fetch_all(pool)
+            .await
+    };
+    match fetched {
+        Ok(notifications) => Ok(notifications),
+        Err(e) => Err::<Vec<KleahNotification>, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    }
+}
+
+/// A function that attempts to count the
+/// unread notifications of a local user. If 
+/// the operation is successful, the number of
+/// unread notifications is returned. If the 
+/// operation fails, an error is returned.
+pub async fn count_unread_notifications(
+    username: &str,
+    pool: &Pool<Postgres>
+) -> Result<i64, KleahErr>{
+    match query!(
+        "SELECT COUNT(*) AS \"count!\" FROM notifications WHERE username = $1 AND NOT is_read",
+        username
+    )
+        .fetch_one(pool)
+        .await
+    {
+        Ok(row) => Ok(row.count),
+        Err(e) => Err::<i64, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    }
+}
+
+/// A function that attempts to mark the
+/// notification with the given ID or, if no
+/// ID is supplied, all notifications of a local
+/// user as read. If the operation is successful,
+/// the number of notifications marked as read is
+/// returned. If the operation fails, an error is
+/// returned.
+pub async fn update_notifications_read(
+    username: &str,
+    notification_id: Option<&str>,
+    pool: &Pool<Postgres>
+) -> Result<u64, KleahErr>{
+    match query!(
+        "UPDATE notifications SET is_read = TRUE WHERE username = $1 AND ($2::text IS NULL OR notification_id = $2) AND NOT is_read",
+        username,
+        notification_id
+    )
+        .execute(pool)
+        .await
+    {
+        Ok(result) => Ok(result.rows_affected()),
+        Err(e) => Err::<u64, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    }
+}
+
+/// A function that attempts to delete a
+/// notification of a local user. If the 
+/// operation is successful, a boolean reflecting
+/// whether the notification existed is returned.
+/// If the operation fails, an error is returned.
+pub async fn delete_notification(
+    username: &str,
+    notification_id: &str,
+    pool: &Pool<Postgres>
+) -> Result<bool, KleahErr>{
+    match query!(
+        "DELETE FROM notifications WHERE username = $1 AND notification_id = $2",
+        username,
+        notification_id
+    )
+        .execute(pool)
+        .await
+    {
+        Ok(result) => Ok(result.rows_affected() > 0),
+        Err(e) => Err::<bool, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    }
+}
+
+/// A function that attempts to retrieve
+/// the kinds of notifications a local user
+/// muted, in alphabetical order. If the 
+/// operation is successful, the mutes are
+/// returned. If the operation fails, an 
+/// error is returned.
+pub async fn get_notification_mutes(
+    username: &str,
+    pool: &Pool<Postgres>
+) -> Result<Vec<KleahNotificationMute>, KleahErr>{
+    match query_as!(
+        KleahNotificationMute,
+        "SELECT * FROM notification_mutes WHERE username = $1 ORDER BY kind",
+        username
+    )
+        .fetch_all(pool)
+        .await
+    {
+        Ok(mutes) => Ok(mutes),
+        Err(e) => Err::<Vec<KleahNotificationMute>, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    }
+}
+
+/// A function that attempts to mute a
+/// kind of notifications for a local user.
+/// Mutes that are already stored are ignored.
+/// If the operation is successful, an empty
+/// function is returned. If the operation
+/// fails, an error is returned.
+pub async fn create_notification_mute(
+    mute: &KleahNotificationMute,
+    pool: &Pool<Postgres>
+) -> Result<(), KleahErr>{
+    match query!(
+        "INSERT INTO notification_mutes (username, kind) VALUES ($1, $2) ON CONFLICT DO NOTHING",
+        mute.username,
+        mute.kind
+    )
+        .execute(pool)
+        .await
+    {
+        Ok(_f) => Ok(()),
+        Err(e) => Err::<(), KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    }
+}
+
+/// A function that attempts to unmute a
+/// kind of notifications for a local user.
+/// If the operation is successful, an empty
+/// function is returned. If the operation
+/// fails, an error is returned.
+pub async fn delete_notification_mute(
+    mute: &KleahNotificationMute,
+    pool: &Pool<Postgres>
+) -> Result<(), KleahErr>{
+    match query!(
+        "DELETE FROM notification_mutes WHERE username = $1 AND kind = $2",
+        mute.username,
+        mute.kind
+    )
+        .execute(pool)
+        .await
+    {
+        Ok(_f) => Ok(()),
+        Err(e) => Err::<(), KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    }
+}
+
+/// A function that attempts to save a
+/// report of an actor to the administrators
+/// of this instance. If the operation is 
+/// successful, an instance of the `KleahReport`
+/// structure is returned. If the operation 
+/// fails, an error is returned.
+pub async fn create_report(
+    report: &KleahReport,
+    pool: &Pool<Postgres>
+) -> Result<KleahReport, KleahErr>{
+    match query_as!(
+        KleahReport,
+        "INSERT INTO reports (report_id, reporter_username, reporter_host, target_username, target_host, note_id, comment, created_at) VALUES ($1, $2, $3, $4, $5, $6, $7, $8) RETURNING *",
+        report.report_id,
+        report.reporter_username,
+        report.reporter_host,
+        report.target_username,
+        report.target_host,
+        report.note_id,
+        report.comment,
+        report.created_at
+    )
+        .fetch_one(pool)
+        .await
+    {
+        Ok(report) => Ok(report),
+        Err(e) => Err::<KleahReport, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    }
+}
+
+/// A function that attempts to retrieve
+/// a report by its ID. If the operation is
+/// successful, an instance of the `KleahReport`
+/// structure is returned. If the operation 
+/// fails, an error is returned.
+pub async fn get_report(
+    report_id: &str,
+    pool: &Pool<Postgres>
+) -> Result<KleahReport, KleahErr>{
+    match query_as!(
+        KleahReport,
+        "SELECT * FROM reports WHERE report_id = $1",
+        report_id
+    )
+        .fetch_one(pool)
+        .await
+    {
+        Ok(report) => Ok(report),
+        Err(e) => Err::<KleahReport, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    }
+}
+
+/// A function that attempts to retrieve
+/// the local actors of the administrators
+/// of this instance. If the operation is 
+/// successful, the actors are returned. If
+/// the operation fails, an error is returned.
+pub async fn get_admin_actors(
+    host: &str,
+    pool: &Pool<Postgres>
+) -> Result<Vec<KleahActor>, KleahErr>{
+    match query_as!(
+        KleahActor,
+        "SELECT actors.* FROM actors JOIN users ON users.username = actors.username WHERE actors.host = $1 AND users.is_admin ORDER BY actors.username",
+        host
+    )
+        .fetch_all(pool)
+        .await
+    {
+        Ok(admins) => Ok(admins),
+        Err(e) => Err::<Vec<KleahActor>, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    }
+}
@@ -0,0 +1,523 @@
+/*
+Kleah by Alyx Shang.
+Licensed under the FSL v1.
+*/
+
+/// Importing the structure
+/// representing a pool of
+/// database connections.
+use sqlx::Pool;
+
+/// Importing the structure
+/// representing the UTC
+/// time zone.
+use chrono::Utc;
+
+/// Importing the function
+/// to remove unsafe HTML.
+use ammonia::clean;
+
+/// Importing the structure
+/// representing a point in
+/// time.
+use chrono::DateTime;
+
+/// Importing the structure
+/// representing an arbitrary
+/// JSON value.
+use serde_json::Value;
+
+/// Importing the structure
+/// representing a database
+/// transaction.
+use sqlx::Transaction;
+
+/// Importing the structure
+/// for catching and handling
+/// errors.
+use super::err::KleahErr;
+
+/// Importing the function
+/// to build the URL of an actor.
+use super::utils::actor_url;
+
+/// Importing the enum
+/// to describe the kind
+/// of error that occurred.
+use super::err::KleahErrType;
+
+/// Importing the structure
+/// representing a Postgres
+/// database.
+use sqlx::postgres::Postgres;
+
+/// Importing the structure
+/// modelling a note.
+use super::models::KleahNote;
+
+/// Importing the structure
+/// modelling a poll.
+use super::models::KleahPoll;
+
+/// Importing the function
+/// to generate IDs.
+use super::utils::generate_id;
+
+/// Importing the function
+/// to retrieve the prior
+/// revisions of a note.
+use super::db::get_note_edits;
+
+/// Importing the function
+/// to retrieve a note by
+/// its ID.
+use super::db::get_note_by_id;
+
+/// Importing the structure
+/// modelling an actor.
+use super::models::KleahActor;
+
+/// Importing the structure
+/// modelling an ActivityPub
+/// activity.
+use super::apub::ApubActivity;
+
+/// Importing the function
+/// to retrieve an actor by
+/// their username and host.
+use super::db::get_actor_by_id;
+
+/// Importing the structure
+/// representing a connection
+/// taken from a pool.
+use sqlx::pool::PoolConnection;
+
+/// Importing the function
+/// to retrieve the poll of
+/// a note.
+use super::polls::get_note_poll;
+
+/// Importing the function
+/// to retrieve the ID of an
+/// ActivityPub object.
+use super::federation::object_id;
+
+/// Importing the function
+/// to save the hashtags
+/// of a note.
+use super::db::add_note_hashtags;
+
+/// Importing the function
+/// to retrieve information
+/// about this instance.
+use super::db::get_instance_info;
+
+/// Importing the structure
+/// modelling a prior revision
+/// of a note.
+use super::models::KleahNoteEdit;
+
+/// Importing the function
+/// to retrieve the IDs of
+/// ActivityPub objects.
+use super::federation::object_ids;
+
+/// Importing the function
+/// to retrieve a note by
+/// its URL.
+use super::notes::get_note_by_url;
+
+/// Importing the function
+/// to read the hashtags
+/// of a received note.
+use super::hashtags::hashtag_names;
+
+/// Importing the function
+/// to find the hashtags
+/// of a local note.
+use super::hashtags::note_hashtags;
+
+/// Importing the function
+/// to retrieve a note on
+/// behalf of a reader.
+use super::notes::get_visible_note;
+
+/// Importing the function
+/// to retrieve the actors a
+/// note is addressed to.
+use super::db::get_note_recipients;
+
+/// Importing the function
+/// to replace the content
+/// of a note.
+use super::db::update_note_content;
+
+/// Importing the structure
+/// modelling an option of
+/// a poll.
+use super::models::KleahPollOption;
+
+/// Importing the function
+/// to normalize the content
+/// warning of a note.
+use super::notes::normalize_summary;
+
+/// Importing the function
+/// to read the content warning
+/// of a received note.
+use super::notes::read_note_summary;
+
+/// Importing the function
+/// to check the content
+/// of a note.
+use super::notes::check_note_content;
+
+/// Importing the function
+/// to delete the hashtags
+/// of a note.
+use super::db::destroy_note_hashtags;
+
+/// Importing the function
+/// to check the content
+/// warning of a note.
+use super::notes::check_note_summary;
+
+/// Importing the function
+/// to address a note to
+/// the actors it mentions.
+use super::mentions::address_mentions;
+
+/// Importing the function
+/// to resolve the actors
+/// mentioned in a note.
+use super::mentions::resolve_mentions;
+
+/// Importing the structure
+/// modelling information
+/// about this instance.
+use super::models::InstanceInformation;
+
+/// Importing the function
+/// to build the "Update"
+/// activity of a note.
+use super::notes::build_update_activity;
+
+/// Importing the function
+/// to send an activity about
+/// a note to its audience.
+use super::notes::deliver_note_activity;
+
+/// Importing the structure
+/// containing all revisions
+/// of a note.
+use super::responses::NoteHistoryResponse;
+
+/// Importing the structure
+/// containing a single
+/// revision of a note.
+use super::responses::NoteRevisionResponse;
+
+/// A function that attempts to replace
+/// the content of a note written by a local
+/// user. Only the author of a note may edit it.
+/// The content warning and the sensitive flag
+/// are only replaced if they are given, and an
+/// empty content warning removes it. The prior
+/// revision is kept in the note's edit history, the note's hashtags are replaced and
+/// newly mentioned actors are addressed. The
+/// note's audience on other servers is sent an
+/// "Update" activity. If the operation is
+/// successful, the edited note is returned. If
+/// the operation fails, an error is returned.
+pub async fn edit_note(
+    username: &str,
+    note_id: &str,
+    content: &str,
+    summary: Option<&str>,
+    sensitive: Option<&bool>,
+    pool: &Pool<Postgres>
+) -> Result<KleahNote, KleahErr> {
+    if !check_note_content(content) {
+        return Err::<KleahNote, KleahErr>(
+            KleahErr::with_type(
+                "The content of the note is empty or too long.",
+                KleahErrType::BadRequest
+            )
+        )
+    }
+    let instance: InstanceInformation = match get_instance_info(pool).await {
+        Ok(instance) => instance,
+        Err(e) => return Err::<KleahNote, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    let note: KleahNote = match get_note_by_id(note_id, pool).await {
+        Ok(note) => note,
+        Err(_e) => return Err::<KleahNote, KleahErr>(
+            KleahErr::with_type(
+                "The requested note does not exist.",
+                KleahErrType::NotFound
+            )
+        )
+    };
+    if note.author_username != username || note.author_host != instance.host {
+        return Err::<KleahNote, KleahErr>(
+            KleahErr::with_type(
+                "The requesting user is not the author of the note.",
+                KleahErrType::Unauthorized
+            )
+        )
+    }
+    let summary: Option<String> = match summary {
+        Some(summary) => normalize_summary(Some(summary)),
+        None => note.summary.clone()
+    };
+    if !check_note_summary(content, summary.as_deref()) {
+        return Err::<KleahNote, KleahErr>(
+            KleahErr::with_type(
+                "The content warning of the note is too long.",
+                KleahErrType::BadRequest
+            )
+        )
+    }
+    let sensitive: bool = *sensitive.unwrap_or(&note.sensitive) || summary.is_some();
+    let author: KleahActor = match get_actor_by_id(username, &instance.host, pool).await {
+        Ok(author) => author,
+        Err(e) => return Err::<KleahNote, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    let edited: KleahNote = match replace_note(
+        &note,
+        content,
+        summary.as_deref(),
+        &sensitive,
+        &note_hashtags(content),
+        &Utc::now(),
+        pool
+    ).await {
+        Ok(edited) => edited,
+        Err(e) => return Err::<KleahNote, KleahErr>(e)
+    };
+    let parent: Option<KleahNote> = match &edited.in_reply_to {
+        Some(in_reply_to) => get_note_by_url(in_reply_to, pool).await.ok(),
+        None => None
+    };
+    let mentions: Vec<KleahActor> = match resolve_mentions(&edited.content, pool).await {
+        Ok(mentions) => mentions,
+        Err(e) => return Err::<KleahNote, KleahErr>(e)
+    };
+    let mut conn: PoolConnection<Postgres> = match pool.acquire().await {
+        Ok(conn) => conn,
+        Err(e) => return Err::<KleahNote, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    match address_mentions(&edited, &author, parent.as_ref(), &mentions, &mut conn).await {
+        Ok(_f) => {},
+        Err(e) => return Err::<KleahNote, KleahErr>(e)
+    };
+    let recipients: Vec<KleahActor> = match get_note_recipients(&edited.note_id, pool).await {
+        Ok(recipients) => recipients,
+        Err(e) => return Err::<KleahNote, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    let poll: Option<(KleahPoll, Vec<KleahPollOption>)> = match get_note_poll(&edited, pool).await {
+        Ok(poll) => poll,
+        Err(e) => return Err::<KleahNote, KleahErr>(e)
+    };
+    let update: ApubActivity = match build_update_activity(&edited, &author, &recipients, poll.as_ref()){
+        Ok(update) => update,
+        Err(e) => return Err::<KleahNote, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    match deliver_note_activity(&edited, &author, &recipients, &update, pool).await {
+        Ok(_f) => Ok(edited),
+        Err(e) => Err::<KleahNote, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    }
+}
+
+/// A function that attempts to replace
+/// the content, the content warning and the
+/// hashtags of a stored note and to keep the
+/// prior revision. All
+/// changes are made in one transaction. If
+/// the operation is successful, the edited
+/// note is returned. If the operation fails,
+/// an error is returned.
+pub async fn replace_note(
+    note: &KleahNote,
+    content: &str,
+    summary: Option<&str>,
+    sensitive: &bool,
+    hashtags: &[String],
+    updated_at: &DateTime<Utc>,
+    pool: &Pool<Postgres>
+) -> Result<KleahNote, KleahErr> {
+    let mut tx: Transaction<'_, Postgres> = match pool.begin().await {
+        Ok(tx) => tx,
+        Err(e) => return Err::<KleahNote, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    let edited: KleahNote = match update_note_content(
+        &note.note_id,
+        &generate_id(&format!("{}{}", note.note_id, content)),
+        content,
+        summary,
+        sensitive,
+        updated_at,
+        &mut *tx
+    ).await {
+        Ok(edited) => edited,
+        Err(e) => return Err::<KleahNote, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    match destroy_note_hashtags(&edited.note_id, &mut *tx).await {
+        Ok(_f) => {},
+        Err(e) => return Err::<KleahNote, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    match add_note_hashtags(&edited, hashtags, &mut *tx).await {
+        Ok(_f) => {},
+        Err(e) => return Err::<KleahNote, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    match tx.commit().await {
+        Ok(_f) => Ok(edited),
+        Err(e) => Err::<KleahNote, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    }
+}
+
+/// A function that attempts to handle a
+/// received "Update" activity for a note from
+/// another server. The stored copy of the note
+/// is only replaced if the note was written by
+/// the actor that sent the activity and if the
+/// edit is newer than the stored copy. Updates
+/// of unknown notes are ignored. If the operation
+/// fails, an error is returned.
+pub async fn handle_note_update(
+    actor: &KleahActor,
+    object: &Value,
+    pool: &Pool<Postgres>
+) -> Result<(), KleahErr> {
+    let ap_id: String = match object_id(object) {
+        Some(ap_id) => ap_id,
+        None => return Err::<(), KleahErr>(
+            KleahErr::with_type(
+                "The note has no ID.",
+                KleahErrType::BadRequest
+            )
+        )
+    };
+    let note: KleahNote = match get_note_by_url(&ap_id, pool).await {
+        Ok(note) => note,
+        Err(_e) => return Ok(())
+    };
+    let attributed_to: Option<String> = object
+        .get("attributedTo")
+        .and_then(|attributed_to| object_ids(attributed_to).into_iter().next());
+    if note.author_username != actor.username ||
+        note.author_host != actor.host ||
+        attributed_to != Some(actor_url(actor))
+    {
+        return Err::<(), KleahErr>(
+            KleahErr::with_type(
+                "The note was not written by the sending actor.",
+                KleahErrType::Unauthorized
+            )
+        )
+    }
+    let updated_at: DateTime<Utc> = object
+        .get("updated")
+        .and_then(|updated| updated.as_str())
+        .and_then(|updated| DateTime::parse_from_rfc3339(updated).ok())
+        .map(|updated| updated.with_timezone(&Utc))
+        .unwrap_or_else(Utc::now);
+    if note.updated_at.unwrap_or(note.created_at) >= updated_at {
+        return Ok(());
+    }
+    let content: String = clean(
+        object
+            .get("content")
+            .and_then(|content| content.as_str())
+            .unwrap_or("")
+    );
+    let summary: Option<String> = read_note_summary(object);
+    let sensitive: bool = object
+        .get("sensitive")
+        .and_then(|sensitive| sensitive.as_bool())
+        .unwrap_or(false) || summary.is_some();
+    match replace_note(
+        &note,
+        &content,
+        summary.as_deref(),
+        &sensitive,
+        &hashtag_names(object),
+        &updated_at,
+        pool
+    ).await {
+        Ok(_edited) => Ok(()),
+        Err(e) => Err::<(), KleahErr>(e)
+    }
+}
+
+/// A function that attempts to read all
+/// revisions of a note on behalf of the user
+/// with the given API token or an anonymous
+/// reader. If the operation is successful, an
+/// instance of the `NoteHistoryResponse` structure
+/// is returned. If the reader may not see the
+/// note or the operation fails, an error is
+/// returned.
+pub async fn read_note_history(
+    note_id: &str,
+    api_token: Option<&str>,
+    pool: &Pool<Postgres>
+) -> Result<NoteHistoryResponse, KleahErr> {
+    let note: KleahNote = match get_visible_note(note_id, api_token, pool).await {
+        Ok(note) => note,
+        Err(e) => return Err::<NoteHistoryResponse, KleahErr>(e)
+    };
+    let edits: Vec<KleahNoteEdit> = match get_note_edits(&note.note_id, pool).await {
+        Ok(edits) => edits,
+        Err(e) => return Err::<NoteHistoryResponse, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    let mut revisions: Vec<NoteRevisionResponse> = edits
+        .iter()
+        .map(
+            |edit| NoteRevisionResponse{
+                content: edit.content.clone(),
+                summary: edit.summary.clone(),
+                sensitive: edit.sensitive,
+                created_at: edit.created_at.to_rfc3339()
+            }
+        )
+        .collect();
+    revisions.push(
+        NoteRevisionResponse{
+            content: note.content.clone(),
+            summary: note.summary.clone(),
+            sensitive: note.sensitive,
+            created_at: note.updated_at.unwrap_or(note.created_at).to_rfc3339()
+        }
+    );
+    Ok(
+        NoteHistoryResponse{
+            note_id: note.note_id,
+            revisions
+        }
+    )
+}
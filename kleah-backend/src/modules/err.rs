@@ -49,11 +49,26 @@ pub struct ErrDetails {
     pub details: String
 }
 
+/// An enumeration of the types
+/// of errors that can occur.
+/// The type of an error decides
+/// which HTTP status code is sent
+/// back when the error is returned
+/// from a service function.
+#[derive(Clone,Eq,PartialEq, Debug)]
+pub enum KleahErrType{
+    Generic,
+    BadRequest,
+    Unauthorized,
+    NotFound
+}
+
 /// A structure to capture and
 /// handle errors.
 #[derive(Clone,Eq,PartialEq, Debug)]
 pub struct KleahErr{
-    pub details: String
+    pub details: String,
+    pub err_type: KleahErrType
 }
 
 /// Defining some useful functions
@@ -65,7 +80,22 @@ impl KleahErr{
     /// structure and return it.
     pub fn new(details: &str) -> KleahErr {
         KleahErr{
-            details: details.to_owned()
+            details: details.to_owned(),
+            err_type: KleahErrType::Generic
+        }
+    }
+
+    /// Implementing a function to create
+    /// a new instance of the `KleahErr`
+    /// structure with the given error
+    /// type and return it.
+    pub fn with_type(
+        details: &str,
+        err_type: KleahErrType
+    ) -> KleahErr {
+        KleahErr{
+            details: details.to_owned(),
+            err_type
         }
     }
 
@@ -119,6 +149,11 @@ impl error::ResponseError for KleahErr {
         let resp: ErrDetails = ErrDetails{ 
             details: (*((&self.details).clone())).to_string()
         };
-        HttpResponse::Ok().json(resp)
+        match self.err_type {
+            KleahErrType::Generic => HttpResponse::Ok().json(resp),
+            KleahErrType::BadRequest => HttpResponse::BadRequest().json(resp),
+            KleahErrType::Unauthorized => HttpResponse::Unauthorized().json(resp),
+            KleahErrType::NotFound => HttpResponse::NotFound().json(resp)
+        }
     }
 }
@@ -0,0 +1,1338 @@
+/*
+Kleah by Alyx Shang.
+Licensed under the FSL v1.
+*/
+
+/// Importing the structure
+/// representing a pool of
+/// database connections.
+use sqlx::Pool;
+
+/// Importing the structure
+/// to get the current time.
+use chrono::Utc;
+
+/// Importing the structure
+/// for making HTTP requests.
+use reqwest::Client;
+
+/// Importing the macro
+/// to build JSON values.
+use serde_json::json;
+
+/// Importing the structure
+/// representing an arbitrary
+/// JSON value.
+use serde_json::Value;
+
+/// Importing the structure
+/// for catching and handling
+/// errors.
+use super::err::KleahErr;
+
+/// Importing the function
+/// to view a single value
+/// as a slice.
+use std::slice::from_ref;
+
+/// Importing the function
+/// to convert a Rust data
+/// structure into a JSON value.
+use serde_json::to_value;
+
+/// Importing the function
+/// to deserialize a JSON value
+/// into a Rust data structure.
+use serde_json::from_value;
+
+/// Importing the function to
+/// extract the host of a URL.
+use super::utils::url_host;
+
+/// Importing the function
+/// to build the base URL
+/// of a server.
+use super::utils::base_url;
+
+/// Importing the function to
+/// retrieve the URL of an actor.
+use super::utils::actor_url;
+
+/// Importing the structure that
+/// represents a connection to a 
+/// PostgreSQL database.
+use sqlx::postgres::Postgres;
+
+/// Importing the enumeration
+/// describing the types of
+/// errors that can occur.
+use super::err::KleahErrType;
+
+/// Importing the function to
+/// store a follow relationship.
+use super::db::create_follow;
+
+/// Importing the function to
+/// mark a follow relationship
+/// as accepted.
+use super::db::accept_follow;
+
+/// Importing the data structure
+/// modelling data about a Kleah user
+/// in the database.
+use super::models::KleahUser;
+
+/// Importing the data structure
+/// modelling data about an ActivityPub
+/// actor in the database.
+use super::models::KleahActor;
+
+/// Importing the function to
+/// generate unique identifiers.
+use super::utils::generate_id;
+
+/// Importing the function to
+/// remove a follow relationship.
+use super::db::destroy_follow;
+
+/// Importing the function to
+/// retrieve the record of a user
+/// in the database given their
+/// username.
+use super::db::get_user_by_id;
+
+/// Importing the structure
+/// modelling an ActivityPub
+/// activity.
+use super::apub::ApubActivity;
+
+/// Importing the function
+/// to handle received
+/// "Like" activities.
+use super::likes::handle_like;
+
+/// Importing the media type
+/// for ActivityPub documents.
+use super::apub::ACTIVITY_JSON;
+
+/// Importing the function to
+/// retrieve the record of an
+/// actor in the database given
+/// their username and host.
+use super::db::get_actor_by_id;
+
+/// Importing the data structure
+/// modelling a follow relationship
+/// in the database.
+use super::models::KleahFollow;
+
+/// Importing the function to
+/// retrieve a follow relationship
+/// given its ID.
+use super::db::get_follow_by_id;
+
+/// Importing the function
+/// to handle a received
+/// report.
+use super::reports::handle_flag;
+
+/// Importing the structure modelling
+/// the document of an actor on
+/// another server.
+use super::apub::ApubRemoteActor;
+
+/// Importing the function for
+/// retrieving information about
+/// the current Kleah instance.
+use super::db::get_instance_info;
+
+/// Importing the function to
+/// retrieve the record of an actor
+/// from another server given the
+/// actor's ID.
+use super::db::get_actor_by_ap_id;
+
+/// Importing the function to
+/// retrieve a follow relationship
+/// between two actors.
+use super::db::get_follow_between;
+
+/// Importing the function to cache
+/// the record of an actor from
+/// another server.
+use super::db::upsert_remote_actor;
+
+/// Importing the function
+/// to queue an activity for
+/// delivery to other servers.
+use super::queue::deliver_activity;
+
+/// Importing the function
+/// to handle undone
+/// "Like" activities.
+use super::likes::handle_undo_like;
+
+/// Importing the function
+/// to handle received
+/// "Announce" activities.
+use super::boosts::handle_announce;
+
+/// Importing the function
+/// to handle a vote in a
+/// local poll.
+use super::polls::handle_poll_vote;
+
+/// Importing the function
+/// to retrieve the followers
+/// of an actor on other servers.
+use super::db::get_remote_followers;
+
+/// Importing the function
+/// to store notes from
+/// other servers.
+use super::notes::store_remote_note;
+
+/// Importing the function
+/// to handle edits of notes
+/// from other servers.
+use super::edits::handle_note_update;
+
+/// Importing the function
+/// to handle deletions of notes
+/// from other servers.
+use super::notes::handle_note_delete;
+
+/// Importing the function
+/// to refresh the tallies of
+/// a poll from another server.
+use super::polls::update_remote_poll;
+
+/// Importing the data structure
+/// modelling data about the current
+/// Kleah instance in the database.
+use super::models::InstanceInformation;
+
+/// Importing the function
+/// to build the document
+/// of a local actor.
+use super::utils::build_actor_document;
+
+/// Importing the function
+/// to handle undone
+/// "Announce" activities.
+use super::boosts::handle_undo_announce;
+
+/// Importing the function
+/// to notify a local user
+/// about a follow.
+use super::notifications::notify_follow;
+
+/// Importing the URI of the
+/// JSON-LD context for
+/// ActivityStreams documents.
+use super::apub::ACTIVITYSTREAMS_CONTEXT;
+
+/// Importing the function
+/// to parse an account
+/// handle.
+use super::utils::parse_account_resource;
+
+/// Importing the function to extract
+/// the username of a local actor from
+/// the actor's URL.
+use super::utils::local_username_from_url;
+
+/// A function that attempts to fetch
+/// an ActivityPub document from the
+/// given URL. If the operation is 
+/// successful, the document is returned 
+/// as a JSON value. If the operation fails,
+/// an error is returned.
+pub async fn fetch_json(
+    url: &str
+) -> Result<Value, KleahErr> {
+    let response = match Client::new()
+        .get(url)
+        .header("Accept", ACTIVITY_JSON)
+        .send()
+        .await
+    {
+        Ok(response) => response,
+        Err(e) => return Err::<Value, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    if !response.status().is_success(){
+        return Err::<Value, KleahErr>(
+            KleahErr::new(
+                &format!("The remote server responded with {}.", response.status())
+            )
+        )
+    }
+    match response.json::<Value>().await {
+        Ok(document) => Ok(document),
+        Err(e) => Err::<Value, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    }
+}
+
+/// A function to convert the document
+/// of an actor on another server into
+/// an instance of the `KleahActor` structure.
+/// All endpoints are stored as absolute URLs
+/// and the actual PEM-encoded public key is
+/// stored. If the document is not valid, an
+/// error is returned.
+pub fn remote_actor_to_kleah(
+    document: ApubRemoteActor
+) -> Result<KleahActor, KleahErr> {
+    let host: String = match url_host(&document.id){
+        Some(host) => host,
+        None => return Err::<KleahActor, KleahErr>(
+            KleahErr::new("The actor's ID is not a valid URL.")
+        )
+    };
+    if document.public_key.owner != document.id {
+        return Err::<KleahActor, KleahErr>(
+            KleahErr::new("The actor's public key belongs to another actor.")
+        )
+    }
+    let actor: KleahActor = KleahActor{
+        name: document.name.unwrap_or(document.preferred_username.clone()),
+        actor_type: document.actor_type,
+        host,
+        liked: document.liked.unwrap_or_default(),
+        inbox: document.inbox,
+        outbox: document.outbox.unwrap_or_default(),
+        following: document.following.unwrap_or_default(),
+        followers: document.followers.unwrap_or_default(),
+        username: document.preferred_username,
+        description: document.summary.unwrap_or_default(),
+        public_key: document.public_key.public_key_pem,
+        ap_id: Some(document.id),
+        key_id: Some(document.public_key.id),
+        shared_inbox: document.endpoints.and_then(|endpoints| endpoints.shared_inbox),
+        fetched_at: None
+    };
+    Ok(actor)
+}
+
+/// A function that attempts to fetch the
+/// document of an actor on another server
+/// from the given URL. The URL may also point
+/// to the actor's public key, in which case
+/// the key's owner is fetched. The actor must
+/// be served from the host of its ID. If the
+/// operation is successful, an instance of the
+/// `KleahActor` structure is returned. If the
+/// operation fails, an error is returned.
+pub async fn fetch_remote_actor(
+    url: &str
+) -> Result<KleahActor, KleahErr> {
+    let location: &str = url
+        .split("#")
+        .next()
+        .unwrap_or(url);
+    let mut document: Value = match fetch_json(location).await {
+        Ok(document) => document,
+        Err(e) => return Err::<KleahActor, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    let mut fetched_from: String = location.to_string();
+    if document.get("inbox").is_none() {
+        let owner: String = match document.get("owner").and_then(|owner| owner.as_str()) {
+            Some(owner) => owner.to_string(),
+            None => return Err::<KleahActor, KleahErr>(
+                KleahErr::new("The fetched document is not an actor.")
+            )
+        };
+        document = match fetch_json(&owner).await {
+            Ok(document) => document,
+            Err(e) => return Err::<KleahActor, KleahErr>(
+                KleahErr::new(&e.to_string())
+            )
+        };
+        fetched_from = owner;
+    }
+    let parsed: ApubRemoteActor = match from_value::<ApubRemoteActor>(document){
+        Ok(parsed) => parsed,
+        Err(e) => return Err::<KleahActor, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    if url_host(&parsed.id) != url_host(&fetched_from) {
+        return Err::<KleahActor, KleahErr>(
+            KleahErr::new("The actor is not served from its own host.")
+        )
+    }
+    remote_actor_to_kleah(parsed)
+}
+
+/// A function that attempts to fetch the
+/// document of an actor on another server
+/// and to cache the actor in the database.
+/// If the operation is successful, an instance 
+/// of the `KleahActor` structure is returned.
+/// If the operation fails, an error is returned.
+pub async fn refresh_remote_actor(
+    url: &str,
+    pool: &Pool<Postgres>
+) -> Result<KleahActor, KleahErr> {
+    let actor: KleahActor = match fetch_remote_actor(url).await {
+        Ok(actor) => actor,
+        Err(e) => return Err::<KleahActor, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    upsert_remote_actor(&actor, pool).await
+}
+
+/// The number of seconds for which
+/// the cached record of an actor from
+/// another server is used before the 
+/// actor is fetched again.
+pub const ACTOR_REFRESH_TTL: i64 = 86400;
+
+/// A function to check whether the cached
+/// record of an actor from another server
+/// was fetched recently enough to be used
+/// without fetching the actor again.
+pub fn actor_is_fresh(
+    actor: &KleahActor
+) -> bool {
+    match actor.fetched_at {
+        Some(fetched_at) => (Utc::now() - fetched_at).num_seconds() < ACTOR_REFRESH_TTL,
+        None => false
+    }
+}
+
+/// A function that attempts to retrieve an
+/// actor from another server by the URL of
+/// its document. The cached record is used 
+/// if it is fresh. Otherwise the actor is
+/// fetched again and a stale record is only
+/// used if the other server cannot be reached.
+/// If the operation is successful, an instance 
+/// of the `KleahActor` structure is returned.
+/// If the operation fails, an error is returned.
+pub async fn get_remote_actor(
+    url: &str,
+    pool: &Pool<Postgres>
+) -> Result<KleahActor, KleahErr> {
+    let cached: Option<KleahActor> = match get_actor_by_ap_id(url, pool).await {
+        Ok(actor) if actor_is_fresh(&actor) => return Ok(actor),
+        Ok(actor) => Some(actor),
+        Err(_e) => None
+    };
+    match refresh_remote_actor(url, pool).await {
+        Ok(actor) => Ok(actor),
+        Err(e) => match cached {
+            Some(actor) => Ok(actor),
+            None => Err::<KleahActor, KleahErr>(
+                KleahErr::new(&e.to_string())
+            )
+        }
+    }
+}
+
+/// A function that attempts to look up
+/// the URL of the document of an actor on
+/// another server with WebFinger. If the 
+/// operation is successful, the URL is 
+/// returned. If the operation fails, an 
+/// error is returned.
+pub async fn webfinger_actor_url(
+    username: &str,
+    host: &str
+) -> Result<String, KleahErr> {
+    let response = match Client::new()
+        .get(format!("{}/.well-known/webfinger", base_url(host)))
+        .query(&[("resource", format!("acct:{}@{}", username, host))])
+        .header("Accept", "application/jrd+json")
+        .send()
+        .await
+    {
+        Ok(response) => response,
+        Err(e) => return Err::<String, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    if !response.status().is_success(){
+        return Err::<String, KleahErr>(
+            KleahErr::with_type(
+                &format!("The remote server responded with {}.", response.status()),
+                KleahErrType::NotFound
+            )
+        )
+    }
+    let document: Value = match response.json::<Value>().await {
+        Ok(document) => document,
+        Err(e) => return Err::<String, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    let links: Vec<Value> = document
+        .get("links")
+        .and_then(|links| links.as_array())
+        .cloned()
+        .unwrap_or_default();
+    let href: Option<String> = links
+        .iter()
+        .filter(|link| link.get("rel").and_then(|rel| rel.as_str()) == Some("self"))
+        .find(
+            |link| {
+                let link_type: &str = link.get("type").and_then(|t| t.as_str()).unwrap_or("");
+                link_type == ACTIVITY_JSON || link_type.starts_with("application/ld+json")
+            }
+        )
+        .and_then(|link| link.get("href"))
+        .and_then(|href| href.as_str())
+        .map(|href| href.to_string());
+    match href {
+        Some(href) => Ok(href),
+        None => Err::<String, KleahErr>(
+            KleahErr::with_type(
+                "The account has no ActivityPub actor.",
+                KleahErrType::NotFound
+            )
+        )
+    }
+}
+
+/// A function that attempts to resolve an
+/// account handle of the form `@user@host`
+/// to an actor. Local accounts are read from
+/// the database. Accounts on other servers are
+/// looked up with WebFinger, fetched and cached,
+/// and are only fetched again once their cached
+/// record has expired. If the operation is 
+/// successful, an instance of the `KleahActor`
+/// structure is returned. If the operation fails, 
+/// an error is returned.
+pub async fn resolve_remote_actor(
+    handle: &str,
+    pool: &Pool<Postgres>
+) -> Result<KleahActor, KleahErr> {
+    let (username, host): (String, String) = match parse_account_resource(handle){
+        Some(account) => account,
+        None => return Err::<KleahActor, KleahErr>(
+            KleahErr::with_type(
+                "The account handle is not valid.",
+                KleahErrType::BadRequest
+            )
+        )
+    };
+    let instance: InstanceInformation = match get_instance_info(pool).await {
+        Ok(instance) => instance,
+        Err(e) => return Err::<KleahActor, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    if host == instance.host {
+        return match get_actor_by_id(&username, &host, pool).await {
+            Ok(actor) => Ok(actor),
+            Err(_e) => Err::<KleahActor, KleahErr>(
+                KleahErr::with_type(
+                    "The requested actor does not exist.",
+                    KleahErrType::NotFound
+                )
+            )
+        };
+    }
+    let cached: Option<KleahActor> = match get_actor_by_id(&username, &host, pool).await {
+        Ok(actor) if actor_is_fresh(&actor) => return Ok(actor),
+        Ok(actor) => Some(actor),
+        Err(_e) => None
+    };
+    let url: String = match webfinger_actor_url(&username, &host).await {
+        Ok(url) => url,
+        Err(e) => return match cached {
+            Some(actor) => Ok(actor),
+            None => Err::<KleahActor, KleahErr>(e)
+        }
+    };
+    get_remote_actor(&url, pool).await
+}
+
+/// A function to retrieve the ID of
+/// an ActivityPub object that is either
+/// embedded or referenced by its ID. If
+/// the object has no ID, `None` is returned.
+pub fn object_id(
+    object: &Value
+) -> Option<String> {
+    match object {
+        Value::String(id) => Some(id.clone()),
+        Value::Object(map) => map
+            .get("id")
+            .and_then(|id| id.as_str())
+            .map(|id| id.to_string()),
+        _ => None
+    }
+}
+
+/// A function to retrieve the IDs of
+/// a single ActivityPub object or of a
+/// list of objects, as used for audiences.
+/// Objects without an ID are skipped.
+pub fn object_ids(
+    objects: &Value
+) -> Vec<String> {
+    match objects {
+        Value::Array(objects) => objects
+            .iter()
+            .filter_map(object_id)
+            .collect(),
+        object => object_id(object)
+            .into_iter()
+            .collect()
+    }
+}
+
+/// A function to build a new activity
+/// performed by the given actor on the
+/// given object. A new unique ID below the
+/// actor's URL is generated for the activity.
+pub fn build_activity(
+    actor: &KleahActor,
+    activity_type: &str,
+    object: Value
+) -> ApubActivity {
+    let actor_id: String = actor_url(actor);
+    ApubActivity{
+        context: ACTIVITYSTREAMS_CONTEXT.to_string(),
+        id: format!(
+            "{}#{}/{}", 
+            &actor_id, 
+            activity_type.to_lowercase(), 
+            generate_id(&actor_id)
+        ),
+        activity_type: activity_type.to_string(),
+        actor: actor_id,
+        object,
+        to: Vec::new(),
+        cc: Vec::new()
+    }
+}
+
+/// A function to rebuild the "Follow"
+/// activity of a stored follow relationship
+/// so that it can be referenced in responses
+/// to it.
+pub fn build_follow_object(
+    follow: &KleahFollow,
+    follower: &KleahActor,
+    followee: &KleahActor
+) -> Value {
+    json!({
+        "id": follow.follow_id,
+        "type": "Follow",
+        "actor": actor_url(follower),
+        "object": actor_url(followee)
+    })
+}
+
+/// A function that attempts to retrieve the
+/// local actor an activity is addressed to from
+/// the URL of the actor. If the URL does not 
+/// belong to a local actor, an error is returned.
+pub async fn get_local_target(
+    url: &str,
+    pool: &Pool<Postgres>
+) -> Result<(KleahUser, KleahActor), KleahErr> {
+    let instance: InstanceInformation = match get_instance_info(pool).await {
+        Ok(instance) => instance,
+        Err(e) => return Err::<(KleahUser, KleahActor), KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    let username: String = match local_username_from_url(url, &instance.host){
+        Some(username) => username,
+        None => return Err::<(KleahUser, KleahActor), KleahErr>(
+            KleahErr::with_type(
+                "The object is not an actor on this instance.",
+                KleahErrType::NotFound
+            )
+        )
+    };
+    let user: KleahUser = match get_user_by_id(&username, pool).await {
+        Ok(user) => user,
+        Err(_e) => return Err::<(KleahUser, KleahActor), KleahErr>(
+            KleahErr::with_type(
+                "The requested actor does not exist.",
+                KleahErrType::NotFound
+            )
+        )
+    };
+    let actor: KleahActor = match get_actor_by_id(
+        &username, 
+        &instance.host, 
+        pool
+    ).await {
+        Ok(actor) => actor,
+        Err(_e) => return Err::<(KleahUser, KleahActor), KleahErr>(
+            KleahErr::with_type(
+                "The requested actor does not exist.",
+                KleahErrType::NotFound
+            )
+        )
+    };
+    Ok((user, actor))
+}
+
+/// A function that attempts to process an
+/// activity received in an inbox. The activity
+/// must have been signed by its own actor. 
+/// "Follow", "Undo", "Accept", "Reject", "Create",
+/// "Update", "Delete", "Like", "Announce" and
+/// "Flag" activities are handled, all other activities 
+/// are ignored. If the operation fails, an error
+/// is returned.
+pub async fn process_activity(
+    actor: &KleahActor,
+    activity: &Value,
+    pool: &Pool<Postgres>
+) -> Result<(), KleahErr> {
+    let activity_actor: Option<String> = activity
+        .get("actor")
+        .and_then(object_id);
+    if activity_actor != Some(actor_url(actor)) {
+        return Err::<(), KleahErr>(
+            KleahErr::with_type(
+                "The activity was not signed by its actor.",
+                KleahErrType::Unauthorized
+            )
+        )
+    }
+    let activity_type: &str = activity
+        .get("type")
+        .and_then(|activity_type| activity_type.as_str())
+        .unwrap_or("");
+    match activity_type {
+        "Follow" => handle_follow(actor, activity, pool).await,
+        "Undo" => handle_undo(actor, activity, pool).await,
+        "Accept" => handle_follow_response(actor, activity, true, pool).await,
+        "Reject" => handle_follow_response(actor, activity, false, pool).await,
+        "Create" => handle_create(actor, activity, pool).await,
+        "Update" => handle_update(actor, activity, pool).await,
+        "Delete" => handle_delete(actor, activity, pool).await,
+        "Like" => handle_like(actor, activity, pool).await,
+        "Announce" => handle_announce(actor, activity, pool).await,
+        "Flag" => handle_flag(actor, activity, pool).await,
+        _ => Ok(())
+    }
+}
+
+/// A function that attempts to handle a
+/// received "Follow" activity for a local
+/// actor. The follow relationship is stored
+/// and accepted right away if the followed user
+/// does not approve followers manually. The user
+/// is notified about new followers and follow
+/// requests. If the operation fails, an error
+/// is returned.
+pub async fn handle_follow(
+    actor: &KleahActor,
+    activity: &Value,
+    pool: &Pool<Postgres>
+) -> Result<(), KleahErr> {
+    let follow_id: String = match object_id(activity){
+        Some(follow_id) => follow_id,
+        None => return Err::<(), KleahErr>(
+            KleahErr::with_type(
+                "The activity has no ID.",
+                KleahErrType::BadRequest
+            )
+        )
+    };
+    let target: String = match activity.get("object").and_then(object_id){
+        Some(target) => target,
+        None => return Err::<(), KleahErr>(
+            KleahErr::with_type(
+                "The activity has no object.",
+                KleahErrType::BadRequest
+            )
+        )
+    };
+    let (user, local_actor): (KleahUser, KleahActor) = match get_local_target(
+        &target, 
+        pool
+    ).await {
+        Ok(target) => target,
+        Err(e) => return Err::<(), KleahErr>(e)
+    };
+    let is_new: bool = get_follow_between(actor, &local_actor, pool).await.is_err();
+    let follow: KleahFollow = match create_follow(
+        &follow_id, 
+        actor, 
+        &local_actor, 
+        &!user.is_locked, 
+        pool
+    ).await {
+        Ok(follow) => follow,
+        Err(e) => return Err::<(), KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    if is_new {
+        match notify_follow(&follow, actor, &local_actor, pool).await {
+            Ok(_f) => {},
+            Err(e) => return Err::<(), KleahErr>(e)
+        };
+    }
+    if follow.accepted {
+        let accept: ApubActivity = build_activity(
+            &local_actor, 
+            "Accept", 
+            build_follow_object(&follow, actor, &local_actor)
+        );
+        deliver_activity(&local_actor.username, from_ref(actor), &accept, pool).await
+    }
+    else {
+        Ok(())
+    }
+}
+
+/// A function that attempts to handle a
+/// received "Undo" activity. Undone "Follow"
+/// activities remove the follow relationship 
+/// they created, undone "Like" activities
+/// remove the like and undone "Announce" 
+/// activities remove the boost. Other undone 
+/// activities are ignored. If the operation fails, an error 
+/// is returned.
+pub async fn handle_undo(
+    actor: &KleahActor,
+    activity: &Value,
+    pool: &Pool<Postgres>
+) -> Result<(), KleahErr> {
+    let object: &Value = match activity.get("object"){
+        Some(object) => object,
+        None => return Err::<(), KleahErr>(
+            KleahErr::with_type(
+                "The activity has no object.",
+                KleahErrType::BadRequest
+            )
+        )
+    };
+    let object_type: &str = object
+        .get("type")
+        .and_then(|object_type| object_type.as_str())
+        .unwrap_or("Follow");
+    match object_type {
+        "Follow" => {},
+        "Like" => return handle_undo_like(actor, object, pool).await,
+        "Announce" => return handle_undo_announce(actor, object, pool).await,
+        _ => return Ok(())
+    };
+    let follow: KleahFollow = match find_follow(actor, object, false, pool).await {
+        Some(follow) => follow,
+        None => return Ok(())
+    };
+    destroy_follow(&follow.follow_id, pool).await
+}
+
+/// A function that attempts to find the
+/// stored follow relationship referenced
+/// by a "Follow" object. The follow is looked 
+/// up by its ID first and by the actors involved
+/// second. The given actor must be the follower
+/// or, if `actor_is_followee` is set, the actor
+/// being followed. If no such follow exists,
+/// `None` is returned.
+pub async fn find_follow(
+    actor: &KleahActor,
+    object: &Value,
+    actor_is_followee: bool,
+    pool: &Pool<Postgres>
+) -> Option<KleahFollow> {
+    let by_id: Option<KleahFollow> = match object_id(object){
+        Some(follow_id) => get_follow_by_id(&follow_id, pool).await.ok(),
+        None => None
+    };
+    let follow: KleahFollow = match by_id {
+        Some(follow) => follow,
+        None => {
+            let local_url: String = object
+                .get(if actor_is_followee { "actor" } else { "object" })
+                .and_then(object_id)?;
+            let (_user, local_actor): (KleahUser, KleahActor) = get_local_target(
+                &local_url, 
+                pool
+            ).await.ok()?;
+            if actor_is_followee {
+                get_follow_between(&local_actor, actor, pool).await.ok()?
+            }
+            else {
+                get_follow_between(actor, &local_actor, pool).await.ok()?
+            }
+        }
+    };
+    let (username, host): (&str, &str) = if actor_is_followee {
+        (&follow.followee_username, &follow.followee_host)
+    }
+    else {
+        (&follow.follower_username, &follow.follower_host)
+    };
+    if username == actor.username && host == actor.host {
+        Some(follow)
+    }
+    else {
+        None
+    }
+}
+
+/// A function that attempts to handle a
+/// received "Accept" or "Reject" activity
+/// for a "Follow" activity sent by a local
+/// actor. Accepted follows are marked as
+/// such, rejected follows are removed. If
+/// the operation fails, an error is returned.
+pub async fn handle_follow_response(
+    actor: &KleahActor,
+    activity: &Value,
+    accepted: bool,
+    pool: &Pool<Postgres>
+) -> Result<(), KleahErr> {
+    let object: &Value = match activity.get("object"){
+        Some(object) => object,
+        None => return Err::<(), KleahErr>(
+            KleahErr::with_type(
+                "The activity has no object.",
+                KleahErrType::BadRequest
+            )
+        )
+    };
+    let follow: KleahFollow = match find_follow(actor, object, true, pool).await {
+        Some(follow) => follow,
+        None => return Ok(())
+    };
+    if accepted {
+        accept_follow(&follow.follow_id, pool).await
+    }
+    else {
+        destroy_follow(&follow.follow_id, pool).await
+    }
+}
+
+/// A function that attempts to make a local
+/// user follow the actor with the given URL
+/// or account handle. Actors from other servers 
+/// are resolved and sent a "Follow" activity. 
+/// Local users are notified about new followers
+/// and follow requests. If the operation 
+/// is successful, an instance of the `KleahFollow`
+/// structure is returned. If the operation fails,
+/// an error is returned.
+pub async fn follow_actor(
+    username: &str,
+    target: &str,
+    pool: &Pool<Postgres>
+) -> Result<KleahFollow, KleahErr> {
+    let instance: InstanceInformation = match get_instance_info(pool).await {
+        Ok(instance) => instance,
+        Err(e) => return Err::<KleahFollow, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    let local_actor: KleahActor = match get_actor_by_id(
+        username, 
+        &instance.host, 
+        pool
+    ).await {
+        Ok(local_actor) => local_actor,
+        Err(e) => return Err::<KleahFollow, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    let target: String = if target.contains("://") {
+        target.to_string()
+    }
+    else {
+        match resolve_remote_actor(target, pool).await {
+            Ok(resolved) => actor_url(&resolved),
+            Err(e) => return Err::<KleahFollow, KleahErr>(e)
+        }
+    };
+    if local_username_from_url(&target, &instance.host).is_some() {
+        let (followed_user, followed_actor): (KleahUser, KleahActor) = match get_local_target(
+            &target, 
+            pool
+        ).await {
+            Ok(followed) => followed,
+            Err(e) => return Err::<KleahFollow, KleahErr>(e)
+        };
+        let follow: ApubActivity = build_activity(
+            &local_actor, 
+            "Follow", 
+            Value::String(actor_url(&followed_actor))
+        );
+        let is_new: bool = get_follow_between(&local_actor, &followed_actor, pool).await.is_err();
+        let created: KleahFollow = match create_follow(
+            &follow.id, 
+            &local_actor, 
+            &followed_actor, 
+            &!followed_user.is_locked, 
+            pool
+        ).await {
+            Ok(created) => created,
+            Err(e) => return Err::<KleahFollow, KleahErr>(
+                KleahErr::new(&e.to_string())
+            )
+        };
+        if is_new {
+            match notify_follow(&created, &local_actor, &followed_actor, pool).await {
+                Ok(_f) => {},
+                Err(e) => return Err::<KleahFollow, KleahErr>(e)
+            };
+        }
+        return Ok(created);
+    }
+    let remote_actor: KleahActor = match get_remote_actor(&target, pool).await {
+        Ok(remote_actor) => remote_actor,
+        Err(e) => return Err::<KleahFollow, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    let follow: ApubActivity = build_activity(
+        &local_actor, 
+        "Follow", 
+        Value::String(actor_url(&remote_actor))
+    );
+    let created: KleahFollow = match create_follow(
+        &follow.id, 
+        &local_actor, 
+        &remote_actor, 
+        &false, 
+        pool
+    ).await {
+        Ok(created) => created,
+        Err(e) => return Err::<KleahFollow, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    match deliver_activity(&local_actor.username, from_ref(&remote_actor), &follow, pool).await {
+        Ok(_f) => Ok(created),
+        Err(e) => Err::<KleahFollow, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    }
+}
+
+/// A function that attempts to make a local
+/// user stop following the actor with the given
+/// URL or account handle. Actors from other servers are sent an
+/// "Undo" activity for the original "Follow"
+/// activity. If the operation fails, an error
+/// is returned.
+pub async fn unfollow_actor(
+    username: &str,
+    target: &str,
+    pool: &Pool<Postgres>
+) -> Result<(), KleahErr> {
+    let instance: InstanceInformation = match get_instance_info(pool).await {
+        Ok(instance) => instance,
+        Err(e) => return Err::<(), KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    let local_actor: KleahActor = match get_actor_by_id(
+        username, 
+        &instance.host, 
+        pool
+    ).await {
+        Ok(local_actor) => local_actor,
+        Err(e) => return Err::<(), KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    let target: String = if target.contains("://") {
+        target.to_string()
+    }
+    else {
+        match resolve_remote_actor(target, pool).await {
+            Ok(resolved) => actor_url(&resolved),
+            Err(e) => return Err::<(), KleahErr>(e)
+        }
+    };
+    let followed_actor: KleahActor = match get_local_target(&target, pool).await {
+        Ok((_user, followed_actor)) => followed_actor,
+        Err(_e) => match get_actor_by_ap_id(&target, pool).await {
+            Ok(followed_actor) => followed_actor,
+            Err(e) => return Err::<(), KleahErr>(
+                KleahErr::with_type(&e.to_string(), KleahErrType::NotFound)
+            )
+        }
+    };
+    let follow: KleahFollow = match get_follow_between(
+        &local_actor, 
+        &followed_actor, 
+        pool
+    ).await {
+        Ok(follow) => follow,
+        Err(e) => return Err::<(), KleahErr>(
+            KleahErr::with_type(&e.to_string(), KleahErrType::NotFound)
+        )
+    };
+    match destroy_follow(&follow.follow_id, pool).await {
+        Ok(_f) => {},
+        Err(e) => return Err::<(), KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    if followed_actor.host == instance.host {
+        return Ok(());
+    }
+    let undo: ApubActivity = build_activity(
+        &local_actor, 
+        "Undo", 
+        build_follow_object(&follow, &local_actor, &followed_actor)
+    );
+    deliver_activity(&local_actor.username, from_ref(&followed_actor), &undo, pool).await
+}
+
+/// A function that attempts to approve or
+/// deny a pending follow request for a local
+/// user. Approved requests are marked as
+/// accepted and answered with an "Accept" 
+/// activity, denied requests are removed and 
+/// answered with a "Reject" activity. If the 
+/// operation fails, an error is returned.
+pub async fn answer_follow_request(
+    username: &str,
+    follow_id: &str,
+    accepted: bool,
+    pool: &Pool<Postgres>
+) -> Result<(), KleahErr> {
+    let instance: InstanceInformation = match get_instance_info(pool).await {
+        Ok(instance) => instance,
+        Err(e) => return Err::<(), KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    let follow: KleahFollow = match get_follow_by_id(follow_id, pool).await {
+        Ok(follow) => follow,
+        Err(e) => return Err::<(), KleahErr>(
+            KleahErr::with_type(&e.to_string(), KleahErrType::NotFound)
+        )
+    };
+    if follow.followee_username != username || follow.followee_host != instance.host {
+        return Err::<(), KleahErr>(
+            KleahErr::with_type(
+                "The follow request is not addressed to this user.",
+                KleahErrType::NotFound
+            )
+        )
+    }
+    let local_actor: KleahActor = match get_actor_by_id(
+        username, 
+        &instance.host, 
+        pool
+    ).await {
+        Ok(local_actor) => local_actor,
+        Err(e) => return Err::<(), KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    let follower: KleahActor = match get_actor_by_id(
+        &follow.follower_username, 
+        &follow.follower_host, 
+        pool
+    ).await {
+        Ok(follower) => follower,
+        Err(e) => return Err::<(), KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    let stored: Result<(), KleahErr> = if accepted {
+        accept_follow(&follow.follow_id, pool).await
+    }
+    else {
+        destroy_follow(&follow.follow_id, pool).await
+    };
+    match stored {
+        Ok(_f) => {},
+        Err(e) => return Err::<(), KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    if follower.host == instance.host {
+        return Ok(());
+    }
+    let response: ApubActivity = build_activity(
+        &local_actor, 
+        if accepted { "Accept" } else { "Reject" }, 
+        build_follow_object(&follow, &follower, &local_actor)
+    );
+    deliver_activity(&local_actor.username, from_ref(&follower), &response, pool).await
+}
+
+/// A function that attempts to send the
+/// current profile of a local user to all
+/// of the user's followers on other servers
+/// as an "Update" activity. If the operation 
+/// is successful, an empty function is returned.
+/// If the operation fails, an error is returned.
+pub async fn federate_profile_update(
+    username: &str,
+    pool: &Pool<Postgres>
+) -> Result<(), KleahErr> {
+    let instance: InstanceInformation = match get_instance_info(pool).await {
+        Ok(instance) => instance,
+        Err(e) => return Err::<(), KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    let local_actor: KleahActor = match get_actor_by_id(
+        username, 
+        &instance.host, 
+        pool
+    ).await {
+        Ok(local_actor) => local_actor,
+        Err(e) => return Err::<(), KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    let user: KleahUser = match get_user_by_id(username, pool).await {
+        Ok(user) => user,
+        Err(e) => return Err::<(), KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    let followers: Vec<KleahActor> = match get_remote_followers(
+        &local_actor, 
+        pool
+    ).await {
+        Ok(followers) => followers,
+        Err(e) => return Err::<(), KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    if followers.is_empty() {
+        return Ok(());
+    }
+    let document: Value = match to_value(
+        build_actor_document(&local_actor, &user, &instance.host)
+    ){
+        Ok(document) => document,
+        Err(e) => return Err::<(), KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    let update: ApubActivity = build_activity(
+        &local_actor, 
+        "Update", 
+        document
+    );
+    deliver_activity(&local_actor.username, &followers, &update, pool).await
+}
+
+/// A function that attempts to handle a
+/// "Create" activity sent by an actor from
+/// another server. Notes and questions are
+/// stored, notes voting in local polls are
+/// counted as votes and objects of other types
+/// are ignored. Objects that 
+/// are only referenced by their ID are fetched.
+/// If the operation is successful, an empty
+/// function is returned. If the operation 
+/// fails, an error is returned.
+pub async fn handle_create(
+    actor: &KleahActor,
+    activity: &Value,
+    pool: &Pool<Postgres>
+) -> Result<(), KleahErr> {
+    let object: Value = match activity.get("object") {
+        Some(Value::String(url)) => match fetch_json(url).await {
+            Ok(object) => object,
+            Err(e) => return Err::<(), KleahErr>(
+                KleahErr::new(&e.to_string())
+            )
+        },
+        Some(object) => object.clone(),
+        None => return Err::<(), KleahErr>(
+            KleahErr::with_type(
+                "The activity has no object.",
+                KleahErrType::BadRequest
+            )
+        )
+    };
+    match object.get("type").and_then(|object_type| object_type.as_str()) {
+        Some("Note") => match handle_poll_vote(actor, &object, pool).await {
+            Ok(true) => Ok(()),
+            Ok(false) => store_remote_note(actor, &object, pool).await,
+            Err(e) => Err::<(), KleahErr>(e)
+        },
+        Some("Question") => store_remote_note(actor, &object, pool).await,
+        _ => Ok(())
+    }
+}
+
+/// A function that attempts to handle a
+/// received "Update" activity sent by an actor
+/// from another server. Edits of notes are 
+/// stored and the tallies of polls are refreshed.
+/// Questions are only treated as edited if they
+/// carry the time of an edit, so that new tallies
+/// leave no revisions. Updates of other objects
+/// are ignored.
+/// Objects that are only referenced by their ID
+/// are fetched. If the operation is successful,
+/// an empty function is returned. If the operation 
+/// fails, an error is returned.
+pub async fn handle_update(
+    actor: &KleahActor,
+    activity: &Value,
+    pool: &Pool<Postgres>
+) -> Result<(), KleahErr> {
+    let object: Value = match activity.get("object") {
+        Some(Value::String(url)) => match fetch_json(url).await {
+            Ok(object) => object,
+            Err(e) => return Err::<(), KleahErr>(
+                KleahErr::new(&e.to_string())
+            )
+        },
+        Some(object) => object.clone(),
+        None => return Err::<(), KleahErr>(
+            KleahErr::with_type(
+                "The activity has no object.",
+                KleahErrType::BadRequest
+            )
+        )
+    };
+    match object.get("type").and_then(|object_type| object_type.as_str()) {
+        Some("Note") => handle_note_update(actor, &object, pool).await,
+        Some("Question") if object.get("updated").is_some() => match handle_note_update(actor, &object, pool).await {
+            Ok(_f) => update_remote_poll(actor, &object, pool).await,
+            Err(e) => Err::<(), KleahErr>(e)
+        },
+        Some("Question") => update_remote_poll(actor, &object, pool).await,
+        _ => Ok(())
+    }
+}
+
+/// A function that attempts to handle a
+/// received "Delete" activity sent by an actor
+/// from another server. Deletions of notes remove
+/// the stored copies, deletions of other objects
+/// are ignored. Deleted objects are not fetched, 
+/// as they are gone. If the operation is successful,
+/// an empty function is returned. If the operation 
+/// fails, an error is returned.
+pub async fn handle_delete(
+    actor: &KleahActor,
+    activity: &Value,
+    pool: &Pool<Postgres>
+) -> Result<(), KleahErr> {
+    match activity.get("object") {
+        Some(object) => handle_note_delete(actor, object, pool).await,
+        None => Err::<(), KleahErr>(
+            KleahErr::with_type(
+                "The activity has no object.",
+                KleahErrType::BadRequest
+            )
+        )
+    }
+}
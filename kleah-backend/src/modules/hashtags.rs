@@ -0,0 +1,309 @@
+/*
+Kleah by Alyx Shang.
+Licensed under the FSL v1.
+*/
+
+/// Importing the structure
+/// representing a pool of
+/// database connections.
+use sqlx::Pool;
+
+/// Importing the structure
+/// representing an arbitrary
+/// JSON value.
+use serde_json::Value;
+
+/// Importing the structure
+/// for catching and handling
+/// errors.
+use super::err::KleahErr;
+
+/// Importing the structure
+/// containing data about
+/// a tag of a note.
+use super::apub::ApubTag;
+
+/// Importing the function
+/// to build the base URL of
+/// a host.
+use super::utils::base_url;
+
+/// Importing the structure
+/// representing a Postgres
+/// database.
+use sqlx::postgres::Postgres;
+
+/// Importing the structure
+/// modelling a note.
+use super::models::KleahNote;
+
+/// Importing the function
+/// to escape text for HTML.
+use super::utils::escape_html;
+
+/// Importing the function
+/// to read a cursor from
+/// a query string.
+use super::utils::parse_cursor;
+
+/// Importing the function
+/// to retrieve information
+/// about this instance.
+use super::db::get_instance_info;
+
+/// Importing the structure
+/// modelling the query string
+/// for reading a timeline.
+use super::payloads::TimelineQuery;
+
+/// Importing the function
+/// to retrieve a page of the
+/// notes with a hashtag.
+use super::db::get_hashtag_timeline;
+
+/// Importing the structure
+/// containing a page of a
+/// timeline.
+use super::responses::TimelineResponse;
+
+/// Importing the structure
+/// modelling information
+/// about this instance.
+use super::models::InstanceInformation;
+
+/// Importing the function
+/// to derive the size of a
+/// page of a timeline.
+use super::timelines::timeline_page_size;
+
+/// Importing the function
+/// to find out whether a reader
+/// expands content warnings.
+use super::notes::get_spoiler_preference;
+
+/// Importing the function
+/// to check whether a reader
+/// may read a timeline.
+use super::timelines::ensure_timeline_access;
+
+/// Importing the function
+/// to build a page of a
+/// timeline of notes.
+use super::timelines::build_note_timeline_response;
+
+/// A function to check whether the
+/// given character can be part of the
+/// name of a hashtag. A boolean reflecting
+/// this is returned.
+pub fn is_hashtag_char(
+    character: char
+) -> bool {
+    character.is_alphanumeric() || character == '_'
+}
+
+/// A function to normalize the name
+/// of a hashtag so that hashtags differing
+/// only in case are the same. A leading "#"
+/// is removed. The name is returned.
+pub fn normalize_hashtag(
+    name: &str
+) -> String {
+    name.strip_prefix('#')
+        .unwrap_or(name)
+        .to_lowercase()
+}
+
+/// A function to check whether the
+/// given name is a valid name for a
+/// hashtag. Names must not be empty
+/// or consist of digits only. A boolean
+/// reflecting this is returned.
+pub fn check_hashtag(
+    name: &str
+) -> bool {
+    !name.is_empty() &&
+        name.chars().all(is_hashtag_char) &&
+        !name.chars().all(|character| character.is_ascii_digit())
+}
+
+/// A function to find the hashtags
+/// in the plain text of a note. Hashtags
+/// that are part of a word are not hashtags.
+/// The byte range of each hashtag and its
+/// name as written are returned in order.
+pub fn find_hashtags(
+    text: &str
+) -> Vec<(usize, usize, String)> {
+    let chars: Vec<(usize, char)> = text.char_indices().collect();
+    let mut hashtags: Vec<(usize, usize, String)> = Vec::new();
+    let mut index: usize = 0;
+    while index < chars.len() {
+        let (start, character): (usize, char) = chars[index];
+        let in_word: bool = index > 0 && (is_hashtag_char(chars[index - 1].1) || matches!(chars[index - 1].1, '#' | '&'));
+        if character != '#' || in_word {
+            index += 1;
+            continue;
+        }
+        let mut end_index: usize = index + 1;
+        while end_index < chars.len() && is_hashtag_char(chars[end_index].1) {
+            end_index += 1;
+        }
+        let end: usize = chars
+            .get(end_index)
+            .map(|(end, _character)| *end)
+            .unwrap_or(text.len());
+        let name: &str = &text[start + 1..end];
+        if check_hashtag(name) {
+            hashtags.push((start, end, name.to_string()));
+        }
+        index = end_index;
+    }
+    hashtags
+}
+
+/// A function to retrieve the normalized
+/// names of the hashtags in the plain text
+/// of a note without duplicates. The names
+/// are returned in order.
+pub fn note_hashtags(
+    content: &str
+) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    for (_start, _end, name) in find_hashtags(content) {
+        let name: String = normalize_hashtag(&name);
+        if !names.contains(&name) {
+            names.push(name);
+        }
+    }
+    names
+}
+
+/// A function to build the URL of
+/// the page of a hashtag on the given
+/// host. The URL is returned.
+pub fn hashtag_url(
+    host: &str,
+    name: &str
+) -> String {
+    format!("{}/tags/{}", base_url(host), normalize_hashtag(name))
+}
+
+/// A function to build the links to
+/// the pages of the hashtags in a line of
+/// the plain text of a note. The byte range
+/// of each hashtag and its HTML are returned
+/// in order.
+pub fn hashtag_links(
+    line: &str,
+    host: &str
+) -> Vec<(usize, usize, String)> {
+    find_hashtags(line)
+        .into_iter()
+        .map(
+            |(start, end, name)| (
+                start,
+                end,
+                format!(
+                    "<a href=\"{}\" class=\"mention hashtag\" rel=\"tag\">#<span>{}</span></a>",
+                    escape_html(&hashtag_url(host, &name)),
+                    escape_html(&name)
+                )
+            )
+        )
+        .collect()
+}
+
+/// A function to build the "Hashtag"
+/// tags of a local note from its plain
+/// text. The tags link to the pages of
+/// the hashtags on the author's host. The
+/// tags are returned.
+pub fn build_hashtag_tags(
+    content: &str,
+    host: &str
+) -> Vec<ApubTag> {
+    note_hashtags(content)
+        .iter()
+        .map(
+            |name| ApubTag{
+                tag_type: "Hashtag".to_string(),
+                href: hashtag_url(host, name),
+                name: format!("#{}", name)
+            }
+        )
+        .collect()
+}
+
+/// A function to read the normalized
+/// names of the "Hashtag" tags of a note
+/// received from another server. Invalid
+/// names are left out. The names are
+/// returned.
+pub fn hashtag_names(
+    object: &Value
+) -> Vec<String> {
+    let tags: Vec<Value> = match object.get("tag") {
+        Some(Value::Array(tags)) => tags.clone(),
+        Some(tag) => vec![tag.clone()],
+        None => Vec::new()
+    };
+    let mut names: Vec<String> = Vec::new();
+    for tag in tags {
+        if tag.get("type").and_then(|tag_type| tag_type.as_str()) != Some("Hashtag") {
+            continue;
+        }
+        let name: String = match tag.get("name").and_then(|name| name.as_str()) {
+            Some(name) => normalize_hashtag(name),
+            None => continue
+        };
+        if check_hashtag(&name) && !names.contains(&name) {
+            names.push(name);
+        }
+    }
+    names
+}
+
+/// A function that attempts to read
+/// a page of the timeline of a hashtag,
+/// which holds the public notes known to
+/// this instance that carry the hashtag.
+/// The timeline is open to the same readers
+/// as the federated timeline. If the operation
+/// is successful, an instance of the
+/// `TimelineResponse` structure is returned.
+/// If the operation fails, an error is returned.
+pub async fn read_hashtag_timeline(
+    name: &str,
+    api_token: Option<&str>,
+    query: &TimelineQuery,
+    pool: &Pool<Postgres>
+) -> Result<TimelineResponse, KleahErr> {
+    let instance: InstanceInformation = match get_instance_info(pool).await {
+        Ok(instance) => instance,
+        Err(e) => return Err::<TimelineResponse, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    match ensure_timeline_access(
+        &instance.federated_timeline,
+        api_token,
+        pool
+    ).await {
+        Ok(_f) => {},
+        Err(e) => return Err::<TimelineResponse, KleahErr>(e)
+    };
+    let notes: Vec<KleahNote> = match get_hashtag_timeline(
+        &normalize_hashtag(name),
+        parse_cursor(query.max_id.as_deref()).as_ref(),
+        parse_cursor(query.min_id.as_deref()).as_ref(),
+        timeline_page_size(query.limit),
+        pool
+    ).await {
+        Ok(notes) => notes,
+        Err(e) => return Err::<TimelineResponse, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    let expand_spoilers: bool = get_spoiler_preference(api_token, pool).await;
+    Ok(build_note_timeline_response(&notes, expand_spoilers))
+}
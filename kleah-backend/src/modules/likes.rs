@@ -0,0 +1,379 @@
+/*
+Kleah by Alyx Shang.
+Licensed under the FSL v1.
+*/
+
+/// Importing the structure
+/// representing a pool of
+/// database connections.
+use sqlx::Pool;
+
+/// Importing the structure
+/// representing the UTC
+/// time zone.
+use chrono::Utc;
+
+/// Importing the macro
+/// to build JSON values.
+use serde_json::json;
+
+/// Importing the structure
+/// representing an arbitrary
+/// JSON value.
+use serde_json::Value;
+
+/// Importing the function
+/// to pass a single value
+/// as a slice.
+use std::slice::from_ref;
+
+/// Importing the structure
+/// for catching and handling
+/// errors.
+use super::err::KleahErr;
+
+/// Importing the function
+/// to save a new like.
+use super::db::create_like;
+
+/// Importing the function
+/// to build the URL of a note.
+use super::utils::note_url;
+
+/// Importing the function
+/// to delete a like.
+use super::db::destroy_like;
+
+/// Importing the function
+/// to build the URL of an actor.
+use super::utils::actor_url;
+
+/// Importing the enum
+/// to describe the kind
+/// of error that occurred.
+use super::err::KleahErrType;
+
+/// Importing the structure
+/// representing a Postgres
+/// database.
+use sqlx::postgres::Postgres;
+
+/// Importing the structure
+/// modelling a like.
+use super::models::KleahLike;
+
+/// Importing the structure
+/// modelling a note.
+use super::models::KleahNote;
+
+/// Importing the function
+/// to retrieve a like by
+/// its ID.
+use super::db::get_like_by_id;
+
+/// Importing the function
+/// to retrieve a note by
+/// its ID.
+use super::db::get_note_by_id;
+
+/// Importing the structure
+/// modelling an actor.
+use super::models::KleahActor;
+
+/// Importing the structure
+/// modelling an ActivityPub
+/// activity.
+use super::apub::ApubActivity;
+
+/// Importing the function
+/// to retrieve an actor by
+/// their username and host.
+use super::db::get_actor_by_id;
+
+/// Importing the function
+/// to check whether an actor
+/// may read a note.
+use super::notes::can_view_note;
+
+/// Importing the function
+/// to retrieve the like of
+/// a note by an actor.
+use super::db::get_like_between;
+
+/// Importing the function
+/// to retrieve the ID of an
+/// ActivityPub object.
+use super::federation::object_id;
+
+/// Importing the function
+/// to retrieve a note by
+/// its URL.
+use super::notes::get_note_by_url;
+
+/// Importing the function
+/// to queue activities for
+/// delivery.
+use super::queue::deliver_activity;
+
+/// Importing the function
+/// to notify a local user.
+use super::notifications::notify_user;
+
+/// Importing the function
+/// to build a new activity.
+use super::federation::build_activity;
+
+/// Importing the function
+/// to retrieve the actor and
+/// note of an interaction.
+use super::notes::get_note_interaction;
+
+/// Importing the constant
+/// containing the URL of the
+/// ActivityStreams context.
+use super::apub::ACTIVITYSTREAMS_CONTEXT;
+
+/// A function to build the "Like"
+/// activity of a stored like so that
+/// it can be sent or referenced in an
+/// "Undo" activity.
+pub fn build_like_activity(
+    like: &KleahLike,
+    actor: &KleahActor,
+    note: &KleahNote,
+    author: &KleahActor
+) -> ApubActivity {
+    ApubActivity{
+        context: ACTIVITYSTREAMS_CONTEXT.to_string(),
+        id: like.like_id.clone(),
+        activity_type: "Like".to_string(),
+        actor: actor_url(actor),
+        object: Value::String(note_url(note)),
+        to: vec![actor_url(author)],
+        cc: Vec::new()
+    }
+}
+
+/// A function that attempts to make the
+/// user with the given API token like a note.
+/// Local authors are notified, authors from
+/// other servers are sent a "Like" activity.
+/// Liking a note twice has no effect. If the
+/// operation is successful, the liked note is
+/// returned. If the operation fails, an error
+/// is returned.
+pub async fn like_note(
+    api_token: &str,
+    note_id: &str,
+    pool: &Pool<Postgres>
+) -> Result<KleahNote, KleahErr> {
+    let (viewer, note, author): (KleahActor, KleahNote, KleahActor) = match get_note_interaction(
+        api_token,
+        note_id,
+        pool
+    ).await {
+        Ok(interaction) => interaction,
+        Err(e) => return Err::<KleahNote, KleahErr>(e)
+    };
+    let activity: ApubActivity = ApubActivity{
+        to: vec![actor_url(&author)],
+        ..build_activity(&viewer, "Like", Value::String(note_url(&note)))
+    };
+    let like: KleahLike = KleahLike{
+        like_id: activity.id.clone(),
+        note_id: note.note_id.clone(),
+        actor_username: viewer.username.clone(),
+        actor_host: viewer.host.clone(),
+        created_at: Utc::now()
+    };
+    let is_new: bool = match create_like(&like, pool).await {
+        Ok(is_new) => is_new,
+        Err(e) => return Err::<KleahNote, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    if is_new {
+        let delivered: Result<(), KleahErr> = if note.ap_id.is_none() {
+            notify_user(&author, "like", &viewer, Some(&note.note_id), pool).await
+        }
+        else {
+            deliver_activity(&viewer.username, from_ref(&author), &activity, pool).await
+        };
+        match delivered {
+            Ok(_f) => {},
+            Err(e) => return Err::<KleahNote, KleahErr>(
+                KleahErr::new(&e.to_string())
+            )
+        };
+    }
+    match get_note_by_id(&note.note_id, pool).await {
+        Ok(note) => Ok(note),
+        Err(e) => Err::<KleahNote, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    }
+}
+
+/// A function that attempts to remove the
+/// like of a note by the user with the given
+/// API token. Authors from other servers are
+/// sent an "Undo" activity for the original
+/// "Like" activity. If the operation is
+/// successful, the note is returned. If the
+/// note was not liked or the operation fails,
+/// an error is returned.
+pub async fn unlike_note(
+    api_token: &str,
+    note_id: &str,
+    pool: &Pool<Postgres>
+) -> Result<KleahNote, KleahErr> {
+    let (viewer, note, author): (KleahActor, KleahNote, KleahActor) = match get_note_interaction(
+        api_token,
+        note_id,
+        pool
+    ).await {
+        Ok(interaction) => interaction,
+        Err(e) => return Err::<KleahNote, KleahErr>(e)
+    };
+    let like: KleahLike = match get_like_between(&note.note_id, &viewer, pool).await {
+        Ok(like) => like,
+        Err(_e) => return Err::<KleahNote, KleahErr>(
+            KleahErr::with_type(
+                "The note has not been liked.",
+                KleahErrType::NotFound
+            )
+        )
+    };
+    match destroy_like(&like.like_id, pool).await {
+        Ok(_f) => {},
+        Err(e) => return Err::<KleahNote, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    if note.ap_id.is_some() {
+        let like_activity: ApubActivity = build_like_activity(&like, &viewer, &note, &author);
+        let undo: ApubActivity = ApubActivity{
+            to: like_activity.to.clone(),
+            ..build_activity(&viewer, "Undo", json!(like_activity))
+        };
+        match deliver_activity(&viewer.username, from_ref(&author), &undo, pool).await {
+            Ok(_f) => {},
+            Err(e) => return Err::<KleahNote, KleahErr>(
+                KleahErr::new(&e.to_string())
+            )
+        };
+    }
+    match get_note_by_id(&note.note_id, pool).await {
+        Ok(note) => Ok(note),
+        Err(e) => Err::<KleahNote, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    }
+}
+
+/// A function that attempts to handle a
+/// received "Like" activity for a note written
+/// by a local actor. The like is counted and
+/// the author is notified. Likes of notes the
+/// actor may not see and of notes from other
+/// servers are ignored. If the operation fails,
+/// an error is returned.
+pub async fn handle_like(
+    actor: &KleahActor,
+    activity: &Value,
+    pool: &Pool<Postgres>
+) -> Result<(), KleahErr> {
+    let like_id: String = match object_id(activity){
+        Some(like_id) => like_id,
+        None => return Err::<(), KleahErr>(
+            KleahErr::with_type(
+                "The activity has no ID.",
+                KleahErrType::BadRequest
+            )
+        )
+    };
+    let target: String = match activity.get("object").and_then(object_id){
+        Some(target) => target,
+        None => return Err::<(), KleahErr>(
+            KleahErr::with_type(
+                "The activity has no object.",
+                KleahErrType::BadRequest
+            )
+        )
+    };
+    let note: KleahNote = match get_note_by_url(&target, pool).await {
+        Ok(note) if note.ap_id.is_none() => note,
+        _ => return Ok(())
+    };
+    match can_view_note(&note, Some(actor), pool).await {
+        Ok(true) => {},
+        Ok(false) => return Ok(()),
+        Err(e) => return Err::<(), KleahErr>(e)
+    };
+    let like: KleahLike = KleahLike{
+        like_id,
+        note_id: note.note_id.clone(),
+        actor_username: actor.username.clone(),
+        actor_host: actor.host.clone(),
+        created_at: Utc::now()
+    };
+    let is_new: bool = match create_like(&like, pool).await {
+        Ok(is_new) => is_new,
+        Err(e) => return Err::<(), KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    if !is_new {
+        return Ok(());
+    }
+    let author: KleahActor = match get_actor_by_id(
+        &note.author_username,
+        &note.author_host,
+        pool
+    ).await {
+        Ok(author) => author,
+        Err(e) => return Err::<(), KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    notify_user(&author, "like", actor, Some(&note.note_id), pool).await
+}
+
+/// A function that attempts to handle a
+/// received "Undo" activity for a "Like"
+/// activity. The like is looked up by its ID
+/// first and by the liked note second, and it
+/// must belong to the given actor. Unknown likes
+/// are ignored. If the operation fails, an error
+/// is returned.
+pub async fn handle_undo_like(
+    actor: &KleahActor,
+    object: &Value,
+    pool: &Pool<Postgres>
+) -> Result<(), KleahErr> {
+    let by_id: Option<KleahLike> = match object_id(object){
+        Some(like_id) => get_like_by_id(&like_id, pool).await.ok(),
+        None => None
+    };
+    let like: KleahLike = match by_id {
+        Some(like) => like,
+        None => {
+            let target: String = match object.get("object").and_then(object_id){
+                Some(target) => target,
+                None => return Ok(())
+            };
+            let note: KleahNote = match get_note_by_url(&target, pool).await {
+                Ok(note) => note,
+                Err(_e) => return Ok(())
+            };
+            match get_like_between(&note.note_id, actor, pool).await {
+                Ok(like) => like,
+                Err(_e) => return Ok(())
+            }
+        }
+    };
+    if like.actor_username != actor.username || like.actor_host != actor.host {
+        return Ok(());
+    }
+    destroy_like(&like.like_id, pool).await
+}
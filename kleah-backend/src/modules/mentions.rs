@@ -0,0 +1,318 @@
+/*
+Kleah by Alyx Shang.
+Licensed under the FSL v1.
+*/
+
+/// Importing the structure
+/// representing a pool of
+/// database connections.
+use sqlx::Pool;
+
+/// Importing the structure
+/// representing an arbitrary
+/// JSON value.
+use serde_json::Value;
+
+/// Importing the structure
+/// for catching and handling
+/// errors.
+use super::err::KleahErr;
+
+/// Importing the structure
+/// containing data about
+/// a tag of a note.
+use super::apub::ApubTag;
+
+/// Importing the function
+/// to build the URL of an actor.
+use super::utils::actor_url;
+
+/// Importing the structure
+/// representing a Postgres
+/// database.
+use sqlx::postgres::Postgres;
+
+/// Importing the structure
+/// modelling a note.
+use super::models::KleahNote;
+
+/// Importing the function
+/// to escape text for HTML.
+use super::utils::escape_html;
+
+/// Importing the structure
+/// modelling an actor.
+use super::models::KleahActor;
+
+/// Importing the function
+/// to retrieve an actor by
+/// their username and host.
+use super::db::get_actor_by_id;
+
+/// Importing the function
+/// to retrieve information
+/// about this instance.
+use super::db::get_instance_info;
+
+/// Importing the structure
+/// representing a single
+/// connection to a Postgres
+/// database.
+use sqlx::postgres::PgConnection;
+
+/// Importing the function
+/// to address a note to
+/// an actor.
+use super::db::add_note_recipient;
+
+/// Importing the function
+/// to retrieve the actors a
+/// note is addressed to.
+use super::db::get_note_recipients;
+
+/// Importing the structure
+/// modelling information
+/// about this instance.
+use super::models::InstanceInformation;
+
+/// Importing the function
+/// to notify a local user
+/// over a connection.
+use super::notifications::add_notification;
+
+/// Importing the function
+/// to resolve an account handle
+/// to an actor.
+use super::federation::resolve_remote_actor;
+
+/// A function to check whether the
+/// given character can be part of the
+/// username or the host in the handle of
+/// an account. A boolean reflecting this
+/// is returned.
+pub fn is_handle_char(
+    character: char
+) -> bool {
+    character.is_ascii_alphanumeric() || matches!(character, '_' | '.' | '-' | ':')
+}
+
+/// A function to find the accounts
+/// mentioned in the plain text of a note.
+/// Mentions are of the form `@user` for local
+/// accounts or `@user@host` for any account.
+/// Handles that are part of a word, such as
+/// email addresses, are not mentions. The byte
+/// range of each mention and the username and
+/// host it names are returned in order.
+pub fn find_mentions(
+    text: &str
+) -> Vec<(usize, usize, String, Option<String>)> {
+    let chars: Vec<(usize, char)> = text.char_indices().collect();
+    let mut mentions: Vec<(usize, usize, String, Option<String>)> = Vec::new();
+    let mut index: usize = 0;
+    while index < chars.len() {
+        let (start, character): (usize, char) = chars[index];
+        let in_word: bool = index > 0 && (is_handle_char(chars[index - 1].1) || chars[index - 1].1 == '@');
+        if character != '@' || in_word {
+            index += 1;
+            continue;
+        }
+        let mut end_index: usize = index + 1;
+        while end_index < chars.len() && (is_handle_char(chars[end_index].1) || chars[end_index].1 == '@') {
+            end_index += 1;
+        }
+        while end_index > index + 1 && matches!(chars[end_index - 1].1, '.' | '-' | ':' | '@') {
+            end_index -= 1;
+        }
+        let end: usize = chars
+            .get(end_index)
+            .map(|(end, _character)| *end)
+            .unwrap_or(text.len());
+        let parts: Vec<&str> = text[start + 1..end].split('@').collect();
+        let is_username = |username: &str| {
+            !username.is_empty() && !username.contains(':')
+        };
+        match parts.as_slice() {
+            [username] if is_username(username) && !username.contains('.') => mentions.push(
+                (start, end, username.to_string(), None)
+            ),
+            [username, host] if is_username(username) && !host.is_empty() => mentions.push(
+                (start, end, username.to_string(), Some(host.to_lowercase()))
+            ),
+            _ => {}
+        };
+        index = end_index.max(index + 1);
+    }
+    mentions
+}
+
+/// A function to find the actor a mention
+/// refers to among the given actors. Mentions
+/// without a host refer to local actors. If no
+/// actor matches, `None` is returned.
+pub fn find_mentioned_actor<'a>(
+    mentions: &'a [KleahActor],
+    username: &str,
+    host: Option<&str>
+) -> Option<&'a KleahActor> {
+    mentions
+        .iter()
+        .find(
+            |actor| actor.username.eq_ignore_ascii_case(username) && match host {
+                Some(host) => actor.host.eq_ignore_ascii_case(host),
+                None => actor.ap_id.is_none()
+            }
+        )
+}
+
+/// A function to build the links to the
+/// profiles of the given actors for their
+/// mentions in a line of the plain text of 
+/// a note. Mentions of other actors are not
+/// linked. The byte range of each linked 
+/// mention and its HTML are returned in order.
+pub fn mention_links(
+    line: &str,
+    mentions: &[KleahActor]
+) -> Vec<(usize, usize, String)> {
+    find_mentions(line)
+        .into_iter()
+        .filter_map(
+            |(start, end, username, host)| {
+                find_mentioned_actor(mentions, &username, host.as_deref()).map(
+                    |actor| (
+                        start,
+                        end,
+                        format!(
+                            "<span class=\"h-card\"><a href=\"{}\" class=\"u-url mention\">@<span>{}</span></a></span>",
+                            escape_html(&actor_url(actor)),
+                            escape_html(&actor.username)
+                        )
+                    )
+                )
+            }
+        )
+        .collect()
+}
+
+/// A function to build the "Mention"
+/// tags of a note for the actors it is
+/// addressed to. The tags are returned.
+pub fn build_mention_tags(
+    mentions: &[KleahActor]
+) -> Vec<ApubTag> {
+    mentions
+        .iter()
+        .map(
+            |actor| ApubTag{
+                tag_type: "Mention".to_string(),
+                href: actor_url(actor),
+                name: format!("@{}@{}", actor.username, actor.host)
+            }
+        )
+        .collect()
+}
+
+/// A function to read the URLs of the
+/// actors tagged as mentioned in a note
+/// received from another server. The URLs
+/// are returned.
+pub fn mention_hrefs(
+    object: &Value
+) -> Vec<String> {
+    let tags: Vec<Value> = match object.get("tag") {
+        Some(Value::Array(tags)) => tags.clone(),
+        Some(tag) => vec![tag.clone()],
+        None => Vec::new()
+    };
+    tags
+        .iter()
+        .filter(|tag| tag.get("type").and_then(|tag_type| tag_type.as_str()) == Some("Mention"))
+        .filter_map(|tag| tag.get("href").and_then(|href| href.as_str()))
+        .map(|href| href.to_string())
+        .collect()
+}
+
+/// A function that attempts to resolve the
+/// accounts mentioned in the plain text of a
+/// note to actors. Local accounts are read from
+/// the database, accounts on other servers are
+/// looked up with WebFinger. Mentions of accounts
+/// that cannot be found are ignored. If the operation
+/// is successful, the actors are returned. If the
+/// operation fails, an error is returned.
+pub async fn resolve_mentions(
+    content: &str,
+    pool: &Pool<Postgres>
+) -> Result<Vec<KleahActor>, KleahErr> {
+    let instance: InstanceInformation = match get_instance_info(pool).await {
+        Ok(instance) => instance,
+        Err(e) => return Err::<Vec<KleahActor>, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    let mut actors: Vec<KleahActor> = Vec::new();
+    for (_start, _end, username, host) in find_mentions(content) {
+        let host: String = host.unwrap_or(instance.host.clone());
+        if find_mentioned_actor(&actors, &username, Some(&host)).is_some() {
+            continue;
+        }
+        let actor: Result<KleahActor, KleahErr> = if host == instance.host {
+            get_actor_by_id(&username.to_lowercase(), &host, pool).await
+        }
+        else {
+            resolve_remote_actor(&format!("{}@{}", username, host), pool).await
+        };
+        if let Ok(actor) = actor {
+            actors.push(actor);
+        }
+    }
+    Ok(actors)
+}
+
+/// A function that attempts to address a
+/// local note to the actors mentioned in it,
+/// as resolved from its content beforehand.
+/// Local users are notified about the mention,
+/// unless the note replies to one of their notes,
+/// which they are notified about instead. Actors
+/// the note is already addressed to are skipped.
+/// Recipients and notifications are saved over the
+/// given connection, so that they can be part of
+/// a transaction. If the operation fails, an error
+/// is returned.
+pub async fn address_mentions(
+    note: &KleahNote,
+    author: &KleahActor,
+    parent: Option<&KleahNote>,
+    mentions: &[KleahActor],
+    conn: &mut PgConnection
+) -> Result<(), KleahErr> {
+    let addressed: Vec<KleahActor> = match get_note_recipients(&note.note_id, &mut *conn).await {
+        Ok(addressed) => addressed,
+        Err(e) => return Err::<(), KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    for mentioned in mentions {
+        if find_mentioned_actor(&addressed, &mentioned.username, Some(&mentioned.host)).is_some() {
+            continue;
+        }
+        match add_note_recipient(&note.note_id, mentioned, &mut *conn).await {
+            Ok(_f) => {},
+            Err(e) => return Err::<(), KleahErr>(
+                KleahErr::new(&e.to_string())
+            )
+        };
+        let wrote_parent: bool = parent.is_some_and(
+            |parent| parent.author_username == mentioned.username && parent.author_host == mentioned.host
+        );
+        if !wrote_parent {
+            match add_notification(mentioned, "mention", author, Some(&note.note_id), &mut *conn).await {
+                Ok(_f) => {},
+                Err(e) => return Err::<(), KleahErr>(e)
+            };
+        }
+    }
+    Ok(())
+}
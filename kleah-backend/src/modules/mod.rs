@@ -54,3 +54,90 @@ pub mod payloads;
 /// for sending JSON
 /// payloads.
 pub mod responses;
+
+/// Exporting the module
+/// containing structures
+/// modelling ActivityPub
+/// objects.
+pub mod apub;
+
+/// Exporting the module
+/// containing functions
+/// for signing and verifying
+/// HTTP requests.
+pub mod signatures;
+
+/// Exporting the module
+/// containing functions
+/// for communicating with
+/// other ActivityPub servers.
+pub mod federation;
+
+/// Exporting the module
+/// containing functions
+/// for queueing and sending
+/// activities to other servers.
+pub mod queue;
+
+/// Exporting the module
+/// containing functions
+/// for describing this
+/// instance with NodeInfo.
+pub mod nodeinfo;
+
+/// Exporting the module
+/// containing functions
+/// for publishing and 
+/// managing notes.
+pub mod notes;
+
+/// Exporting the module
+/// containing functions
+/// for reading timelines.
+pub mod timelines;
+
+/// Exporting the module
+/// containing functions
+/// for notifying local
+/// users.
+pub mod notifications;
+
+/// Exporting the module
+/// containing functions
+/// for liking notes.
+pub mod likes;
+
+/// Exporting the module
+/// containing functions
+/// for boosting notes.
+pub mod boosts;
+
+/// Exporting the module
+/// containing functions
+/// for threads of notes.
+pub mod threads;
+
+/// Exporting the module
+/// containing functions
+/// for mentioning actors.
+pub mod mentions;
+
+/// Exporting the module
+/// containing functions
+/// for hashtags.
+pub mod hashtags;
+
+/// Exporting the module
+/// containing functions
+/// for editing notes.
+pub mod edits;
+
+/// Exporting the module
+/// containing functions
+/// for polls.
+pub mod polls;
+
+/// Exporting the module
+/// containing functions
+/// for reports.
+pub mod reports;
@@ -10,6 +10,15 @@ Licensed under the FSL v1.
 /// structures.
 use sqlx::FromRow;
 
+/// Importing the structure
+/// representing the UTC
+/// time zone.
+use chrono::Utc;
+
+/// Importing this structure
+/// to store points in time.
+use chrono::DateTime;
+
 /// A structure to model
 /// a Kleah user on a
 /// Kleah instance in the
@@ -23,14 +32,17 @@ pub struct KleahUser{
     pub public_key: String,
     pub description: String,
     pub private_key: String,
-    pub is_admin: bool
+    pub is_admin: bool,
+    pub is_locked: bool,
+    pub last_active: Option<DateTime<Utc>>,
+    pub expand_spoilers: bool
 }
 
 /// A structure to model
 /// an ActivityPub actor on a
 /// Kleah instance in the
 /// database.
-#[derive(FromRow)]
+#[derive(FromRow, Clone)]
 pub struct KleahActor{
     pub name: String,
     pub actor_type: String,
@@ -43,6 +55,10 @@ pub struct KleahActor{
     pub username: String,
     pub description: String,
     pub public_key: String,
+    pub ap_id: Option<String>,
+    pub key_id: Option<String>,
+    pub shared_inbox: Option<String>,
+    pub fetched_at: Option<DateTime<Utc>>
 }
 
 /// A structure to model
@@ -52,7 +68,9 @@ pub struct KleahActor{
 #[derive(FromRow)]
 pub struct InstanceInformation{
     pub host: String,
-    pub uses_invites: bool
+    pub uses_invites: bool,
+    pub local_timeline: String,
+    pub federated_timeline: String
 }
 
 /// A structure to model
@@ -77,3 +95,244 @@ pub struct UserFile{
     pub is_public: bool,
     pub file_path: String
 }
+
+/// A structure to model
+/// a follow relationship
+/// between two ActivityPub
+/// actors in the database.
+#[derive(FromRow)]
+pub struct KleahFollow{
+    pub follow_id: String,
+    pub follower_username: String,
+    pub follower_host: String,
+    pub followee_username: String,
+    pub followee_host: String,
+    pub accepted: bool,
+    pub created_at: DateTime<Utc>
+}
+
+/// A structure to model
+/// a single item of an
+/// ActivityPub collection
+/// retrieved from the database
+/// together with the point in 
+/// time it was added to the
+/// collection and the ID of the
+/// row it was read from.
+#[derive(FromRow)]
+pub struct CollectionItem{
+    pub item_id: String,
+    pub cursor_id: String,
+    pub created_at: DateTime<Utc>
+}
+
+/// A structure to model
+/// an activity waiting to
+/// be delivered to the inbox
+/// of a remote server in
+/// the database.
+#[derive(FromRow)]
+pub struct KleahDelivery{
+    pub delivery_id: String,
+    pub activity_id: String,
+    pub sender_username: String,
+    pub inbox: String,
+    pub payload: String,
+    pub attempts: i32,
+    pub next_attempt_at: DateTime<Utc>,
+    pub last_error: Option<String>,
+    pub failed_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>
+}
+
+/// A structure to model
+/// the number of users on
+/// this instance in total 
+/// and those recently active.
+#[derive(FromRow)]
+pub struct KleahUserCounts{
+    pub total: i64,
+    pub active_month: i64,
+    pub active_halfyear: i64
+}
+
+/// A structure to model
+/// a note posted by a local
+/// or remote actor in the
+/// database.
+#[derive(FromRow)]
+pub struct KleahNote{
+    pub note_id: String,
+    pub ap_id: Option<String>,
+    pub author_username: String,
+    pub author_host: String,
+    pub content: String,
+    pub in_reply_to: Option<String>,
+    pub visibility: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: Option<DateTime<Utc>>,
+    pub like_count: i64,
+    pub boost_count: i64,
+    pub conversation: Option<String>,
+    pub summary: Option<String>,
+    pub sensitive: bool,
+    pub has_poll: bool
+}
+
+/// A structure to model
+/// a notification for a 
+/// local user about an 
+/// action of another actor
+/// in the database.
+#[derive(FromRow)]
+pub struct KleahNotification{
+    pub notification_id: String,
+    pub username: String,
+    pub kind: String,
+    pub actor_username: String,
+    pub actor_host: String,
+    pub note_id: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub is_read: bool,
+    pub report_id: Option<String>
+}
+
+/// A structure to model
+/// a like of a note by a
+/// local or remote actor in
+/// the database.
+#[derive(FromRow)]
+pub struct KleahLike{
+    pub like_id: String,
+    pub note_id: String,
+    pub actor_username: String,
+    pub actor_host: String,
+    pub created_at: DateTime<Utc>
+}
+
+/// A structure to model
+/// a boost of a note by a
+/// local or remote actor in
+/// the database.
+#[derive(FromRow)]
+pub struct KleahBoost{
+    pub boost_id: String,
+    pub note_id: String,
+    pub actor_username: String,
+    pub actor_host: String,
+    pub created_at: DateTime<Utc>
+}
+
+/// A structure to model an
+/// entry of a timeline or an
+/// outbox, which is either a 
+/// note or the boost of a note.
+/// Boosts have the ID of the boost
+/// and the booster set.
+#[derive(FromRow)]
+pub struct KleahTimelineEntry{
+    pub note_id: String,
+    pub boost_id: Option<String>,
+    pub booster_username: Option<String>,
+    pub booster_host: Option<String>,
+    pub created_at: DateTime<Utc>
+}
+
+/// A structure to model
+/// the position of an item
+/// on a page of a list that
+/// is ordered by creation time.
+/// Items created at the same time
+/// are ordered by their ID.
+pub struct KleahCursor{
+    pub created_at: DateTime<Utc>,
+    pub id: String
+}
+
+/// A structure to model
+/// a prior revision of an
+/// edited note in the database.
+#[derive(FromRow)]
+pub struct KleahNoteEdit{
+    pub edit_id: String,
+    pub note_id: String,
+    pub content: String,
+    pub summary: Option<String>,
+    pub sensitive: bool,
+    pub created_at: DateTime<Utc>
+}
+
+/// A structure to model
+/// the tombstone left behind
+/// by a deleted local note in
+/// the database.
+#[derive(FromRow)]
+pub struct KleahTombstone{
+    pub note_id: String,
+    pub deleted_at: DateTime<Utc>
+}
+
+/// A structure to model
+/// a poll attached to a note
+/// in the database.
+#[derive(FromRow, Clone)]
+pub struct KleahPoll{
+    pub note_id: String,
+    pub multiple: bool,
+    pub expires_at: DateTime<Utc>,
+    pub closed_at: Option<DateTime<Utc>>,
+    pub voters_count: i64
+}
+
+/// A structure to model
+/// an option of a poll in
+/// the database.
+#[derive(FromRow)]
+pub struct KleahPollOption{
+    pub note_id: String,
+    pub position: i32,
+    pub title: String,
+    pub votes_count: i64
+}
+
+/// A structure to model
+/// a vote of an actor for
+/// an option of a poll in
+/// the database.
+#[derive(FromRow)]
+pub struct KleahPollVote{
+    pub vote_id: String,
+    pub note_id: String,
+    pub position: i32,
+    pub actor_username: String,
+    pub actor_host: String,
+    pub created_at: DateTime<Utc>
+}
+
+/// A structure to model
+/// a category of notifications
+/// a local user muted in the
+/// database.
+#[derive(FromRow)]
+pub struct KleahNotificationMute{
+    pub username: String,
+    pub kind: String
+}
+
+/// A structure to model
+/// a report of an actor and
+/// optionally one of their
+/// notes to the administrators
+/// of this instance in the
+/// database.
+#[derive(FromRow)]
+pub struct KleahReport{
+    pub report_id: String,
+    pub reporter_username: String,
+    pub reporter_host: String,
+    pub target_username: String,
+    pub target_host: String,
+    pub note_id: Option<String>,
+    pub comment: String,
+    pub created_at: DateTime<Utc>
+}
@@ -0,0 +1,186 @@
+/*
+Kleah by Alyx Shang.
+Licensed under the FSL v1.
+*/
+
+/// Importing the structure
+/// representing a pool of
+/// database connections.
+use sqlx::Pool;
+
+/// Importing the structure
+/// to guard data shared
+/// between threads.
+use std::sync::Mutex;
+
+/// Importing the macro
+/// to build JSON values.
+use serde_json::json;
+
+/// Importing the structure
+/// to measure elapsed time.
+use std::time::Instant;
+
+/// Importing the structure
+/// to represent a span of time.
+use std::time::Duration;
+
+/// Importing the structure
+/// for catching and handling
+/// errors.
+use super::err::KleahErr;
+
+/// Importing the function
+/// to count the users on
+/// this instance.
+use super::db::count_users;
+
+/// Importing the structure that
+/// represents a connection to a 
+/// PostgreSQL database.
+use sqlx::postgres::Postgres;
+
+/// Importing the function to
+/// retrieve information about
+/// the current instance.
+use super::db::get_instance_info;
+
+/// Importing the data structure
+/// modelling the number of
+/// users on this instance.
+use super::models::KleahUserCounts;
+
+/// Importing the function
+/// to count the notes
+/// of this instance.
+use super::db::count_notes_by_host;
+
+/// Importing the structure
+/// modelling the usage of
+/// this instance.
+use super::responses::NodeInfoUsage;
+
+/// Importing the structure
+/// modelling the number of
+/// users on this instance.
+use super::responses::NodeInfoUsers;
+
+/// Importing the function
+/// to check whether an instance
+/// accepts new users.
+use super::utils::registrations_open;
+
+/// Importing the structure
+/// modelling the NodeInfo
+/// document of this instance.
+use super::responses::NodeInfoResponse;
+
+/// Importing the structure
+/// modelling the services this
+/// instance connects to.
+use super::responses::NodeInfoServices;
+
+/// Importing the structure
+/// modelling the software
+/// running this instance.
+use super::responses::NodeInfoSoftware;
+
+/// Importing the data structure
+/// modelling data about the
+/// current instance.
+use super::models::InstanceInformation;
+
+/// The URI of the schema of
+/// the NodeInfo documents served
+/// by this instance.
+pub const NODEINFO_SCHEMA: &str = "http://nodeinfo.diaspora.software/ns/schema/2.1";
+
+/// The number of seconds for 
+/// which the usage statistics
+/// of this instance are cached.
+pub const NODEINFO_CACHE_TTL: u64 = 1800;
+
+/// The usage statistics of this
+/// instance together with the time
+/// they were counted.
+static NODEINFO_CACHE: Mutex<Option<(Instant, NodeInfoUsage)>> = Mutex::new(None);
+
+/// A function that attempts to retrieve 
+/// the usage statistics of this instance.
+/// The statistics are counted at most once
+/// per cache period. If the operation is 
+/// successful, the statistics are returned. 
+/// If the operation fails, an error is returned.
+pub async fn get_node_usage(
+    pool: &Pool<Postgres>
+) -> Result<NodeInfoUsage, KleahErr> {
+    if let Ok(cache) = NODEINFO_CACHE.lock() && 
+       let Some((counted_at, usage)) = cache.as_ref() &&
+       counted_at.elapsed() < Duration::from_secs(NODEINFO_CACHE_TTL)
+    {
+        return Ok(usage.clone());
+    }
+    let users: KleahUserCounts = match count_users(pool).await {
+        Ok(users) => users,
+        Err(e) => return Err::<NodeInfoUsage, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    let instance: InstanceInformation = match get_instance_info(pool).await {
+        Ok(instance) => instance,
+        Err(e) => return Err::<NodeInfoUsage, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    let local_posts: i64 = match count_notes_by_host(&instance.host, pool).await {
+        Ok(local_posts) => local_posts,
+        Err(e) => return Err::<NodeInfoUsage, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    let usage: NodeInfoUsage = NodeInfoUsage{
+        users: NodeInfoUsers{
+            total: users.total,
+            active_month: users.active_month,
+            active_halfyear: users.active_halfyear
+        },
+        local_posts
+    };
+    if let Ok(mut cache) = NODEINFO_CACHE.lock() {
+        *cache = Some((Instant::now(), usage.clone()));
+    }
+    Ok(usage)
+}
+
+/// A function to build the NodeInfo
+/// document of this instance from the
+/// instance's information and usage 
+/// statistics. The document is returned.
+pub fn build_nodeinfo(
+    instance: &InstanceInformation,
+    usage: NodeInfoUsage
+) -> NodeInfoResponse {
+    let repository: Option<String> = Some(env!("CARGO_PKG_REPOSITORY"))
+        .filter(|repository| !repository.is_empty())
+        .map(|repository| repository.to_string());
+    let homepage: Option<String> = Some(env!("CARGO_PKG_HOMEPAGE"))
+        .filter(|homepage| !homepage.is_empty())
+        .map(|homepage| homepage.to_string());
+    NodeInfoResponse{
+        version: "2.1".to_string(),
+        software: NodeInfoSoftware{
+            name: env!("CARGO_PKG_NAME").to_string(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            repository,
+            homepage
+        },
+        protocols: vec!["activitypub".to_string()],
+        services: NodeInfoServices{
+            inbound: Vec::new(),
+            outbound: Vec::new()
+        },
+        open_registrations: registrations_open(instance),
+        usage,
+        metadata: json!({})
+    }
+}
@@ -0,0 +1,1910 @@
+/*
+Kleah by Alyx Shang.
+Licensed under the FSL v1.
+*/
+
+/// Importing the structure
+/// representing a pool of
+/// database connections.
+use sqlx::Pool;
+
+/// Importing the structure
+/// representing the UTC
+/// time zone.
+use chrono::Utc;
+
+/// Importing the function
+/// to sanitize HTML.
+use ammonia::clean;
+
+/// Importing this structure
+/// to store points in time.
+use chrono::DateTime;
+
+/// Importing the structure
+/// representing an arbitrary
+/// JSON value.
+use serde_json::Value;
+
+/// Importing the structure
+/// representing a database
+/// transaction.
+use sqlx::Transaction;
+
+/// Importing the structure
+/// for catching and handling
+/// errors.
+use super::err::KleahErr;
+
+/// Importing the function
+/// to convert a Rust data
+/// structure into a JSON value.
+use serde_json::to_value;
+
+/// Importing the structure
+/// containing data about
+/// a tag of a note.
+use super::apub::ApubTag;
+
+/// Importing the enum
+/// to format points in time.
+use chrono::SecondsFormat;
+
+/// Importing the structure
+/// modelling an ActivityPub
+/// note.
+use super::apub::ApubNote;
+
+/// Importing the function
+/// to save a new note.
+use super::db::create_note;
+
+/// Importing the function
+/// to build the URL of a note.
+use super::utils::note_url;
+
+/// Importing the function
+/// to extract the host
+/// of a URL.
+use super::utils::url_host;
+
+/// Importing the function
+/// to delete a note.
+use super::db::destroy_note;
+
+/// Importing the function
+/// to retrieve the URL
+/// of an actor.
+use super::utils::actor_url;
+
+/// Importing the enum
+/// describing the kind of
+/// an error.
+use super::err::KleahErrType;
+
+/// Importing the structure that
+/// represents a connection to a 
+/// PostgreSQL database.
+use sqlx::postgres::Postgres;
+
+/// Importing the data structure
+/// modelling a note in the
+/// database.
+use super::models::KleahNote;
+
+/// Importing the data structure
+/// modelling data about a Kleah user
+/// in the database.
+use super::models::KleahUser;
+
+/// Importing the function
+/// to retrieve the tombstone
+/// of a deleted note.
+use super::db::get_tombstone;
+
+/// Importing the function
+/// to check whether the poll
+/// of a new note is valid.
+use super::polls::check_poll;
+
+/// Importing the structure
+/// modelling a poll.
+use super::models::KleahPoll;
+
+/// Importing the function
+/// to generate a new unique
+/// identifier.
+use super::utils::generate_id;
+
+/// Importing the function
+/// to retrieve a note by
+/// its ID.
+use super::db::get_note_by_id;
+
+/// Importing the data structure
+/// modelling data about an ActivityPub
+/// actor in the database.
+use super::models::KleahActor;
+
+/// Importing the structure
+/// modelling an ActivityPub
+/// activity.
+use super::apub::ApubActivity;
+
+/// Importing the function
+/// to escape text for HTML.
+use super::utils::escape_html;
+
+/// Importing the structure
+/// modelling a boost.
+use super::models::KleahBoost;
+
+/// Importing the function to
+/// retrieve an actor by their
+/// username and host.
+use super::db::get_actor_by_id;
+
+/// Importing the structure
+/// containing data about the
+/// tombstone of a deleted note.
+use super::apub::ApubTombstone;
+
+/// Importing the structure
+/// representing a connection
+/// taken from a pool.
+use sqlx::pool::PoolConnection;
+
+/// Importing the function
+/// to read a page of the
+/// outbox of an actor.
+use super::db::get_outbox_page;
+
+/// Importing the structure
+/// modelling the position
+/// of an item in a collection.
+use super::models::KleahCursor;
+
+/// Importing the function
+/// to leave a tombstone for
+/// a deleted note.
+use super::db::create_tombstone;
+
+/// Importing the structure
+/// containing data about an
+/// option of a poll.
+use super::apub::ApubPollOption;
+
+/// Importing the function
+/// to retrieve the poll of
+/// a note.
+use super::polls::get_note_poll;
+
+/// Importing the function
+/// to retrieve several
+/// notes by their IDs.
+use super::db::get_notes_by_ids;
+
+/// Importing the function to
+/// retrieve information about
+/// the current instance.
+use super::db::get_instance_info;
+
+/// Importing the function to
+/// retrieve a user by one of 
+/// their API tokens.
+use super::db::get_user_by_token;
+
+/// Importing the function
+/// to retrieve the ID of
+/// an ActivityPub object.
+use super::federation::object_id;
+
+/// Importing the function
+/// to retrieve a note by
+/// its ActivityPub ID.
+use super::db::get_note_by_ap_id;
+
+/// Importing the function
+/// to build the URL of
+/// a local note.
+use super::utils::local_note_url;
+
+/// Importing the function
+/// to save the hashtags
+/// of a note.
+use super::db::add_note_hashtags;
+
+/// Importing the structure
+/// modelling the poll of a
+/// new note.
+use super::payloads::PollPayload;
+
+/// Importing the function
+/// to queue an activity for
+/// delivery to other servers.
+use super::queue::queue_activity;
+
+/// Importing the structure
+/// representing a single
+/// connection to a Postgres
+/// database.
+use sqlx::postgres::PgConnection;
+
+/// Importing the function
+/// to retrieve the IDs of
+/// a list of ActivityPub objects.
+use super::federation::object_ids;
+
+/// Importing the function
+/// to check whether an actor
+/// may see a note.
+use super::db::note_is_visible_to;
+
+/// Importing the function
+/// to record the recipients
+/// of a note.
+use super::db::add_note_recipient;
+
+/// Importing the function
+/// to fetch ActivityPub
+/// documents.
+use super::federation::fetch_json;
+
+/// Importing the structure
+/// to send data about a note.
+use super::responses::NoteResponse;
+
+/// Importing the URI
+/// addressing public objects
+/// to everyone.
+use super::apub::PUBLIC_COLLECTION;
+
+/// Importing the function
+/// to retrieve the recipients
+/// of a note.
+use super::db::get_note_recipients;
+
+/// Importing the function
+/// to read the mentions of
+/// a received note.
+use super::mentions::mention_hrefs;
+
+/// Importing the function
+/// to link the mentions
+/// in a line of text.
+use super::mentions::mention_links;
+
+/// Importing the function
+/// to link the hashtags
+/// in a line of text.
+use super::hashtags::hashtag_links;
+
+/// Importing the function
+/// to find the hashtags
+/// of a local note.
+use super::hashtags::note_hashtags;
+
+/// Importing the function
+/// to read the hashtags
+/// of a received note.
+use super::hashtags::hashtag_names;
+
+/// Importing the structure
+/// modelling an option of
+/// a poll.
+use super::models::KleahPollOption;
+
+/// Importing the function
+/// to attach a poll to a
+/// new note.
+use super::polls::create_note_poll;
+
+/// Importing the function
+/// to build the cursor
+/// of a timeline entry.
+use super::timelines::entry_cursor;
+
+/// Importing the function
+/// to retrieve the followers
+/// of an actor on other servers.
+use super::db::get_remote_followers;
+
+/// Importing the function
+/// to store the poll of a
+/// note from another server.
+use super::polls::store_remote_poll;
+
+/// Importing the function
+/// to read the options of
+/// a poll from another server.
+use super::polls::read_poll_choices;
+
+/// Importing the function
+/// to build the options of
+/// a poll for a question.
+use super::polls::build_poll_options;
+
+/// Importing the function
+/// to retrieve the URL of the
+/// followers of an actor.
+use super::utils::actor_followers_url;
+
+/// Importing the function
+/// to notify a local user.
+use super::notifications::notify_user;
+
+/// Importing the function
+/// to address a note to
+/// the actors it mentions.
+use super::mentions::address_mentions;
+
+/// Importing the function
+/// to resolve the actors
+/// mentioned in a note.
+use super::mentions::resolve_mentions;
+
+/// Importing the number
+/// of items on a page
+/// of a collection.
+use super::apub::COLLECTION_PAGE_SIZE;
+
+/// Importing the structure
+/// modelling an entry
+/// of a timeline.
+use super::models::KleahTimelineEntry;
+
+/// Importing the data structure
+/// modelling data about the
+/// current instance.
+use super::models::InstanceInformation;
+
+/// Importing the function
+/// to retrieve the cached
+/// record of a remote actor.
+use super::federation::get_remote_actor;
+
+/// Importing the function
+/// to build the ID of a
+/// new conversation.
+use super::threads::new_conversation_id;
+
+/// Importing the function
+/// to build the tags of
+/// mentioned actors.
+use super::mentions::build_mention_tags;
+
+/// Importing the function
+/// to build the tags of
+/// hashtags.
+use super::hashtags::build_hashtag_tags;
+
+/// Importing the URI of the
+/// JSON-LD context for
+/// ActivityStreams documents.
+use super::apub::ACTIVITYSTREAMS_CONTEXT;
+
+/// Importing the function
+/// to extract the username
+/// from the URL of a local actor.
+use super::utils::local_username_from_url;
+
+/// Importing the function
+/// to derive the conversation
+/// of a received note.
+use super::threads::incoming_conversation;
+
+/// Importing the function
+/// to notify a local user
+/// over a connection.
+use super::notifications::add_notification;
+
+/// Importing the function
+/// to build the "Announce"
+/// activity of a boost.
+use super::boosts::build_announce_activity;
+
+/// The maximum number of
+/// characters a note may
+/// contain.
+pub const NOTE_MAX_LENGTH: usize = 500;
+
+/// The levels of visibility
+/// a note may have.
+pub const NOTE_VISIBILITIES: [&str; 4] = ["public", "unlisted", "followers", "direct"];
+
+/// A function to check whether the content
+/// of a note is neither empty nor longer
+/// than allowed. A boolean reflecting this
+/// is returned.
+pub fn check_note_content(
+    content: &str
+) -> bool {
+    !content.trim().is_empty() && content.chars().count() <= NOTE_MAX_LENGTH
+}
+
+/// A function to normalize the content
+/// warning of a note. Surrounding whitespace
+/// is removed and empty content warnings are
+/// dropped. The content warning is returned 
+/// if there is one.
+pub fn normalize_summary(
+    summary: Option<&str>
+) -> Option<String> {
+    summary
+        .map(|summary| summary.trim())
+        .filter(|summary| !summary.is_empty())
+        .map(|summary| summary.to_string())
+}
+
+/// A function to check whether the content
+/// and the content warning of a note together
+/// are no longer than allowed, as content warnings
+/// count towards the length of a note. A boolean 
+/// reflecting this is returned.
+pub fn check_note_summary(
+    content: &str,
+    summary: Option<&str>
+) -> bool {
+    content.chars().count() + summary.map(|summary| summary.chars().count()).unwrap_or(0) <= NOTE_MAX_LENGTH
+}
+
+/// A function to read the content warning
+/// of a note received from another server.
+/// The content warning is cleaned of unsafe
+/// markup and normalized. The content warning
+/// is returned if there is one.
+pub fn read_note_summary(
+    object: &Value
+) -> Option<String> {
+    normalize_summary(
+        object
+            .get("summary")
+            .and_then(|summary| summary.as_str())
+            .map(clean)
+            .as_deref()
+    )
+}
+
+/// A function to check whether the given
+/// string is a known level of visibility
+/// for notes. A boolean reflecting this
+/// is returned.
+pub fn check_visibility(
+    visibility: &str
+) -> bool {
+    NOTE_VISIBILITIES.contains(&visibility)
+}
+
+/// A function to build the response
+/// describing a note for the API of
+/// this instance. Notes with a content
+/// warning are only marked as expanded if
+/// the reader prefers this. The response is
+/// returned.
+pub fn build_note_response(
+    note: &KleahNote,
+    expand_spoilers: bool
+) -> NoteResponse {
+    NoteResponse{
+        note_id: note.note_id.clone(),
+        url: note_url(note),
+        author: note.author_username.clone(),
+        host: note.author_host.clone(),
+        content: note.content.clone(),
+        in_reply_to: note.in_reply_to.clone(),
+        visibility: note.visibility.clone(),
+        created_at: note.created_at.to_rfc3339(),
+        updated_at: note.updated_at.map(|updated_at| updated_at.to_rfc3339()),
+        likes: note.like_count,
+        boosts: note.boost_count,
+        summary: note.summary.clone(),
+        sensitive: note.sensitive,
+        expanded: note.summary.is_none() || expand_spoilers,
+        has_poll: note.has_poll
+    }
+}
+
+/// A function that attempts to find out
+/// whether the user with the given API token
+/// prefers notes with a content warning to be
+/// expanded. Anonymous readers and readers with
+/// an invalid token see them collapsed. A boolean
+/// reflecting this is returned.
+pub async fn get_spoiler_preference(
+    api_token: Option<&str>,
+    pool: &Pool<Postgres>
+) -> bool {
+    match api_token {
+        Some(api_token) => match get_user_by_token(api_token, pool).await {
+            Ok(user) => user.expand_spoilers,
+            Err(_e) => false
+        },
+        None => false
+    }
+}
+
+/// A function that attempts to publish a
+/// new note written by a local user. The note
+/// may reply to another note given by its ID
+/// or URL, in which case it joins the note's
+/// conversation and is addressed to its author.
+/// Notes the author may not see cannot be
+/// replied to.
+/// Mentioned actors are addressed as well and
+/// hashtags are stored. If no visibility is given,
+/// the note is public. A note with a content warning
+/// is always marked as sensitive. A note may carry
+/// a poll. Unless the note is direct, it is sent to
+/// the author's followers on other servers. The note
+/// and everything attached to it are saved in one
+/// transaction and only sent once they are saved.
+/// If the operation is successful, an instance of
+/// the `KleahNote` structure is returned. If the
+/// operation fails, an error is returned.
+#[allow(clippy::too_many_arguments)]
+pub async fn publish_note(
+    username: &str,
+    content: &str,
+    in_reply_to: Option<&str>,
+    visibility: Option<&str>,
+    summary: Option<&str>,
+    sensitive: &bool,
+    poll: Option<&PollPayload>,
+    pool: &Pool<Postgres>
+) -> Result<KleahNote, KleahErr> {
+    let visibility: &str = visibility.unwrap_or("public");
+    if !check_visibility(visibility) {
+        return Err::<KleahNote, KleahErr>(
+            KleahErr::with_type(
+                "The visibility of the note is not valid.",
+                KleahErrType::BadRequest
+            )
+        )
+    }
+    if !check_note_content(content) {
+        return Err::<KleahNote, KleahErr>(
+            KleahErr::with_type(
+                "The content of the note is empty or too long.",
+                KleahErrType::BadRequest
+            )
+        )
+    }
+    let summary: Option<String> = normalize_summary(summary);
+    if !check_note_summary(content, summary.as_deref()) {
+        return Err::<KleahNote, KleahErr>(
+            KleahErr::with_type(
+                "The content warning of the note is too long.",
+                KleahErrType::BadRequest
+            )
+        )
+    }
+    if poll.is_some_and(|poll| !check_poll(poll)) {
+        return Err::<KleahNote, KleahErr>(
+            KleahErr::with_type(
+                "The poll of the note is not valid.",
+                KleahErrType::BadRequest
+            )
+        )
+    }
+    let instance: InstanceInformation = match get_instance_info(pool).await {
+        Ok(instance) => instance,
+        Err(e) => return Err::<KleahNote, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    let author: KleahActor = match get_actor_by_id(
+        username, 
+        &instance.host, 
+        pool
+    ).await {
+        Ok(author) => author,
+        Err(e) => return Err::<KleahNote, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    let parent: Option<KleahNote> = match in_reply_to {
+        Some(parent) if parent.contains("://") => match get_note_by_url(parent, pool).await {
+            Ok(parent) => match can_view_note(&parent, Some(&author), pool).await {
+                Ok(true) => Some(parent),
+                Ok(false) => return Err::<KleahNote, KleahErr>(
+                    KleahErr::with_type(
+                        "The note being replied to does not exist.",
+                        KleahErrType::NotFound
+                    )
+                ),
+                Err(e) => return Err::<KleahNote, KleahErr>(
+                    KleahErr::new(&e.to_string())
+                )
+            },
+            Err(_e) => None
+        },
+        Some(parent) => match get_note_for_viewer(parent, Some(&author), pool).await {
+            Ok(parent) => Some(parent),
+            Err(_e) => return Err::<KleahNote, KleahErr>(
+                KleahErr::with_type(
+                    "The note being replied to does not exist.",
+                    KleahErrType::NotFound
+                )
+            )
+        },
+        None => None
+    };
+    let in_reply_to: Option<String> = match &parent {
+        Some(parent) => Some(note_url(parent)),
+        None => in_reply_to.map(|parent| parent.to_string())
+    };
+    let note_id: String = generate_id(&format!("{}{}", username, content));
+    let created_at: DateTime<Utc> = Utc::now();
+    let conversation: String = match parent.as_ref().and_then(|parent| parent.conversation.clone()) {
+        Some(conversation) => conversation,
+        None => new_conversation_id(&instance.host, &note_id, &created_at)
+    };
+    let mentions: Vec<KleahActor> = match resolve_mentions(content, pool).await {
+        Ok(mentions) => mentions,
+        Err(e) => return Err::<KleahNote, KleahErr>(e)
+    };
+    let mut tx: Transaction<'_, Postgres> = match pool.begin().await {
+        Ok(tx) => tx,
+        Err(e) => return Err::<KleahNote, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    let note: KleahNote = match create_note(
+        &KleahNote{
+            note_id,
+            ap_id: None,
+            author_username: author.username.clone(),
+            author_host: author.host.clone(),
+            content: content.to_string(),
+            in_reply_to,
+            visibility: visibility.to_string(),
+            created_at,
+            updated_at: None,
+            like_count: 0,
+            boost_count: 0,
+            conversation: Some(conversation),
+            sensitive: *sensitive || summary.is_some(),
+            summary,
+            has_poll: poll.is_some()
+        },
+        &mut *tx
+    ).await {
+        Ok(note) => note,
+        Err(e) => return Err::<KleahNote, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    if let Some(parent) = &parent {
+        match address_parent_author(&note, parent, &author, &mut tx, pool).await {
+            Ok(_f) => {},
+            Err(e) => return Err::<KleahNote, KleahErr>(e)
+        };
+    }
+    match address_mentions(&note, &author, parent.as_ref(), &mentions, &mut tx).await {
+        Ok(_f) => {},
+        Err(e) => return Err::<KleahNote, KleahErr>(e)
+    };
+    match add_note_hashtags(&note, &note_hashtags(&note.content), &mut *tx).await {
+        Ok(_f) => {},
+        Err(e) => return Err::<KleahNote, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    if let Some(poll) = poll {
+        match create_note_poll(&note, poll, &mut *tx).await {
+            Ok(_f) => {},
+            Err(e) => return Err::<KleahNote, KleahErr>(e)
+        };
+    }
+    match tx.commit().await {
+        Ok(_f) => {},
+        Err(e) => return Err::<KleahNote, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    match deliver_note_creation(&note, &author, pool).await {
+        Ok(_f) => Ok(note),
+        Err(e) => Err::<KleahNote, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    }
+}
+
+/// A function that attempts to address a
+/// reply to the author of the note it replies
+/// to, so that they can read it and receive it
+/// on other servers. Local authors are notified
+/// about the reply. Replies to one's own notes
+/// are not addressed. The recipient and the
+/// notification are saved over the given
+/// connection. If the operation fails, an error
+/// is returned.
+pub async fn address_parent_author(
+    note: &KleahNote,
+    parent: &KleahNote,
+    author: &KleahActor,
+    conn: &mut PgConnection,
+    pool: &Pool<Postgres>
+) -> Result<(), KleahErr> {
+    if parent.author_username == author.username && parent.author_host == author.host {
+        return Ok(());
+    }
+    let parent_author: KleahActor = match get_actor_by_id(
+        &parent.author_username,
+        &parent.author_host,
+        pool
+    ).await {
+        Ok(parent_author) => parent_author,
+        Err(e) => return Err::<(), KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    match add_note_recipient(&note.note_id, &parent_author, &mut *conn).await {
+        Ok(_f) => {},
+        Err(e) => return Err::<(), KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    add_notification(&parent_author, "reply", author, Some(&note.note_id), conn).await
+}
+
+/// A function to check whether a note
+/// can be read by anyone because it is
+/// public or unlisted. A boolean reflecting
+/// this is returned.
+pub fn is_publicly_visible(
+    note: &KleahNote
+) -> bool {
+    note.visibility == "public" || note.visibility == "unlisted"
+}
+
+/// A function that attempts to check whether
+/// the given actor may read a note. Public and 
+/// unlisted notes can be read by anyone, including
+/// anonymous readers. Followers-only notes can be
+/// read by the author's followers and direct notes
+/// only by the actors they are addressed to. Authors
+/// can always read their own notes. If the operation
+/// is successful, a boolean reflecting this is
+/// returned. If the operation fails, an error is
+/// returned.
+pub async fn can_view_note(
+    note: &KleahNote,
+    viewer: Option<&KleahActor>,
+    pool: &Pool<Postgres>
+) -> Result<bool, KleahErr> {
+    if is_publicly_visible(note) {
+        return Ok(true);
+    }
+    match viewer {
+        Some(viewer) => note_is_visible_to(note, viewer, pool).await,
+        None => Ok(false)
+    }
+}
+
+/// A function that attempts to retrieve the
+/// local actor of the user with the given API 
+/// token. If no token is given, `None` is returned
+/// for an anonymous reader. If the token is not 
+/// valid, an error is returned.
+pub async fn get_viewer(
+    api_token: Option<&str>,
+    pool: &Pool<Postgres>
+) -> Result<Option<KleahActor>, KleahErr> {
+    let api_token: &str = match api_token {
+        Some(api_token) => api_token,
+        None => return Ok(None)
+    };
+    let user: KleahUser = match get_user_by_token(api_token, pool).await {
+        Ok(user) => user,
+        Err(e) => return Err::<Option<KleahActor>, KleahErr>(
+            KleahErr::with_type(&e.to_string(), KleahErrType::Unauthorized)
+        )
+    };
+    let instance: InstanceInformation = match get_instance_info(pool).await {
+        Ok(instance) => instance,
+        Err(e) => return Err::<Option<KleahActor>, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    match get_actor_by_id(&user.username, &instance.host, pool).await {
+        Ok(viewer) => Ok(Some(viewer)),
+        Err(e) => Err::<Option<KleahActor>, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    }
+}
+
+/// A function that attempts to retrieve
+/// a note on behalf of the user with the
+/// given API token or an anonymous reader.
+/// Notes the reader may not see are treated 
+/// as nonexistent. If the operation is successful,
+/// an instance of the `KleahNote` structure is 
+/// returned. If the operation fails, an error
+/// is returned.
+pub async fn get_visible_note(
+    note_id: &str,
+    api_token: Option<&str>,
+    pool: &Pool<Postgres>
+) -> Result<KleahNote, KleahErr> {
+    let viewer: Option<KleahActor> = match get_viewer(api_token, pool).await {
+        Ok(viewer) => viewer,
+        Err(e) => return Err::<KleahNote, KleahErr>(e)
+    };
+    get_note_for_viewer(note_id, viewer.as_ref(), pool).await
+}
+
+/// A function that attempts to retrieve
+/// a note on behalf of the given actor or
+/// an anonymous reader. Notes the reader may 
+/// not see are treated as nonexistent. If the 
+/// operation is successful, an instance of the
+/// `KleahNote` structure is returned. If the 
+/// operation fails, an error is returned.
+pub async fn get_note_for_viewer(
+    note_id: &str,
+    viewer: Option<&KleahActor>,
+    pool: &Pool<Postgres>
+) -> Result<KleahNote, KleahErr> {
+    let note: Option<KleahNote> = get_note_by_id(note_id, pool).await.ok();
+    let visible: bool = match &note {
+        Some(note) => match can_view_note(note, viewer, pool).await {
+            Ok(visible) => visible,
+            Err(e) => return Err::<KleahNote, KleahErr>(
+                KleahErr::new(&e.to_string())
+            )
+        },
+        None => false
+    };
+    match note {
+        Some(note) if visible => Ok(note),
+        _ => Err::<KleahNote, KleahErr>(
+            KleahErr::with_type(
+                "The requested note does not exist.",
+                KleahErrType::NotFound
+            )
+        )
+    }
+}
+
+/// A function that attempts to retrieve
+/// the local actor of an API token and the
+/// note with the given ID, so that the actor
+/// can interact with the note. If the operation
+/// is successful, the actor, the note and its
+/// author are returned. If the actor may not see
+/// the note, an error is returned.
+pub async fn get_note_interaction(
+    api_token: &str,
+    note_id: &str,
+    pool: &Pool<Postgres>
+) -> Result<(KleahActor, KleahNote, KleahActor), KleahErr> {
+    let viewer: KleahActor = match get_viewer(Some(api_token), pool).await {
+        Ok(Some(viewer)) => viewer,
+        Ok(None) => return Err::<(KleahActor, KleahNote, KleahActor), KleahErr>(
+            KleahErr::with_type(
+                "An API token is required.",
+                KleahErrType::Unauthorized
+            )
+        ),
+        Err(e) => return Err::<(KleahActor, KleahNote, KleahActor), KleahErr>(e)
+    };
+    let note: KleahNote = match get_note_for_viewer(note_id, Some(&viewer), pool).await {
+        Ok(note) => note,
+        Err(e) => return Err::<(KleahActor, KleahNote, KleahActor), KleahErr>(e)
+    };
+    match get_actor_by_id(&note.author_username, &note.author_host, pool).await {
+        Ok(author) => Ok((viewer, note, author)),
+        Err(e) => Err::<(KleahActor, KleahNote, KleahActor), KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    }
+}
+
+/// A function that attempts to retrieve
+/// a note by its URL. Local notes are looked
+/// up by the ID in their URL, notes from other
+/// servers by their stored ID. If the operation
+/// is successful, an instance of the `KleahNote`
+/// structure is returned. If the note is not
+/// known, an error is returned.
+pub async fn get_note_by_url(
+    url: &str,
+    pool: &Pool<Postgres>
+) -> Result<KleahNote, KleahErr> {
+    let instance: InstanceInformation = match get_instance_info(pool).await {
+        Ok(instance) => instance,
+        Err(e) => return Err::<KleahNote, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    let fetched: Result<KleahNote, KleahErr> = match url.strip_prefix(
+        &local_note_url(&instance.host, "")
+    ) {
+        Some(note_id) => match get_note_by_id(note_id, pool).await {
+            Ok(note) if note.ap_id.is_none() => Ok(note),
+            Ok(_note) => Err::<KleahNote, KleahErr>(
+                KleahErr::new("The note is not a local note.")
+            ),
+            Err(e) => Err::<KleahNote, KleahErr>(e)
+        },
+        None => get_note_by_ap_id(url, pool).await
+    };
+    match fetched {
+        Ok(note) => Ok(note),
+        Err(_e) => Err::<KleahNote, KleahErr>(
+            KleahErr::with_type(
+                "The requested note does not exist.",
+                KleahErrType::NotFound
+            )
+        )
+    }
+}
+
+/// A function that attempts to delete a
+/// note written by a local user. Only the
+/// author of a note may delete it. Likes,
+/// boosts and notifications of the note are
+/// deleted with it. Public and unlisted notes 
+/// leave a tombstone at their URL. Notes for
+/// followers and direct notes do not, as their
+/// URL would otherwise tell anyone that a note
+/// only meant for some readers existed and when
+/// it was deleted. Their readers on other servers
+/// learn about the deletion from the "Delete"
+/// activity instead, which everyone on other
+/// servers who received the note is sent. If the
+/// operation is successful, the deleted note is
+/// returned. If the operation fails, an error is
+/// returned.
+pub async fn remove_note(
+    username: &str,
+    note_id: &str,
+    pool: &Pool<Postgres>
+) -> Result<KleahNote, KleahErr> {
+    let instance: InstanceInformation = match get_instance_info(pool).await {
+        Ok(instance) => instance,
+        Err(e) => return Err::<KleahNote, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    let note: KleahNote = match get_note_by_id(note_id, pool).await {
+        Ok(note) => note,
+        Err(_e) => return Err::<KleahNote, KleahErr>(
+            KleahErr::with_type(
+                "The requested note does not exist.",
+                KleahErrType::NotFound
+            )
+        )
+    };
+    if note.author_username != username || note.author_host != instance.host {
+        return Err::<KleahNote, KleahErr>(
+            KleahErr::with_type(
+                "The requesting user is not the author of the note.",
+                KleahErrType::Unauthorized
+            )
+        )
+    }
+    let author: KleahActor = match get_actor_by_id(username, &instance.host, pool).await {
+        Ok(author) => author,
+        Err(e) => return Err::<KleahNote, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    let recipients: Vec<KleahActor> = match get_note_recipients(&note.note_id, pool).await {
+        Ok(recipients) => recipients,
+        Err(e) => return Err::<KleahNote, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    let deleted_at: DateTime<Utc> = Utc::now();
+    let delete: ApubActivity = match build_delete_activity(&note, &author, &recipients, &deleted_at){
+        Ok(delete) => delete,
+        Err(e) => return Err::<KleahNote, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    match destroy_note(&note.note_id, pool).await {
+        Ok(_f) => {},
+        Err(e) => return Err::<KleahNote, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    if is_publicly_visible(&note) {
+        match create_tombstone(&note.note_id, &deleted_at, pool).await {
+            Ok(_tombstone) => {},
+            Err(e) => return Err::<KleahNote, KleahErr>(
+                KleahErr::new(&e.to_string())
+            )
+        };
+    }
+    match deliver_note_activity(&note, &author, &recipients, &delete, pool).await {
+        Ok(_f) => Ok(note),
+        Err(e) => Err::<KleahNote, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    }
+}
+
+/// A function that attempts to handle a
+/// received "Delete" activity for a note from
+/// another server. The stored copy of the note
+/// is only deleted if the note was written by
+/// the actor that sent the activity. Deletions
+/// of unknown notes are ignored. If the operation
+/// fails, an error is returned.
+pub async fn handle_note_delete(
+    actor: &KleahActor,
+    object: &Value,
+    pool: &Pool<Postgres>
+) -> Result<(), KleahErr> {
+    let ap_id: String = match object_id(object) {
+        Some(ap_id) => ap_id,
+        None => return Err::<(), KleahErr>(
+            KleahErr::with_type(
+                "The activity has no object.",
+                KleahErrType::BadRequest
+            )
+        )
+    };
+    let note: KleahNote = match get_note_by_url(&ap_id, pool).await {
+        Ok(note) => note,
+        Err(_e) => return Ok(())
+    };
+    if note.author_username != actor.username || note.author_host != actor.host {
+        return Err::<(), KleahErr>(
+            KleahErr::with_type(
+                "The note was not written by the sending actor.",
+                KleahErrType::Unauthorized
+            )
+        )
+    }
+    match destroy_note(&note.note_id, pool).await {
+        Ok(_f) => Ok(()),
+        Err(e) => Err::<(), KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    }
+}
+
+/// A function that attempts to retrieve
+/// the tombstone of a deleted local note so
+/// that it can be served to other servers.
+/// If the operation is successful, an instance
+/// of the `ApubTombstone` structure is returned.
+/// If the note never existed or may not be 
+/// served, an error is returned.
+pub async fn get_note_tombstone(
+    note_id: &str,
+    pool: &Pool<Postgres>
+) -> Result<ApubTombstone, KleahErr> {
+    let instance: InstanceInformation = match get_instance_info(pool).await {
+        Ok(instance) => instance,
+        Err(e) => return Err::<ApubTombstone, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    match get_tombstone(note_id, pool).await {
+        Ok(tombstone) => Ok(
+            build_tombstone(
+                &local_note_url(&instance.host, &tombstone.note_id),
+                &tombstone.deleted_at
+            )
+        ),
+        Err(_e) => Err::<ApubTombstone, KleahErr>(
+            KleahErr::with_type(
+                "The requested note does not exist.",
+                KleahErrType::NotFound
+            )
+        )
+    }
+}
+
+/// A function to render a line of the
+/// plain text of a local note as HTML. 
+/// Mentions of the given actors link to their
+/// profiles and hashtags link to their pages
+/// on the given host. All other text is 
+/// escaped. The HTML is returned.
+pub fn render_note_line(
+    line: &str,
+    mentions: &[KleahActor],
+    host: &str
+) -> String {
+    let mut links: Vec<(usize, usize, String)> = mention_links(line, mentions);
+    links.extend(hashtag_links(line, host));
+    links.sort_by_key(|(start, _end, _html)| *start);
+    let mut rendered: String = String::new();
+    let mut position: usize = 0;
+    for (start, end, html) in links {
+        if start < position {
+            continue;
+        }
+        rendered.push_str(&escape_html(&line[position..start]));
+        rendered.push_str(&html);
+        position = end;
+    }
+    rendered.push_str(&escape_html(&line[position..]));
+    rendered
+}
+
+/// A function to render the plain text of
+/// a local note as HTML for other servers.
+/// Paragraphs are separated by empty lines and
+/// single line breaks are kept. Mentions of the
+/// given actors and hashtags are linked. The HTML
+/// is returned.
+pub fn render_note_content(
+    content: &str,
+    mentions: &[KleahActor],
+    host: &str
+) -> String {
+    content
+        .split("\n\n")
+        .filter(|paragraph| !paragraph.trim().is_empty())
+        .map(
+            |paragraph| format!(
+                "<p>{}</p>", 
+                paragraph
+                    .lines()
+                    .map(|line| render_note_line(line, mentions, host))
+                    .collect::<Vec<String>>()
+                    .join("<br>")
+            )
+        )
+        .collect::<Vec<String>>()
+        .join("")
+}
+
+/// A function to build the primary and
+/// secondary audiences of a note from its
+/// visibility and the actors it is explicitly
+/// addressed to. Public notes are addressed to
+/// everyone, unlisted notes to the author's
+/// followers while still being public,
+/// followers-only notes to the followers alone
+/// and direct notes only to their recipients.
+/// The audiences are returned as lists of URLs.
+pub fn note_addressing(
+    note: &KleahNote,
+    author: &KleahActor,
+    recipients: &[KleahActor]
+) -> (Vec<String>, Vec<String>) {
+    let followers: String = actor_followers_url(author);
+    let recipients: Vec<String> = recipients
+        .iter()
+        .map(actor_url)
+        .collect();
+    match note.visibility.as_str() {
+        "public" => (
+            vec![PUBLIC_COLLECTION.to_string()], 
+            [vec![followers], recipients].concat()
+        ),
+        "unlisted" => (
+            vec![followers], 
+            [vec![PUBLIC_COLLECTION.to_string()], recipients].concat()
+        ),
+        "followers" => (vec![followers], recipients),
+        _ => (recipients, Vec::new())
+    }
+}
+
+/// A function to build the ActivityPub
+/// object of a note written by the given
+/// author and addressed to the given actors. 
+/// The content of local notes is rendered as 
+/// HTML and they link to the collection of
+/// their replies. The actors the note is 
+/// addressed to are tagged as mentioned and
+/// the hashtags of local notes are tagged as
+/// well. Notes with a poll are questions that
+/// carry the poll's options and tallies. The
+/// object is returned.
+pub fn build_note_object(
+    note: &KleahNote,
+    author: &KleahActor,
+    recipients: &[KleahActor],
+    poll: Option<&(KleahPoll, Vec<KleahPollOption>)>
+) -> ApubNote {
+    let (to, cc): (Vec<String>, Vec<String>) = note_addressing(note, author, recipients);
+    let (content, hashtags): (String, Vec<ApubTag>) = match note.ap_id {
+        Some(_) => (note.content.clone(), Vec::new()),
+        None => (
+            render_note_content(&note.content, recipients, &author.host),
+            build_hashtag_tags(&note.content, &author.host)
+        )
+    };
+    let (one_of, any_of): (Option<Vec<ApubPollOption>>, Option<Vec<ApubPollOption>>) = match poll {
+        Some((poll, options)) if poll.multiple => (None, Some(build_poll_options(options))),
+        Some((_poll, options)) => (Some(build_poll_options(options)), None),
+        None => (None, None)
+    };
+    ApubNote{
+        context: None,
+        id: note_url(note),
+        note_type: match poll {
+            Some(_) => "Question".to_string(),
+            None => "Note".to_string()
+        },
+        attributed_to: actor_url(author),
+        content,
+        summary: note.summary.clone(),
+        sensitive: note.sensitive,
+        published: note.created_at.to_rfc3339_opts(SecondsFormat::Secs, true),
+        updated: note.updated_at.map(|updated_at| updated_at.to_rfc3339_opts(SecondsFormat::Secs, true)),
+        in_reply_to: note.in_reply_to.clone(),
+        thread_context: note.conversation.clone(),
+        conversation: note.conversation.clone(),
+        replies: match note.ap_id {
+            Some(_) => None,
+            None => Some(format!("{}/replies", note_url(note)))
+        },
+        url: note_url(note),
+        to,
+        cc,
+        tag: build_mention_tags(recipients).into_iter().chain(hashtags).collect(),
+        one_of,
+        any_of,
+        end_time: poll.map(|(poll, _options)| poll.expires_at.to_rfc3339_opts(SecondsFormat::Secs, true)),
+        closed: poll
+            .and_then(|(poll, _options)| poll.closed_at)
+            .map(|closed_at| closed_at.to_rfc3339_opts(SecondsFormat::Secs, true)),
+        voters_count: poll.map(|(poll, _options)| poll.voters_count)
+    }
+}
+
+/// A function that attempts to build an
+/// activity with the given type and ID that 
+/// carries a note addressed to the given actors
+/// and its poll, if it has one.
+/// The activity has the same audience as the 
+/// note. If the operation is successful, the 
+/// activity is returned. If the operation fails,
+/// an error is returned.
+pub fn build_note_activity(
+    note: &KleahNote,
+    author: &KleahActor,
+    recipients: &[KleahActor],
+    poll: Option<&(KleahPoll, Vec<KleahPollOption>)>,
+    activity_type: &str,
+    id: &str
+) -> Result<ApubActivity, KleahErr> {
+    let object: ApubNote = build_note_object(note, author, recipients, poll);
+    let (to, cc): (Vec<String>, Vec<String>) = (object.to.clone(), object.cc.clone());
+    let object: Value = match to_value(object){
+        Ok(object) => object,
+        Err(e) => return Err::<ApubActivity, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    Ok(
+        ApubActivity{
+            context: ACTIVITYSTREAMS_CONTEXT.to_string(),
+            id: id.to_string(),
+            activity_type: activity_type.to_string(),
+            actor: actor_url(author),
+            object,
+            to,
+            cc
+        }
+    )
+}
+
+/// A function that attempts to build the
+/// "Create" activity that publishes a note
+/// addressed to the given actors. The activity
+/// has a stable ID below the note's URL and the
+/// same audience as the note. If the operation 
+/// is successful, the activity is returned. If
+/// the operation fails, an error is returned.
+pub fn build_create_activity(
+    note: &KleahNote,
+    author: &KleahActor,
+    recipients: &[KleahActor],
+    poll: Option<&(KleahPoll, Vec<KleahPollOption>)>
+) -> Result<ApubActivity, KleahErr> {
+    build_note_activity(
+        note,
+        author,
+        recipients,
+        poll,
+        "Create",
+        &format!("{}/activity", note_url(note))
+    )
+}
+
+/// A function that attempts to build the
+/// "Update" activity that announces the latest
+/// edit of a note addressed to the given actors.
+/// The activity's ID is derived from the time of
+/// the edit. If the operation is successful, the
+/// activity is returned. If the operation fails,
+/// an error is returned.
+pub fn build_update_activity(
+    note: &KleahNote,
+    author: &KleahActor,
+    recipients: &[KleahActor],
+    poll: Option<&(KleahPoll, Vec<KleahPollOption>)>
+) -> Result<ApubActivity, KleahErr> {
+    let updated_at: DateTime<Utc> = note.updated_at.unwrap_or(note.created_at);
+    build_note_activity(
+        note,
+        author,
+        recipients,
+        poll,
+        "Update",
+        &format!("{}/updates/{}", note_url(note), updated_at.timestamp_micros())
+    )
+}
+
+/// A function to build the tombstone
+/// left behind at the URL of a deleted
+/// note. The tombstone is returned.
+pub fn build_tombstone(
+    url: &str,
+    deleted_at: &DateTime<Utc>
+) -> ApubTombstone {
+    ApubTombstone{
+        context: None,
+        id: url.to_string(),
+        tombstone_type: "Tombstone".to_string(),
+        former_type: "Note".to_string(),
+        deleted: deleted_at.to_rfc3339_opts(SecondsFormat::Secs, true)
+    }
+}
+
+/// A function that attempts to build the
+/// "Delete" activity that retracts a note 
+/// addressed to the given actors. The activity's
+/// object is the note's tombstone and its audience
+/// is the note's audience. If the operation is 
+/// successful, the activity is returned. If the 
+/// operation fails, an error is returned.
+pub fn build_delete_activity(
+    note: &KleahNote,
+    author: &KleahActor,
+    recipients: &[KleahActor],
+    deleted_at: &DateTime<Utc>
+) -> Result<ApubActivity, KleahErr> {
+    let audience: ApubNote = build_note_object(note, author, recipients, None);
+    let object: Value = match to_value(build_tombstone(&note_url(note), deleted_at)){
+        Ok(object) => object,
+        Err(e) => return Err::<ApubActivity, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    Ok(
+        ApubActivity{
+            context: ACTIVITYSTREAMS_CONTEXT.to_string(),
+            id: format!("{}#delete", note_url(note)),
+            activity_type: "Delete".to_string(),
+            actor: actor_url(author),
+            object,
+            to: audience.to,
+            cc: audience.cc
+        }
+    )
+}
+
+/// A function that attempts to queue an
+/// activity about a local note for the note's
+/// audience on other servers over the given
+/// connection, so that the deliveries can be
+/// part of a transaction. Unless the note is
+/// direct, the audience includes the author's
+/// followers. Actors the note is addressed to
+/// always receive it. If the operation is
+/// successful, an empty function is returned.
+/// If the operation fails, an error is returned.
+pub async fn queue_note_activity(
+    note: &KleahNote,
+    author: &KleahActor,
+    recipients: &[KleahActor],
+    activity: &ApubActivity,
+    conn: &mut PgConnection
+) -> Result<(), KleahErr> {
+    let mut audience: Vec<KleahActor> = match note.visibility.as_str() {
+        "direct" => Vec::new(),
+        _ => match get_remote_followers(author, &mut *conn).await {
+            Ok(followers) => followers,
+            Err(e) => return Err::<(), KleahErr>(
+                KleahErr::new(&e.to_string())
+            )
+        }
+    };
+    audience.extend(
+        recipients
+            .iter()
+            .filter(|recipient| recipient.ap_id.is_some())
+            .cloned()
+    );
+    if audience.is_empty() {
+        return Ok(());
+    }
+    queue_activity(&author.username, &audience, activity, conn).await
+}
+
+/// A function that attempts to send an
+/// activity about a local note to the note's
+/// audience on other servers. Unless the note 
+/// is direct, the audience includes the author's
+/// followers. Actors the note is addressed to 
+/// always receive it. If the operation is 
+/// successful, an empty function is returned. 
+/// If the operation fails, an error is returned.
+pub async fn deliver_note_activity(
+    note: &KleahNote,
+    author: &KleahActor,
+    recipients: &[KleahActor],
+    activity: &ApubActivity,
+    pool: &Pool<Postgres>
+) -> Result<(), KleahErr> {
+    let mut conn: PoolConnection<Postgres> = match pool.acquire().await {
+        Ok(conn) => conn,
+        Err(e) => return Err::<(), KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    queue_note_activity(note, author, recipients, activity, &mut conn).await
+}
+
+/// A function that attempts to send the
+/// "Create" activity for a new local note
+/// and its poll to its audience on other
+/// servers. If the operation is successful, an empty function
+/// is returned. If the operation fails, an error
+/// is returned.
+pub async fn deliver_note_creation(
+    note: &KleahNote,
+    author: &KleahActor,
+    pool: &Pool<Postgres>
+) -> Result<(), KleahErr> {
+    let recipients: Vec<KleahActor> = match get_note_recipients(&note.note_id, pool).await {
+        Ok(recipients) => recipients,
+        Err(e) => return Err::<(), KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    let poll: Option<(KleahPoll, Vec<KleahPollOption>)> = match get_note_poll(note, pool).await {
+        Ok(poll) => poll,
+        Err(e) => return Err::<(), KleahErr>(e)
+    };
+    let create: ApubActivity = match build_create_activity(note, author, &recipients, poll.as_ref()){
+        Ok(create) => create,
+        Err(e) => return Err::<(), KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    deliver_note_activity(note, author, &recipients, &create, pool).await
+}
+
+/// A function to derive the visibility
+/// of a note received from another server
+/// from its audiences. A note addressed to
+/// no public collection and not to the author's
+/// followers is treated as direct. The visibility
+/// is returned.
+pub fn incoming_visibility(
+    object: &Value,
+    author: &KleahActor
+) -> String {
+    let to: Vec<String> = object.get("to").map(object_ids).unwrap_or_default();
+    let cc: Vec<String> = object.get("cc").map(object_ids).unwrap_or_default();
+    let is_public = |address: &String| {
+        address == PUBLIC_COLLECTION || address == "as:Public" || address == "Public"
+    };
+    let followers: String = actor_followers_url(author);
+    if to.iter().any(is_public) {
+        "public".to_string()
+    }
+    else if cc.iter().any(is_public) {
+        "unlisted".to_string()
+    }
+    else if to.contains(&followers) || cc.contains(&followers) {
+        "followers".to_string()
+    }
+    else {
+        "direct".to_string()
+    }
+}
+
+/// A function that attempts to store a
+/// note received from another server. The 
+/// note must be attributed to the actor that
+/// sent it and be served from that actor's host.
+/// Local actors the note is addressed to are
+/// recorded as its recipients, as are local actors
+/// tagged as mentioned, and they are notified about
+/// the mention. Hashtags are read from the tags.
+/// Polls of questions are stored as well. Replies
+/// join the conversation of the note they reply to
+/// and local authors who may read a reply to their
+/// note are notified. Notes that are already stored
+/// are ignored. If the operation is successful, an
+/// empty function is returned. If the operation
+/// fails, an error is returned.
+pub async fn store_remote_note(
+    actor: &KleahActor,
+    object: &Value,
+    pool: &Pool<Postgres>
+) -> Result<(), KleahErr> {
+    let ap_id: String = match object_id(object) {
+        Some(ap_id) => ap_id,
+        None => return Err::<(), KleahErr>(
+            KleahErr::with_type(
+                "The note has no ID.",
+                KleahErrType::BadRequest
+            )
+        )
+    };
+    let author: Option<String> = object
+        .get("attributedTo")
+        .and_then(|attributed_to| object_ids(attributed_to).into_iter().next());
+    if author != Some(actor_url(actor)) || url_host(&ap_id) != url_host(&actor_url(actor)) {
+        return Err::<(), KleahErr>(
+            KleahErr::with_type(
+                "The note was not written by the sending actor.",
+                KleahErrType::Unauthorized
+            )
+        )
+    }
+    if get_note_by_ap_id(&ap_id, pool).await.is_ok() {
+        return Ok(());
+    }
+    let content: String = clean(
+        object
+            .get("content")
+            .and_then(|content| content.as_str())
+            .unwrap_or("")
+    );
+    let in_reply_to: Option<String> = object
+        .get("inReplyTo")
+        .and_then(object_id);
+    let parent: Option<KleahNote> = match &in_reply_to {
+        Some(in_reply_to) => get_note_by_url(in_reply_to, pool).await.ok(),
+        None => None
+    };
+    let published: DateTime<Utc> = object
+        .get("published")
+        .and_then(|published| published.as_str())
+        .and_then(|published| DateTime::parse_from_rfc3339(published).ok())
+        .map(|published| published.with_timezone(&Utc))
+        .unwrap_or_else(Utc::now);
+    let summary: Option<String> = read_note_summary(object);
+    let sensitive: bool = object
+        .get("sensitive")
+        .and_then(|sensitive| sensitive.as_bool())
+        .unwrap_or(false);
+    let has_poll: bool = object.get("type").and_then(|object_type| object_type.as_str()) == Some("Question") &&
+        read_poll_choices(object).is_some();
+    let instance: InstanceInformation = match get_instance_info(pool).await {
+        Ok(instance) => instance,
+        Err(e) => return Err::<(), KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    let note: KleahNote = match create_note(
+        &KleahNote{
+            note_id: generate_id(&ap_id),
+            ap_id: Some(ap_id.clone()),
+            author_username: actor.username.clone(),
+            author_host: actor.host.clone(),
+            content,
+            in_reply_to,
+            visibility: incoming_visibility(object, actor),
+            created_at: published,
+            updated_at: None,
+            like_count: 0,
+            boost_count: 0,
+            conversation: incoming_conversation(object, parent.as_ref()),
+            sensitive: sensitive || summary.is_some(),
+            summary,
+            has_poll
+        },
+        pool
+    ).await {
+        Ok(note) => note,
+        Err(e) => return Err::<(), KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    let mut addresses: Vec<String> = ["to", "cc"]
+        .iter()
+        .filter_map(|field| object.get(*field))
+        .flat_map(object_ids)
+        .collect();
+    match add_note_hashtags(&note, &hashtag_names(object), pool).await {
+        Ok(_f) => {},
+        Err(e) => return Err::<(), KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    if has_poll {
+        match store_remote_poll(&note, object, pool).await {
+            Ok(_f) => {},
+            Err(e) => return Err::<(), KleahErr>(e)
+        };
+    }
+    addresses.extend(mention_hrefs(object));
+    let mut notified: Vec<String> = Vec::new();
+    for address in addresses {
+        let username: String = match local_username_from_url(&address, &instance.host) {
+            Some(username) => username,
+            None => continue
+        };
+        let recipient: KleahActor = match get_actor_by_id(&username, &instance.host, pool).await {
+            Ok(recipient) => recipient,
+            Err(_e) => continue
+        };
+        match add_note_recipient(&note.note_id, &recipient, pool).await {
+            Ok(_f) => {},
+            Err(e) => return Err::<(), KleahErr>(
+                KleahErr::new(&e.to_string())
+            )
+        };
+        let wrote_parent: bool = parent.as_ref().is_some_and(
+            |parent| parent.author_username == recipient.username && parent.author_host == recipient.host
+        );
+        if !wrote_parent && !notified.contains(&recipient.username) {
+            match notify_user(&recipient, "mention", actor, Some(&note.note_id), pool).await {
+                Ok(_f) => {},
+                Err(e) => return Err::<(), KleahErr>(e)
+            };
+            notified.push(recipient.username.clone());
+        }
+    }
+    let parent: KleahNote = match parent {
+        Some(parent) if parent.ap_id.is_none() => parent,
+        _ => return Ok(())
+    };
+    let parent_author: KleahActor = match get_actor_by_id(
+        &parent.author_username,
+        &parent.author_host,
+        pool
+    ).await {
+        Ok(parent_author) => parent_author,
+        Err(e) => return Err::<(), KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    match can_view_note(&note, Some(&parent_author), pool).await {
+        Ok(true) => notify_user(&parent_author, "reply", actor, Some(&note.note_id), pool).await,
+        Ok(false) => Ok(()),
+        Err(e) => Err::<(), KleahErr>(e)
+    }
+}
+
+/// A function that attempts to retrieve
+/// a note from another server by its URL. 
+/// Notes that are not known yet are fetched 
+/// from the server they are served from and
+/// stored with their author. Polls are stored
+/// with their options. Unknown local notes
+/// are not fetched. If the operation 
+/// is successful, an instance of the `KleahNote`
+/// structure is returned. If the operation fails,
+/// an error is returned.
+pub async fn fetch_remote_note(
+    url: &str,
+    pool: &Pool<Postgres>
+) -> Result<KleahNote, KleahErr> {
+    if let Ok(note) = get_note_by_url(url, pool).await {
+        return Ok(note);
+    }
+    let instance: InstanceInformation = match get_instance_info(pool).await {
+        Ok(instance) => instance,
+        Err(e) => return Err::<KleahNote, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    if url_host(url) == Some(instance.host) {
+        return Err::<KleahNote, KleahErr>(
+            KleahErr::with_type(
+                "The requested note does not exist.",
+                KleahErrType::NotFound
+            )
+        )
+    }
+    let object: Value = match fetch_json(url).await {
+        Ok(object) => object,
+        Err(e) => return Err::<KleahNote, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    let ap_id: String = match object_id(&object) {
+        Some(ap_id) if url_host(&ap_id) == url_host(url) => ap_id,
+        _ => return Err::<KleahNote, KleahErr>(
+            KleahErr::with_type(
+                "The note is not served from its own server.",
+                KleahErrType::BadRequest
+            )
+        )
+    };
+    if !matches!(object.get("type").and_then(|object_type| object_type.as_str()), Some("Note") | Some("Question")) {
+        return Err::<KleahNote, KleahErr>(
+            KleahErr::with_type(
+                "The object is not a note.",
+                KleahErrType::BadRequest
+            )
+        )
+    }
+    let author_url: String = match object
+        .get("attributedTo")
+        .and_then(|attributed_to| object_ids(attributed_to).into_iter().next())
+    {
+        Some(author_url) => author_url,
+        None => return Err::<KleahNote, KleahErr>(
+            KleahErr::with_type(
+                "The note has no author.",
+                KleahErrType::BadRequest
+            )
+        )
+    };
+    let author: KleahActor = match get_remote_actor(&author_url, pool).await {
+        Ok(author) => author,
+        Err(e) => return Err::<KleahNote, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    match store_remote_note(&author, &object, pool).await {
+        Ok(_f) => {},
+        Err(e) => return Err::<KleahNote, KleahErr>(e)
+    };
+    match get_note_by_ap_id(&ap_id, pool).await {
+        Ok(note) => Ok(note),
+        Err(e) => Err::<KleahNote, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    }
+}
+
+/// A function that attempts to retrieve
+/// a public or unlisted note written by a
+/// local actor together with its author and
+/// recipients, so that it can be served to 
+/// other servers. If the operation is successful, 
+/// the note, its author and its recipients are 
+/// returned. If the note 
+/// does not exist or may not be served, an 
+/// error is returned.
+pub async fn get_public_local_note(
+    note_id: &str,
+    pool: &Pool<Postgres>
+) -> Result<(KleahNote, KleahActor, Vec<KleahActor>), KleahErr> {
+    let instance: InstanceInformation = match get_instance_info(pool).await {
+        Ok(instance) => instance,
+        Err(e) => return Err::<(KleahNote, KleahActor, Vec<KleahActor>), KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    let note: KleahNote = match get_note_by_id(note_id, pool).await {
+        Ok(note) if note.ap_id.is_none() && 
+            note.author_host == instance.host &&
+            is_publicly_visible(&note) => note,
+        _ => return Err::<(KleahNote, KleahActor, Vec<KleahActor>), KleahErr>(
+            KleahErr::with_type(
+                "The requested note does not exist.",
+                KleahErrType::NotFound
+            )
+        )
+    };
+    let author: KleahActor = match get_actor_by_id(
+        &note.author_username, 
+        &note.author_host, 
+        pool
+    ).await {
+        Ok(author) => author,
+        Err(e) => return Err::<(KleahNote, KleahActor, Vec<KleahActor>), KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    match get_note_recipients(&note.note_id, pool).await {
+        Ok(recipients) => Ok((note, author, recipients)),
+        Err(e) => Err::<(KleahNote, KleahActor, Vec<KleahActor>), KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    }
+}
+
+/// A function that attempts to read a page
+/// of the outbox of a local actor, newest
+/// first. Notes are wrapped in "Create" 
+/// activities and boosts in "Announce" 
+/// activities. If the operation is successful,
+/// each activity is returned together with 
+/// the cursor pointing at it. If the operation
+/// fails, an error is returned.
+pub async fn read_outbox_items(
+    actor: &KleahActor,
+    max_id: Option<&KleahCursor>,
+    min_id: Option<&KleahCursor>,
+    pool: &Pool<Postgres>
+) -> Result<Vec<(Value, String)>, KleahErr> {
+    let entries: Vec<KleahTimelineEntry> = match get_outbox_page(
+        actor,
+        max_id,
+        min_id,
+        COLLECTION_PAGE_SIZE,
+        pool
+    ).await {
+        Ok(entries) => entries,
+        Err(e) => return Err::<Vec<(Value, String)>, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    let note_ids: Vec<String> = entries
+        .iter()
+        .map(|entry| entry.note_id.clone())
+        .collect();
+    let notes: Vec<KleahNote> = match get_notes_by_ids(
+        &note_ids,
+        pool
+    ).await {
+        Ok(notes) => notes,
+        Err(e) => return Err::<Vec<(Value, String)>, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    let mut items: Vec<(Value, String)> = Vec::new();
+    for entry in entries {
+        let note: &KleahNote = match notes.iter().find(|note| note.note_id == entry.note_id){
+            Some(note) => note,
+            None => continue
+        };
+        let activity: ApubActivity = match &entry.boost_id {
+            Some(boost_id) => {
+                let author: KleahActor = match get_actor_by_id(
+                    &note.author_username,
+                    &note.author_host,
+                    pool
+                ).await {
+                    Ok(author) => author,
+                    Err(e) => return Err::<Vec<(Value, String)>, KleahErr>(
+                        KleahErr::new(&e.to_string())
+                    )
+                };
+                let boost: KleahBoost = KleahBoost{
+                    boost_id: boost_id.clone(),
+                    note_id: note.note_id.clone(),
+                    actor_username: actor.username.clone(),
+                    actor_host: actor.host.clone(),
+                    created_at: entry.created_at
+                };
+                build_announce_activity(&boost, actor, note, &author)
+            },
+            None => {
+                let recipients: Vec<KleahActor> = match get_note_recipients(
+                    &note.note_id, 
+                    pool
+                ).await {
+                    Ok(recipients) => recipients,
+                    Err(e) => return Err::<Vec<(Value, String)>, KleahErr>(
+                        KleahErr::new(&e.to_string())
+                    )
+                };
+                let poll: Option<(KleahPoll, Vec<KleahPollOption>)> = match get_note_poll(
+                    note,
+                    pool
+                ).await {
+                    Ok(poll) => poll,
+                    Err(e) => return Err::<Vec<(Value, String)>, KleahErr>(e)
+                };
+                match build_create_activity(note, actor, &recipients, poll.as_ref()){
+                    Ok(create) => create,
+                    Err(e) => return Err::<Vec<(Value, String)>, KleahErr>(
+                        KleahErr::new(&e.to_string())
+                    )
+                }
+            }
+        };
+        let activity: Value = match to_value(activity){
+            Ok(activity) => activity,
+            Err(e) => return Err::<Vec<(Value, String)>, KleahErr>(
+                KleahErr::new(&e.to_string())
+            )
+        };
+        items.push((activity, entry_cursor(&entry)));
+    }
+    Ok(items)
+}
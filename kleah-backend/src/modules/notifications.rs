@@ -0,0 +1,527 @@
+/*
+Kleah by Alyx Shang.
+Licensed under the FSL v1.
+*/
+
+/// Importing the structure
+/// representing a pool of
+/// database connections.
+use sqlx::Pool;
+
+/// Importing the structure
+/// representing the UTC
+/// time zone.
+use chrono::Utc;
+
+/// Importing the structure
+/// for catching and handling
+/// errors.
+use super::err::KleahErr;
+
+/// Importing the structure
+/// representing a Postgres
+/// database.
+use sqlx::postgres::Postgres;
+
+/// Importing the structure
+/// modelling a user.
+use super::models::KleahUser;
+
+/// Importing the enum
+/// to describe the kind
+/// of error that occurred.
+use super::err::KleahErrType;
+
+/// Importing the function
+/// to generate IDs.
+use super::utils::generate_id;
+
+/// Importing the structure
+/// modelling an actor.
+use super::models::KleahActor;
+
+/// Importing the structure
+/// modelling a follow.
+use super::models::KleahFollow;
+
+/// Importing the function
+/// to read a cursor from
+/// a query string.
+use super::utils::parse_cursor;
+
+/// Importing the structure
+/// representing a connection
+/// taken from a pool.
+use sqlx::pool::PoolConnection;
+
+/// Importing the function
+/// to build the cursor
+/// pointing at an item.
+use super::utils::format_cursor;
+
+/// Importing the function
+/// to retrieve information
+/// about this instance.
+use super::db::get_instance_info;
+
+/// Importing the function
+/// to retrieve a user by
+/// their API token.
+use super::db::get_user_by_token;
+
+/// Importing the function
+/// to retrieve a page of
+/// notifications.
+use super::db::get_notifications;
+
+/// Importing the structure
+/// representing a single
+/// connection to a Postgres
+/// database.
+use sqlx::postgres::PgConnection;
+
+/// Importing the function
+/// to save a new notification.
+use super::db::create_notification;
+
+/// Importing the function
+/// to delete a notification.
+use super::db::delete_notification;
+
+/// Importing the structure
+/// modelling a notification.
+use super::models::KleahNotification;
+
+/// Importing the function
+/// to retrieve the muted kinds
+/// of notifications.
+use super::db::get_notification_mutes;
+
+/// Importing the structure
+/// modelling information
+/// about this instance.
+use super::models::InstanceInformation;
+
+/// Importing the structure
+/// modelling the query string
+/// for reading notifications.
+use super::payloads::NotificationQuery;
+
+/// Importing the function
+/// to mute a kind of
+/// notifications.
+use super::db::create_notification_mute;
+
+/// Importing the function
+/// to unmute a kind of
+/// notifications.
+use super::db::delete_notification_mute;
+
+/// Importing the function
+/// to mark notifications
+/// as read.
+use super::db::update_notifications_read;
+
+/// Importing the structure
+/// modelling a muted kind
+/// of notifications.
+use super::models::KleahNotificationMute;
+
+/// Importing the function
+/// to count unread
+/// notifications.
+use super::db::count_unread_notifications;
+
+/// Importing the structure
+/// describing a notification.
+use super::responses::NotificationResponse;
+
+/// Importing the structure
+/// containing a page of
+/// notifications.
+use super::responses::NotificationsResponse;
+
+/// Importing the structure
+/// containing the muted kinds
+/// of notifications.
+use super::responses::NotificationMutesResponse;
+
+/// The kinds of notifications local
+/// users can receive and mute.
+pub const NOTIFICATION_KINDS: [&str; 8] = [
+    "follow",
+    "follow_request",
+    "mention",
+    "reply",
+    "like",
+    "boost",
+    "poll",
+    "report"
+];
+
+/// The number of notifications
+/// on a page if no other size is
+/// requested.
+pub const NOTIFICATION_PAGE_SIZE: i64 = 20;
+
+/// The largest number of notifications
+/// that can be requested for a single
+/// page.
+pub const NOTIFICATION_MAX_PAGE_SIZE: i64 = 40;
+
+/// A function to check whether
+/// the given kind of notifications
+/// exists. A boolean reflecting
+/// this is returned.
+pub fn check_notification_kind(
+    kind: &str
+) -> bool {
+    NOTIFICATION_KINDS.contains(&kind)
+}
+
+/// A function to derive the number
+/// of notifications to serve on a page
+/// from the size requested. The size
+/// is returned.
+pub fn notification_page_size(
+    limit: Option<i64>
+) -> i64 {
+    limit
+        .unwrap_or(NOTIFICATION_PAGE_SIZE)
+        .clamp(1, NOTIFICATION_MAX_PAGE_SIZE)
+}
+
+/// A function to build the notification
+/// of a local user about an action of an actor,
+/// optionally on a note. Users are not notified
+/// about their own actions, and actors from
+/// other servers are not notified at all. The
+/// notification is returned or nothing if the
+/// user is not to be notified.
+pub fn build_notification(
+    recipient: &KleahActor,
+    kind: &str,
+    actor: &KleahActor,
+    note_id: Option<&str>,
+    instance: &InstanceInformation
+) -> Option<KleahNotification> {
+    if recipient.host != instance.host ||
+        (recipient.username == actor.username && recipient.host == actor.host)
+    {
+        return None;
+    }
+    Some(
+        KleahNotification{
+            notification_id: generate_id(
+                &format!(
+                    "{}{}{}@{}{}",
+                    recipient.username,
+                    kind,
+                    actor.username,
+                    actor.host,
+                    note_id.unwrap_or_default()
+                )
+            ),
+            username: recipient.username.clone(),
+            kind: kind.to_string(),
+            actor_username: actor.username.clone(),
+            actor_host: actor.host.clone(),
+            note_id: note_id.map(|note_id| note_id.to_string()),
+            created_at: Utc::now(),
+            is_read: false,
+            report_id: None
+        }
+    )
+}
+
+/// A function that attempts to notify
+/// a local user about an action of an actor,
+/// optionally on a note, over the given
+/// connection, so that the notification can be
+/// part of a transaction. Users are not notified
+/// about their own actions, and actors from
+/// other servers are not notified at all. If
+/// the operation fails, an error is returned.
+pub async fn add_notification(
+    recipient: &KleahActor,
+    kind: &str,
+    actor: &KleahActor,
+    note_id: Option<&str>,
+    conn: &mut PgConnection
+) -> Result<(), KleahErr> {
+    let instance: InstanceInformation = match get_instance_info(&mut *conn).await {
+        Ok(instance) => instance,
+        Err(e) => return Err::<(), KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    match build_notification(recipient, kind, actor, note_id, &instance) {
+        Some(notification) => create_notification(&notification, conn).await,
+        None => Ok(())
+    }
+}
+
+/// A function that attempts to notify
+/// a local user about an action of an actor,
+/// optionally on a note. Users are not notified
+/// about their own actions, and actors from
+/// other servers are not notified at all. If
+/// the operation fails, an error is returned.
+pub async fn notify_user(
+    recipient: &KleahActor,
+    kind: &str,
+    actor: &KleahActor,
+    note_id: Option<&str>,
+    pool: &Pool<Postgres>
+) -> Result<(), KleahErr> {
+    let mut conn: PoolConnection<Postgres> = match pool.acquire().await {
+        Ok(conn) => conn,
+        Err(e) => return Err::<(), KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    add_notification(recipient, kind, actor, note_id, &mut conn).await
+}
+
+/// A function that attempts to notify
+/// a local user about a new follower or,
+/// if the follow still has to be approved,
+/// about a new follow request. If the 
+/// operation fails, an error is returned.
+pub async fn notify_follow(
+    follow: &KleahFollow,
+    follower: &KleahActor,
+    followee: &KleahActor,
+    pool: &Pool<Postgres>
+) -> Result<(), KleahErr> {
+    let kind: &str = if follow.accepted {
+        "follow"
+    }
+    else {
+        "follow_request"
+    };
+    notify_user(followee, kind, follower, None, pool).await
+}
+
+/// A function to build the response
+/// describing a notification for the
+/// API of this instance. The response
+/// is returned.
+pub fn build_notification_response(
+    notification: &KleahNotification
+) -> NotificationResponse {
+    NotificationResponse{
+        notification_id: notification.notification_id.clone(),
+        kind: notification.kind.clone(),
+        actor: notification.actor_username.clone(),
+        host: notification.actor_host.clone(),
+        note_id: notification.note_id.clone(),
+        report_id: notification.report_id.clone(),
+        read: notification.is_read,
+        created_at: notification.created_at.to_rfc3339()
+    }
+}
+
+/// A function that attempts to retrieve
+/// the user with the given API token. If
+/// the operation is successful, an instance
+/// of the `KleahUser` structure is returned.
+/// If the token is not valid, an error is
+/// returned.
+pub async fn get_notified_user(
+    api_token: &str,
+    pool: &Pool<Postgres>
+) -> Result<KleahUser, KleahErr> {
+    match get_user_by_token(api_token, pool).await {
+        Ok(user) => Ok(user),
+        Err(e) => Err::<KleahUser, KleahErr>(
+            KleahErr::with_type(&e.to_string(), KleahErrType::Unauthorized)
+        )
+    }
+}
+
+/// A function that attempts to read a
+/// page of the notifications of the user
+/// with the given API token, newest first.
+/// Notifications can be limited to a single
+/// kind or to unread ones. If the operation 
+/// is successful, an instance of the 
+/// `NotificationsResponse` structure is 
+/// returned. If the operation fails, an 
+/// error is returned.
+pub async fn read_notifications(
+    api_token: &str,
+    query: &NotificationQuery,
+    pool: &Pool<Postgres>
+) -> Result<NotificationsResponse, KleahErr> {
+    let user: KleahUser = match get_notified_user(api_token, pool).await {
+        Ok(user) => user,
+        Err(e) => return Err::<NotificationsResponse, KleahErr>(e)
+    };
+    if query.kind.as_deref().is_some_and(|kind| !check_notification_kind(kind)) {
+        return Err::<NotificationsResponse, KleahErr>(
+            KleahErr::with_type(
+                "The kind of notifications is not valid.",
+                KleahErrType::BadRequest
+            )
+        )
+    }
+    let notifications: Vec<KleahNotification> = match get_notifications(
+        &user.username,
+        query.kind.as_deref(),
+        &query.unread.unwrap_or(false),
+        parse_cursor(query.max_id.as_deref()).as_ref(),
+        parse_cursor(query.min_id.as_deref()).as_ref(),
+        notification_page_size(query.limit),
+        pool
+    ).await {
+        Ok(notifications) => notifications,
+        Err(e) => return Err::<NotificationsResponse, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    let unread: i64 = match count_unread_notifications(&user.username, pool).await {
+        Ok(unread) => unread,
+        Err(e) => return Err::<NotificationsResponse, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    Ok(
+        NotificationsResponse{
+            notifications: notifications
+                .iter()
+                .map(build_notification_response)
+                .collect(),
+            unread,
+            next_max_id: notifications.last().map(|notification| format_cursor(&notification.created_at, &notification.notification_id)),
+            prev_min_id: notifications.first().map(|notification| format_cursor(&notification.created_at, &notification.notification_id))
+        }
+    )
+}
+
+/// A function that attempts to mark the
+/// notification with the given ID or, if no
+/// ID is given, all notifications of the user
+/// with the given API token as read. If the 
+/// operation is successful, the number of 
+/// notifications marked as read is returned.
+/// If the operation fails, an error is returned.
+pub async fn mark_notifications_read(
+    api_token: &str,
+    notification_id: Option<&str>,
+    pool: &Pool<Postgres>
+) -> Result<u64, KleahErr> {
+    let user: KleahUser = match get_notified_user(api_token, pool).await {
+        Ok(user) => user,
+        Err(e) => return Err::<u64, KleahErr>(e)
+    };
+    match update_notifications_read(&user.username, notification_id, pool).await {
+        Ok(marked) => Ok(marked),
+        Err(e) => Err::<u64, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    }
+}
+
+/// A function that attempts to dismiss
+/// a notification of the user with the given
+/// API token. Dismissed notifications are
+/// deleted. If the operation fails or the
+/// notification does not exist, an error is
+/// returned.
+pub async fn dismiss_notification(
+    api_token: &str,
+    notification_id: &str,
+    pool: &Pool<Postgres>
+) -> Result<(), KleahErr> {
+    let user: KleahUser = match get_notified_user(api_token, pool).await {
+        Ok(user) => user,
+        Err(e) => return Err::<(), KleahErr>(e)
+    };
+    match delete_notification(&user.username, notification_id, pool).await {
+        Ok(true) => Ok(()),
+        Ok(false) => Err::<(), KleahErr>(
+            KleahErr::with_type(
+                "The notification does not exist.",
+                KleahErrType::NotFound
+            )
+        ),
+        Err(e) => Err::<(), KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    }
+}
+
+/// A function that attempts to read the
+/// kinds of notifications the user with the
+/// given API token muted. If the operation is
+/// successful, an instance of the 
+/// `NotificationMutesResponse` structure is 
+/// returned. If the operation fails, an error
+/// is returned.
+pub async fn read_notification_mutes(
+    api_token: &str,
+    pool: &Pool<Postgres>
+) -> Result<NotificationMutesResponse, KleahErr> {
+    let user: KleahUser = match get_notified_user(api_token, pool).await {
+        Ok(user) => user,
+        Err(e) => return Err::<NotificationMutesResponse, KleahErr>(e)
+    };
+    match get_notification_mutes(&user.username, pool).await {
+        Ok(mutes) => Ok(
+            NotificationMutesResponse{
+                muted: mutes.into_iter().map(|mute| mute.kind).collect()
+            }
+        ),
+        Err(e) => Err::<NotificationMutesResponse, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    }
+}
+
+/// A function that attempts to mute or
+/// unmute a kind of notifications for the
+/// user with the given API token. Muted kinds
+/// of notifications are no longer created for
+/// the user, notifications the user already
+/// received are kept. If the operation is
+/// successful, an instance of the 
+/// `NotificationMutesResponse` structure is 
+/// returned. If the operation fails, an error
+/// is returned.
+pub async fn mute_notifications(
+    api_token: &str,
+    kind: &str,
+    muted: &bool,
+    pool: &Pool<Postgres>
+) -> Result<NotificationMutesResponse, KleahErr> {
+    let user: KleahUser = match get_notified_user(api_token, pool).await {
+        Ok(user) => user,
+        Err(e) => return Err::<NotificationMutesResponse, KleahErr>(e)
+    };
+    if !check_notification_kind(kind) {
+        return Err::<NotificationMutesResponse, KleahErr>(
+            KleahErr::with_type(
+                "The kind of notifications is not valid.",
+                KleahErrType::BadRequest
+            )
+        )
+    }
+    let mute: KleahNotificationMute = KleahNotificationMute{
+        username: user.username.clone(),
+        kind: kind.to_string()
+    };
+    let stored: Result<(), KleahErr> = if *muted {
+        create_notification_mute(&mute, pool).await
+    }
+    else {
+        delete_notification_mute(&mute, pool).await
+    };
+    match stored {
+        Ok(_f) => {},
+        Err(e) => return Err::<NotificationMutesResponse, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    read_notification_mutes(api_token, pool).await
+}
@@ -96,3 +96,233 @@ pub struct EditInviteSystemPayload{
     pub api_token: String,
     pub uses_invites: bool
 }
+
+/// Declaring a data structure
+/// that models data in a JSON 
+/// string received for editing
+/// who may read the local and
+/// the federated timeline.
+#[derive(Serialize, Deserialize)]
+pub struct EditTimelineAccessPayload{
+    pub api_token: String,
+    pub local_timeline: String,
+    pub federated_timeline: String
+}
+
+/// Declaring a data structure
+/// that models the query string
+/// received for a WebFinger lookup.
+#[derive(Serialize, Deserialize)]
+pub struct WebFingerQuery{
+    pub resource: String
+}
+
+/// Declaring a data structure
+/// that models data in a JSON 
+/// string received for following
+/// or unfollowing an actor. The
+/// target is the URL of the actor.
+#[derive(Serialize, Deserialize)]
+pub struct FollowPayload{
+    pub api_token: String,
+    pub target: String
+}
+
+/// Declaring a data structure
+/// that models data in a JSON 
+/// string received for approving
+/// or denying a follow request.
+#[derive(Serialize, Deserialize)]
+pub struct FollowRequestPayload{
+    pub api_token: String,
+    pub follow_id: String
+}
+
+/// Declaring a data structure
+/// that models data in a JSON 
+/// string received for editing
+/// whether follow requests for a
+/// user have to be approved manually.
+#[derive(Serialize, Deserialize)]
+pub struct EditLockedPayload{
+    pub api_token: String,
+    pub is_locked: bool
+}
+
+/// Declaring a data structure
+/// that models data in a JSON 
+/// string received for editing
+/// whether notes with a content 
+/// warning are expanded for a user
+/// by default.
+#[derive(Serialize, Deserialize)]
+pub struct EditSpoilersPayload{
+    pub api_token: String,
+    pub expand_spoilers: bool
+}
+
+/// Declaring a data structure
+/// that models the query string
+/// received for retrieving an
+/// ActivityPub collection or one
+/// of its pages. The cursors are
+/// points in time in microseconds
+/// followed by the ID of an item.
+#[derive(Serialize, Deserialize)]
+pub struct CollectionQuery{
+    pub page: Option<bool>,
+    pub max_id: Option<String>,
+    pub min_id: Option<String>
+}
+
+/// Declaring a data structure
+/// that models the payload 
+/// received for publishing a
+/// new note.
+#[derive(Serialize, Deserialize)]
+pub struct NoteCreatePayload{
+    pub api_token: String,
+    pub content: String,
+    pub in_reply_to: Option<String>,
+    pub visibility: Option<String>,
+    pub summary: Option<String>,
+    pub sensitive: Option<bool>,
+    pub poll: Option<PollPayload>
+}
+
+/// Declaring a data structure
+/// that models the poll received
+/// with a new note. The poll ends
+/// the given number of seconds
+/// after the note is published.
+#[derive(Serialize, Deserialize)]
+pub struct PollPayload{
+    pub options: Vec<String>,
+    pub expires_in: i64,
+    pub multiple: Option<bool>
+}
+
+/// Declaring a data structure
+/// that models the payload 
+/// received for voting in the
+/// poll of a note. Choices are
+/// the positions of the options,
+/// starting at zero.
+#[derive(Serialize, Deserialize)]
+pub struct PollVotePayload{
+    pub api_token: String,
+    pub note_id: String,
+    pub choices: Vec<i32>
+}
+
+/// Declaring a data structure
+/// that models the payload 
+/// received for deleting a
+/// note.
+#[derive(Serialize, Deserialize)]
+pub struct NoteDeletePayload{
+    pub api_token: String,
+    pub note_id: String
+}
+
+/// Declaring a data structure
+/// that models the payload 
+/// received for editing a
+/// note. The content warning and
+/// the sensitive flag are kept as
+/// they are if they are not given.
+#[derive(Serialize, Deserialize)]
+pub struct NoteEditPayload{
+    pub api_token: String,
+    pub note_id: String,
+    pub content: String,
+    pub summary: Option<String>,
+    pub sensitive: Option<bool>
+}
+
+/// Declaring a data structure
+/// that models the query string
+/// received for reading a timeline.
+/// The cursors are points in time
+/// in microseconds followed by the
+/// ID of an entry.
+#[derive(Serialize, Deserialize)]
+pub struct TimelineQuery{
+    pub max_id: Option<String>,
+    pub min_id: Option<String>,
+    pub limit: Option<i64>
+}
+
+/// Declaring a data structure
+/// that models data in a JSON 
+/// string received for interacting
+/// with a note, for example for 
+/// liking it.
+#[derive(Serialize, Deserialize)]
+pub struct NoteInteractionPayload{
+    pub api_token: String,
+    pub note_id: String
+}
+
+/// Declaring a data structure
+/// that models the query string
+/// received for reading the
+/// notifications of a user. The
+/// cursors are points in time in
+/// microseconds followed by the ID
+/// of a notification.
+#[derive(Serialize, Deserialize)]
+pub struct NotificationQuery{
+    pub kind: Option<String>,
+    pub unread: Option<bool>,
+    pub max_id: Option<String>,
+    pub min_id: Option<String>,
+    pub limit: Option<i64>
+}
+
+/// Declaring a data structure
+/// that models data in a JSON 
+/// string received for marking a
+/// notification as read. If no
+/// notification is given, all
+/// notifications are marked.
+#[derive(Serialize, Deserialize)]
+pub struct NotificationReadPayload{
+    pub api_token: String,
+    pub notification_id: Option<String>
+}
+
+/// Declaring a data structure
+/// that models data in a JSON 
+/// string received for dismissing
+/// a notification.
+#[derive(Serialize, Deserialize)]
+pub struct NotificationDismissPayload{
+    pub api_token: String,
+    pub notification_id: String
+}
+
+/// Declaring a data structure
+/// that models data in a JSON 
+/// string received for muting or
+/// unmuting a kind of notifications.
+#[derive(Serialize, Deserialize)]
+pub struct NotificationMutePayload{
+    pub api_token: String,
+    pub kind: String,
+    pub muted: bool
+}
+
+/// Declaring a data structure
+/// that models data in a JSON 
+/// string received for reporting
+/// an actor and optionally one of
+/// their notes to the administrators.
+#[derive(Serialize, Deserialize)]
+pub struct ReportPayload{
+    pub api_token: String,
+    pub username: String,
+    pub host: String,
+    pub note_id: Option<String>,
+    pub comment: Option<String>
+}
@@ -0,0 +1,1080 @@
+/*
+Kleah by Alyx Shang.
+Licensed under the FSL v1.
+*/
+
+/// Importing the structure
+/// representing a pool of
+/// database connections.
+use sqlx::Pool;
+
+/// Importing the structure
+/// representing the UTC
+/// time zone.
+use chrono::Utc;
+
+/// Importing the structure
+/// representing a point in
+/// time.
+use chrono::DateTime;
+
+/// Importing the structure
+/// representing a span of
+/// time.
+use chrono::TimeDelta;
+
+/// Importing the structure
+/// representing an arbitrary
+/// JSON value.
+use serde_json::Value;
+
+/// Importing the structure
+/// representing a database
+/// transaction.
+use sqlx::Transaction;
+
+/// Importing the structure
+/// representing a span of
+/// time to wait.
+use std::time::Duration;
+
+/// Importing the function
+/// to retrieve a poll.
+use super::db::get_poll;
+
+/// Importing the function
+/// to borrow a value as a
+/// slice of one element.
+use std::slice::from_ref;
+
+/// Importing the function
+/// to run a task in the
+/// background.
+use actix_web::rt::spawn;
+
+/// Importing the structure
+/// for catching and handling
+/// errors.
+use super::err::KleahErr;
+
+/// Importing the function
+/// to convert a structure into
+/// a JSON value.
+use serde_json::to_value;
+
+/// Importing the function
+/// to close a poll.
+use super::db::close_poll;
+
+/// Importing the function
+/// to build the URL of a note.
+use super::utils::note_url;
+
+/// Importing the function
+/// to save a poll.
+use super::db::create_poll;
+
+/// Importing the function
+/// to build the URL of an actor.
+use super::utils::actor_url;
+
+/// Importing the enum
+/// to describe the kind
+/// of error that occurred.
+use super::err::KleahErrType;
+
+/// Importing the structure
+/// representing a Postgres
+/// database.
+use sqlx::postgres::Postgres;
+
+/// Importing the structure
+/// modelling a note.
+use super::models::KleahNote;
+
+/// Importing the structure
+/// modelling a poll.
+use super::models::KleahPoll;
+
+/// Importing the function
+/// to read the notes a local
+/// user may see.
+use super::notes::get_viewer;
+
+/// Importing the function
+/// to generate IDs.
+use super::utils::generate_id;
+
+/// Importing the function
+/// to retrieve a note by
+/// its ID.
+use super::db::get_note_by_id;
+
+/// Importing the structure
+/// modelling an actor.
+use super::models::KleahActor;
+
+/// Importing the structure
+/// modelling an ActivityPub
+/// activity.
+use super::apub::ApubActivity;
+
+/// Importing the structure
+/// containing data about a
+/// vote in a poll.
+use super::apub::ApubPollVote;
+
+/// Importing the function
+/// to wait for some time.
+use actix_web::rt::time::sleep;
+
+/// Importing the function
+/// to retrieve an actor by
+/// their username and host.
+use super::db::get_actor_by_id;
+
+/// Importing the function
+/// to retrieve the actors that
+/// voted in a poll.
+use super::db::get_poll_voters;
+
+/// Importing the trait for
+/// anything that can run
+/// queries against a Postgres
+/// database.
+use sqlx::postgres::PgExecutor;
+
+/// Importing the function
+/// to retrieve the options
+/// of a poll.
+use super::db::get_poll_options;
+
+/// Importing the structure
+/// containing data about an
+/// option of a poll.
+use super::apub::ApubPollOption;
+
+/// Importing the function
+/// to retrieve the ID of an
+/// ActivityPub object.
+use super::federation::object_id;
+
+/// Importing the structure
+/// modelling a vote in
+/// a poll.
+use super::models::KleahPollVote;
+
+/// Importing the function
+/// to save votes in a poll.
+use super::db::create_poll_votes;
+
+/// Importing the structure
+/// containing data about the
+/// votes for an option of a poll.
+use super::apub::ApubPollReplies;
+
+/// Importing the structure
+/// modelling the poll of a
+/// new note.
+use super::payloads::PollPayload;
+
+/// Importing the function
+/// to retrieve the polls
+/// that have ended.
+use super::db::get_expired_polls;
+
+/// Importing the structure
+/// representing a single
+/// connection to a Postgres
+/// database.
+use sqlx::postgres::PgConnection;
+
+/// Importing the function
+/// to retrieve information
+/// about this instance.
+use super::db::get_instance_info;
+
+/// Importing the function
+/// to retrieve the IDs of
+/// ActivityPub objects.
+use super::federation::object_ids;
+
+/// Importing the function
+/// to retrieve a note by
+/// its URL.
+use super::notes::get_note_by_url;
+
+/// Importing the function
+/// to queue activities for
+/// delivery.
+use super::queue::deliver_activity;
+
+/// Importing the function
+/// to save a new notification.
+use super::db::create_notification;
+
+/// Importing the function
+/// to replace the tallies
+/// of a poll.
+use super::db::update_poll_tallies;
+
+/// Importing the structure
+/// modelling an option of
+/// a poll.
+use super::models::KleahPollOption;
+
+/// Importing the function
+/// to retrieve the actors a
+/// note is addressed to.
+use super::db::get_note_recipients;
+
+/// Importing the structure
+/// containing a poll.
+use super::responses::PollResponse;
+
+/// Importing the function
+/// to retrieve the votes of
+/// an actor in a poll.
+use super::db::get_actor_poll_votes;
+
+/// Importing the structure
+/// modelling a notification.
+use super::models::KleahNotification;
+
+/// Importing the function
+/// to build an activity about
+/// a note.
+use super::notes::build_note_activity;
+
+/// Importing the function
+/// to retrieve a note on
+/// behalf of a reader.
+use super::notes::get_note_for_viewer;
+
+/// Importing the function
+/// to queue an activity about
+/// a note for its audience.
+use super::notes::queue_note_activity;
+
+/// Importing the function
+/// to retrieve the actor and
+/// note of an interaction.
+use super::notes::get_note_interaction;
+
+/// Importing the structure
+/// modelling information
+/// about this instance.
+use super::models::InstanceInformation;
+
+/// Importing the constant
+/// containing the URL of the
+/// ActivityStreams context.
+use super::apub::ACTIVITYSTREAMS_CONTEXT;
+
+/// Importing the structure
+/// containing an option of
+/// a poll.
+use super::responses::PollOptionResponse;
+
+/// Importing the function
+/// to build the notification
+/// of a local user.
+use super::notifications::build_notification;
+
+/// The largest number of
+/// options a poll may have.
+pub const POLL_MAX_OPTIONS: usize = 4;
+
+/// The largest number of
+/// characters an option of
+/// a poll may have.
+pub const POLL_OPTION_MAX_LENGTH: usize = 50;
+
+/// The shortest time in
+/// seconds a poll may run
+/// for.
+pub const POLL_MIN_DURATION: i64 = 300;
+
+/// The longest time in
+/// seconds a poll may run
+/// for.
+pub const POLL_MAX_DURATION: i64 = 2_592_000;
+
+/// The number of seconds the
+/// worker closing polls waits
+/// between checks for polls
+/// that have ended.
+pub const POLL_CHECK_INTERVAL: u64 = 60;
+
+/// A function to check whether a poll
+/// received with a new note is valid. A
+/// poll needs between two and the maximum
+/// number of distinct, non-empty options and
+/// must run for an allowed time. A boolean
+/// reflecting this is returned.
+pub fn check_poll(
+    poll: &PollPayload
+) -> bool {
+    let titles: Vec<&str> = poll.options
+        .iter()
+        .map(|title| title.trim())
+        .collect();
+    (2..=POLL_MAX_OPTIONS).contains(&titles.len()) &&
+        titles.iter().all(|title| !title.is_empty() && title.chars().count() <= POLL_OPTION_MAX_LENGTH) &&
+        titles.iter().enumerate().all(|(index, title)| !titles[..index].contains(title)) &&
+        (POLL_MIN_DURATION..=POLL_MAX_DURATION).contains(&poll.expires_in)
+}
+
+/// A function to check whether the
+/// given choices are a valid vote in a
+/// poll. Single-choice polls take exactly
+/// one choice, all choices must be distinct
+/// options of the poll. A boolean reflecting
+/// this is returned.
+pub fn check_poll_choices(
+    poll: &KleahPoll,
+    options: &[KleahPollOption],
+    choices: &[i32]
+) -> bool {
+    !choices.is_empty() &&
+        (poll.multiple || choices.len() == 1) &&
+        choices.iter().all(|choice| options.iter().any(|option| option.position == *choice)) &&
+        choices.iter().enumerate().all(|(index, choice)| !choices[..index].contains(choice))
+}
+
+/// A function to check whether votes
+/// can still be cast in a poll. A boolean
+/// reflecting this is returned.
+pub fn poll_is_open(
+    poll: &KleahPoll
+) -> bool {
+    poll.closed_at.is_none() && poll.expires_at > Utc::now()
+}
+
+/// A function to read a point in time
+/// from a field of an ActivityPub object.
+/// The point in time is returned if the
+/// field holds a valid one.
+pub fn object_time(
+    object: &Value,
+    field: &str
+) -> Option<DateTime<Utc>> {
+    object
+        .get(field)
+        .and_then(|time| time.as_str())
+        .and_then(|time| DateTime::parse_from_rfc3339(time).ok())
+        .map(|time| time.with_timezone(&Utc))
+}
+
+/// A function to read the options of a
+/// poll received from another server from
+/// a "Question" object. Single-choice polls
+/// list their options in "oneOf", multiple-choice
+/// polls in "anyOf". Whether the poll allows
+/// multiple choices and the title and tally of
+/// each option are returned if the object holds
+/// a poll.
+pub fn read_poll_choices(
+    object: &Value
+) -> Option<(bool, Vec<(String, i64)>)> {
+    let (multiple, choices): (bool, &Vec<Value>) = match (object.get("oneOf"), object.get("anyOf")) {
+        (Some(Value::Array(choices)), _) => (false, choices),
+        (_, Some(Value::Array(choices))) => (true, choices),
+        _ => return None
+    };
+    let choices: Vec<(String, i64)> = choices
+        .iter()
+        .filter_map(
+            |choice| choice
+                .get("name")
+                .and_then(|name| name.as_str())
+                .map(
+                    |name| (
+                        name.trim().to_string(),
+                        choice
+                            .get("replies")
+                            .and_then(|replies| replies.get("totalItems"))
+                            .and_then(|total_items| total_items.as_i64())
+                            .unwrap_or(0)
+                    )
+                )
+        )
+        .collect();
+    if choices.is_empty() {
+        None
+    }
+    else {
+        Some((multiple, choices))
+    }
+}
+
+/// A function to build the options of
+/// a poll for a "Question" object. Each
+/// option carries its tally. The options
+/// are returned in order.
+pub fn build_poll_options(
+    options: &[KleahPollOption]
+) -> Vec<ApubPollOption> {
+    options
+        .iter()
+        .map(
+            |option| ApubPollOption{
+                option_type: "Note".to_string(),
+                name: option.title.clone(),
+                replies: ApubPollReplies{
+                    replies_type: "Collection".to_string(),
+                    total_items: option.votes_count
+                }
+            }
+        )
+        .collect()
+}
+
+/// A function to build the response
+/// describing a poll for the API of this
+/// instance with the votes of the reader.
+/// The response is returned.
+pub fn build_poll_response(
+    poll: &KleahPoll,
+    options: &[KleahPollOption],
+    votes: &[KleahPollVote]
+) -> PollResponse {
+    PollResponse{
+        note_id: poll.note_id.clone(),
+        multiple: poll.multiple,
+        expires_at: poll.expires_at.to_rfc3339(),
+        expired: !poll_is_open(poll),
+        voters: poll.voters_count,
+        options: options
+            .iter()
+            .map(
+                |option| PollOptionResponse{
+                    title: option.title.clone(),
+                    votes: option.votes_count
+                }
+            )
+            .collect(),
+        voted: !votes.is_empty(),
+        own_votes: votes.iter().map(|vote| vote.position).collect()
+    }
+}
+
+/// A function that attempts to build the
+/// "Create" activity that sends a local vote
+/// to a poll on another server. Votes are sent
+/// as notes named after the chosen option that
+/// reply to the poll and are only addressed to
+/// its author. If the operation is successful,
+/// the activity is returned. If the operation
+/// fails, an error is returned.
+pub fn build_vote_activity(
+    vote_id: &str,
+    title: &str,
+    voter: &KleahActor,
+    note: &KleahNote,
+    author: &KleahActor
+) -> Result<ApubActivity, KleahErr> {
+    let url: String = format!("{}#votes/{}", actor_url(voter), vote_id);
+    let object: Value = match to_value(
+        ApubPollVote{
+            id: url.clone(),
+            vote_type: "Note".to_string(),
+            name: title.to_string(),
+            attributed_to: actor_url(voter),
+            in_reply_to: note_url(note),
+            to: vec![actor_url(author)]
+        }
+    ){
+        Ok(object) => object,
+        Err(e) => return Err::<ApubActivity, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    Ok(
+        ApubActivity{
+            context: ACTIVITYSTREAMS_CONTEXT.to_string(),
+            id: format!("{}/activity", url),
+            activity_type: "Create".to_string(),
+            actor: actor_url(voter),
+            object,
+            to: vec![actor_url(author)],
+            cc: Vec::new()
+        }
+    )
+}
+
+/// A function that attempts to attach a
+/// poll to a new local note. The poll ends
+/// the given number of seconds after the
+/// note was published. If the operation is
+/// successful, an empty function is returned.
+/// If the operation fails, an error is returned.
+pub async fn create_note_poll<'c>(
+    note: &KleahNote,
+    poll: &PollPayload,
+    executor: impl PgExecutor<'c>
+) -> Result<(), KleahErr> {
+    let titles: Vec<String> = poll.options
+        .iter()
+        .map(|title| title.trim().to_string())
+        .collect();
+    create_poll(
+        &KleahPoll{
+            note_id: note.note_id.clone(),
+            multiple: poll.multiple.unwrap_or(false),
+            expires_at: note.created_at + TimeDelta::seconds(poll.expires_in),
+            closed_at: None,
+            voters_count: 0
+        },
+        &titles,
+        &vec![0; titles.len()],
+        executor
+    ).await
+}
+
+/// A function that attempts to retrieve
+/// the poll attached to a note and its
+/// options. If the operation is successful,
+/// the poll and its options are returned if
+/// the note has a poll. If the operation
+/// fails, an error is returned.
+pub async fn get_note_poll(
+    note: &KleahNote,
+    pool: &Pool<Postgres>
+) -> Result<Option<(KleahPoll, Vec<KleahPollOption>)>, KleahErr> {
+    if !note.has_poll {
+        return Ok(None);
+    }
+    let poll: KleahPoll = match get_poll(&note.note_id, pool).await {
+        Ok(poll) => poll,
+        Err(e) => return Err::<Option<(KleahPoll, Vec<KleahPollOption>)>, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    match get_poll_options(&note.note_id, pool).await {
+        Ok(options) => Ok(Some((poll, options))),
+        Err(e) => Err::<Option<(KleahPoll, Vec<KleahPollOption>)>, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    }
+}
+
+/// A function that attempts to describe
+/// the poll of a note for a reader, who is
+/// told which options they voted for. If the
+/// operation is successful, an instance of
+/// the `PollResponse` structure is returned.
+/// If the note has no poll or the operation
+/// fails, an error is returned.
+pub async fn get_poll_response(
+    note: &KleahNote,
+    viewer: Option<&KleahActor>,
+    pool: &Pool<Postgres>
+) -> Result<PollResponse, KleahErr> {
+    let (poll, options): (KleahPoll, Vec<KleahPollOption>) = match get_note_poll(note, pool).await {
+        Ok(Some(found)) => found,
+        Ok(None) => return Err::<PollResponse, KleahErr>(
+            KleahErr::with_type(
+                "The note has no poll.",
+                KleahErrType::NotFound
+            )
+        ),
+        Err(e) => return Err::<PollResponse, KleahErr>(e)
+    };
+    let votes: Vec<KleahPollVote> = match viewer {
+        Some(viewer) => match get_actor_poll_votes(&note.note_id, viewer, pool).await {
+            Ok(votes) => votes,
+            Err(e) => return Err::<PollResponse, KleahErr>(
+                KleahErr::new(&e.to_string())
+            )
+        },
+        None => Vec::new()
+    };
+    Ok(build_poll_response(&poll, &options, &votes))
+}
+
+/// A function that attempts to read the
+/// poll of a note on behalf of the user with
+/// the given API token or an anonymous reader.
+/// If the operation is successful, an instance
+/// of the `PollResponse` structure is returned.
+/// If the reader may not see the note, the note
+/// has no poll or the operation fails, an error
+/// is returned.
+pub async fn read_poll(
+    note_id: &str,
+    api_token: Option<&str>,
+    pool: &Pool<Postgres>
+) -> Result<PollResponse, KleahErr> {
+    let viewer: Option<KleahActor> = match get_viewer(api_token, pool).await {
+        Ok(viewer) => viewer,
+        Err(e) => return Err::<PollResponse, KleahErr>(e)
+    };
+    let note: KleahNote = match get_note_for_viewer(note_id, viewer.as_ref(), pool).await {
+        Ok(note) => note,
+        Err(e) => return Err::<PollResponse, KleahErr>(e)
+    };
+    get_poll_response(&note, viewer.as_ref(), pool).await
+}
+
+/// A function that attempts to cast the
+/// vote of the user with the given API token
+/// in the poll of a note. Users may vote once
+/// per poll while it is open and not in their
+/// own polls. Votes in polls on other servers
+/// are sent to the poll's author. If the operation
+/// is successful, an instance of the `PollResponse`
+/// structure is returned. If the operation fails,
+/// an error is returned.
+pub async fn vote_in_poll(
+    api_token: &str,
+    note_id: &str,
+    choices: &[i32],
+    pool: &Pool<Postgres>
+) -> Result<PollResponse, KleahErr> {
+    let (viewer, note, author): (KleahActor, KleahNote, KleahActor) = match get_note_interaction(
+        api_token,
+        note_id,
+        pool
+    ).await {
+        Ok(interaction) => interaction,
+        Err(e) => return Err::<PollResponse, KleahErr>(e)
+    };
+    let (poll, options): (KleahPoll, Vec<KleahPollOption>) = match get_note_poll(&note, pool).await {
+        Ok(Some(found)) => found,
+        Ok(None) => return Err::<PollResponse, KleahErr>(
+            KleahErr::with_type(
+                "The note has no poll.",
+                KleahErrType::NotFound
+            )
+        ),
+        Err(e) => return Err::<PollResponse, KleahErr>(e)
+    };
+    if !poll_is_open(&poll) {
+        return Err::<PollResponse, KleahErr>(
+            KleahErr::with_type(
+                "The poll has ended.",
+                KleahErrType::BadRequest
+            )
+        )
+    }
+    if author.username == viewer.username && author.host == viewer.host {
+        return Err::<PollResponse, KleahErr>(
+            KleahErr::with_type(
+                "Authors cannot vote in their own polls.",
+                KleahErrType::BadRequest
+            )
+        )
+    }
+    let votes: Vec<KleahPollVote> = match get_actor_poll_votes(&note.note_id, &viewer, pool).await {
+        Ok(votes) => votes,
+        Err(e) => return Err::<PollResponse, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    if !votes.is_empty() {
+        return Err::<PollResponse, KleahErr>(
+            KleahErr::with_type(
+                "The user has already voted in the poll.",
+                KleahErrType::BadRequest
+            )
+        )
+    }
+    if !check_poll_choices(&poll, &options, choices) {
+        return Err::<PollResponse, KleahErr>(
+            KleahErr::with_type(
+                "The choices are not valid for the poll.",
+                KleahErrType::BadRequest
+            )
+        )
+    }
+    let vote_ids: Vec<String> = choices
+        .iter()
+        .map(|choice| generate_id(&format!("{}{}@{}{}", note.note_id, viewer.username, viewer.host, choice)))
+        .collect();
+    match create_poll_votes(&note.note_id, &vote_ids, choices, &viewer, pool).await {
+        Ok(_f) => {},
+        Err(e) => return Err::<PollResponse, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    if note.ap_id.is_some() {
+        for (vote_id, choice) in vote_ids.iter().zip(choices) {
+            let title: &str = options
+                .iter()
+                .find(|option| option.position == *choice)
+                .map(|option| option.title.as_str())
+                .unwrap_or_default();
+            let activity: ApubActivity = match build_vote_activity(vote_id, title, &viewer, &note, &author){
+                Ok(activity) => activity,
+                Err(e) => return Err::<PollResponse, KleahErr>(e)
+            };
+            match deliver_activity(&viewer.username, from_ref(&author), &activity, pool).await {
+                Ok(_f) => {},
+                Err(e) => return Err::<PollResponse, KleahErr>(
+                    KleahErr::new(&e.to_string())
+                )
+            };
+        }
+    }
+    get_poll_response(&note, Some(&viewer), pool).await
+}
+
+/// A function that attempts to handle a
+/// note received from another server that
+/// may be a vote in a local poll. Votes are
+/// notes named after the chosen option that
+/// reply to the poll. Votes for unknown options,
+/// in closed polls or repeated votes in
+/// single-choice polls are ignored. If the
+/// operation is successful, a boolean reflecting
+/// whether the note was a vote is returned. If
+/// the operation fails, an error is returned.
+pub async fn handle_poll_vote(
+    actor: &KleahActor,
+    object: &Value,
+    pool: &Pool<Postgres>
+) -> Result<bool, KleahErr> {
+    let title: String = match object.get("name").and_then(|name| name.as_str()) {
+        Some(title) => title.trim().to_string(),
+        None => return Ok(false)
+    };
+    let in_reply_to: String = match object.get("inReplyTo").and_then(object_id) {
+        Some(in_reply_to) => in_reply_to,
+        None => return Ok(false)
+    };
+    let note: KleahNote = match get_note_by_url(&in_reply_to, pool).await {
+        Ok(note) if note.ap_id.is_none() => note,
+        _ => return Ok(false)
+    };
+    let (poll, options): (KleahPoll, Vec<KleahPollOption>) = match get_note_poll(&note, pool).await {
+        Ok(Some(found)) => found,
+        Ok(None) => return Ok(false),
+        Err(e) => return Err::<bool, KleahErr>(e)
+    };
+    let attributed_to: Option<String> = object
+        .get("attributedTo")
+        .and_then(|attributed_to| object_ids(attributed_to).into_iter().next());
+    if attributed_to != Some(actor_url(actor)) {
+        return Err::<bool, KleahErr>(
+            KleahErr::with_type(
+                "The vote was not cast by the sending actor.",
+                KleahErrType::Unauthorized
+            )
+        )
+    }
+    let option: &KleahPollOption = match options.iter().find(|option| option.title == title) {
+        Some(option) if poll_is_open(&poll) => option,
+        _ => return Ok(true)
+    };
+    let votes: Vec<KleahPollVote> = match get_actor_poll_votes(&note.note_id, actor, pool).await {
+        Ok(votes) => votes,
+        Err(e) => return Err::<bool, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    if !poll.multiple && !votes.is_empty() {
+        return Ok(true);
+    }
+    let vote_id: String = object_id(object)
+        .unwrap_or_else(|| generate_id(&format!("{}{}@{}{}", note.note_id, actor.username, actor.host, title)));
+    match create_poll_votes(&note.note_id, from_ref(&vote_id), from_ref(&option.position), actor, pool).await {
+        Ok(_f) => Ok(true),
+        Err(e) => Err::<bool, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    }
+}
+
+/// A function that attempts to store the
+/// poll of a "Question" object received from
+/// another server for the stored copy of the
+/// note. Polls without an end time end after
+/// the longest allowed time. If the operation
+/// is successful, an empty function is returned.
+/// If the operation fails, an error is returned.
+pub async fn store_remote_poll(
+    note: &KleahNote,
+    object: &Value,
+    pool: &Pool<Postgres>
+) -> Result<(), KleahErr> {
+    let (multiple, choices): (bool, Vec<(String, i64)>) = match read_poll_choices(object) {
+        Some(found) => found,
+        None => return Ok(())
+    };
+    let closed_at: Option<DateTime<Utc>> = read_poll_closed(object);
+    let (titles, tallies): (Vec<String>, Vec<i64>) = choices.into_iter().unzip();
+    create_poll(
+        &KleahPoll{
+            note_id: note.note_id.clone(),
+            multiple,
+            expires_at: object_time(object, "endTime")
+                .or(closed_at)
+                .unwrap_or(note.created_at + TimeDelta::seconds(POLL_MAX_DURATION)),
+            closed_at,
+            voters_count: object
+                .get("votersCount")
+                .and_then(|voters_count| voters_count.as_i64())
+                .unwrap_or(0)
+        },
+        &titles,
+        &tallies,
+        pool
+    ).await
+}
+
+/// A function to read when a poll from
+/// another server was closed. Some servers
+/// only mark closed polls without a time,
+/// these are treated as closed now. The point
+/// in time is returned if the poll is closed.
+pub fn read_poll_closed(
+    object: &Value
+) -> Option<DateTime<Utc>> {
+    match object.get("closed") {
+        Some(Value::Bool(true)) => Some(Utc::now()),
+        Some(_closed) => object_time(object, "closed"),
+        None => None
+    }
+}
+
+/// A function that attempts to replace
+/// the tallies of a stored poll from another
+/// server with the tallies of a received
+/// "Question" object. Only the author of the
+/// poll may update it. Updates of unknown polls
+/// are ignored. If the operation fails, an
+/// error is returned.
+pub async fn update_remote_poll(
+    actor: &KleahActor,
+    object: &Value,
+    pool: &Pool<Postgres>
+) -> Result<(), KleahErr> {
+    let note: KleahNote = match object_id(object) {
+        Some(ap_id) => match get_note_by_url(&ap_id, pool).await {
+            Ok(note) => note,
+            Err(_e) => return Ok(())
+        },
+        None => return Ok(())
+    };
+    if note.ap_id.is_none() || note.author_username != actor.username || note.author_host != actor.host {
+        return Err::<(), KleahErr>(
+            KleahErr::with_type(
+                "The poll was not created by the sending actor.",
+                KleahErrType::Unauthorized
+            )
+        )
+    }
+    let poll: KleahPoll = match get_note_poll(&note, pool).await {
+        Ok(Some((poll, _options))) => poll,
+        Ok(None) => return Ok(()),
+        Err(e) => return Err::<(), KleahErr>(e)
+    };
+    let tallies: Vec<i64> = match read_poll_choices(object) {
+        Some((_multiple, choices)) => choices.into_iter().map(|(_title, tally)| tally).collect(),
+        None => return Ok(())
+    };
+    match update_poll_tallies(
+        &note.note_id,
+        &tallies,
+        object
+            .get("votersCount")
+            .and_then(|voters_count| voters_count.as_i64())
+            .unwrap_or(poll.voters_count),
+        read_poll_closed(object),
+        pool
+    ).await {
+        Ok(_f) => Ok(()),
+        Err(e) => Err::<(), KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    }
+}
+
+/// A function that attempts to finish a
+/// poll that was just closed. The poll is
+/// described as it is given, as its closing
+/// may not be saved yet. Local voters are
+/// notified that the poll has ended. If the poll
+/// is local, its author is notified as well and
+/// its audience on other servers is sent the final
+/// tallies. Notifications and deliveries are saved
+/// over the given connection, so that they are
+/// only kept together with the poll's closing.
+/// If the operation is successful, an empty
+/// function is returned. If the operation fails,
+/// an error is returned.
+pub async fn finish_poll(
+    poll: &KleahPoll,
+    conn: &mut PgConnection,
+    pool: &Pool<Postgres>
+) -> Result<(), KleahErr> {
+    let instance: InstanceInformation = match get_instance_info(pool).await {
+        Ok(instance) => instance,
+        Err(e) => return Err::<(), KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    let note: KleahNote = match get_note_by_id(&poll.note_id, pool).await {
+        Ok(note) => note,
+        Err(e) => return Err::<(), KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    let author: KleahActor = match get_actor_by_id(&note.author_username, &note.author_host, pool).await {
+        Ok(author) => author,
+        Err(e) => return Err::<(), KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    let voters: Vec<KleahActor> = match get_poll_voters(&note.note_id, pool).await {
+        Ok(voters) => voters,
+        Err(e) => return Err::<(), KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    for voter in &voters {
+        let notification: KleahNotification = match build_notification(voter, "poll", &author, Some(&note.note_id), &instance) {
+            Some(notification) => notification,
+            None => continue
+        };
+        match create_notification(&notification, &mut *conn).await {
+            Ok(_f) => {},
+            Err(e) => return Err::<(), KleahErr>(e)
+        };
+    }
+    if note.ap_id.is_some() {
+        return Ok(());
+    }
+    let notification: KleahNotification = KleahNotification{
+        notification_id: generate_id(&format!("{}poll{}", author.username, note.note_id)),
+        username: author.username.clone(),
+        kind: "poll".to_string(),
+        actor_username: author.username.clone(),
+        actor_host: author.host.clone(),
+        note_id: Some(note.note_id.clone()),
+        created_at: Utc::now(),
+        is_read: false,
+        report_id: None
+    };
+    match create_notification(&notification, &mut *conn).await {
+        Ok(_f) => {},
+        Err(e) => return Err::<(), KleahErr>(e)
+    };
+    let recipients: Vec<KleahActor> = match get_note_recipients(&note.note_id, pool).await {
+        Ok(recipients) => recipients,
+        Err(e) => return Err::<(), KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    let found: Option<(KleahPoll, Vec<KleahPollOption>)> = match get_note_poll(&note, pool).await {
+        Ok(found) => found.map(|(_stored, options)| (poll.clone(), options)),
+        Err(e) => return Err::<(), KleahErr>(e)
+    };
+    let update: ApubActivity = match build_note_activity(
+        &note,
+        &author,
+        &recipients,
+        found.as_ref(),
+        "Update",
+        &format!("{}/updates/poll", note_url(&note))
+    ){
+        Ok(update) => update,
+        Err(e) => return Err::<(), KleahErr>(e)
+    };
+    queue_note_activity(&note, &author, &recipients, &update, conn).await
+}
+
+/// A function that attempts to close a
+/// single poll that has ended and to finish
+/// it in one transaction, so that a poll that
+/// cannot be finished stays open and its voters
+/// are not notified twice. If the operation is
+/// successful, a boolean reflecting whether the
+/// poll was closed by this call is returned. If
+/// the operation fails, an error is returned.
+pub async fn process_expired_poll(
+    poll: &KleahPoll,
+    pool: &Pool<Postgres>
+) -> Result<bool, KleahErr> {
+    let mut tx: Transaction<'_, Postgres> = match pool.begin().await {
+        Ok(tx) => tx,
+        Err(e) => return Err::<bool, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    let poll: KleahPoll = match close_poll(&poll.note_id, &mut *tx).await {
+        Ok(Some(poll)) => poll,
+        Ok(None) => return Ok(false),
+        Err(e) => return Err::<bool, KleahErr>(e)
+    };
+    match finish_poll(&poll, &mut tx, pool).await {
+        Ok(_f) => {},
+        Err(e) => return Err::<bool, KleahErr>(e)
+    };
+    match tx.commit().await {
+        Ok(_f) => Ok(true),
+        Err(e) => Err::<bool, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    }
+}
+
+/// A function that attempts to close all
+/// polls that have ended and to finish them.
+/// Each poll is closed and finished in its own
+/// transaction, so a poll that cannot be finished
+/// stays open and is tried again on the next check
+/// while the other polls are closed. If the operation
+/// is successful, the number of closed polls is
+/// returned. If any poll could not be closed, an
+/// error naming the failures is returned once all
+/// polls were tried.
+pub async fn process_expired_polls(
+    pool: &Pool<Postgres>
+) -> Result<usize, KleahErr> {
+    let polls: Vec<KleahPoll> = match get_expired_polls(pool).await {
+        Ok(polls) => polls,
+        Err(e) => return Err::<usize, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    let mut closed: usize = 0;
+    let mut failures: Vec<String> = Vec::new();
+    for poll in &polls {
+        match process_expired_poll(poll, pool).await {
+            Ok(true) => closed += 1,
+            Ok(false) => {},
+            Err(e) => failures.push(format!("{}: {}", poll.note_id, e))
+        };
+    }
+    if !failures.is_empty() {
+        return Err::<usize, KleahErr>(
+            KleahErr::new(&format!("Could not close polls: {}", failures.join("; ")))
+        )
+    }
+    Ok(closed)
+}
+
+/// A function to start the worker closing
+/// polls that have ended in the background.
+/// The worker runs for as long as the server
+/// does. Polls that ended while the server was
+/// not running are closed on its first check.
+/// Polls that could not be closed are reported
+/// on the standard error stream.
+pub fn start_poll_worker(
+    pool: &Pool<Postgres>
+) {
+    let pool: Pool<Postgres> = pool.clone();
+    spawn(
+        async move {
+            loop {
+                if let Err(e) = process_expired_polls(&pool).await {
+                    eprintln!("{}", e);
+                }
+                sleep(Duration::from_secs(POLL_CHECK_INTERVAL)).await;
+            }
+        }
+    );
+}
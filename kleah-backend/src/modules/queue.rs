@@ -0,0 +1,305 @@
+/*
+Kleah by Alyx Shang.
+Licensed under the FSL v1.
+*/
+
+/// Importing the structure
+/// representing a pool of
+/// database connections.
+use sqlx::Pool;
+
+/// Importing the structure
+/// to represent a span of time.
+use std::time::Duration;
+
+/// Importing the structure
+/// for catching and handling
+/// errors.
+use super::err::KleahErr;
+
+/// Importing the function
+/// to run a task in the
+/// background.
+use actix_web::rt::spawn;
+
+/// Importing the function
+/// to serialize a Rust data
+/// structure into a JSON string.
+use serde_json::to_string;
+
+/// Importing the structure that
+/// represents a connection to a 
+/// PostgreSQL database.
+use sqlx::postgres::Postgres;
+
+/// Importing the data structure
+/// modelling data about an ActivityPub
+/// actor in the database.
+use super::models::KleahActor;
+
+/// Importing the function
+/// to generate a new unique
+/// identifier.
+use super::utils::generate_id;
+
+/// Importing the structure
+/// modelling an ActivityPub
+/// activity.
+use super::apub::ApubActivity;
+
+/// Importing the function
+/// to wait for a period of
+/// time asynchronously.
+use actix_web::rt::time::sleep;
+
+/// Importing the structure
+/// for storing a sorted set
+/// of unique values.
+use std::collections::BTreeSet;
+
+/// Importing the function
+/// to add an activity to the
+/// queue of deliveries.
+use super::db::create_delivery;
+
+/// Importing the structure
+/// representing a connection
+/// taken from a pool.
+use sqlx::pool::PoolConnection;
+
+/// Importing the function
+/// to remove a sent delivery
+/// from the queue.
+use super::db::destroy_delivery;
+
+/// Importing the function
+/// to claim deliveries that
+/// are due for sending.
+use super::db::claim_deliveries;
+
+/// Importing the data structure
+/// modelling an activity waiting
+/// to be delivered.
+use super::models::KleahDelivery;
+
+/// Importing the structure
+/// representing a single
+/// connection to a Postgres
+/// database.
+use sqlx::postgres::PgConnection;
+
+/// Importing the function
+/// to record a failed attempt
+/// to send a delivery.
+use super::db::record_delivery_failure;
+
+/// Importing the function to
+/// send a signed "POST" request
+/// to another server.
+use super::signatures::send_signed_post;
+
+/// The number of attempts
+/// after which the delivery
+/// of an activity is given up.
+pub const MAX_DELIVERY_ATTEMPTS: i32 = 8;
+
+/// The delay in seconds before
+/// the first retry of a failed
+/// delivery. The delay doubles 
+/// with every further attempt.
+pub const DELIVERY_BASE_DELAY: f64 = 60.0;
+
+/// The number of deliveries
+/// a worker claims at once.
+pub const DELIVERY_BATCH_SIZE: i64 = 32;
+
+/// The number of seconds a 
+/// claimed delivery is hidden
+/// from other workers.
+pub const DELIVERY_LEASE: f64 = 300.0;
+
+/// The number of seconds an
+/// idle worker waits before
+/// looking for new deliveries.
+pub const DELIVERY_POLL_INTERVAL: u64 = 5;
+
+/// A function to retrieve the inbox an
+/// activity for the given actor should be
+/// delivered to. The shared inbox of the
+/// actor's server is preferred.
+pub fn delivery_inbox(
+    actor: &KleahActor
+) -> String {
+    match &actor.shared_inbox {
+        Some(shared_inbox) => shared_inbox.clone(),
+        None => actor.inbox.clone()
+    }
+}
+
+/// A function to calculate the delay in
+/// seconds before a delivery is retried
+/// after the given number of failed attempts.
+pub fn retry_delay(
+    attempts: i32
+) -> f64 {
+    DELIVERY_BASE_DELAY * 2f64.powi(attempts.max(0))
+}
+
+/// A function that attempts to queue an
+/// activity for delivery to the inboxes
+/// of the given actors on behalf of a local
+/// user over the given connection, so that
+/// the deliveries can be part of a transaction.
+/// Every inbox receives the activity only once,
+/// so recipients sharing an inbox are served by
+/// a single delivery. If the operation is
+/// successful, an empty function is returned.
+/// If the operation fails, an error is returned.
+pub async fn queue_activity(
+    username: &str,
+    recipients: &[KleahActor],
+    activity: &ApubActivity,
+    conn: &mut PgConnection
+) -> Result<(), KleahErr> {
+    let payload: String = match to_string(activity){
+        Ok(payload) => payload,
+        Err(e) => return Err::<(), KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    let inboxes: BTreeSet<String> = recipients.iter()
+        .map(delivery_inbox)
+        .collect();
+    for inbox in inboxes {
+        match create_delivery(
+            &generate_id(&format!("{}{}", &activity.id, &inbox)),
+            &activity.id,
+            username,
+            &inbox,
+            &payload,
+            &mut *conn
+        ).await {
+            Ok(_f) => {},
+            Err(e) => return Err::<(), KleahErr>(
+                KleahErr::new(&e.to_string())
+            )
+        };
+    }
+    Ok(())
+}
+
+/// A function that attempts to queue an
+/// activity for delivery to the inboxes
+/// of the given actors on behalf of a local
+/// user. Every inbox receives the activity
+/// only once, so recipients sharing an inbox
+/// are served by a single delivery. If the
+/// operation is successful, an empty function
+/// is returned. If the operation fails, an
+/// error is returned.
+pub async fn deliver_activity(
+    username: &str,
+    recipients: &[KleahActor],
+    activity: &ApubActivity,
+    pool: &Pool<Postgres>
+) -> Result<(), KleahErr> {
+    let mut conn: PoolConnection<Postgres> = match pool.acquire().await {
+        Ok(conn) => conn,
+        Err(e) => return Err::<(), KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    queue_activity(username, recipients, activity, &mut conn).await
+}
+
+/// A function that attempts to send a
+/// single queued delivery. Sent deliveries
+/// are removed from the queue, failed ones
+/// are scheduled for a retry or given up
+/// after too many attempts. If the delivery
+/// could not be updated, an error is returned.
+pub async fn send_delivery(
+    delivery: &KleahDelivery,
+    pool: &Pool<Postgres>
+) -> Result<(), KleahErr> {
+    match send_signed_post(
+        &delivery.sender_username, 
+        &delivery.inbox, 
+        &delivery.payload, 
+        pool
+    ).await {
+        Ok(_f) => destroy_delivery(&delivery.delivery_id, pool).await,
+        Err(e) => record_delivery_failure(
+            &delivery.delivery_id,
+            &e.to_string(),
+            retry_delay(delivery.attempts),
+            &(delivery.attempts + 1 >= MAX_DELIVERY_ATTEMPTS),
+            pool
+        ).await
+    }
+}
+
+/// A function that attempts to send all
+/// deliveries that are currently due. The
+/// deliveries of one batch are sent at the
+/// same time. If the operation is successful,
+/// the number of deliveries attempted is
+/// returned. If the operation fails, an error
+/// is returned.
+pub async fn process_deliveries(
+    pool: &Pool<Postgres>
+) -> Result<usize, KleahErr> {
+    let deliveries: Vec<KleahDelivery> = match claim_deliveries(
+        DELIVERY_BATCH_SIZE,
+        DELIVERY_LEASE,
+        pool
+    ).await {
+        Ok(deliveries) => deliveries,
+        Err(e) => return Err::<usize, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    let attempted: usize = deliveries.len();
+    let handles = deliveries.into_iter()
+        .map(
+            |delivery| {
+                let pool: Pool<Postgres> = pool.clone();
+                spawn(async move { send_delivery(&delivery, &pool).await })
+            }
+        )
+        .collect::<Vec<_>>();
+    for handle in handles {
+        match handle.await {
+            Ok(Ok(_f)) => {},
+            Ok(Err(e)) => return Err::<usize, KleahErr>(
+                KleahErr::new(&e.to_string())
+            ),
+            Err(e) => return Err::<usize, KleahErr>(
+                KleahErr::new(&e.to_string())
+            )
+        };
+    }
+    Ok(attempted)
+}
+
+/// A function to start the worker sending
+/// queued deliveries in the background. The
+/// worker runs for as long as the server does.
+/// Deliveries are stored in the database, so
+/// no deliveries are lost if the server is
+/// restarted.
+pub fn start_delivery_worker(
+    pool: &Pool<Postgres>
+) {
+    let pool: Pool<Postgres> = pool.clone();
+    spawn(
+        async move {
+            loop {
+                match process_deliveries(&pool).await {
+                    Ok(0) | Err(_) => sleep(Duration::from_secs(DELIVERY_POLL_INTERVAL)).await,
+                    Ok(_attempted) => {}
+                };
+            }
+        }
+    );
+}
@@ -0,0 +1,393 @@
+/*
+Kleah by Alyx Shang.
+Licensed under the FSL v1.
+*/
+
+/// Importing the structure
+/// representing a pool of
+/// database connections.
+use sqlx::Pool;
+
+/// Importing the structure
+/// representing the UTC
+/// time zone.
+use chrono::Utc;
+
+/// Importing the structure
+/// representing an arbitrary
+/// JSON value.
+use serde_json::Value;
+
+/// Importing the structure
+/// for catching and handling
+/// errors.
+use super::err::KleahErr;
+
+/// Importing the function
+/// to retrieve a report.
+use super::db::get_report;
+
+/// Importing the enum
+/// to describe the kind
+/// of error that occurred.
+use super::err::KleahErrType;
+
+/// Importing the structure
+/// modelling a user.
+use super::models::KleahUser;
+
+/// Importing the function
+/// to save a report.
+use super::db::create_report;
+
+/// Importing the structure
+/// representing a Postgres
+/// database.
+use sqlx::postgres::Postgres;
+
+/// Importing the structure
+/// modelling a note.
+use super::models::KleahNote;
+
+/// Importing the function
+/// to read the notes a local
+/// user may see.
+use super::notes::get_viewer;
+
+/// Importing the function
+/// to generate IDs.
+use super::utils::generate_id;
+
+/// Importing the structure
+/// modelling an actor.
+use super::models::KleahActor;
+
+/// Importing the structure
+/// modelling a report.
+use super::models::KleahReport;
+
+/// Importing the function
+/// to retrieve an actor by
+/// their username and host.
+use super::db::get_actor_by_id;
+
+/// Importing the function
+/// to retrieve the administrators
+/// of this instance.
+use super::db::get_admin_actors;
+
+/// Importing the function
+/// to retrieve information
+/// about this instance.
+use super::db::get_instance_info;
+
+/// Importing the function
+/// to retrieve the IDs of
+/// ActivityPub objects.
+use super::federation::object_ids;
+
+/// Importing the function
+/// to retrieve a note by
+/// its URL.
+use super::notes::get_note_by_url;
+
+/// Importing the function
+/// to save a new notification.
+use super::db::create_notification;
+
+/// Importing the structure
+/// modelling the payload for
+/// reporting an actor.
+use super::payloads::ReportPayload;
+
+/// Importing the structure
+/// containing a report.
+use super::responses::ReportResponse;
+
+/// Importing the structure
+/// modelling a notification.
+use super::models::KleahNotification;
+
+/// Importing the function
+/// to retrieve a note on
+/// behalf of a reader.
+use super::notes::get_note_for_viewer;
+
+/// Importing the structure
+/// modelling information
+/// about this instance.
+use super::models::InstanceInformation;
+
+/// Importing the function
+/// to retrieve the local actor
+/// an activity is addressed to.
+use super::federation::get_local_target;
+
+/// Importing the function
+/// to retrieve the user an
+/// API token belongs to.
+use super::notifications::get_notified_user;
+
+/// The largest number of
+/// characters the comment of
+/// a report may have.
+pub const REPORT_COMMENT_MAX_LENGTH: usize = 1000;
+
+/// A function to bring the comment of
+/// a report into the form it is stored
+/// in. Surrounding whitespace is removed
+/// and overly long comments are cut off.
+/// The comment is returned.
+pub fn normalize_report_comment(
+    comment: &str
+) -> String {
+    comment
+        .trim()
+        .chars()
+        .take(REPORT_COMMENT_MAX_LENGTH)
+        .collect()
+}
+
+/// A function to build the response
+/// describing a report for the API of
+/// this instance. The response is
+/// returned.
+pub fn build_report_response(
+    report: &KleahReport
+) -> ReportResponse {
+    ReportResponse{
+        report_id: report.report_id.clone(),
+        reporter: report.reporter_username.clone(),
+        reporter_host: report.reporter_host.clone(),
+        target: report.target_username.clone(),
+        target_host: report.target_host.clone(),
+        note_id: report.note_id.clone(),
+        comment: report.comment.clone(),
+        created_at: report.created_at.to_rfc3339()
+    }
+}
+
+/// A function that attempts to save a
+/// report of an actor and optionally one of
+/// their notes and to notify the administrators
+/// of this instance about it. Administrators are
+/// not notified about their own reports. If the
+/// operation is successful, an instance of the
+/// `KleahReport` structure is returned. If the
+/// operation fails, an error is returned.
+pub async fn file_report(
+    reporter: &KleahActor,
+    target: &KleahActor,
+    note_id: Option<&str>,
+    comment: &str,
+    pool: &Pool<Postgres>
+) -> Result<KleahReport, KleahErr> {
+    let instance: InstanceInformation = match get_instance_info(pool).await {
+        Ok(instance) => instance,
+        Err(e) => return Err::<KleahReport, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    let report: KleahReport = match create_report(
+        &KleahReport{
+            report_id: generate_id(&format!("{}@{}{}@{}", reporter.username, reporter.host, target.username, target.host)),
+            reporter_username: reporter.username.clone(),
+            reporter_host: reporter.host.clone(),
+            target_username: target.username.clone(),
+            target_host: target.host.clone(),
+            note_id: note_id.map(|note_id| note_id.to_string()),
+            comment: normalize_report_comment(comment),
+            created_at: Utc::now()
+        },
+        pool
+    ).await {
+        Ok(report) => report,
+        Err(e) => return Err::<KleahReport, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    let admins: Vec<KleahActor> = match get_admin_actors(&instance.host, pool).await {
+        Ok(admins) => admins,
+        Err(e) => return Err::<KleahReport, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    for admin in admins {
+        if admin.username == reporter.username && admin.host == reporter.host {
+            continue;
+        }
+        let notification: KleahNotification = KleahNotification{
+            notification_id: generate_id(&format!("{}report{}", admin.username, report.report_id)),
+            username: admin.username.clone(),
+            kind: "report".to_string(),
+            actor_username: reporter.username.clone(),
+            actor_host: reporter.host.clone(),
+            note_id: report.note_id.clone(),
+            created_at: report.created_at,
+            is_read: false,
+            report_id: Some(report.report_id.clone())
+        };
+        match create_notification(&notification, pool).await {
+            Ok(_f) => {},
+            Err(e) => return Err::<KleahReport, KleahErr>(e)
+        };
+    }
+    Ok(report)
+}
+
+/// A function that attempts to report an
+/// actor and optionally one of their notes on
+/// behalf of the user with the given API token.
+/// Reported notes must be visible to the user
+/// and written by the reported actor. Reports
+/// stay on this instance. If the operation is
+/// successful, an instance of the `KleahReport`
+/// structure is returned. If the operation fails,
+/// an error is returned.
+pub async fn report_actor(
+    payload: &ReportPayload,
+    pool: &Pool<Postgres>
+) -> Result<KleahReport, KleahErr> {
+    let reporter: KleahActor = match get_viewer(Some(&payload.api_token), pool).await {
+        Ok(Some(reporter)) => reporter,
+        Ok(None) => return Err::<KleahReport, KleahErr>(
+            KleahErr::with_type(
+                "An API token is required.",
+                KleahErrType::Unauthorized
+            )
+        ),
+        Err(e) => return Err::<KleahReport, KleahErr>(e)
+    };
+    let target: KleahActor = match get_actor_by_id(&payload.username, &payload.host, pool).await {
+        Ok(target) => target,
+        Err(_e) => return Err::<KleahReport, KleahErr>(
+            KleahErr::with_type(
+                "The reported actor does not exist.",
+                KleahErrType::NotFound
+            )
+        )
+    };
+    if target.username == reporter.username && target.host == reporter.host {
+        return Err::<KleahReport, KleahErr>(
+            KleahErr::with_type(
+                "Users cannot report themselves.",
+                KleahErrType::BadRequest
+            )
+        )
+    }
+    if let Some(note_id) = &payload.note_id {
+        let note: KleahNote = match get_note_for_viewer(note_id, Some(&reporter), pool).await {
+            Ok(note) => note,
+            Err(e) => return Err::<KleahReport, KleahErr>(e)
+        };
+        if note.author_username != target.username || note.author_host != target.host {
+            return Err::<KleahReport, KleahErr>(
+                KleahErr::with_type(
+                    "The reported note was not written by the reported actor.",
+                    KleahErrType::BadRequest
+                )
+            )
+        }
+    }
+    file_report(
+        &reporter,
+        &target,
+        payload.note_id.as_deref(),
+        payload.comment.as_deref().unwrap_or(""),
+        pool
+    ).await
+}
+
+/// A function that attempts to handle a
+/// received "Flag" activity that reports a
+/// local actor or one of their notes from
+/// another server. The first local actor among
+/// the flagged objects is reported, or else the
+/// author of the first flagged local note. Flags
+/// without a local target are ignored. If the
+/// operation fails, an error is returned.
+pub async fn handle_flag(
+    actor: &KleahActor,
+    activity: &Value,
+    pool: &Pool<Postgres>
+) -> Result<(), KleahErr> {
+    let objects: Vec<String> = activity
+        .get("object")
+        .map(object_ids)
+        .unwrap_or_default();
+    let mut target: Option<KleahActor> = None;
+    let mut note: Option<KleahNote> = None;
+    for object in &objects {
+        match get_note_by_url(object, pool).await {
+            Ok(flagged) if flagged.ap_id.is_none() => {
+                if note.is_none() {
+                    note = Some(flagged);
+                }
+                continue;
+            },
+            _ => {}
+        };
+        if target.is_none() {
+            target = get_local_target(object, pool).await
+                .ok()
+                .map(|(_user, local_actor)| local_actor);
+        }
+    }
+    let target: KleahActor = match (target, &note) {
+        (Some(target), _) => target,
+        (None, Some(note)) => match get_actor_by_id(&note.author_username, &note.author_host, pool).await {
+            Ok(author) => author,
+            Err(e) => return Err::<(), KleahErr>(
+                KleahErr::new(&e.to_string())
+            )
+        },
+        (None, None) => return Ok(())
+    };
+    let note_id: Option<&str> = note
+        .as_ref()
+        .filter(|note| note.author_username == target.username && note.author_host == target.host)
+        .map(|note| note.note_id.as_str());
+    let comment: &str = activity
+        .get("content")
+        .and_then(|content| content.as_str())
+        .unwrap_or("");
+    match file_report(actor, &target, note_id, comment, pool).await {
+        Ok(_report) => Ok(()),
+        Err(e) => Err::<(), KleahErr>(e)
+    }
+}
+
+/// A function that attempts to read a
+/// report on behalf of the user with the
+/// given API token. Only administrators may
+/// read reports. If the operation is successful,
+/// an instance of the `ReportResponse` structure
+/// is returned. If the operation fails, an error
+/// is returned.
+pub async fn read_report(
+    report_id: &str,
+    api_token: &str,
+    pool: &Pool<Postgres>
+) -> Result<ReportResponse, KleahErr> {
+    let user: KleahUser = match get_notified_user(api_token, pool).await {
+        Ok(user) => user,
+        Err(e) => return Err::<ReportResponse, KleahErr>(e)
+    };
+    if !user.is_admin {
+        return Err::<ReportResponse, KleahErr>(
+            KleahErr::with_type(
+                "Only administrators may read reports.",
+                KleahErrType::Unauthorized
+            )
+        )
+    }
+    match get_report(report_id, pool).await {
+        Ok(report) => Ok(build_report_response(&report)),
+        Err(_e) => Err::<ReportResponse, KleahErr>(
+            KleahErr::with_type(
+                "The report does not exist.",
+                KleahErrType::NotFound
+            )
+        )
+    }
+}
@@ -10,6 +10,11 @@ Licensed under the FSL v1.
 /// string.
 use serde::Serialize;
 
+/// Importing the structure
+/// representing an arbitrary
+/// JSON value.
+use serde_json::Value;
+
 /// A structure to contain data
 /// about a created user and serialize
 /// this data into a JSON string.
@@ -49,3 +54,283 @@ pub struct StatusResponse{
 pub struct InviteCreateResponse{
     pub code: String
 }
+
+/// A structure to contain data
+/// about a single link in a
+/// WebFinger response and serialize
+/// this data into a JSON string.
+#[derive(Serialize)]
+pub struct WebFingerLink{
+    pub rel: String,
+    #[serde(rename = "type")]
+    pub link_type: String,
+    pub href: String
+}
+
+/// A structure to contain data
+/// about a Kleah actor in the
+/// JRD format for WebFinger lookups
+/// and serialize this data into a
+/// JSON string.
+#[derive(Serialize)]
+pub struct WebFingerResponse{
+    pub subject: String,
+    pub aliases: Vec<String>,
+    pub links: Vec<WebFingerLink>
+}
+
+/// A structure to contain data
+/// about a created follow relationship
+/// and serialize this data into a
+/// JSON string.
+#[derive(Serialize)]
+pub struct FollowResponse{
+    pub follow_id: String,
+    pub accepted: bool
+}
+
+/// A structure to contain data
+/// about a link to a NodeInfo
+/// document and serialize this
+/// data into a JSON string.
+#[derive(Serialize)]
+pub struct NodeInfoLink{
+    pub rel: String,
+    pub href: String
+}
+
+/// A structure to contain data
+/// about the NodeInfo documents
+/// this instance serves and serialize
+/// this data into a JSON string.
+#[derive(Serialize)]
+pub struct NodeInfoLinks{
+    pub links: Vec<NodeInfoLink>
+}
+
+/// A structure to contain data
+/// about the software running this
+/// instance and serialize this data
+/// into a JSON string.
+#[derive(Serialize)]
+pub struct NodeInfoSoftware{
+    pub name: String,
+    pub version: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub repository: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub homepage: Option<String>
+}
+
+/// A structure to contain data
+/// about third-party services this
+/// instance connects to and serialize
+/// this data into a JSON string.
+#[derive(Serialize)]
+pub struct NodeInfoServices{
+    pub inbound: Vec<String>,
+    pub outbound: Vec<String>
+}
+
+/// A structure to contain data
+/// about the number of users on this
+/// instance and serialize this data
+/// into a JSON string.
+#[derive(Serialize, Clone)]
+pub struct NodeInfoUsers{
+    pub total: i64,
+    #[serde(rename = "activeMonth")]
+    pub active_month: i64,
+    #[serde(rename = "activeHalfyear")]
+    pub active_halfyear: i64
+}
+
+/// A structure to contain data
+/// about the usage of this instance
+/// and serialize this data into a
+/// JSON string.
+#[derive(Serialize, Clone)]
+pub struct NodeInfoUsage{
+    pub users: NodeInfoUsers,
+    #[serde(rename = "localPosts")]
+    pub local_posts: i64
+}
+
+/// A structure to contain data
+/// about this instance in the NodeInfo
+/// format and serialize this data into
+/// a JSON string.
+#[derive(Serialize)]
+pub struct NodeInfoResponse{
+    pub version: String,
+    pub software: NodeInfoSoftware,
+    pub protocols: Vec<String>,
+    pub services: NodeInfoServices,
+    #[serde(rename = "openRegistrations")]
+    pub open_registrations: bool,
+    pub usage: NodeInfoUsage,
+    pub metadata: Value
+}
+
+/// A structure to contain data
+/// about a note and serialize this
+/// data into a JSON string.
+#[derive(Serialize)]
+pub struct NoteResponse{
+    pub note_id: String,
+    pub url: String,
+    pub author: String,
+    pub host: String,
+    pub content: String,
+    pub in_reply_to: Option<String>,
+    pub visibility: String,
+    pub created_at: String,
+    pub updated_at: Option<String>,
+    pub likes: i64,
+    pub boosts: i64,
+    pub summary: Option<String>,
+    pub sensitive: bool,
+    pub expanded: bool,
+    pub has_poll: bool
+}
+
+/// A structure containing
+/// one page of a timeline and
+/// the cursors to read the older
+/// and newer pages next to it.
+#[derive(Serialize)]
+pub struct TimelineResponse{
+    pub notes: Vec<TimelineNoteResponse>,
+    pub next_max_id: Option<String>,
+    pub prev_min_id: Option<String>
+}
+
+/// A structure containing
+/// information about the boost
+/// that put a note on a timeline.
+#[derive(Serialize)]
+pub struct BoostResponse{
+    pub boost_id: String,
+    pub username: String,
+    pub host: String,
+    pub created_at: String
+}
+
+/// A structure containing
+/// a note on a timeline and,
+/// if the note was boosted onto
+/// it, the boost.
+#[derive(Serialize)]
+pub struct TimelineNoteResponse{
+    #[serde(flatten)]
+    pub note: NoteResponse,
+    pub boost: Option<BoostResponse>
+}
+
+/// A structure containing
+/// the notes a note replies to
+/// and the replies it received,
+/// both oldest first.
+#[derive(Serialize)]
+pub struct NoteContextResponse{
+    pub ancestors: Vec<NoteResponse>,
+    pub descendants: Vec<NoteResponse>
+}
+
+/// A structure containing
+/// information about a single
+/// revision of a note.
+#[derive(Serialize)]
+pub struct NoteRevisionResponse{
+    pub content: String,
+    pub summary: Option<String>,
+    pub sensitive: bool,
+    pub created_at: String
+}
+
+/// A structure containing
+/// all revisions of a note,
+/// oldest first and ending
+/// with the current one.
+#[derive(Serialize)]
+pub struct NoteHistoryResponse{
+    pub note_id: String,
+    pub revisions: Vec<NoteRevisionResponse>
+}
+
+/// A structure containing
+/// information about a poll,
+/// its tallies and the votes
+/// of the reader.
+#[derive(Serialize)]
+pub struct PollResponse{
+    pub note_id: String,
+    pub multiple: bool,
+    pub expires_at: String,
+    pub expired: bool,
+    pub voters: i64,
+    pub options: Vec<PollOptionResponse>,
+    pub voted: bool,
+    pub own_votes: Vec<i32>
+}
+
+/// A structure containing
+/// information about an option
+/// of a poll and its tally.
+#[derive(Serialize)]
+pub struct PollOptionResponse{
+    pub title: String,
+    pub votes: i64
+}
+
+/// A structure containing
+/// information about a
+/// notification.
+#[derive(Serialize)]
+pub struct NotificationResponse{
+    pub notification_id: String,
+    pub kind: String,
+    pub actor: String,
+    pub host: String,
+    pub note_id: Option<String>,
+    pub report_id: Option<String>,
+    pub read: bool,
+    pub created_at: String
+}
+
+/// A structure containing
+/// a page of the notifications
+/// of a user, the number of 
+/// unread notifications and
+/// the cursors for the next
+/// pages.
+#[derive(Serialize)]
+pub struct NotificationsResponse{
+    pub notifications: Vec<NotificationResponse>,
+    pub unread: i64,
+    pub next_max_id: Option<String>,
+    pub prev_min_id: Option<String>
+}
+
+/// A structure containing
+/// the kinds of notifications
+/// a user muted.
+#[derive(Serialize)]
+pub struct NotificationMutesResponse{
+    pub muted: Vec<String>
+}
+
+/// A structure containing
+/// information about a report
+/// to the administrators.
+#[derive(Serialize)]
+pub struct ReportResponse{
+    pub report_id: String,
+    pub reporter: String,
+    pub reporter_host: String,
+    pub target: String,
+    pub target_host: String,
+    pub note_id: Option<String>,
+    pub comment: String,
+    pub created_at: String
+}
@@ -0,0 +1,892 @@
+/*
+Kleah by Alyx Shang.
+Licensed under the FSL v1.
+*/
+
+/// Importing the structure
+/// representing a pool of
+/// database connections.
+use sqlx::Pool;
+
+/// Importing the 
+/// structure to get
+/// the current time.
+use chrono::Utc;
+
+/// Importing the trait
+/// to generate digests.
+use sha2::Digest;
+
+/// Importing the structure
+/// to generate a SHA-256 
+/// checksum.
+use sha2::Sha256;
+
+/// Importing the structure
+/// to parse URLs.
+use reqwest::Url;
+
+/// Importing the structure
+/// to pin futures.
+use std::pin::Pin;
+
+/// Importing the structure
+/// for making HTTP requests.
+use reqwest::Client;
+
+/// Importing this structure
+/// to parse the dates of
+/// requests.
+use chrono::DateTime;
+
+/// Importing the structure
+/// representing an RSA key.
+use openssl::rsa::Rsa;
+
+/// Importing the structure
+/// representing an arbitrary
+/// JSON value.
+use serde_json::Value;
+
+/// Importing the structure
+/// representing a key of
+/// any type.
+use openssl::pkey::PKey;
+
+/// Importing the trait
+/// for futures.
+use std::future::Future;
+
+/// Importing the entity
+/// representing a time zone
+/// with a fixed offset.
+use chrono::FixedOffset;
+
+/// Importing the structure
+/// for catching and handling
+/// errors.
+use super::err::KleahErr;
+
+/// Importing the structure
+/// for maintaining a persistent
+/// state of data at app runtime.
+use actix_web::web::Data;
+
+/// Importing the structure
+/// to create signatures.
+use openssl::sign::Signer;
+
+/// Importing the entity that
+/// represents a public key.
+use openssl::pkey::Public;
+
+/// Importing the structure
+/// representing the body of
+/// a request.
+use actix_web::web::Bytes;
+
+/// Importing the data structure
+/// containing the app-wide
+/// pool of connections to the
+/// database.
+use super::units::AppData;
+
+/// Importing the entity that
+/// represents a private key.
+use openssl::pkey::Private;
+
+/// Importing the function to
+/// build the base URL of an
+/// instance from its host.
+use super::utils::base_url;
+
+/// Importing the function to
+/// extract the host of a URL.
+use super::utils::url_host;
+
+/// Importing the function
+/// to deserialize bytes into
+/// a JSON value.
+use serde_json::from_slice;
+
+/// Importing the trait
+/// to implement extractors.
+use actix_web::FromRequest;
+
+/// Importing the structure
+/// representing a received
+/// request.
+use actix_web::HttpRequest;
+
+/// Importing the structure
+/// to verify signatures.
+use openssl::sign::Verifier;
+
+/// Importing the structure
+/// representing the payload
+/// of a request.
+use actix_web::dev::Payload;
+
+/// Importing the structure that
+/// represents a connection to a 
+/// PostgreSQL database.
+use sqlx::postgres::Postgres;
+
+/// Importing the data structure
+/// modelling data about a Kleah user
+/// in the database.
+use super::models::KleahUser;
+
+/// Importing the enumeration
+/// describing the types of
+/// errors that can occur.
+use super::err::KleahErrType;
+
+/// Importing the data structure
+/// modelling data about an ActivityPub
+/// actor in the database.
+use super::models::KleahActor;
+
+/// Importing the function to
+/// retrieve the record of a user
+/// in the database given their
+/// username.
+use super::db::get_user_by_id;
+
+/// Importing the structure
+/// to store the parameters
+/// of a signature header.
+use std::collections::HashMap;
+
+/// Importing the media type
+/// for ActivityPub documents.
+use super::apub::ACTIVITY_JSON;
+
+/// Importing the function to
+/// retrieve the record of an
+/// actor in the database given
+/// their username and host.
+use super::db::get_actor_by_id;
+
+/// Importing the structure holding
+/// the headers of a signed request.
+use super::units::SignedHeaders;
+
+/// Importing the entity to
+/// choose the SHA-256 digest
+/// for signatures.
+use openssl::hash::MessageDigest;
+
+/// Importing the function for
+/// retrieving information about
+/// the current Kleah instance.
+use super::db::get_instance_info;
+
+/// Importing the structure holding
+/// an activity whose signature has
+/// been verified.
+use super::units::SignedActivity;
+
+/// Importing the function to
+/// encode bytes as Base64.
+use openssl::base64::encode_block;
+
+/// Importing the function to
+/// decode Base64 strings.
+use openssl::base64::decode_block;
+
+/// Importing the structure holding
+/// the parameters of a "Signature"
+/// header.
+use super::units::ParsedSignature;
+
+/// Importing the function to retrieve
+/// the cached record of an actor given
+/// the ID of their public key.
+use super::db::get_actor_by_key_id;
+
+/// Importing the data structure
+/// modelling data about the current
+/// Kleah instance in the database.
+use super::models::InstanceInformation;
+
+/// Importing the function to fetch
+/// and cache the record of an actor
+/// from another server.
+use super::federation::refresh_remote_actor;
+
+/// A function to generate the value
+/// of the "Digest" header for the given
+/// request body. The value is returned
+/// as a string.
+pub fn create_digest(
+    body: &[u8]
+) -> String {
+    let mut hasher: Sha256 = Sha256::new();
+    hasher.update(body);
+    format!("SHA-256={}", encode_block(&hasher.finalize()))
+}
+
+/// A function that generates a string
+/// containing the current time in the
+/// format used for the "Date" header.
+pub fn http_date() -> String {
+    Utc::now()
+        .format("%a, %d %b %Y %H:%M:%S GMT")
+        .to_string()
+}
+
+/// A function to build the string
+/// that is signed for a request. The
+/// supplied headers are pairs of lowercase
+/// header names and values. The pseudo-header
+/// "(request-target)" is built from the method
+/// and the path of the request.
+pub fn build_signing_string(
+    method: &str,
+    path: &str,
+    headers: &[(String, String)]
+) -> String {
+    let mut lines: Vec<String> = vec![
+        format!("(request-target): {} {}", method.to_lowercase(), path)
+    ];
+    for (name, value) in headers {
+        lines.push(format!("{}: {}", name.to_lowercase(), value));
+    }
+    lines.join("\n")
+}
+
+/// A function that attempts to sign
+/// the given string with an RSA private
+/// key in the PEM format using RSA-SHA256.
+/// If the operation is successful, the 
+/// signature is returned as a Base64 string.
+/// If the operation fails, an error is
+/// returned.
+pub fn sign_string(
+    private_key: &str,
+    subject: &str
+) -> Result<String, KleahErr> {
+    let rsa: Rsa<Private> = match Rsa::private_key_from_pem(
+        private_key.as_bytes()
+    ){
+        Ok(rsa) => rsa,
+        Err(e) => return Err::<String, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    let key: PKey<Private> = match PKey::from_rsa(rsa){
+        Ok(key) => key,
+        Err(e) => return Err::<String, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    let mut signer: Signer = match Signer::new(
+        MessageDigest::sha256(), 
+        &key
+    ){
+        Ok(signer) => signer,
+        Err(e) => return Err::<String, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    let signature: Vec<u8> = match signer.sign_oneshot_to_vec(
+        subject.as_bytes()
+    ){
+        Ok(signature) => signature,
+        Err(e) => return Err::<String, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    Ok(encode_block(&signature))
+}
+
+/// A function that attempts to parse
+/// an RSA public key in the PEM format.
+/// Keys in the SPKI format and in the
+/// PKCS#1 format are accepted. If the 
+/// operation fails, an error is returned.
+pub fn parse_public_key(
+    public_key: &str
+) -> Result<PKey<Public>, KleahErr> {
+    if let Ok(key) = PKey::public_key_from_pem(public_key.as_bytes()) {
+        return Ok(key);
+    }
+    let rsa: Rsa<Public> = match Rsa::public_key_from_pem_pkcs1(
+        public_key.as_bytes()
+    ){
+        Ok(rsa) => rsa,
+        Err(e) => return Err::<PKey<Public>, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    match PKey::from_rsa(rsa){
+        Ok(key) => Ok(key),
+        Err(e) => Err::<PKey<Public>, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    }
+}
+
+/// A function that attempts to verify
+/// a Base64-encoded RSA-SHA256 signature
+/// of the given string with an RSA public
+/// key in the PEM format. A boolean reflecting
+/// whether the signature is valid is returned.
+/// If the key or the signature cannot be read,
+/// an error is returned.
+pub fn verify_string(
+    public_key: &str,
+    subject: &str,
+    signature: &str
+) -> Result<bool, KleahErr> {
+    let key: PKey<Public> = match parse_public_key(public_key){
+        Ok(key) => key,
+        Err(e) => return Err::<bool, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    let decoded: Vec<u8> = match decode_block(signature){
+        Ok(decoded) => decoded,
+        Err(e) => return Err::<bool, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    let mut verifier: Verifier = match Verifier::new(
+        MessageDigest::sha256(), 
+        &key
+    ){
+        Ok(verifier) => verifier,
+        Err(e) => return Err::<bool, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    match verifier.verify_oneshot(&decoded, subject.as_bytes()){
+        Ok(verified) => Ok(verified),
+        Err(e) => Err::<bool, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    }
+}
+
+/// A function that attempts to sign a request
+/// to the given URL with the given private key.
+/// The "(request-target)", "host" and "date" 
+/// headers are always signed, the "digest" header
+/// is signed if a body is supplied. If the 
+/// operation is successful, an instance of the
+/// `SignedHeaders` structure is returned. If the
+/// operation fails, an error is returned.
+pub fn sign_request(
+    private_key: &str,
+    key_id: &str,
+    method: &str,
+    url: &str,
+    body: Option<&[u8]>
+) -> Result<SignedHeaders, KleahErr> {
+    let parsed: Url = match Url::parse(url){
+        Ok(parsed) => parsed,
+        Err(e) => return Err::<SignedHeaders, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    let host: String = match url_host(url){
+        Some(host) => host,
+        None => return Err::<SignedHeaders, KleahErr>(
+            KleahErr::new("The supplied URL has no host.")
+        )
+    };
+    let path: String = match parsed.query(){
+        Some(query) => format!("{}?{}", parsed.path(), query),
+        None => parsed.path().to_string()
+    };
+    let date: String = http_date();
+    let digest: Option<String> = body.map(create_digest);
+    let mut headers: Vec<(String, String)> = vec![
+        ("host".to_string(), host.clone()),
+        ("date".to_string(), date.clone())
+    ];
+    if let Some(digest) = &digest {
+        headers.push(("digest".to_string(), digest.clone()));
+    }
+    let signing_string: String = build_signing_string(
+        method, 
+        &path, 
+        &headers
+    );
+    let signed: String = match sign_string(private_key, &signing_string){
+        Ok(signed) => signed,
+        Err(e) => return Err::<SignedHeaders, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    let header_names: String = headers
+        .iter()
+        .map(|(name, _value)| name.clone())
+        .collect::<Vec<String>>()
+        .join(" ");
+    let signature: String = format!(
+        "keyId=\"{}\",algorithm=\"rsa-sha256\",headers=\"(request-target) {}\",signature=\"{}\"",
+        key_id,
+        header_names,
+        signed
+    );
+    Ok(
+        SignedHeaders{
+            host,
+            date,
+            digest,
+            signature
+        }
+    )
+}
+
+/// A function that attempts to retrieve
+/// the private key of a local user and 
+/// the ID of the user's public key. If the
+/// operation is successful, the private key
+/// and the key ID are returned. If the operation
+/// fails, an error is returned.
+pub async fn get_signing_key(
+    username: &str,
+    pool: &Pool<Postgres>
+) -> Result<(String, String), KleahErr> {
+    let user: KleahUser = match get_user_by_id(username, pool).await {
+        Ok(user) => user,
+        Err(e) => return Err::<(String, String), KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    let instance: InstanceInformation = match get_instance_info(pool).await {
+        Ok(instance) => instance,
+        Err(e) => return Err::<(String, String), KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    let actor: KleahActor = match get_actor_by_id(
+        username, 
+        &instance.host, 
+        pool
+    ).await {
+        Ok(actor) => actor,
+        Err(e) => return Err::<(String, String), KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    let key_id: String = format!(
+        "{}{}", 
+        base_url(&instance.host), 
+        actor.public_key
+    );
+    Ok((user.private_key, key_id))
+}
+
+/// A function that attempts to send a
+/// signed "POST" request containing an
+/// ActivityPub document to the given URL 
+/// on behalf of a local user. If the operation
+/// is successful, nothing is returned. If the
+/// request fails or the receiving server does
+/// not accept it, an error is returned.
+pub async fn send_signed_post(
+    username: &str,
+    url: &str,
+    body: &str,
+    pool: &Pool<Postgres>
+) -> Result<(), KleahErr> {
+    let (private_key, key_id): (String, String) = match get_signing_key(
+        username, 
+        pool
+    ).await {
+        Ok(key) => key,
+        Err(e) => return Err::<(), KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    let signed: SignedHeaders = match sign_request(
+        &private_key, 
+        &key_id, 
+        "post", 
+        url, 
+        Some(body.as_bytes())
+    ){
+        Ok(signed) => signed,
+        Err(e) => return Err::<(), KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    let mut request = Client::new()
+        .post(url)
+        .header("Date", signed.date)
+        .header("Signature", signed.signature)
+        .header("Content-Type", ACTIVITY_JSON)
+        .header("Accept", ACTIVITY_JSON);
+    if let Some(digest) = signed.digest {
+        request = request.header("Digest", digest);
+    }
+    let response = match request
+        .body(body.to_string())
+        .send()
+        .await
+    {
+        Ok(response) => response,
+        Err(e) => return Err::<(), KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    if response.status().is_success(){
+        Ok(())
+    }
+    else {
+        Err::<(), KleahErr>(
+            KleahErr::new(
+                &format!("The receiving server responded with {}.", response.status())
+            )
+        )
+    }
+}
+
+/// The maximum age in seconds
+/// of the "Date" header of a
+/// signed request.
+pub const SIGNATURE_MAX_AGE: i64 = 43200;
+
+/// The maximum number of seconds
+/// the "Date" header of a signed
+/// request may lie in the future.
+pub const SIGNATURE_MAX_SKEW: i64 = 3600;
+
+/// A function that attempts to parse the
+/// value of a "Signature" header. If no
+/// headers are listed, only the "date" header
+/// is assumed to be signed. If the operation 
+/// is successful, an instance of the
+/// `ParsedSignature` structure is returned.
+/// If the header is of the wrong format, an
+/// error is returned.
+pub fn parse_signature_header(
+    value: &str
+) -> Result<ParsedSignature, KleahErr> {
+    let mut params: HashMap<String, String> = HashMap::new();
+    let mut rest: &str = value.trim();
+    while !rest.is_empty() {
+        let (name, after) = match rest.split_once("="){
+            Some(pair) => pair,
+            None => return Err::<ParsedSignature, KleahErr>(
+                KleahErr::new("The signature header is of the wrong format.")
+            )
+        };
+        let after: &str = match after.strip_prefix("\""){
+            Some(after) => after,
+            None => return Err::<ParsedSignature, KleahErr>(
+                KleahErr::new("The signature header is of the wrong format.")
+            )
+        };
+        let (param, after) = match after.split_once("\""){
+            Some(pair) => pair,
+            None => return Err::<ParsedSignature, KleahErr>(
+                KleahErr::new("The signature header is of the wrong format.")
+            )
+        };
+        params.insert(name.trim().to_string(), param.to_string());
+        rest = after.trim_start_matches(",").trim();
+    }
+    let key_id: String = match params.remove("keyId"){
+        Some(key_id) => key_id,
+        None => return Err::<ParsedSignature, KleahErr>(
+            KleahErr::new("The signature header has no key ID.")
+        )
+    };
+    let signature: String = match params.remove("signature"){
+        Some(signature) => signature,
+        None => return Err::<ParsedSignature, KleahErr>(
+            KleahErr::new("The signature header has no signature.")
+        )
+    };
+    let headers: Vec<String> = params
+        .remove("headers")
+        .unwrap_or("date".to_string())
+        .to_lowercase()
+        .split_whitespace()
+        .map(|name| name.to_string())
+        .collect::<Vec<String>>();
+    let algorithm: String = params
+        .remove("algorithm")
+        .unwrap_or("hs2019".to_string());
+    Ok(
+        ParsedSignature{
+            key_id,
+            algorithm,
+            headers,
+            signature
+        }
+    )
+}
+
+/// A function to check whether the value
+/// of a "Digest" header matches the given
+/// request body. Only SHA-256 digests are
+/// accepted. A boolean reflecting this
+/// is returned.
+pub fn check_digest(
+    header: &str,
+    body: &[u8]
+) -> bool {
+    let expected: String = create_digest(body);
+    header
+        .split(",")
+        .any(|digest| digest.trim() == expected)
+}
+
+/// A function to check whether the value
+/// of a "Date" header is recent enough for
+/// a signed request to be accepted. A boolean
+/// reflecting this is returned.
+pub fn check_date(
+    header: &str
+) -> bool {
+    let date: DateTime<FixedOffset> = match DateTime::parse_from_rfc2822(header){
+        Ok(date) => date,
+        Err(_e) => return false
+    };
+    let age: i64 = Utc::now().timestamp() - date.timestamp();
+    (-SIGNATURE_MAX_SKEW..=SIGNATURE_MAX_AGE).contains(&age)
+}
+
+/// A function that attempts to retrieve the
+/// value of a header from a request. Multiple
+/// values are joined with commas. If the header
+/// does not exist, an error is returned.
+pub fn get_header_value(
+    req: &HttpRequest,
+    name: &str
+) -> Result<String, KleahErr> {
+    let values: Vec<&str> = req.headers()
+        .get_all(name)
+        .filter_map(|value| value.to_str().ok())
+        .collect::<Vec<&str>>();
+    if values.is_empty(){
+        Err::<String, KleahErr>(
+            KleahErr::with_type(
+                &format!("The \"{}\" header is missing.", name),
+                KleahErrType::Unauthorized
+            )
+        )
+    }
+    else {
+        Ok(values.join(", "))
+    }
+}
+
+/// A function that attempts to verify the
+/// signature of a received request with the
+/// public key of the given actor. A boolean 
+/// reflecting whether the signature is valid
+/// is returned. If a signed header is missing,
+/// an error is returned.
+pub fn verify_request_signature(
+    req: &HttpRequest,
+    parsed: &ParsedSignature,
+    actor: &KleahActor
+) -> Result<bool, KleahErr> {
+    let path: String = match req.uri().path_and_query(){
+        Some(path) => path.to_string(),
+        None => req.path().to_string()
+    };
+    let mut lines: Vec<String> = Vec::new();
+    for name in &parsed.headers {
+        if name == "(request-target)" {
+            lines.push(
+                format!(
+                    "(request-target): {} {}", 
+                    req.method().as_str().to_lowercase(), 
+                    path
+                )
+            );
+        }
+        else {
+            let value: String = match get_header_value(req, name){
+                Ok(value) => value,
+                Err(e) => return Err::<bool, KleahErr>(
+                    KleahErr::with_type(&e.to_string(), KleahErrType::Unauthorized)
+                )
+            };
+            lines.push(format!("{}: {}", name, value));
+        }
+    }
+    let signing_string: String = lines.join("\n");
+    Ok(
+        verify_string(
+            &actor.public_key, 
+            &signing_string, 
+            &parsed.signature
+        ).unwrap_or(false)
+    )
+}
+
+/// A function that attempts to verify a 
+/// signed request delivered to an inbox.
+/// The digest of the body, the age of the
+/// request and the signature are checked.
+/// The signing actor's public key is taken 
+/// from the cached record of the actor or 
+/// fetched from the actor's server. If the 
+/// signature does not match a cached key, the
+/// key is fetched again once in case it was 
+/// rotated. If the operation is successful, 
+/// the signing actor is returned. If the 
+/// operation fails, an error is returned.
+pub async fn verify_request(
+    req: &HttpRequest,
+    body: &[u8],
+    pool: &Pool<Postgres>
+) -> Result<KleahActor, KleahErr> {
+    let header: String = match get_header_value(req, "signature"){
+        Ok(header) => header,
+        Err(e) => return Err::<KleahActor, KleahErr>(e)
+    };
+    let parsed: ParsedSignature = match parse_signature_header(&header){
+        Ok(parsed) => parsed,
+        Err(e) => return Err::<KleahActor, KleahErr>(
+            KleahErr::with_type(&e.to_string(), KleahErrType::Unauthorized)
+        )
+    };
+    if parsed.algorithm != "rsa-sha256" && parsed.algorithm != "hs2019" {
+        return Err::<KleahActor, KleahErr>(
+            KleahErr::with_type(
+                "The signature algorithm is not supported.",
+                KleahErrType::Unauthorized
+            )
+        )
+    }
+    for required in ["(request-target)", "date", "digest"] {
+        if !parsed.headers.iter().any(|name| name == required) {
+            return Err::<KleahActor, KleahErr>(
+                KleahErr::with_type(
+                    &format!("The \"{}\" header is not signed.", required),
+                    KleahErrType::Unauthorized
+                )
+            )
+        }
+    }
+    let digest: String = match get_header_value(req, "digest"){
+        Ok(digest) => digest,
+        Err(e) => return Err::<KleahActor, KleahErr>(e)
+    };
+    if !check_digest(&digest, body) {
+        return Err::<KleahActor, KleahErr>(
+            KleahErr::with_type(
+                "The digest does not match the body.",
+                KleahErrType::Unauthorized
+            )
+        )
+    }
+    let date: String = match get_header_value(req, "date"){
+        Ok(date) => date,
+        Err(e) => return Err::<KleahActor, KleahErr>(e)
+    };
+    if !check_date(&date) {
+        return Err::<KleahActor, KleahErr>(
+            KleahErr::with_type(
+                "The request is too old or its date is invalid.",
+                KleahErrType::Unauthorized
+            )
+        )
+    }
+    let (actor, cached): (KleahActor, bool) = match get_actor_by_key_id(
+        &parsed.key_id, 
+        pool
+    ).await {
+        Ok(actor) => (actor, true),
+        Err(_e) => match refresh_remote_actor(&parsed.key_id, pool).await {
+            Ok(actor) => (actor, false),
+            Err(e) => return Err::<KleahActor, KleahErr>(
+                KleahErr::with_type(&e.to_string(), KleahErrType::Unauthorized)
+            )
+        }
+    };
+    let verified: bool = match verify_request_signature(req, &parsed, &actor){
+        Ok(verified) => verified,
+        Err(e) => return Err::<KleahActor, KleahErr>(e)
+    };
+    if verified {
+        return Ok(actor);
+    }
+    if cached {
+        let refreshed: KleahActor = match refresh_remote_actor(&parsed.key_id, pool).await {
+            Ok(refreshed) => refreshed,
+            Err(e) => return Err::<KleahActor, KleahErr>(
+                KleahErr::with_type(&e.to_string(), KleahErrType::Unauthorized)
+            )
+        };
+        let reverified: bool = match verify_request_signature(req, &parsed, &refreshed){
+            Ok(reverified) => reverified,
+            Err(e) => return Err::<KleahActor, KleahErr>(e)
+        };
+        if reverified {
+            return Ok(refreshed);
+        }
+    }
+    Err::<KleahActor, KleahErr>(
+        KleahErr::with_type(
+            "The signature could not be verified.",
+            KleahErrType::Unauthorized
+        )
+    )
+}
+
+/// Implementing the `FromRequest`
+/// trait for the `SignedActivity`
+/// structure so that service functions
+/// for inboxes only receive activities
+/// whose signatures have been verified.
+impl FromRequest for SignedActivity {
+    type Error = KleahErr;
+    type Future = Pin<Box<dyn Future<Output = Result<SignedActivity, KleahErr>>>>;
+
+    /// The function that implements
+    /// this `FromRequest` trait.
+    fn from_request(
+        req: &HttpRequest, 
+        payload: &mut Payload
+    ) -> Self::Future {
+        let req: HttpRequest = req.clone();
+        let body_future = Bytes::from_request(&req, payload);
+        Box::pin(
+            async move {
+                let data: Data<AppData> = match req.app_data::<Data<AppData>>(){
+                    Some(data) => data.clone(),
+                    None => return Err::<SignedActivity, KleahErr>(
+                        KleahErr::new("The app data could not be retrieved.")
+                    )
+                };
+                let body: Bytes = match body_future.await {
+                    Ok(body) => body,
+                    Err(e) => return Err::<SignedActivity, KleahErr>(
+                        KleahErr::with_type(&e.to_string(), KleahErrType::BadRequest)
+                    )
+                };
+                let actor: KleahActor = match verify_request(
+                    &req, 
+                    &body, 
+                    &data.pool
+                ).await {
+                    Ok(actor) => actor,
+                    Err(e) => return Err::<SignedActivity, KleahErr>(e)
+                };
+                let activity: Value = match from_slice::<Value>(&body){
+                    Ok(activity) => activity,
+                    Err(e) => return Err::<SignedActivity, KleahErr>(
+                        KleahErr::with_type(&e.to_string(), KleahErrType::BadRequest)
+                    )
+                };
+                Ok(
+                    SignedActivity{
+                        actor,
+                        activity
+                    }
+                )
+            }
+        )
+    }
+}
@@ -3,6 +3,16 @@ Kleah by Alyx Shang.
 Licensed under the FSL v1.
 */
 
+/// Importing the structure
+/// representing a pool of
+/// database connections.
+use sqlx::Pool;
+
+/// Importing the structure
+/// representing the UTC
+/// time zone.
+use chrono::Utc;
+
 /// Importing the function
 /// to read environment
 /// variables.
@@ -13,12 +23,31 @@ use std::env::var;
 /// App.
 use actix_web::App;
 
+/// Importing the structure
+/// to share mutable state with
+/// a stand-in server.
+use std::sync::Mutex;
+
+/// Importing this structure
+/// to store points in time.
+use chrono::DateTime;
+
+/// Importing the function
+/// to retrieve a poll
+/// to test it.
+use super::db::get_poll;
+
 /// Importing the structure
 /// to contain any custom
 /// data structures to keep
 /// track of app-wide data.
 use actix_web::web::Data;
 
+/// Importing the structure
+/// for catching and handling
+/// errors.
+use super::err::KleahErr;
+
 /// Importing the function
 /// for generating a string
 /// containing the time in
@@ -32,6 +61,87 @@ use super::utils::rfc2282;
 /// database.
 use super::units::AppData;
 
+/// Importing the structure
+/// to run a stand-in server
+/// for remote instances.
+use actix_web::HttpServer;
+
+/// Importing the structure
+/// representing the body of
+/// a received request.
+use actix_web::web::Bytes;
+
+/// Importing the structure to
+/// accept query strings in
+/// stand-in services.
+use actix_web::web::Query;
+
+/// Importing the structure
+/// representing a request
+/// received by a stand-in server.
+use actix_web::HttpRequest;
+
+/// Importing the function
+/// to save a new note
+/// to test it.
+use super::db::create_note;
+
+/// Importing the function
+/// to build the URL of a note
+/// to test it.
+use super::utils::note_url;
+
+/// Importing the function
+/// to save a poll
+/// to test it.
+use super::db::create_poll;
+
+/// Importing the function
+/// to check whether a user
+/// exists.
+use super::db::user_exists;
+
+/// Importing the structure
+/// to return responses from
+/// a stand-in server.
+use actix_web::HttpResponse;
+
+/// Importing the function
+/// to build the URL of an actor
+/// to test it.
+use super::utils::actor_url;
+
+/// Importing the structure that
+/// represents a connection to a
+/// PostgreSQL database.
+use sqlx::postgres::Postgres;
+
+/// Importing the function to lock
+/// an account to test it.
+use super::db::update_locked;
+
+/// Importing the function to create
+/// a follow relationship to test it.
+use super::db::create_follow;
+
+/// Importing the data structure
+/// modelling a Kleah user.
+use super::models::KleahUser;
+
+/// Importing the data structure
+/// modelling a note in
+/// the database.
+use super::models::KleahNote;
+
+/// Importing the API service function
+/// for reading polls
+/// to test it.
+use super::api::poll_service;
+
+/// Importing the structure
+/// modelling a poll.
+use super::models::KleahPoll;
+
 /// Importing the function to
 /// check the validity of an
 /// email address string to test
@@ -43,12 +153,49 @@ use super::utils::check_email;
 /// string to test it.
 use super::utils::hash_string;
 
-
 /// Importing the function to retrieve
 /// user information from the database
 /// to test it.
 use super::db::get_user_by_id;
 
+/// Importing the data structure
+/// modelling data about an ActivityPub
+/// actor to set up tests.
+use super::models::KleahActor;
+
+/// Importing the API service function
+/// for serving actor documents to
+/// test it.
+use super::api::actor_service;
+
+/// Importing the API service function
+/// for personal inboxes to test it.
+use super::api::inbox_service;
+
+/// Importing the API service function
+/// for serving liked collections
+/// to test it.
+use super::api::liked_service;
+
+/// Importing the function
+/// to calculate the delay
+/// of retries to test it.
+use super::queue::retry_delay;
+
+/// Importing the function
+/// to generate IDs.
+use super::utils::generate_id;
+
+/// Importing the function
+/// to retrieve a note by
+/// its ID to test it.
+use super::db::get_note_by_id;
+
+/// Importing the function
+/// to retrieve the prior
+/// revisions of a note.
+use super::db::get_note_edits;
+
 /// Importing the function to
 /// create a new user to test it.
 use super::db::create_new_user;
@@ -58,10 +205,54 @@ use super::db::create_new_user;
 /// to test it.
 use super::db::get_actor_by_id;
 
+/// Importing the API service function
+/// for serving outboxes
+/// to test it.
+use super::api::outbox_service;
+
+/// Importing the function
+/// to publish notes
+/// to test it.
+use super::notes::publish_note;
+
+/// Importing the API service function
+/// for following actors
+/// to test it.
+use super::api::follow_service;
+
+/// Importing the API service function
+/// for reporting actors
+/// to test it.
+use super::api::report_service;
+
+/// Importing the function
+/// to read the API token
+/// of a request.
+use super::utils::bearer_token;
+
 /// Importing the function to
 /// create a new actor to test it.
 use super::db::create_new_actor;
 
+/// Importing the function to retrieve
+/// a follow relationship to test it.
+use super::db::get_follow_by_id;
+
+/// Importing the function
+/// to create API tokens
+/// to test it.
+use super::db::create_api_token;
+
+/// Importing the function
+/// to retrieve the options
+/// of a poll to test it.
+use super::db::get_poll_options;
+
+/// Importing the function
+/// to retrieve an invite
+/// code.
+use super::db::get_code_by_code;
+
 /// Importing the function to retrieve
 /// instance information from the database
 /// to test it.
@@ -79,6 +270,106 @@ use super::utils::check_username;
 /// it.
 use super::utils::check_password;
 
+/// Importing the extractor for
+/// verified activities to test it.
+use super::units::SignedActivity;
+
+/// Importing the structure
+/// to stop a stand-in server.
+use actix_web::dev::ServerHandle;
+
+/// Importing the function
+/// to retrieve users by
+/// their API tokens to test it.
+use super::db::get_user_by_token;
+
+/// Importing the API service function
+/// for serving NodeInfo documents
+/// to test it.
+use super::api::nodeinfo_service;
+
+/// Importing the API service function
+/// for reading notes
+/// to test it.
+use super::api::get_note_service;
+
+/// Importing the function
+/// to retrieve notes from
+/// other servers to test it.
+use super::db::get_note_by_ap_id;
+
+/// Importing the structure
+/// modelling a prior revision
+/// of a note.
+use super::models::KleahNoteEdit;
+
+/// Importing the structure
+/// modelling the payload
+/// for a poll.
+use super::payloads::PollPayload;
+
+/// Importing the API service function
+/// for WebFinger lookups to test it.
+use super::api::webfinger_service;
+
+/// Importing the function to retrieve
+/// a follow relationship between two
+/// actors to test it.
+use super::db::get_follow_between;
+
+/// Importing the API service function
+/// for serving followers collections
+/// to test it.
+use super::api::followers_service;
+
+/// Importing the API service function
+/// for serving following collections
+/// to test it.
+use super::api::following_service;
+
+/// Importing the API service function
+/// for serving notes to other
+/// servers to test it.
+use super::api::apub_note_service;
+
+/// Importing the function
+/// to record the recipients
+/// of a note to test it.
+use super::db::add_note_recipient;
+
+/// Importing the function
+/// to address notes
+/// to test it.
+use super::notes::note_addressing;
+
+/// Importing the service function
+/// to like notes to test it.
+use super::api::like_note_service;
+
+/// Importing the function
+/// to check whether an actor
+/// may read a note to test it.
+use super::db::note_is_visible_to;
+
+/// Importing the API service function
+/// for editing notes to test it.
+use super::api::edit_note_service;
+
+/// Importing the API service function
+/// for voting in polls
+/// to test it.
+use super::api::vote_poll_service;
+
+/// Importing the function
+/// to save an invite
+/// code.
+use super::db::create_invite_code;
+
+/// Importing the function
+/// to change whether an
+/// instance uses invites.
+use super::db::edit_invite_system;
+
 /// Importing the function to generate
 /// an RSA keypari to test it.
 use super::utils::generate_keypair;
@@ -88,6 +379,50 @@ use super::utils::generate_keypair;
 /// test it.
 use super::payloads::KleahUserType;
 
+/// Importing the function to
+/// sign strings to test it.
+use super::signatures::sign_string;
+
+/// Importing the function
+/// to queue activities for
+/// delivery to test it.
+use super::queue::deliver_activity;
+
+/// Importing the function
+/// to cache actors from
+/// other servers.
+use super::db::upsert_remote_actor;
+
+/// Importing the function
+/// to read notes a user
+/// may see to test it.
+use super::notes::get_visible_note;
+
+/// Importing the service function
+/// to boost notes to test it.
+use super::api::boost_note_service;
+
+/// Importing the function
+/// to find mentions in a
+/// note to test it.
+use super::mentions::find_mentions;
+
+/// Importing the function
+/// to retrieve the actors a
+/// note is addressed to
+/// to test it.
+use super::db::get_note_recipients;
+
+/// Importing the function
+/// to find the hashtags of
+/// a note to test it.
+use super::hashtags::note_hashtags;
+
+/// Importing the API service function
+/// for reading reports
+/// to test it.
+use super::api::get_report_service;
+
 /// Importing the function to create
 /// a connection to the database to
 /// test it.
@@ -102,15 +437,252 @@ use super::api::create_user_service;
 /// record to test it.
 use super::db::create_instance_info;
 
+/// Importing the function to
+/// sign requests to test it.
+use super::signatures::sign_request;
+
+/// Importing the structure
+/// modelling the query string
+/// of WebFinger requests.
+use super::payloads::WebFingerQuery;
+
+/// Importing the API service function
+/// for editing display names
+/// to test it.
+use super::api::update_name_service;
+
+/// Importing the API service function
+/// for publishing notes
+/// to test it.
+use super::api::create_note_service;
+
+/// Importing the API service function
+/// for deleting notes
+/// to test it.
+use super::api::delete_note_service;
+
+/// Importing the function
+/// to edit who may read
+/// the public timelines.
+use super::db::edit_timeline_access;
+
+/// Importing the service function
+/// to take back likes
+/// to test it.
+use super::api::unlike_note_service;
+
+/// Importing the function
+/// to fetch notes from
+/// other servers to test it.
+use super::notes::fetch_remote_note;
+
+/// Importing the function to
+/// generate the value of a
+/// "Digest" header to test it.
+use super::signatures::create_digest;
+
+/// Importing the function to
+/// verify signatures to test it.
+use super::signatures::verify_string;
+
+/// Importing the API service function
+/// for the shared inbox to test it.
+use super::api::shared_inbox_service;
+
+/// Importing the function
+/// to send queued deliveries
+/// to test it.
+use super::queue::process_deliveries;
+
+/// Importing the service function
+/// to take back boosts
+/// to test it.
+use super::api::unboost_note_service;
+
+/// Importing the service function
+/// to read the thread around
+/// a note to test it.
+use super::api::note_context_service;
+
+/// Importing the API service function
+/// for reading the revisions of
+/// notes to test it.
+use super::api::note_history_service;
+
+/// Importing the structure
+/// modelling the payload for
+/// editing a note.
+use super::payloads::NoteEditPayload;
+
+/// Importing the delay
+/// before the first retry
+/// of a failed delivery.
+use super::queue::DELIVERY_BASE_DELAY;
+
+/// Importing the function
+/// to build activities
+/// for testing deliveries.
+use super::federation::build_activity;
+
+/// Importing the function
+/// to check whether a cached
+/// actor is fresh to test it.
+use super::federation::actor_is_fresh;
+
+/// Importing the service function
+/// to read the home timeline
+/// to test it.
+use super::api::home_timeline_service;
+
+/// Importing the API service function
+/// for reading notifications
+/// to test it.
+use super::api::notifications_service;
+
 /// Importing the data structure to
 /// supply data for creating a new
 /// Kleah user.
 use super::payloads::UserCreatePayload;
 
+/// Importing the data structure
+/// modelling data about the current
+/// Kleah instance to set up tests.
+use super::models::InstanceInformation;
+
+/// Importing the function to retrieve
+/// the key a user signs requests with
+/// to test it.
+use super::signatures::get_signing_key;
+
+/// Importing the API service function
+/// for serving NodeInfo links
+/// to test it.
+use super::api::nodeinfo_links_service;
+
+/// Importing the service function
+/// to read the local timeline
+/// to test it.
+use super::api::local_timeline_service;
+
+/// Importing the structure
+/// modelling the payload for
+/// publishing a note.
+use super::payloads::NoteCreatePayload;
+
+/// Importing the function to send
+/// signed requests to test it.
+use super::signatures::send_signed_post;
+
+/// Importing the number of
+/// attempts after which a
+/// delivery is given up.
+use super::queue::MAX_DELIVERY_ATTEMPTS;
+
+/// Importing the function
+/// to send new notes to
+/// their audience to test it.
+use super::notes::deliver_note_creation;
+
+/// Importing the API service function
+/// for editing whether content
+/// warnings are expanded to test it.
+use super::api::update_spoilers_service;
+
+/// Importing the function
+/// to close polls that have
+/// ended to test it.
+use super::polls::process_expired_polls;
+
 /// Importing this entity to set the content-type
 /// header for test requests.
 use actix_web::http::header::ContentType;
 
+/// Importing the service function
+/// to read the timeline of
+/// a hashtag to test it.
+use super::api::hashtag_timeline_service;
+
+/// Importing the structure
+/// modelling the payload for
+/// editing whether notes with a
+/// content warning are expanded.
+use super::payloads::EditSpoilersPayload;
+
+/// Importing the service function
+/// to serve the replies to
+/// a note to test it.
+use super::api::apub_note_replies_service;
+
+/// Importing the function to
+/// retrieve the queued deliveries
+/// of an activity to test it.
+use super::db::get_deliveries_for_activity;
+
+/// Importing the API service function
+/// for editing descriptions
+/// to test it.
+use super::api::update_description_service;
+
+/// Importing the API service function
+/// for serving the activities
+/// publishing notes to test it.
+use super::api::apub_note_activity_service;
+
+/// Importing the service function
+/// to read the federated timeline
+/// to test it.
+use super::api::federated_timeline_service;
+
+/// Importing the service function
+/// to serve the collection of
+/// a hashtag to test it.
+use super::api::hashtag_collection_service;
+
+/// Importing the API service function
+/// for marking notifications as read
+/// to test it.
+use super::api::read_notifications_service;
+
+/// Importing the API service function
+/// for reading muted notifications
+/// to test it.
+use super::api::notification_mutes_service;
+
+/// Importing the API service function
+/// for muting notifications
+/// to test it.
+use super::api::mute_notifications_service;
+
+/// Importing the function to build
+/// the string that is signed for a
+/// request to test it.
+use super::signatures::build_signing_string;
+
+/// Importing the function
+/// to resolve account handles
+/// to actors to test it.
+use super::federation::resolve_remote_actor;
+
+/// Importing the structure
+/// modelling the payload for
+/// interacting with a note.
+use super::payloads::NoteInteractionPayload;
+
+/// Importing the service function
+/// to edit who may read
+/// the public timelines to test it.
+use super::api::edit_timeline_access_service;
+
+/// Importing the API service function
+/// for dismissing notifications
+/// to test it.
+use super::api::dismiss_notification_service;
+
+/// Importing the structure
+/// modelling the payload for
+/// editing timeline access.
+use super::payloads::EditTimelineAccessPayload;
+
 /// The function to test functions
 /// from the module containing utlity
 /// functions.
@@ -128,6 +700,18 @@ pub async fn test_utility_functions(){
     let connection = create_connection(&db_url).await;
     let time_str: String = rfc2282();
     let time_hash: String = hash_string(&time_str);
+    let token_request = |header: Option<&str>| {
+        let mut req = actix_web::test::TestRequest::default();
+        if let Some(header) = header {
+            req = req.insert_header(("Authorization", header));
+        }
+        req.to_http_request()
+    };
+    let bearer_t: Option<String> = bearer_token(&token_request(Some("Bearer ABC123")));
+    let bearer_lower: Option<String> = bearer_token(&token_request(Some("bearer ABC123")));
+    let bearer_basic: Option<String> = bearer_token(&token_request(Some("Basic ABC123")));
+    let bearer_empty: Option<String> = bearer_token(&token_request(Some("Bearer ")));
+    let bearer_none: Option<String> = bearer_token(&token_request(None));
     assert_eq!(email_t, true);
     assert_eq!(email_f, false);
     assert_eq!(username_t, true);
@@ -138,6 +722,11 @@ pub async fn test_utility_functions(){
     assert_eq!(password_f, false);
     assert_eq!(gen_kp.is_ok(), true);
     assert_eq!(connection.is_ok(), true);
+    assert_eq!(bearer_t, Some("ABC123".to_string()));
+    assert_eq!(bearer_lower, Some("ABC123".to_string()));
+    assert_eq!(bearer_basic, None);
+    assert_eq!(bearer_empty, None);
+    assert_eq!(bearer_none, None);
 }
 
 /// The function to test functions
@@ -232,3 +821,3834 @@ pub async fn test_api_services(){
     assert_eq!(cu_tr_resp.status().is_success(), true);
     
 }
+
+/// A function to retrieve the record
+/// of the current instance for tests
+/// or to create it if it does not
+/// exist yet.
+pub async fn setup_instance(
+    pool: &Pool<Postgres>
+) -> InstanceInformation {
+    match get_instance_info(pool).await {
+        Ok(instance) => instance,
+        Err(_e) => create_instance_info("example.com", &false, pool).await
+            .expect("Could not create instance information.")
+    }
+}
+
+/// A function to build the header
+/// carrying the given API token as a
+/// bearer token.
+pub fn bearer(
+    token: &str
+) -> (&'static str, String) {
+    ("Authorization", format!("Bearer {}", token))
+}
+
+/// A function to retrieve the actor
+/// of a local test user or to create
+/// the user and the actor if they do
+/// not exist yet.
+pub async fn setup_actor(
+    username: &str,
+    pool: &Pool<Postgres>
+) -> KleahActor {
+    let instance: InstanceInformation = setup_instance(pool).await;
+    if let Ok(actor) = get_actor_by_id(username, &instance.host, pool).await {
+        return actor;
+    }
+    let _user = create_new_user(
+        username, 
+        "123456789", 
+        username, 
+        "me@example.com", 
+        "A test user.",
+        &false,
+        pool
+    ).await
+        .expect("Could not create user.");
+    create_new_actor(
+        username, 
+        &instance.host, 
+        username, 
+        "Person", 
+        "A test user.", 
+        &format!("/apub/{}/liked", username), 
+        &format!("/apub/{}/inbox", username), 
+        &format!("/apub/{}/outbox", username), 
+        &format!("/apub/{}/following", username), 
+        &format!("/apub/{}/followers", username), 
+        &format!("/apub/{}/pubkey", username), 
+        pool
+    ).await
+        .expect("Could not create actor.")
+}
+
+/// The function to test the service
+/// function for WebFinger lookups.
+#[actix_web::test]
+pub async fn test_webfinger_service(){
+    use actix_web::test;
+    let db_url: String = var("KLEAH_DB_URL")
+        .expect("Failed to read environment variable.");
+    let connection = create_connection(&db_url).await
+        .expect("Could not create connection.");
+    let instance: InstanceInformation = setup_instance(&connection).await;
+    let _actor: KleahActor = setup_actor("fingered", &connection).await;
+    let app = test::init_service(
+        App::new()
+            .app_data(Data::new(AppData{ pool: connection }))
+            .service(webfinger_service)
+    ).await;
+    let found_req = test::TestRequest::get()
+        .uri(&format!("/.well-known/webfinger?resource=acct:fingered@{}", instance.host))
+        .to_request();
+    let found: serde_json::Value = test::call_and_read_body_json(&app, found_req).await;
+    let missing_req = test::TestRequest::get()
+        .uri(&format!("/.well-known/webfinger?resource=acct:nobody@{}", instance.host))
+        .to_request();
+    let missing_resp = test::call_service(&app, missing_req).await;
+    let foreign_req = test::TestRequest::get()
+        .uri("/.well-known/webfinger?resource=acct:fingered@elsewhere.org")
+        .to_request();
+    let foreign_resp = test::call_service(&app, foreign_req).await;
+    assert_eq!(found["subject"], format!("acct:fingered@{}", instance.host));
+    assert_eq!(found["links"][0]["rel"], "self");
+    assert_eq!(found["links"][0]["href"], format!("https://{}/apub/fingered", instance.host));
+    assert_eq!(missing_resp.status().as_u16(), 404);
+    assert_eq!(foreign_resp.status().as_u16(), 404);
+}
+
+/// The function to test the service
+/// function for serving ActivityPub
+/// actor documents.
+#[actix_web::test]
+pub async fn test_serving_actor_documents(){
+    use actix_web::test;
+    let db_url: String = var("KLEAH_DB_URL")
+        .expect("Failed to read environment variable.");
+    let connection = create_connection(&db_url).await
+        .expect("Could not create connection.");
+    let instance: InstanceInformation = setup_instance(&connection).await;
+    let _actor: KleahActor = setup_actor("documented", &connection).await;
+    let app = test::init_service(
+        App::new()
+            .app_data(Data::new(AppData{ pool: connection }))
+            .service(actor_service)
+    ).await;
+    let found_req = test::TestRequest::get()
+        .uri("/apub/documented")
+        .to_request();
+    let found_resp = test::call_service(&app, found_req).await;
+    let content_type: String = found_resp.headers()
+        .get("content-type")
+        .expect("No content type was set.")
+        .to_str()
+        .expect("Could not read content type.")
+        .to_string();
+    let found: serde_json::Value = test::read_body_json(found_resp).await;
+    let missing_req = test::TestRequest::get()
+        .uri("/apub/nobody")
+        .to_request();
+    let missing_resp = test::call_service(&app, missing_req).await;
+    let actor_url: String = format!("https://{}/apub/documented", instance.host);
+    assert_eq!(content_type, "application/activity+json");
+    assert_eq!(found["id"], actor_url);
+    assert_eq!(found["type"], "Person");
+    assert_eq!(found["inbox"], format!("{}/inbox", actor_url));
+    assert_eq!(found["publicKey"]["owner"], actor_url);
+    assert!(found["publicKey"]["publicKeyPem"].as_str().unwrap_or("").contains("PUBLIC KEY"));
+    assert_eq!(missing_resp.status().as_u16(), 404);
+}
+
+/// A service function that stands in
+/// for the inbox of a remote server.
+/// The digest and the signature of 
+/// every received request are checked 
+/// against the public key of the local
+/// user named in the "keyId" of the
+/// signature.
+pub async fn stand_in_inbox(
+    req: HttpRequest,
+    body: Bytes,
+    data: Data<AppData>
+) -> HttpResponse {
+    let header = |name: &str| -> String {
+        req.headers()
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or("")
+            .to_string()
+    };
+    let mut key_id: String = String::new();
+    let mut signed_headers: String = String::new();
+    let mut signature: String = String::new();
+    for part in header("signature").split(",") {
+        if let Some((name, value)) = part.split_once("=") {
+            let value: String = value.trim_matches('"').to_string();
+            match name {
+                "keyId" => key_id = value,
+                "headers" => signed_headers = value,
+                "signature" => signature = value,
+                _ => {}
+            }
+        }
+    }
+    if header("digest") != create_digest(&body) {
+        return HttpResponse::Unauthorized().finish();
+    }
+    let headers: Vec<(String, String)> = signed_headers
+        .split(" ")
+        .filter(|name| *name != "(request-target)")
+        .map(|name| (name.to_string(), header(name)))
+        .collect::<Vec<(String, String)>>();
+    let signing_string: String = build_signing_string(
+        req.method().as_str(), 
+        req.path(), 
+        &headers
+    );
+    let username: String = key_id
+        .split("/")
+        .nth(4)
+        .unwrap_or("")
+        .to_string();
+    let user = match get_user_by_id(&username, &data.pool).await {
+        Ok(user) => user,
+        Err(_e) => return HttpResponse::Unauthorized().finish()
+    };
+    match verify_string(&user.public_key, &signing_string, &signature) {
+        Ok(true) => HttpResponse::Accepted().finish(),
+        _ => HttpResponse::Unauthorized().finish()
+    }
+}
+
+/// The function to test the functions
+/// for signing outgoing requests against
+/// a local stand-in for a remote server.
+#[actix_web::test]
+pub async fn test_signed_requests(){
+    let db_url: String = var("KLEAH_DB_URL")
+        .expect("Failed to read environment variable.");
+    let connection = create_connection(&db_url).await
+        .expect("Could not create connection.");
+    let _actor: KleahActor = setup_actor("signer", &connection).await;
+    let server_pool = connection.clone();
+    let server = HttpServer::new(
+        move || {
+            App::new()
+                .app_data(Data::new(AppData{ pool: server_pool.clone() }))
+                .route("/inbox", actix_web::web::post().to(stand_in_inbox))
+        }
+    )
+        .workers(1)
+        .bind(("127.0.0.1", 0))
+        .expect("Could not bind stand-in server.");
+    let inbox_url: String = format!("http://{}/inbox", server.addrs()[0]);
+    let running = server.run();
+    let handle = running.handle();
+    actix_web::rt::spawn(running);
+    let body: &str = "{\"type\":\"Follow\"}";
+    let sent = send_signed_post("signer", &inbox_url, body, &connection).await;
+    let (private_key, key_id) = get_signing_key("signer", &connection).await
+        .expect("Could not fetch signing key.");
+    let signed = sign_request(&private_key, &key_id, "post", &inbox_url, Some(body.as_bytes()))
+        .expect("Could not sign request.");
+    let tampered = reqwest::Client::new()
+        .post(&inbox_url)
+        .header("Date", signed.date)
+        .header("Digest", signed.digest.unwrap_or_default())
+        .header("Signature", signed.signature)
+        .body("{\"type\":\"Delete\"}")
+        .send()
+        .await
+        .expect("Could not send tampered request.");
+    handle.stop(true).await;
+    assert!(key_id.ends_with("/apub/signer/pubkey"));
+    assert!(sent.is_ok());
+    assert_eq!(tampered.status().as_u16(), 401);
+}
+
+/// A service function that stands in
+/// for the inbox of a user on a remote 
+/// server. The bodies of all received 
+/// requests are recorded.
+pub async fn stand_in_remote_inbox(
+    body: Bytes,
+    received: Data<Mutex<Vec<String>>>
+) -> HttpResponse {
+    received
+        .lock()
+        .expect("Could not record the request.")
+        .push(String::from_utf8_lossy(&body).to_string());
+    HttpResponse::Accepted().finish()
+}
+
+/// A function to start a stand-in for
+/// a remote server serving the actor
+/// "remoteuser" with the given public
+/// key and recording all deliveries to
+/// the actor's inboxes. The host of the
+/// server and a handle to stop it are
+/// returned.
+pub fn start_remote_server(
+    public_key: Data<Mutex<String>>,
+    received: Data<Mutex<Vec<String>>>
+) -> (String, ServerHandle) {
+    let server = HttpServer::new(
+        move || {
+            App::new()
+                .app_data(public_key.clone())
+                .app_data(received.clone())
+                .route("/.well-known/webfinger", actix_web::web::get().to(stand_in_webfinger))
+                .route("/users/remoteuser", actix_web::web::get().to(stand_in_actor))
+                .route("/users/remoteuser/statuses/1", actix_web::web::get().to(stand_in_note))
+                .route("/users/remoteuser/statuses/11", actix_web::web::get().to(stand_in_question))
+                .route("/users/remoteuser/inbox", actix_web::web::post().to(stand_in_remote_inbox))
+                .route("/inbox", actix_web::web::post().to(stand_in_remote_inbox))
+        }
+    )
+        .workers(1)
+        .bind(("127.0.0.1", 0))
+        .expect("Could not bind stand-in server.");
+    let remote_host: String = server.addrs()[0].to_string();
+    let running = server.run();
+    let handle: ServerHandle = running.handle();
+    actix_web::rt::spawn(running);
+    (remote_host, handle)
+}
+
+/// A function to build a test request
+/// delivering the given body to the given
+/// path of the local server, signed with 
+/// the given key.
+pub fn build_signed_request(
+    path: &str,
+    private_key: &str,
+    key_id: &str,
+    body: &str
+) -> actix_web::test::TestRequest {
+    let signed = sign_request(
+        private_key, 
+        key_id, 
+        "post", 
+        &format!("https://example.com{}", path), 
+        Some(body.as_bytes())
+    ).expect("Could not sign request.");
+    actix_web::test::TestRequest::post()
+        .uri(path)
+        .insert_header(("Host", signed.host))
+        .insert_header(("Date", signed.date))
+        .insert_header(("Digest", signed.digest.unwrap_or_default()))
+        .insert_header(("Signature", signed.signature))
+        .set_payload(body.to_string())
+}
+
+/// A service function that stands in
+/// for the actor document of a user on
+/// a remote server. The public key in 
+/// the document can be swapped out to
+/// simulate a key rotation.
+pub async fn stand_in_actor(
+    req: HttpRequest,
+    public_key: Data<Mutex<String>>
+) -> HttpResponse {
+    let actor_url: String = format!(
+        "http://{}/users/remoteuser", 
+        req.connection_info().host()
+    );
+    let public_key_pem: String = public_key
+        .lock()
+        .expect("Could not read the public key.")
+        .clone();
+    HttpResponse::Ok()
+        .content_type("application/activity+json")
+        .json(
+            serde_json::json!({
+                "@context": ["https://www.w3.org/ns/activitystreams"],
+                "id": actor_url,
+                "type": "Person",
+                "preferredUsername": "remoteuser",
+                "name": "Remote User",
+                "inbox": format!("{}/inbox", actor_url),
+                "outbox": format!("{}/outbox", actor_url),
+                "followers": format!("{}/followers", actor_url),
+                "endpoints": { "sharedInbox": format!("http://{}/inbox", req.connection_info().host()) },
+                "publicKey": {
+                    "id": format!("{}#main-key", actor_url),
+                    "owner": actor_url,
+                    "publicKeyPem": public_key_pem
+                }
+            })
+        )
+}
+
+/// A service function standing in for
+/// a public note of the actor on another
+/// server.
+pub async fn stand_in_note(
+    req: HttpRequest
+) -> HttpResponse {
+    let actor_url: String = format!(
+        "http://{}/users/remoteuser", 
+        req.connection_info().host()
+    );
+    HttpResponse::Ok()
+        .content_type("application/activity+json")
+        .json(
+            serde_json::json!({
+                "@context": "https://www.w3.org/ns/activitystreams",
+                "id": format!("{}/statuses/1", actor_url),
+                "type": "Note",
+                "attributedTo": actor_url,
+                "content": "<p>Boost me.</p>",
+                "published": "2024-01-01T00:00:00Z",
+                "to": ["https://www.w3.org/ns/activitystreams#Public"],
+                "cc": [format!("{}/followers", actor_url)]
+            })
+        )
+}
+
+/// A service function standing in for
+/// a public poll of the actor on another
+/// server.
+pub async fn stand_in_question(
+    req: HttpRequest
+) -> HttpResponse {
+    let actor_url: String = format!(
+        "http://{}/users/remoteuser", 
+        req.connection_info().host()
+    );
+    HttpResponse::Ok()
+        .content_type("application/activity+json")
+        .json(
+            serde_json::json!({
+                "@context": "https://www.w3.org/ns/activitystreams",
+                "id": format!("{}/statuses/11", actor_url),
+                "type": "Question",
+                "attributedTo": actor_url,
+                "content": "<p>Rain or shine?</p>",
+                "published": "2024-01-01T00:00:00Z",
+                "endTime": "2099-01-01T00:00:00Z",
+                "votersCount": 2,
+                "oneOf": [
+                    { "type": "Note", "name": "Rain", "replies": { "type": "Collection", "totalItems": 0 } },
+                    { "type": "Note", "name": "Shine", "replies": { "type": "Collection", "totalItems": 2 } }
+                ],
+                "to": ["https://www.w3.org/ns/activitystreams#Public"],
+                "cc": [format!("{}/followers", actor_url)]
+            })
+        )
+}
+
+/// A service function standing in for
+/// an inbox that only accepts requests
+/// whose signatures have been verified.
+pub async fn verified_inbox(
+    signed: SignedActivity
+) -> HttpResponse {
+    HttpResponse::Accepted().body(signed.actor.username)
+}
+
+/// The function to test the extractor
+/// for verifying the signatures of requests
+/// delivered to inboxes.
+#[actix_web::test]
+pub async fn test_verified_inbox_requests(){
+    use actix_web::test;
+    let db_url: String = var("KLEAH_DB_URL")
+        .expect("Failed to read environment variable.");
+    let connection = create_connection(&db_url).await
+        .expect("Could not create connection.");
+    let first_keys = generate_keypair().expect("Could not generate keys.");
+    let second_keys = generate_keypair().expect("Could not generate keys.");
+    let served_key: Data<Mutex<String>> = Data::new(Mutex::new(first_keys.public_key.clone()));
+    let received: Data<Mutex<Vec<String>>> = Data::new(Mutex::new(Vec::new()));
+    let (remote_host, handle) = start_remote_server(served_key.clone(), received);
+    let key_id: String = format!("http://{}/users/remoteuser#main-key", remote_host);
+    let app = test::init_service(
+        App::new()
+            .app_data(Data::new(AppData{ pool: connection.clone() }))
+            .route("/inbox", actix_web::web::post().to(verified_inbox))
+    ).await;
+    let body: &str = "{\"type\":\"Follow\"}";
+    let signed_req = |private_key: &str, body_sent: &str| {
+        let signed = sign_request(
+            private_key, 
+            &key_id, 
+            "post", 
+            "https://example.com/inbox", 
+            Some(body.as_bytes())
+        ).expect("Could not sign request.");
+        test::TestRequest::post()
+            .uri("/inbox")
+            .insert_header(("Host", signed.host))
+            .insert_header(("Date", signed.date))
+            .insert_header(("Digest", signed.digest.unwrap_or_default()))
+            .insert_header(("Signature", signed.signature))
+            .set_payload(body_sent.to_string())
+            .to_request()
+    };
+    let valid_resp = test::call_service(&app, signed_req(&first_keys.private_key, body)).await;
+    let cached = get_actor_by_id("remoteuser", &remote_host, &connection).await
+        .expect("Could not fetch cached actor.");
+    let tampered_resp = test::call_service(
+        &app, 
+        signed_req(&first_keys.private_key, "{\"type\":\"Delete\"}")
+    ).await;
+    let stale_date: &str = "Mon, 01 Jan 2001 00:00:00 GMT";
+    let digest: String = create_digest(body.as_bytes());
+    let stale_signature: String = sign_string(
+        &first_keys.private_key,
+        &build_signing_string(
+            "post",
+            "/inbox",
+            &[
+                ("host".to_string(), "example.com".to_string()),
+                ("date".to_string(), stale_date.to_string()),
+                ("digest".to_string(), digest.clone())
+            ]
+        )
+    ).expect("Could not sign string.");
+    let stale_req = test::TestRequest::post()
+        .uri("/inbox")
+        .insert_header(("Host", "example.com"))
+        .insert_header(("Date", stale_date))
+        .insert_header(("Digest", digest))
+        .insert_header((
+            "Signature", 
+            format!(
+                "keyId=\"{}\",algorithm=\"rsa-sha256\",headers=\"(request-target) host date digest\",signature=\"{}\"",
+                key_id,
+                stale_signature
+            )
+        ))
+        .set_payload(body.to_string())
+        .to_request();
+    let stale_resp = test::call_service(&app, stale_req).await;
+    *served_key.lock().expect("Could not swap the public key.") = second_keys.public_key.clone();
+    let rotated_resp = test::call_service(&app, signed_req(&second_keys.private_key, body)).await;
+    let refreshed = get_actor_by_id("remoteuser", &remote_host, &connection).await
+        .expect("Could not fetch cached actor.");
+    let unsigned_req = test::TestRequest::post()
+        .uri("/inbox")
+        .set_payload(body.to_string())
+        .to_request();
+    let unsigned_resp = test::call_service(&app, unsigned_req).await;
+    handle.stop(true).await;
+    assert_eq!(valid_resp.status().as_u16(), 202);
+    assert_eq!(cached.key_id, Some(key_id.clone()));
+    assert_eq!(cached.public_key, first_keys.public_key);
+    assert_eq!(cached.shared_inbox, Some(format!("http://{}/inbox", remote_host)));
+    assert_eq!(tampered_resp.status().as_u16(), 401);
+    assert_eq!(stale_resp.status().as_u16(), 401);
+    assert_eq!(rotated_resp.status().as_u16(), 202);
+    assert_eq!(refreshed.public_key, second_keys.public_key);
+    assert_eq!(unsigned_resp.status().as_u16(), 401);
+}
+
+/// A function to wait until a stand-in
+/// server has received at least the given
+/// number of requests or until five seconds
+/// have passed. The received bodies are 
+/// returned.
+pub async fn wait_for_deliveries(
+    received: &Data<Mutex<Vec<String>>>,
+    count: usize
+) -> Vec<String> {
+    for _attempt in 0..50 {
+        let bodies: Vec<String> = received
+            .lock()
+            .expect("Could not read the received requests.")
+            .clone();
+        if bodies.len() >= count {
+            return bodies;
+        }
+        actix_web::rt::time::sleep(std::time::Duration::from_millis(100)).await;
+    }
+    received
+        .lock()
+        .expect("Could not read the received requests.")
+        .clone()
+}
+
+/// The function to test the service
+/// functions for the personal and shared
+/// inboxes with follow-related activities.
+#[actix_web::test]
+pub async fn test_inbox_follow_activities(){
+    use actix_web::test;
+    let db_url: String = var("KLEAH_DB_URL")
+        .expect("Failed to read environment variable.");
+    let connection = create_connection(&db_url).await
+        .expect("Could not create connection.");
+    let instance: InstanceInformation = setup_instance(&connection).await;
+    let popular: KleahActor = setup_actor("popular", &connection).await;
+    let private: KleahActor = setup_actor("private", &connection).await;
+    update_locked("private", &true, &connection).await
+        .expect("Could not lock account.");
+    let keys = generate_keypair().expect("Could not generate keys.");
+    let received: Data<Mutex<Vec<String>>> = Data::new(Mutex::new(Vec::new()));
+    let (remote_host, handle) = start_remote_server(
+        Data::new(Mutex::new(keys.public_key.clone())), 
+        received.clone()
+    );
+    let remote_url: String = format!("http://{}/users/remoteuser", remote_host);
+    let key_id: String = format!("{}#main-key", remote_url);
+    let popular_url: String = format!("https://{}/apub/popular", instance.host);
+    let private_url: String = format!("https://{}/apub/private", instance.host);
+    let app = test::init_service(
+        App::new()
+            .app_data(Data::new(AppData{ pool: connection.clone() }))
+            .service(inbox_service)
+            .service(shared_inbox_service)
+    ).await;
+    let follow: String = serde_json::json!({
+        "id": format!("{}/follows/1", remote_url),
+        "type": "Follow",
+        "actor": remote_url,
+        "object": popular_url
+    }).to_string();
+    let follow_resp = test::call_service(
+        &app, 
+        build_signed_request("/apub/popular/inbox", &keys.private_key, &key_id, &follow).to_request()
+    ).await;
+    let remote: KleahActor = get_actor_by_id("remoteuser", &remote_host, &connection).await
+        .expect("Could not fetch remote actor.");
+    let accepted = get_follow_between(&remote, &popular, &connection).await
+        .expect("Could not fetch follow.");
+    process_deliveries(&connection).await
+        .expect("Could not send deliveries.");
+    let deliveries: Vec<String> = wait_for_deliveries(&received, 1).await;
+    let locked_follow: String = serde_json::json!({
+        "id": format!("{}/follows/2", remote_url),
+        "type": "Follow",
+        "actor": remote_url,
+        "object": private_url
+    }).to_string();
+    let locked_resp = test::call_service(
+        &app, 
+        build_signed_request("/inbox", &keys.private_key, &key_id, &locked_follow).to_request()
+    ).await;
+    let pending = get_follow_between(&remote, &private, &connection).await
+        .expect("Could not fetch follow.");
+    let undo: String = serde_json::json!({
+        "id": format!("{}/undos/1", remote_url),
+        "type": "Undo",
+        "actor": remote_url,
+        "object": {
+            "id": format!("{}/follows/1", remote_url),
+            "type": "Follow",
+            "actor": remote_url,
+            "object": popular_url
+        }
+    }).to_string();
+    let undo_resp = test::call_service(
+        &app, 
+        build_signed_request("/apub/popular/inbox", &keys.private_key, &key_id, &undo).to_request()
+    ).await;
+    let undone: bool = get_follow_between(&remote, &popular, &connection).await.is_err();
+    let outgoing_id: String = format!("{}#follow/1", popular_url);
+    create_follow(&outgoing_id, &popular, &remote, &false, &connection).await
+        .expect("Could not create follow.");
+    let accept: String = serde_json::json!({
+        "id": format!("{}/accepts/1", remote_url),
+        "type": "Accept",
+        "actor": remote_url,
+        "object": outgoing_id
+    }).to_string();
+    let accept_resp = test::call_service(
+        &app, 
+        build_signed_request("/inbox", &keys.private_key, &key_id, &accept).to_request()
+    ).await;
+    let outgoing = get_follow_by_id(&outgoing_id, &connection).await
+        .expect("Could not fetch follow.");
+    let spoofed: String = serde_json::json!({
+        "id": format!("{}/follows/3", remote_url),
+        "type": "Follow",
+        "actor": "https://elsewhere.org/users/someone",
+        "object": popular_url
+    }).to_string();
+    let spoofed_resp = test::call_service(
+        &app, 
+        build_signed_request("/inbox", &keys.private_key, &key_id, &spoofed).to_request()
+    ).await;
+    handle.stop(true).await;
+    let accept_sent: serde_json::Value = serde_json::from_str(
+        deliveries.first().expect("No response was delivered.")
+    ).expect("Could not parse delivered activity.");
+    assert_eq!(follow_resp.status().as_u16(), 202);
+    assert!(accepted.accepted);
+    assert_eq!(accept_sent["type"], "Accept");
+    assert_eq!(accept_sent["actor"], popular_url);
+    assert_eq!(accept_sent["object"]["id"], format!("{}/follows/1", remote_url));
+    assert_eq!(locked_resp.status().as_u16(), 202);
+    assert!(!pending.accepted);
+    assert_eq!(undo_resp.status().as_u16(), 202);
+    assert!(undone);
+    assert_eq!(accept_resp.status().as_u16(), 202);
+    assert!(outgoing.accepted);
+    assert_eq!(spoofed_resp.status().as_u16(), 401);
+}
+
+/// The function to test the service
+/// functions for serving an actor's
+/// collections and their pages.
+#[actix_web::test]
+pub async fn test_paginated_collections(){
+    use actix_web::test;
+    let db_url: String = var("KLEAH_DB_URL")
+        .expect("Failed to read environment variable.");
+    let connection = create_connection(&db_url).await
+        .expect("Could not create connection.");
+    let instance: InstanceInformation = setup_instance(&connection).await;
+    let celebrity: KleahActor = setup_actor("celebrity", &connection).await;
+    for number in 0..21 {
+        let fan: KleahActor = setup_actor(&format!("fan{}", number), &connection).await;
+        create_follow(
+            &format!("https://{}/apub/fan{}#follow/1", instance.host, number), 
+            &fan, 
+            &celebrity, 
+            &true, 
+            &connection
+        ).await
+            .expect("Could not create follow.");
+    }
+    let app = test::init_service(
+        App::new()
+            .app_data(Data::new(AppData{ pool: connection }))
+            .service(outbox_service)
+            .service(followers_service)
+            .service(following_service)
+            .service(liked_service)
+    ).await;
+    let collection: serde_json::Value = test::call_and_read_body_json(
+        &app,
+        test::TestRequest::get().uri("/apub/celebrity/followers").to_request()
+    ).await;
+    let first: serde_json::Value = test::call_and_read_body_json(
+        &app,
+        test::TestRequest::get().uri("/apub/celebrity/followers?page=true").to_request()
+    ).await;
+    let next_url: String = first["next"].as_str()
+        .expect("The first page has no next page.")
+        .to_string();
+    let next_path: String = next_url.replace(&format!("https://{}", instance.host), "");
+    let second: serde_json::Value = test::call_and_read_body_json(
+        &app,
+        test::TestRequest::get().uri(&next_path).to_request()
+    ).await;
+    let prev_path: String = second["prev"].as_str()
+        .expect("The second page has no previous page.")
+        .replace(&format!("https://{}", instance.host), "");
+    let back: serde_json::Value = test::call_and_read_body_json(
+        &app,
+        test::TestRequest::get().uri(&prev_path).to_request()
+    ).await;
+    let following: serde_json::Value = test::call_and_read_body_json(
+        &app,
+        test::TestRequest::get().uri("/apub/fan0/following?page=true").to_request()
+    ).await;
+    let outbox: serde_json::Value = test::call_and_read_body_json(
+        &app,
+        test::TestRequest::get().uri("/apub/celebrity/outbox").to_request()
+    ).await;
+    let liked_resp = test::call_service(
+        &app,
+        test::TestRequest::get().uri("/apub/nobody/liked").to_request()
+    ).await;
+    let followers_url: String = format!("https://{}/apub/celebrity/followers", instance.host);
+    assert_eq!(collection["type"], "OrderedCollection");
+    assert_eq!(collection["totalItems"], 21);
+    assert_eq!(collection["first"], format!("{}?page=true", followers_url));
+    assert_eq!(first["type"], "OrderedCollectionPage");
+    assert_eq!(first["partOf"], followers_url);
+    assert_eq!(first["orderedItems"].as_array().map(|items| items.len()), Some(20));
+    assert_eq!(first["orderedItems"][0], format!("https://{}/apub/fan20", instance.host));
+    assert_eq!(second["orderedItems"].as_array().map(|items| items.len()), Some(1));
+    assert_eq!(second["orderedItems"][0], format!("https://{}/apub/fan0", instance.host));
+    assert!(second.get("next").is_none());
+    assert_eq!(back["orderedItems"], first["orderedItems"]);
+    assert_eq!(following["totalItems"], 1);
+    assert_eq!(following["orderedItems"][0], format!("https://{}/apub/celebrity", instance.host));
+    assert_eq!(outbox["totalItems"], 0);
+    assert_eq!(liked_resp.status().as_u16(), 404);
+}
+
+/// A service function that stands in
+/// for the WebFinger endpoint of a remote
+/// server. Only the account of the user
+/// "remoteuser" is known.
+pub async fn stand_in_webfinger(
+    req: HttpRequest,
+    query: Query<WebFingerQuery>
+) -> HttpResponse {
+    let host: String = req.connection_info().host().to_string();
+    if query.resource != format!("acct:remoteuser@{}", host) {
+        return HttpResponse::NotFound().finish();
+    }
+    HttpResponse::Ok()
+        .content_type("application/jrd+json")
+        .json(
+            serde_json::json!({
+                "subject": query.resource,
+                "links": [
+                    {
+                        "rel": "http://webfinger.net/rel/profile-page",
+                        "type": "text/html",
+                        "href": format!("http://{}/@remoteuser", host)
+                    },
+                    {
+                        "rel": "self",
+                        "type": "application/activity+json",
+                        "href": format!("http://{}/users/remoteuser", host)
+                    }
+                ]
+            })
+        )
+}
+
+/// A function to build an actor
+/// from another server for testing
+/// deliveries to it.
+pub fn remote_recipient(
+    username: &str,
+    host: &str,
+    shared_inbox: Option<String>
+) -> KleahActor {
+    let actor_url: String = format!("http://{}/users/{}", host, username);
+    KleahActor{
+        name: username.to_string(),
+        actor_type: "Person".to_string(),
+        host: host.to_string(),
+        liked: format!("{}/liked", actor_url),
+        inbox: format!("{}/inbox", actor_url),
+        outbox: format!("{}/outbox", actor_url),
+        following: format!("{}/following", actor_url),
+        followers: format!("{}/followers", actor_url),
+        username: username.to_string(),
+        description: String::new(),
+        public_key: String::new(),
+        ap_id: Some(actor_url.clone()),
+        key_id: Some(format!("{}#main-key", actor_url)),
+        shared_inbox,
+        fetched_at: None
+    }
+}
+
+/// The function to test the queue
+/// for delivering activities to other
+/// servers.
+#[actix_web::test]
+pub async fn test_queued_deliveries(){
+    let db_url: String = var("KLEAH_DB_URL")
+        .expect("Failed to read environment variable.");
+    let connection = create_connection(&db_url).await
+        .expect("Could not create connection.");
+    let broadcaster: KleahActor = setup_actor("broadcaster", &connection).await;
+    let keys = generate_keypair().expect("Could not generate keys.");
+    let received: Data<Mutex<Vec<String>>> = Data::new(Mutex::new(Vec::new()));
+    let (remote_host, handle) = start_remote_server(
+        Data::new(Mutex::new(keys.public_key.clone())), 
+        received.clone()
+    );
+    let shared_inbox: String = format!("http://{}/inbox", remote_host);
+    let recipients: Vec<KleahActor> = vec![
+        remote_recipient("first", &remote_host, Some(shared_inbox.clone())),
+        remote_recipient("second", &remote_host, Some(shared_inbox.clone())),
+        remote_recipient("unreachable", "127.0.0.1:1", None)
+    ];
+    let activity = build_activity(
+        &broadcaster, 
+        "Create", 
+        serde_json::json!({ "type": "Note", "content": "Hello!" })
+    );
+    deliver_activity("broadcaster", &recipients, &activity, &connection).await
+        .expect("Could not queue activity.");
+    deliver_activity("broadcaster", &recipients, &activity, &connection).await
+        .expect("Could not queue activity.");
+    let queued = get_deliveries_for_activity(&activity.id, &connection).await
+        .expect("Could not fetch deliveries.");
+    let attempted: usize = process_deliveries(&connection).await
+        .expect("Could not send deliveries.");
+    let bodies: Vec<String> = wait_for_deliveries(&received, 1).await;
+    let retried = get_deliveries_for_activity(&activity.id, &connection).await
+        .expect("Could not fetch deliveries.");
+    let not_due: usize = process_deliveries(&connection).await
+        .expect("Could not send deliveries.");
+    sqlx::query("UPDATE deliveries SET attempts = $1, next_attempt_at = now() WHERE activity_id = $2")
+        .bind(MAX_DELIVERY_ATTEMPTS - 1)
+        .bind(&activity.id)
+        .execute(&connection)
+        .await
+        .expect("Could not reschedule delivery.");
+    let last_attempt: usize = process_deliveries(&connection).await
+        .expect("Could not send deliveries.");
+    let given_up = get_deliveries_for_activity(&activity.id, &connection).await
+        .expect("Could not fetch deliveries.");
+    handle.stop(true).await;
+    let delivered: serde_json::Value = serde_json::from_str(
+        bodies.first().expect("No activity was delivered.")
+    ).expect("Could not parse delivered activity.");
+    assert_eq!(queued.len(), 2);
+    assert_eq!(attempted, 2);
+    assert_eq!(bodies.len(), 1);
+    assert_eq!(delivered["id"], activity.id);
+    assert_eq!(retried.len(), 1);
+    assert_eq!(retried[0].inbox, "http://127.0.0.1:1/users/unreachable/inbox");
+    assert_eq!(retried[0].attempts, 1);
+    assert!(retried[0].last_error.is_some());
+    assert!(retried[0].failed_at.is_none());
+    assert!(retried[0].next_attempt_at > retried[0].created_at);
+    assert_eq!(not_due, 0);
+    assert_eq!(last_attempt, 1);
+    assert_eq!(given_up[0].attempts, MAX_DELIVERY_ATTEMPTS);
+    assert!(given_up[0].failed_at.is_some());
+    assert_eq!(retry_delay(0), DELIVERY_BASE_DELAY);
+    assert_eq!(retry_delay(3), DELIVERY_BASE_DELAY * 8.0);
+}
+
+/// The function to test resolving
+/// account handles to actors and
+/// caching actors from other servers.
+#[actix_web::test]
+pub async fn test_resolving_remote_actors(){
+    let db_url: String = var("KLEAH_DB_URL")
+        .expect("Failed to read environment variable.");
+    let connection = create_connection(&db_url).await
+        .expect("Could not create connection.");
+    let instance: InstanceInformation = setup_instance(&connection).await;
+    let _local: KleahActor = setup_actor("resolvable", &connection).await;
+    let first_keys = generate_keypair().expect("Could not generate keys.");
+    let second_keys = generate_keypair().expect("Could not generate keys.");
+    let public_key: Data<Mutex<String>> = Data::new(Mutex::new(first_keys.public_key.clone()));
+    let (remote_host, handle) = start_remote_server(
+        public_key.clone(), 
+        Data::new(Mutex::new(Vec::new()))
+    );
+    let remote_url: String = format!("http://{}/users/remoteuser", remote_host);
+    let handle_name: String = format!("@remoteuser@{}", remote_host);
+    let resolved: KleahActor = resolve_remote_actor(&handle_name, &connection).await
+        .expect("Could not resolve remote actor.");
+    *public_key.lock().expect("Could not swap the public key.") = second_keys.public_key.clone();
+    let cached: KleahActor = resolve_remote_actor(&handle_name, &connection).await
+        .expect("Could not resolve remote actor.");
+    sqlx::query("UPDATE actors SET fetched_at = now() - interval '2 days' WHERE host = $1")
+        .bind(&remote_host)
+        .execute(&connection)
+        .await
+        .expect("Could not expire cached actor.");
+    let refreshed: KleahActor = resolve_remote_actor(&handle_name, &connection).await
+        .expect("Could not resolve remote actor.");
+    let missing = resolve_remote_actor(&format!("@nobody@{}", remote_host), &connection).await;
+    handle.stop(true).await;
+    sqlx::query("UPDATE actors SET fetched_at = now() - interval '2 days' WHERE host = $1")
+        .bind(&remote_host)
+        .execute(&connection)
+        .await
+        .expect("Could not expire cached actor.");
+    let stale: KleahActor = resolve_remote_actor(&handle_name, &connection).await
+        .expect("Could not fall back to the cached actor.");
+    let local: KleahActor = resolve_remote_actor(
+        &format!("@resolvable@{}", instance.host), 
+        &connection
+    ).await
+        .expect("Could not resolve local actor.");
+    let invalid = resolve_remote_actor("resolvable", &connection).await;
+    assert_eq!(resolved.username, "remoteuser");
+    assert_eq!(resolved.host, remote_host);
+    assert_eq!(resolved.ap_id, Some(remote_url.clone()));
+    assert_eq!(resolved.inbox, format!("{}/inbox", remote_url));
+    assert_eq!(resolved.shared_inbox, Some(format!("http://{}/inbox", remote_host)));
+    assert_eq!(resolved.public_key, first_keys.public_key);
+    assert!(resolved.fetched_at.is_some());
+    assert!(actor_is_fresh(&resolved));
+    assert_eq!(cached.public_key, first_keys.public_key);
+    assert_eq!(refreshed.public_key, second_keys.public_key);
+    assert!(missing.is_err());
+    assert_eq!(stale.public_key, second_keys.public_key);
+    assert_eq!(local.username, "resolvable");
+    assert!(local.ap_id.is_none());
+    assert!(invalid.is_err());
+}
+
+/// The function to test the service
+/// functions for serving the NodeInfo
+/// documents of an instance.
+#[actix_web::test]
+pub async fn test_nodeinfo_services(){
+    use actix_web::test;
+    let db_url: String = var("KLEAH_DB_URL")
+        .expect("Failed to read environment variable.");
+    let connection = create_connection(&db_url).await
+        .expect("Could not create connection.");
+    let instance: InstanceInformation = setup_instance(&connection).await;
+    let _actor: KleahActor = setup_actor("counted", &connection).await;
+    let token = create_api_token("counted", &connection).await
+        .expect("Could not create token.");
+    let _user = get_user_by_token(&token.token, &connection).await
+        .expect("Could not fetch user.");
+    let active: KleahUser = get_user_by_id("counted", &connection).await
+        .expect("Could not fetch user.");
+    let app = test::init_service(
+        App::new()
+            .app_data(Data::new(AppData{ pool: connection.clone() }))
+            .service(nodeinfo_links_service)
+            .service(nodeinfo_service)
+    ).await;
+    let links: serde_json::Value = test::call_and_read_body_json(
+        &app,
+        test::TestRequest::get().uri("/.well-known/nodeinfo").to_request()
+    ).await;
+    let document_resp = test::call_service(
+        &app,
+        test::TestRequest::get().uri("/nodeinfo/2.1").to_request()
+    ).await;
+    let content_type: String = document_resp.headers()
+        .get("content-type")
+        .expect("No content type was set.")
+        .to_str()
+        .expect("Could not read content type.")
+        .to_string();
+    let document: serde_json::Value = test::read_body_json(document_resp).await;
+    let _later: KleahActor = setup_actor("uncounted", &connection).await;
+    let cached: serde_json::Value = test::call_and_read_body_json(
+        &app,
+        test::TestRequest::get().uri("/nodeinfo/2.1").to_request()
+    ).await;
+    let total: i64 = document["usage"]["users"]["total"].as_i64().unwrap_or(0);
+    assert!(active.last_active.is_some());
+    assert_eq!(links["links"][0]["rel"], "http://nodeinfo.diaspora.software/ns/schema/2.1");
+    assert_eq!(links["links"][0]["href"], format!("https://{}/nodeinfo/2.1", instance.host));
+    assert!(content_type.contains("schema/2.1#"));
+    assert_eq!(document["version"], "2.1");
+    assert_eq!(document["software"]["name"], "kleah-backend");
+    assert_eq!(document["software"]["version"], env!("CARGO_PKG_VERSION"));
+    assert_eq!(document["protocols"], serde_json::json!(["activitypub"]));
+    assert_eq!(document["openRegistrations"], !instance.uses_invites);
+    assert!(total >= 1);
+    assert!(document["usage"]["users"]["activeMonth"].as_i64().unwrap_or(0) >= 1);
+    assert!(document["usage"]["users"]["activeHalfyear"].as_i64().unwrap_or(0) >= 1);
+    assert!(document["usage"]["localPosts"].as_i64().is_some());
+    assert_eq!(cached["usage"]["users"]["total"], total);
+}
+
+/// A function to build the payload
+/// for registering a test user with
+/// an optional invite code.
+pub fn registration_payload(
+    username: &str,
+    invite_code: Option<&str>
+) -> UserCreatePayload {
+    UserCreatePayload{
+        name: username.to_string(),
+        password: "123456789".to_string(),
+        username: username.to_string(),
+        email_addr: "me@example.com".to_string(),
+        description: "A test user.".to_string(),
+        user_type: KleahUserType::Person,
+        invite_code: invite_code.map(|code| code.to_string())
+    }
+}
+
+/// The function to test that registrations
+/// require invite codes exactly when the
+/// NodeInfo document of an instance says
+/// they are closed.
+#[actix_web::test]
+pub async fn test_registration_invites(){
+    use actix_web::test;
+    let db_url: String = var("KLEAH_DB_URL")
+        .expect("Failed to read environment variable.");
+    let connection = create_connection(&db_url).await
+        .expect("Could not create connection.");
+    let instance: InstanceInformation = setup_instance(&connection).await;
+    let app = test::init_service(
+        App::new()
+            .app_data(Data::new(AppData{ pool: connection.clone() }))
+            .service(create_user_service)
+            .service(nodeinfo_service)
+    ).await;
+    edit_invite_system(&false, &connection).await
+        .expect("Could not open registrations.");
+    let open_document: serde_json::Value = test::call_and_read_body_json(
+        &app,
+        test::TestRequest::get().uri("/nodeinfo/2.1").to_request()
+    ).await;
+    let _open_resp = test::call_service(
+        &app,
+        test::TestRequest::post()
+            .uri("/api/user/create")
+            .insert_header(ContentType::json())
+            .set_json(registration_payload("openjoiner", None))
+            .to_request()
+    ).await;
+    let open_joined: bool = user_exists("openjoiner", &connection).await;
+    edit_invite_system(&true, &connection).await
+        .expect("Could not close registrations.");
+    let _invite = create_invite_code("JOINCODE1234", &connection).await
+        .expect("Could not create invite code.");
+    let closed_document: serde_json::Value = test::call_and_read_body_json(
+        &app,
+        test::TestRequest::get().uri("/nodeinfo/2.1").to_request()
+    ).await;
+    let _uninvited_resp = test::call_service(
+        &app,
+        test::TestRequest::post()
+            .uri("/api/user/create")
+            .insert_header(ContentType::json())
+            .set_json(registration_payload("uninvited", None))
+            .to_request()
+    ).await;
+    let _invited_resp = test::call_service(
+        &app,
+        test::TestRequest::post()
+            .uri("/api/user/create")
+            .insert_header(ContentType::json())
+            .set_json(registration_payload("invited", Some("JOINCODE1234")))
+            .to_request()
+    ).await;
+    let _reused_resp = test::call_service(
+        &app,
+        test::TestRequest::post()
+            .uri("/api/user/create")
+            .insert_header(ContentType::json())
+            .set_json(registration_payload("reinvited", Some("JOINCODE1234")))
+            .to_request()
+    ).await;
+    let uninvited_joined: bool = user_exists("uninvited", &connection).await;
+    let reinvited_joined: bool = user_exists("reinvited", &connection).await;
+    let invited_joined: bool = user_exists("invited", &connection).await;
+    let used_invite = get_code_by_code("JOINCODE1234", &connection).await;
+    edit_invite_system(&instance.uses_invites, &connection).await
+        .expect("Could not restore registrations.");
+    assert_eq!(open_document["openRegistrations"], true);
+    assert!(open_joined);
+    assert_eq!(closed_document["openRegistrations"], false);
+    assert!(!uninvited_joined);
+    assert!(invited_joined);
+    assert!(!reinvited_joined);
+    assert!(used_invite.is_err());
+}
+
+/// The function to test sending
+/// profile changes to followers on
+/// other servers.
+#[actix_web::test]
+pub async fn test_profile_update_federation(){
+    use actix_web::test;
+    let db_url: String = var("KLEAH_DB_URL")
+        .expect("Failed to read environment variable.");
+    let connection = create_connection(&db_url).await
+        .expect("Could not create connection.");
+    let instance: InstanceInformation = setup_instance(&connection).await;
+    let editor: KleahActor = setup_actor("editor", &connection).await;
+    let local_fan: KleahActor = setup_actor("localfan", &connection).await;
+    let token = create_api_token("editor", &connection).await
+        .expect("Could not create token.");
+    let keys = generate_keypair().expect("Could not generate keys.");
+    let received: Data<Mutex<Vec<String>>> = Data::new(Mutex::new(Vec::new()));
+    let (remote_host, handle) = start_remote_server(
+        Data::new(Mutex::new(keys.public_key.clone())), 
+        received.clone()
+    );
+    let shared_inbox: String = format!("http://{}/inbox", remote_host);
+    for username in ["firstfan", "secondfan"] {
+        let remote: KleahActor = upsert_remote_actor(
+            &remote_recipient(username, &remote_host, Some(shared_inbox.clone())),
+            &connection
+        ).await
+            .expect("Could not cache remote actor.");
+        create_follow(
+            &format!("{}#follow", remote.ap_id.clone().unwrap_or_default()), 
+            &remote, 
+            &editor, 
+            &true, 
+            &connection
+        ).await
+            .expect("Could not create follow.");
+    }
+    create_follow("editor-local-fan", &local_fan, &editor, &true, &connection).await
+        .expect("Could not create follow.");
+    let app = test::init_service(
+        App::new()
+            .app_data(Data::new(AppData{ pool: connection.clone() }))
+            .service(update_name_service)
+            .service(update_description_service)
+    ).await;
+    let name_resp: serde_json::Value = test::call_and_read_body_json(
+        &app,
+        test::TestRequest::post()
+            .uri("/api/user/edit/name")
+            .set_json(serde_json::json!({ "api_token": token.token, "new_entity": "Edited Name" }))
+            .to_request()
+    ).await;
+    process_deliveries(&connection).await
+        .expect("Could not send deliveries.");
+    let bio_resp: serde_json::Value = test::call_and_read_body_json(
+        &app,
+        test::TestRequest::post()
+            .uri("/api/user/edit/bio")
+            .set_json(serde_json::json!({ "api_token": token.token, "new_entity": "An edited bio." }))
+            .to_request()
+    ).await;
+    process_deliveries(&connection).await
+        .expect("Could not send deliveries.");
+    let bodies: Vec<String> = wait_for_deliveries(&received, 2).await;
+    handle.stop(true).await;
+    let updates: Vec<serde_json::Value> = bodies
+        .iter()
+        .map(|body| serde_json::from_str(body).expect("Could not parse delivered activity."))
+        .collect();
+    let actor_url: String = format!("https://{}/apub/editor", instance.host);
+    assert_eq!(name_resp["status"], true);
+    assert_eq!(bio_resp["status"], true);
+    assert_eq!(updates.len(), 2);
+    assert_eq!(updates[0]["type"], "Update");
+    assert_eq!(updates[0]["actor"], actor_url);
+    assert_eq!(updates[0]["object"]["id"], actor_url);
+    assert_eq!(updates[0]["object"]["type"], "Person");
+    assert_eq!(updates[0]["object"]["name"], "Edited Name");
+    assert_eq!(updates[1]["object"]["summary"], "An edited bio.");
+}
+
+/// The function to test the service
+/// functions for publishing, reading
+/// and deleting notes.
+#[actix_web::test]
+pub async fn test_note_services(){
+    use actix_web::test;
+    let db_url: String = var("KLEAH_DB_URL")
+        .expect("Failed to read environment variable.");
+    let connection = create_connection(&db_url).await
+        .expect("Could not create connection.");
+    let instance: InstanceInformation = setup_instance(&connection).await;
+    let _author: KleahActor = setup_actor("writer", &connection).await;
+    let _other: KleahActor = setup_actor("reader", &connection).await;
+    let token = create_api_token("writer", &connection).await
+        .expect("Could not create token.");
+    let other_token = create_api_token("reader", &connection).await
+        .expect("Could not create token.");
+    let app = test::init_service(
+        App::new()
+            .app_data(Data::new(AppData{ pool: connection.clone() }))
+            .service(create_note_service)
+            .service(delete_note_service)
+            .service(get_note_service)
+    ).await;
+    let created: serde_json::Value = test::call_and_read_body_json(
+        &app,
+        test::TestRequest::post()
+            .uri("/api/post/create")
+            .set_json(serde_json::json!({ "api_token": token.token, "content": "Hello, world!" }))
+            .to_request()
+    ).await;
+    let note_id: String = created["note_id"].as_str()
+        .expect("No note was created.")
+        .to_string();
+    let fetched: serde_json::Value = test::call_and_read_body_json(
+        &app,
+        test::TestRequest::get().uri(&format!("/api/post/{}", note_id)).to_request()
+    ).await;
+    let reply: serde_json::Value = test::call_and_read_body_json(
+        &app,
+        test::TestRequest::post()
+            .uri("/api/post/create")
+            .set_json(
+                serde_json::json!({ 
+                    "api_token": token.token, 
+                    "content": "Only for my followers.",
+                    "in_reply_to": note_id,
+                    "visibility": "followers"
+                })
+            )
+            .to_request()
+    ).await;
+    let reply_id: String = reply["note_id"].as_str()
+        .expect("No reply was created.")
+        .to_string();
+    let hidden_resp = test::call_service(
+        &app,
+        test::TestRequest::get().uri(&format!("/api/post/{}", reply_id)).to_request()
+    ).await;
+    let own_resp = test::call_service(
+        &app,
+        test::TestRequest::get()
+            .uri(&format!("/api/post/{}", reply_id))
+            .insert_header(bearer(&token.token))
+            .to_request()
+    ).await;
+    let invalid_resp = test::call_service(
+        &app,
+        test::TestRequest::post()
+            .uri("/api/post/create")
+            .set_json(serde_json::json!({ "api_token": token.token, "content": "Hi", "visibility": "everyone" }))
+            .to_request()
+    ).await;
+    let empty_resp = test::call_service(
+        &app,
+        test::TestRequest::post()
+            .uri("/api/post/create")
+            .set_json(serde_json::json!({ "api_token": token.token, "content": "   " }))
+            .to_request()
+    ).await;
+    let foreign_delete_resp = test::call_service(
+        &app,
+        test::TestRequest::post()
+            .uri("/api/post/delete")
+            .set_json(serde_json::json!({ "api_token": other_token.token, "note_id": note_id }))
+            .to_request()
+    ).await;
+    let deleted: serde_json::Value = test::call_and_read_body_json(
+        &app,
+        test::TestRequest::post()
+            .uri("/api/post/delete")
+            .set_json(serde_json::json!({ "api_token": token.token, "note_id": note_id }))
+            .to_request()
+    ).await;
+    let gone_resp = test::call_service(
+        &app,
+        test::TestRequest::get().uri(&format!("/api/post/{}", note_id)).to_request()
+    ).await;
+    let note_url: String = format!("https://{}/apub/notes/{}", instance.host, note_id);
+    assert_eq!(created["author"], "writer");
+    assert_eq!(created["host"], instance.host);
+    assert_eq!(created["content"], "Hello, world!");
+    assert_eq!(created["visibility"], "public");
+    assert_eq!(created["url"], note_url);
+    assert_eq!(fetched["note_id"], note_id);
+    assert_eq!(reply["in_reply_to"], note_url);
+    assert_eq!(reply["visibility"], "followers");
+    assert_eq!(hidden_resp.status().as_u16(), 404);
+    assert_eq!(own_resp.status().as_u16(), 200);
+    assert_eq!(invalid_resp.status().as_u16(), 400);
+    assert_eq!(empty_resp.status().as_u16(), 400);
+    assert_eq!(foreign_delete_resp.status().as_u16(), 401);
+    assert_eq!(deleted["status"], true);
+    assert_eq!(gone_resp.status().as_u16(), 404);
+}
+
+/// The function to test serving local
+/// notes as ActivityPub objects, sending
+/// them to followers and storing notes
+/// received from other servers.
+#[actix_web::test]
+pub async fn test_note_federation(){
+    use actix_web::test;
+    let db_url: String = var("KLEAH_DB_URL")
+        .expect("Failed to read environment variable.");
+    let connection = create_connection(&db_url).await
+        .expect("Could not create connection.");
+    let instance: InstanceInformation = setup_instance(&connection).await;
+    let publisher: KleahActor = setup_actor("publisher", &connection).await;
+    let keys = generate_keypair().expect("Could not generate keys.");
+    let received: Data<Mutex<Vec<String>>> = Data::new(Mutex::new(Vec::new()));
+    let (remote_host, handle) = start_remote_server(
+        Data::new(Mutex::new(keys.public_key.clone())), 
+        received.clone()
+    );
+    let remote_url: String = format!("http://{}/users/remoteuser", remote_host);
+    let key_id: String = format!("{}#main-key", remote_url);
+    let follower: KleahActor = upsert_remote_actor(
+        &remote_recipient("reposter", &remote_host, Some(format!("http://{}/inbox", remote_host))),
+        &connection
+    ).await
+        .expect("Could not cache remote actor.");
+    create_follow(
+        &format!("{}#follow", follower.ap_id.clone().unwrap_or_default()), 
+        &follower, 
+        &publisher, 
+        &true, 
+        &connection
+    ).await
+        .expect("Could not create follow.");
+    let note: KleahNote = publish_note(
+        "publisher", 
+        "Hello <b>fediverse</b>!\nSecond line", 
+        None, 
+        None, 
+        None, 
+        &false, 
+        None,
+        &connection
+    ).await
+        .expect("Could not publish note.");
+    let private_note: KleahNote = publish_note(
+        "publisher", 
+        "Only for followers.", 
+        None, 
+        Some("followers"), 
+        None, 
+        &false, 
+        None,
+        &connection
+    ).await
+        .expect("Could not publish note.");
+    process_deliveries(&connection).await
+        .expect("Could not send deliveries.");
+    let bodies: Vec<String> = wait_for_deliveries(&received, 2).await;
+    let app = test::init_service(
+        App::new()
+            .app_data(Data::new(AppData{ pool: connection.clone() }))
+            .service(apub_note_activity_service)
+            .service(apub_note_service)
+            .service(outbox_service)
+            .service(inbox_service)
+            .service(shared_inbox_service)
+    ).await;
+    let object_resp = test::call_service(
+        &app,
+        test::TestRequest::get().uri(&format!("/apub/notes/{}", note.note_id)).to_request()
+    ).await;
+    let content_type: String = object_resp.headers()
+        .get("content-type")
+        .expect("No content type was set.")
+        .to_str()
+        .expect("Could not read content type.")
+        .to_string();
+    let object: serde_json::Value = test::read_body_json(object_resp).await;
+    let private_resp = test::call_service(
+        &app,
+        test::TestRequest::get().uri(&format!("/apub/notes/{}", private_note.note_id)).to_request()
+    ).await;
+    let activity: serde_json::Value = test::call_and_read_body_json(
+        &app,
+        test::TestRequest::get().uri(&format!("/apub/notes/{}/activity", note.note_id)).to_request()
+    ).await;
+    let outbox: serde_json::Value = test::call_and_read_body_json(
+        &app,
+        test::TestRequest::get().uri("/apub/publisher/outbox?page=true").to_request()
+    ).await;
+    let note_url: String = format!("https://{}/apub/notes/{}", instance.host, note.note_id);
+    let remote_note_url: String = format!("{}/statuses/1", remote_url);
+    let create: String = serde_json::json!({
+        "id": format!("{}/activity", remote_note_url),
+        "type": "Create",
+        "actor": remote_url,
+        "to": ["https://www.w3.org/ns/activitystreams#Public"],
+        "object": {
+            "id": remote_note_url,
+            "type": "Note",
+            "attributedTo": remote_url,
+            "content": "<p>Nice!</p><script>alert(1)</script>",
+            "inReplyTo": note_url,
+            "published": "2025-01-01T12:00:00Z",
+            "to": ["https://www.w3.org/ns/activitystreams#Public"],
+            "cc": [format!("{}/followers", remote_url)]
+        }
+    }).to_string();
+    let create_resp = test::call_service(
+        &app, 
+        build_signed_request("/apub/publisher/inbox", &keys.private_key, &key_id, &create).to_request()
+    ).await;
+    let spoofed: String = serde_json::json!({
+        "id": format!("{}/statuses/2/activity", remote_url),
+        "type": "Create",
+        "actor": remote_url,
+        "object": {
+            "id": format!("{}/statuses/2", remote_url),
+            "type": "Note",
+            "attributedTo": "https://elsewhere.org/users/someone",
+            "content": "<p>Not mine.</p>"
+        }
+    }).to_string();
+    let spoofed_resp = test::call_service(
+        &app, 
+        build_signed_request("/inbox", &keys.private_key, &key_id, &spoofed).to_request()
+    ).await;
+    handle.stop(true).await;
+    let stored: KleahNote = get_note_by_ap_id(&remote_note_url, &connection).await
+        .expect("Could not fetch stored note.");
+    let delivered: serde_json::Value = serde_json::from_str(
+        bodies.first().expect("No activity was delivered.")
+    ).expect("Could not parse delivered activity.");
+    let followers_url: String = format!("https://{}/apub/publisher/followers", instance.host);
+    assert_eq!(content_type, "application/activity+json");
+    assert_eq!(object["type"], "Note");
+    assert_eq!(object["id"], note_url);
+    assert_eq!(object["attributedTo"], format!("https://{}/apub/publisher", instance.host));
+    assert_eq!(object["content"], "<p>Hello &lt;b&gt;fediverse&lt;/b&gt;!<br>Second line</p>");
+    assert_eq!(object["to"], serde_json::json!(["https://www.w3.org/ns/activitystreams#Public"]));
+    assert_eq!(object["cc"], serde_json::json!([followers_url]));
+    assert_eq!(private_resp.status().as_u16(), 404);
+    assert_eq!(activity["type"], "Create");
+    assert_eq!(activity["id"], format!("{}/activity", note_url));
+    assert_eq!(activity["object"]["id"], note_url);
+    assert_eq!(bodies.len(), 2);
+    assert_eq!(delivered["type"], "Create");
+    assert_eq!(delivered["object"]["type"], "Note");
+    assert_eq!(outbox["totalItems"], 1);
+    assert_eq!(outbox["orderedItems"][0]["object"]["id"], note_url);
+    assert_eq!(create_resp.status().as_u16(), 202);
+    assert_eq!(stored.author_username, "remoteuser");
+    assert_eq!(stored.author_host, remote_host);
+    assert_eq!(stored.content, "<p>Nice!</p>");
+    assert_eq!(stored.in_reply_to, Some(note_url));
+    assert_eq!(stored.visibility, "public");
+    assert_eq!(stored.created_at.to_rfc3339(), "2025-01-01T12:00:00+00:00");
+    assert_eq!(spoofed_resp.status().as_u16(), 401);
+}
+
+/// The function to test enforcing the
+/// visibility of notes and addressing
+/// them to the right audiences.
+#[actix_web::test]
+pub async fn test_visibility_levels(){
+    use actix_web::test;
+    let db_url: String = var("KLEAH_DB_URL")
+        .expect("Failed to read environment variable.");
+    let connection = create_connection(&db_url).await
+        .expect("Could not create connection.");
+    let instance: InstanceInformation = setup_instance(&connection).await;
+    let author: KleahActor = setup_actor("discreet", &connection).await;
+    let follower: KleahActor = setup_actor("confidant", &connection).await;
+    let recipient: KleahActor = setup_actor("addressee", &connection).await;
+    let _stranger: KleahActor = setup_actor("stranger", &connection).await;
+    create_follow("confidant-discreet", &follower, &author, &true, &connection).await
+        .expect("Could not create follow.");
+    let mut tokens: Vec<String> = Vec::new();
+    for username in ["discreet", "confidant", "addressee", "stranger"] {
+        let token = create_api_token(username, &connection).await
+            .expect("Could not create token.");
+        tokens.push(token.token);
+    }
+    let keys = generate_keypair().expect("Could not generate keys.");
+    let received: Data<Mutex<Vec<String>>> = Data::new(Mutex::new(Vec::new()));
+    let (remote_host, handle) = start_remote_server(
+        Data::new(Mutex::new(keys.public_key.clone())), 
+        received.clone()
+    );
+    let remote_url: String = format!("http://{}/users/remoteuser", remote_host);
+    let key_id: String = format!("{}#main-key", remote_url);
+    let remote_follower: KleahActor = upsert_remote_actor(
+        &remote_recipient("onlooker", &remote_host, Some(format!("http://{}/inbox", remote_host))),
+        &connection
+    ).await
+        .expect("Could not cache remote actor.");
+    create_follow(
+        &format!("{}#follow", remote_follower.ap_id.clone().unwrap_or_default()), 
+        &remote_follower, 
+        &author, 
+        &true, 
+        &connection
+    ).await
+        .expect("Could not create follow.");
+    let remote_addressee: KleahActor = upsert_remote_actor(
+        &remote_recipient("whisperee", &remote_host, Some(format!("http://{}/inbox", remote_host))),
+        &connection
+    ).await
+        .expect("Could not cache remote actor.");
+    let followers_note: KleahNote = publish_note("discreet", "For followers.", None, Some("followers"), None, &false, None, &connection).await
+        .expect("Could not publish note.");
+    let direct_note: KleahNote = publish_note("discreet", "Just for you.", None, Some("direct"), None, &false, None, &connection).await
+        .expect("Could not publish note.");
+    process_deliveries(&connection).await
+        .expect("Could not send deliveries.");
+    let follower_deliveries: Vec<String> = wait_for_deliveries(&received, 1).await;
+    add_note_recipient(&direct_note.note_id, &recipient, &connection).await
+        .expect("Could not add recipient.");
+    add_note_recipient(&direct_note.note_id, &remote_addressee, &connection).await
+        .expect("Could not add recipient.");
+    deliver_note_creation(&direct_note, &author, &connection).await
+        .expect("Could not queue note.");
+    process_deliveries(&connection).await
+        .expect("Could not send deliveries.");
+    let all_deliveries: Vec<String> = wait_for_deliveries(&received, 2).await;
+    let app = test::init_service(
+        App::new()
+            .app_data(Data::new(AppData{ pool: connection.clone() }))
+            .service(get_note_service)
+            .service(outbox_service)
+            .service(shared_inbox_service)
+    ).await;
+    let mut statuses: Vec<(u16, u16)> = Vec::new();
+    for token in tokens.iter().map(|token| Some(token.as_str())).chain([None]) {
+        let mut status: Vec<u16> = Vec::new();
+        for note in [&followers_note, &direct_note] {
+            let mut req = test::TestRequest::get()
+                .uri(&format!("/api/post/{}", note.note_id));
+            if let Some(token) = token {
+                req = req.insert_header(bearer(token));
+            }
+            let resp = test::call_service(&app, req.to_request()).await;
+            status.push(resp.status().as_u16());
+        }
+        statuses.push((status[0], status[1]));
+    }
+    let outbox: serde_json::Value = test::call_and_read_body_json(
+        &app,
+        test::TestRequest::get().uri("/apub/discreet/outbox").to_request()
+    ).await;
+    let addressee_url: String = format!("https://{}/apub/addressee", instance.host);
+    let mut inbound_visibilities: Vec<String> = Vec::new();
+    let audiences: [(serde_json::Value, serde_json::Value); 4] = [
+        (serde_json::json!(["https://www.w3.org/ns/activitystreams#Public"]), serde_json::json!([])),
+        (serde_json::json!([format!("{}/followers", remote_url)]), serde_json::json!(["as:Public"])),
+        (serde_json::json!([format!("{}/followers", remote_url)]), serde_json::json!([])),
+        (serde_json::json!(addressee_url), serde_json::json!([]))
+    ];
+    for (number, (to, cc)) in audiences.iter().enumerate() {
+        let remote_note_url: String = format!("{}/statuses/visibility/{}", remote_url, number);
+        let create: String = serde_json::json!({
+            "id": format!("{}/activity", remote_note_url),
+            "type": "Create",
+            "actor": remote_url,
+            "object": {
+                "id": remote_note_url,
+                "type": "Note",
+                "attributedTo": remote_url,
+                "content": "<p>Hello.</p>",
+                "to": to,
+                "cc": cc
+            }
+        }).to_string();
+        let _resp = test::call_service(
+            &app, 
+            build_signed_request("/inbox", &keys.private_key, &key_id, &create).to_request()
+        ).await;
+        let stored: KleahNote = get_note_by_ap_id(&remote_note_url, &connection).await
+            .expect("Could not fetch stored note.");
+        inbound_visibilities.push(stored.visibility);
+    }
+    handle.stop(true).await;
+    let inbound_direct: KleahNote = get_note_by_ap_id(
+        &format!("{}/statuses/visibility/3", remote_url), 
+        &connection
+    ).await
+        .expect("Could not fetch stored note.");
+    let addressee_sees = get_visible_note(&inbound_direct.note_id, Some(&tokens[2]), &connection).await;
+    let stranger_sees = get_visible_note(&inbound_direct.note_id, Some(&tokens[3]), &connection).await;
+    let followers_delivery: serde_json::Value = serde_json::from_str(&follower_deliveries[0])
+        .expect("Could not parse delivered activity.");
+    let direct_delivery: serde_json::Value = serde_json::from_str(&all_deliveries[1])
+        .expect("Could not parse delivered activity.");
+    let public_note: KleahNote = KleahNote{ visibility: "public".to_string(), ..direct_note };
+    let recipients: Vec<KleahActor> = vec![recipient];
+    let (public_to, public_cc) = note_addressing(&public_note, &author, &recipients);
+    let followers_url: String = format!("https://{}/apub/discreet/followers", instance.host);
+    assert_eq!(statuses, vec![(200, 200), (200, 404), (404, 200), (404, 404), (404, 404)]);
+    assert_eq!(outbox["totalItems"], 0);
+    assert_eq!(all_deliveries.len(), 2);
+    assert_eq!(follower_deliveries[0], all_deliveries[0]);
+    assert_eq!(followers_delivery["object"]["to"], serde_json::json!([followers_url]));
+    assert_eq!(direct_delivery["object"]["id"], format!("https://{}/apub/notes/{}", instance.host, public_note.note_id));
+    assert_eq!(direct_delivery["to"], serde_json::json!([format!("http://{}/users/whisperee", remote_host), addressee_url]));
+    assert!(direct_delivery.get("cc").is_none());
+    assert_eq!(public_to, vec!["https://www.w3.org/ns/activitystreams#Public".to_string()]);
+    assert_eq!(public_cc, vec![followers_url, addressee_url]);
+    assert_eq!(inbound_visibilities, vec!["public", "unlisted", "followers", "direct"]);
+    assert!(addressee_sees.is_ok());
+    assert!(stranger_sees.is_err());
+}
+
+/// The function to test reading
+/// the home timeline of a user page
+/// by page.
+#[actix_web::test]
+pub async fn test_home_timeline(){
+    use actix_web::test;
+    let db_url: String = var("KLEAH_DB_URL")
+        .expect("Failed to read environment variable.");
+    let connection = create_connection(&db_url).await
+        .expect("Could not create connection.");
+    let _instance: InstanceInformation = setup_instance(&connection).await;
+    let reader: KleahActor = setup_actor("homebody", &connection).await;
+    let followed: KleahActor = setup_actor("neighbour", &connection).await;
+    let pending: KleahActor = setup_actor("newcomer", &connection).await;
+    let _unrelated: KleahActor = setup_actor("outsider", &connection).await;
+    let remote: KleahActor = upsert_remote_actor(
+        &remote_recipient("penpal", "remote.example", None),
+        &connection
+    ).await
+        .expect("Could not cache remote actor.");
+    create_follow("homebody-neighbour", &reader, &followed, &true, &connection).await
+        .expect("Could not create follow.");
+    create_follow("homebody-newcomer", &reader, &pending, &false, &connection).await
+        .expect("Could not create follow.");
+    create_follow("homebody-penpal", &reader, &remote, &true, &connection).await
+        .expect("Could not create follow.");
+    let token: String = create_api_token("homebody", &connection).await
+        .expect("Could not create token.")
+        .token;
+    let mut expected: Vec<String> = Vec::new();
+    let posts: [(&str, &str, bool); 8] = [
+        ("homebody", "public", true),
+        ("neighbour", "public", true),
+        ("newcomer", "public", false),
+        ("neighbour", "followers", true),
+        ("outsider", "public", false),
+        ("neighbour", "direct", false),
+        ("homebody", "direct", true),
+        ("neighbour", "unlisted", true)
+    ];
+    for (author, visibility, shown) in posts {
+        let note: KleahNote = publish_note(author, "Hello.", None, Some(visibility), None, &false, None, &connection).await
+            .expect("Could not publish note.");
+        if shown {
+            expected.push(note.note_id);
+        }
+    }
+    let addressed: KleahNote = publish_note("outsider", "Psst.", None, Some("direct"), None, &false, None, &connection).await
+        .expect("Could not publish note.");
+    add_note_recipient(&addressed.note_id, &reader, &connection).await
+        .expect("Could not add recipient.");
+    expected.push(addressed.note_id);
+    let remote_note: KleahNote = create_note(
+        &KleahNote{
+            note_id: generate_id("https://remote.example/users/penpal/statuses/1"),
+            ap_id: Some("https://remote.example/users/penpal/statuses/1".to_string()),
+            author_username: remote.username.clone(),
+            author_host: remote.host.clone(),
+            content: "<p>Greetings.</p>".to_string(),
+            in_reply_to: None,
+            visibility: "followers".to_string(),
+            created_at: Utc::now(),
+            updated_at: None,
+            like_count: 0,
+            boost_count: 0,
+            conversation: None,
+            summary: None,
+            sensitive: false,
+            has_poll: false
+        },
+        &connection
+    ).await
+        .expect("Could not store note.");
+    expected.push(remote_note.note_id);
+    expected.reverse();
+    let app = test::init_service(
+        App::new()
+            .app_data(Data::new(AppData{ pool: connection.clone() }))
+            .service(home_timeline_service)
+    ).await;
+    let mut pages: Vec<serde_json::Value> = Vec::new();
+    let mut cursor: String = String::new();
+    for _page in 0..3 {
+        let page: serde_json::Value = test::call_and_read_body_json(
+            &app,
+            test::TestRequest::get()
+                .uri(&format!("/api/timeline/home?limit=3{}", cursor))
+                .insert_header(bearer(&token))
+                .to_request()
+        ).await;
+        cursor = format!("&max_id={}", page["next_max_id"].as_str().unwrap_or_default());
+        pages.push(page);
+    }
+    let newer: serde_json::Value = test::call_and_read_body_json(
+        &app,
+        test::TestRequest::get()
+            .uri(&format!("/api/timeline/home?limit=3&min_id={}", pages[1]["prev_min_id"].as_str().unwrap_or_default()))
+            .insert_header(bearer(&token))
+            .to_request()
+    ).await;
+    let anonymous = test::call_service(
+        &app,
+        test::TestRequest::get().uri("/api/timeline/home").to_request()
+    ).await;
+    let note_ids = |page: &serde_json::Value| -> Vec<String> {
+        page["notes"]
+            .as_array()
+            .expect("Could not read notes.")
+            .iter()
+            .map(|note| note["note_id"].as_str().unwrap_or_default().to_string())
+            .collect()
+    };
+    let read: Vec<String> = pages.iter().flat_map(note_ids).collect();
+    assert_eq!(read, expected);
+    assert_eq!(note_ids(&pages[0]).len(), 3);
+    assert_eq!(note_ids(&pages[2]).len(), 1);
+    assert_eq!(note_ids(&newer), note_ids(&pages[0]));
+    assert_eq!(pages[0]["notes"][0]["host"], "remote.example");
+    assert_eq!(anonymous.status().as_u16(), 401);
+}
+
+/// The function to test paging through
+/// the home and the local timeline when
+/// their notes were all created at the same
+/// time.
+#[actix_web::test]
+pub async fn test_timeline_ties(){
+    use actix_web::test;
+    let db_url: String = var("KLEAH_DB_URL")
+        .expect("Failed to read environment variable.");
+    let connection = create_connection(&db_url).await
+        .expect("Could not create connection.");
+    let instance: InstanceInformation = setup_instance(&connection).await;
+    let _lockstep: KleahActor = setup_actor("lockstep", &connection).await;
+    let token: String = create_api_token("lockstep", &connection).await
+        .expect("Could not create token.")
+        .token;
+    let created_at: DateTime<Utc> = Utc::now();
+    let mut expected: Vec<String> = Vec::new();
+    for number in 0..5 {
+        let note: KleahNote = create_note(
+            &KleahNote{
+                note_id: generate_id(&format!("lockstep{}", number)),
+                ap_id: None,
+                author_username: "lockstep".to_string(),
+                author_host: instance.host.clone(),
+                content: format!("Tick {}.", number),
+                in_reply_to: None,
+                visibility: "public".to_string(),
+                created_at,
+                updated_at: None,
+                like_count: 0,
+                boost_count: 0,
+                conversation: None,
+                summary: None,
+                sensitive: false,
+                has_poll: false
+            },
+            &connection
+        ).await
+            .expect("Could not store note.");
+        expected.push(note.note_id);
+    }
+    expected.sort();
+    expected.reverse();
+    let app = test::init_service(
+        App::new()
+            .app_data(Data::new(AppData{ pool: connection.clone() }))
+            .service(home_timeline_service)
+            .service(local_timeline_service)
+    ).await;
+    let mut pages: Vec<serde_json::Value> = Vec::new();
+    let mut cursor: String = String::new();
+    for _page in 0..3 {
+        let page: serde_json::Value = test::call_and_read_body_json(
+            &app,
+            test::TestRequest::get()
+                .uri(&format!("/api/timeline/home?limit=2{}", cursor))
+                .insert_header(bearer(&token))
+                .to_request()
+        ).await;
+        cursor = format!("&max_id={}", page["next_max_id"].as_str().unwrap_or_default());
+        pages.push(page);
+    }
+    let newer: serde_json::Value = test::call_and_read_body_json(
+        &app,
+        test::TestRequest::get()
+            .uri(&format!("/api/timeline/home?limit=2&min_id={}", pages[1]["prev_min_id"].as_str().unwrap_or_default()))
+            .insert_header(bearer(&token))
+            .to_request()
+    ).await;
+    let mut local_pages: Vec<serde_json::Value> = Vec::new();
+    let mut local_cursor: String = (created_at.timestamp_micros() + 1).to_string();
+    for _page in 0..3 {
+        let page: serde_json::Value = test::call_and_read_body_json(
+            &app,
+            test::TestRequest::get()
+                .uri(&format!("/api/timeline/local?limit=2&max_id={}", local_cursor))
+                .insert_header(bearer(&token))
+                .to_request()
+        ).await;
+        local_cursor = page["next_max_id"].as_str().unwrap_or_default().to_string();
+        local_pages.push(page);
+    }
+    let note_ids = |page: &serde_json::Value| -> Vec<String> {
+        page["notes"]
+            .as_array()
+            .expect("Could not read notes.")
+            .iter()
+            .map(|note| note["note_id"].as_str().unwrap_or_default().to_string())
+            .collect()
+    };
+    let read: Vec<String> = pages.iter().flat_map(note_ids).collect();
+    let read_local: Vec<String> = local_pages.iter().flat_map(note_ids).take(5).collect();
+    assert_eq!(read, expected);
+    assert_eq!(read_local, expected);
+    assert_eq!(note_ids(&newer), note_ids(&pages[0]));
+}
+
+/// The function to test reading
+/// the local and the federated timeline
+/// and restricting access to them.
+#[actix_web::test]
+pub async fn test_public_timelines(){
+    use actix_web::test;
+    let db_url: String = var("KLEAH_DB_URL")
+        .expect("Failed to read environment variable.");
+    let connection = create_connection(&db_url).await
+        .expect("Could not create connection.");
+    let _instance: InstanceInformation = setup_instance(&connection).await;
+    let _author: KleahActor = setup_actor("broadcaster", &connection).await;
+    if get_user_by_id("curator", &connection).await.is_err() {
+        let _admin = create_new_user(
+            "curator", 
+            "123456789", 
+            "curator", 
+            "curator@example.com", 
+            "A test administrator.",
+            &true,
+            &connection
+        ).await
+            .expect("Could not create user.");
+    }
+    let admin_token: String = create_api_token("curator", &connection).await
+        .expect("Could not create token.")
+        .token;
+    let user_token: String = create_api_token("broadcaster", &connection).await
+        .expect("Could not create token.")
+        .token;
+    let public_note: KleahNote = publish_note("broadcaster", "Hello, world.", None, Some("public"), None, &false, None, &connection).await
+        .expect("Could not publish note.");
+    for visibility in ["unlisted", "followers"] {
+        let _note: KleahNote = publish_note("broadcaster", "Hello.", None, Some(visibility), None, &false, None, &connection).await
+            .expect("Could not publish note.");
+    }
+    let remote_note: KleahNote = create_note(
+        &KleahNote{
+            note_id: generate_id("https://remote.example/users/herald/statuses/1"),
+            ap_id: Some("https://remote.example/users/herald/statuses/1".to_string()),
+            author_username: "herald".to_string(),
+            author_host: "remote.example".to_string(),
+            content: "<p>Hear ye.</p>".to_string(),
+            in_reply_to: None,
+            visibility: "public".to_string(),
+            created_at: Utc::now(),
+            updated_at: None,
+            like_count: 0,
+            boost_count: 0,
+            conversation: None,
+            summary: None,
+            sensitive: false,
+            has_poll: false
+        },
+        &connection
+    ).await
+        .expect("Could not store note.");
+    let app = test::init_service(
+        App::new()
+            .app_data(Data::new(AppData{ pool: connection.clone() }))
+            .service(local_timeline_service)
+            .service(federated_timeline_service)
+            .service(edit_timeline_access_service)
+    ).await;
+    let local: serde_json::Value = test::call_and_read_body_json(
+        &app,
+        test::TestRequest::get().uri("/api/timeline/local?limit=1").to_request()
+    ).await;
+    let federated: serde_json::Value = test::call_and_read_body_json(
+        &app,
+        test::TestRequest::get().uri("/api/timeline/federated?limit=2").to_request()
+    ).await;
+    let mut statuses: Vec<u16> = Vec::new();
+    let edits: [(&str, &str, &str); 3] = [
+        (&user_token, "users", "disabled"),
+        (&admin_token, "everyone", "disabled"),
+        (&admin_token, "users", "disabled")
+    ];
+    for (token, local_timeline, federated_timeline) in edits {
+        let resp = test::call_service(
+            &app,
+            test::TestRequest::post()
+                .uri("/api/admin/edit/timelines")
+                .set_json(EditTimelineAccessPayload{
+                    api_token: token.to_string(),
+                    local_timeline: local_timeline.to_string(),
+                    federated_timeline: federated_timeline.to_string()
+                })
+                .to_request()
+        ).await;
+        statuses.push(resp.status().as_u16());
+    }
+    let settings: InstanceInformation = get_instance_info(&connection).await
+        .expect("Could not fetch instance information.");
+    for (uri, api_token) in [
+        ("/api/timeline/local", None),
+        ("/api/timeline/local", Some(&user_token)),
+        ("/api/timeline/federated", Some(&user_token))
+    ] {
+        let mut req = test::TestRequest::get().uri(uri);
+        if let Some(api_token) = api_token {
+            req = req.insert_header(bearer(api_token));
+        }
+        let resp = test::call_service(&app, req.to_request()).await;
+        statuses.push(resp.status().as_u16());
+    }
+    edit_timeline_access("public", "public", &connection).await
+        .expect("Could not reset timeline access.");
+    assert_eq!(local["notes"][0]["note_id"], public_note.note_id);
+    assert_eq!(local["notes"].as_array().map(|notes| notes.len()), Some(1));
+    assert_eq!(federated["notes"][0]["note_id"], remote_note.note_id);
+    assert_eq!(federated["notes"][1]["note_id"], public_note.note_id);
+    assert_eq!(settings.local_timeline, "users");
+    assert_eq!(settings.federated_timeline, "disabled");
+    assert_eq!(statuses, vec![401, 400, 200, 401, 200, 404]);
+}
+
+/// The function to test liking notes
+/// through the API and through activities
+/// from other servers.
+#[actix_web::test]
+pub async fn test_note_likes(){
+    use actix_web::test;
+    let db_url: String = var("KLEAH_DB_URL")
+        .expect("Failed to read environment variable.");
+    let connection = create_connection(&db_url).await
+        .expect("Could not create connection.");
+    let instance: InstanceInformation = setup_instance(&connection).await;
+    let _author: KleahActor = setup_actor("admired", &connection).await;
+    let _liker: KleahActor = setup_actor("admirer", &connection).await;
+    let token: String = create_api_token("admirer", &connection).await
+        .expect("Could not create token.")
+        .token;
+    let keys = generate_keypair().expect("Could not generate keys.");
+    let received: Data<Mutex<Vec<String>>> = Data::new(Mutex::new(Vec::new()));
+    let (remote_host, handle) = start_remote_server(
+        Data::new(Mutex::new(keys.public_key.clone())), 
+        received.clone()
+    );
+    let remote_url: String = format!("http://{}/users/remoteuser", remote_host);
+    let key_id: String = format!("{}#main-key", remote_url);
+    let remote_author: KleahActor = upsert_remote_actor(
+        &remote_recipient("penned", &remote_host, Some(format!("http://{}/inbox", remote_host))),
+        &connection
+    ).await
+        .expect("Could not cache remote actor.");
+    let remote_note_url: String = format!("http://{}/users/penned/statuses/1", remote_host);
+    let remote_note: KleahNote = create_note(
+        &KleahNote{
+            note_id: generate_id(&remote_note_url),
+            ap_id: Some(remote_note_url.clone()),
+            author_username: remote_author.username.clone(),
+            author_host: remote_author.host.clone(),
+            content: "<p>Like me.</p>".to_string(),
+            in_reply_to: None,
+            visibility: "public".to_string(),
+            created_at: Utc::now(),
+            updated_at: None,
+            like_count: 0,
+            boost_count: 0,
+            conversation: None,
+            summary: None,
+            sensitive: false,
+            has_poll: false
+        },
+        &connection
+    ).await
+        .expect("Could not store note.");
+    let note: KleahNote = publish_note("admired", "Likeable.", None, None, None, &false, None, &connection).await
+        .expect("Could not publish note.");
+    let hidden: KleahNote = publish_note("admired", "Secret.", None, Some("followers"), None, &false, None, &connection).await
+        .expect("Could not publish note.");
+    let note_url: String = format!("https://{}/apub/notes/{}", instance.host, note.note_id);
+    let app = test::init_service(
+        App::new()
+            .app_data(Data::new(AppData{ pool: connection.clone() }))
+            .service(like_note_service)
+            .service(unlike_note_service)
+            .service(liked_service)
+            .service(shared_inbox_service)
+    ).await;
+    let interact = |path: &str, note_id: &str| {
+        test::TestRequest::post()
+            .uri(path)
+            .set_json(NoteInteractionPayload{
+                api_token: token.clone(),
+                note_id: note_id.to_string()
+            })
+            .to_request()
+    };
+    let _first: serde_json::Value = test::call_and_read_body_json(
+        &app, 
+        interact("/api/post/like", &note.note_id)
+    ).await;
+    let liked_twice: serde_json::Value = test::call_and_read_body_json(
+        &app, 
+        interact("/api/post/like", &note.note_id)
+    ).await;
+    let liked_remote: serde_json::Value = test::call_and_read_body_json(
+        &app, 
+        interact("/api/post/like", &remote_note.note_id)
+    ).await;
+    let unliked_remote: serde_json::Value = test::call_and_read_body_json(
+        &app, 
+        interact("/api/post/unlike", &remote_note.note_id)
+    ).await;
+    let unlike_again = test::call_service(&app, interact("/api/post/unlike", &remote_note.note_id)).await;
+    let like_hidden = test::call_service(&app, interact("/api/post/like", &hidden.note_id)).await;
+    process_deliveries(&connection).await
+        .expect("Could not send deliveries.");
+    let bodies: Vec<String> = wait_for_deliveries(&received, 2).await;
+    let liked: serde_json::Value = test::call_and_read_body_json(
+        &app,
+        test::TestRequest::get().uri("/apub/admirer/liked?page=true").to_request()
+    ).await;
+    let like_id: String = format!("{}#likes/1", remote_url);
+    let like: String = serde_json::json!({
+        "id": like_id,
+        "type": "Like",
+        "actor": remote_url,
+        "object": note_url
+    }).to_string();
+    let inbound_like = test::call_service(
+        &app, 
+        build_signed_request("/inbox", &keys.private_key, &key_id, &like).to_request()
+    ).await;
+    let _repeated = test::call_service(
+        &app, 
+        build_signed_request("/inbox", &keys.private_key, &key_id, &like).to_request()
+    ).await;
+    let remotely_liked: KleahNote = get_note_by_id(&note.note_id, &connection).await
+        .expect("Could not fetch note.");
+    let notifications: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM notifications WHERE username = $1 AND kind = 'like' AND note_id = $2")
+        .bind("admired")
+        .bind(&note.note_id)
+        .fetch_one(&connection)
+        .await
+        .expect("Could not count notifications.");
+    let undo: String = serde_json::json!({
+        "id": format!("{}#likes/1/undo", remote_url),
+        "type": "Undo",
+        "actor": remote_url,
+        "object": { "type": "Like", "actor": remote_url, "object": note_url }
+    }).to_string();
+    let _undone = test::call_service(
+        &app, 
+        build_signed_request("/inbox", &keys.private_key, &key_id, &undo).to_request()
+    ).await;
+    handle.stop(true).await;
+    let remotely_unliked: KleahNote = get_note_by_id(&note.note_id, &connection).await
+        .expect("Could not fetch note.");
+    let sent: Vec<serde_json::Value> = bodies
+        .iter()
+        .map(|body| serde_json::from_str(body).expect("Could not parse delivered activity."))
+        .collect();
+    let sent_like: &serde_json::Value = sent
+        .iter()
+        .find(|activity| activity["type"] == "Like")
+        .expect("No \"Like\" activity was delivered.");
+    let sent_undo: &serde_json::Value = sent
+        .iter()
+        .find(|activity| activity["type"] == "Undo")
+        .expect("No \"Undo\" activity was delivered.");
+    assert_eq!(liked_twice["likes"], 1);
+    assert_eq!(liked_remote["likes"], 1);
+    assert_eq!(unliked_remote["likes"], 0);
+    assert_eq!(unlike_again.status().as_u16(), 404);
+    assert_eq!(like_hidden.status().as_u16(), 404);
+    assert_eq!(sent_like["type"], "Like");
+    assert_eq!(sent_like["object"], remote_note_url);
+    assert_eq!(sent_like["to"], serde_json::json!([format!("http://{}/users/penned", remote_host)]));
+    assert_eq!(sent_undo["type"], "Undo");
+    assert_eq!(sent_undo["object"]["id"], sent_like["id"]);
+    assert_eq!(liked["totalItems"], 1);
+    assert_eq!(liked["orderedItems"], serde_json::json!([note_url]));
+    assert!(inbound_like.status().is_success());
+    assert_eq!(remotely_liked.like_count, 2);
+    assert_eq!(notifications, 2);
+    assert_eq!(remotely_unliked.like_count, 1);
+}
+
+/// The function to test boosting notes
+/// through the API and through activities
+/// from other servers.
+#[actix_web::test]
+pub async fn test_note_boosts(){
+    use actix_web::test;
+    let db_url: String = var("KLEAH_DB_URL")
+        .expect("Failed to read environment variable.");
+    let connection = create_connection(&db_url).await
+        .expect("Could not create connection.");
+    let instance: InstanceInformation = setup_instance(&connection).await;
+    let _author: KleahActor = setup_actor("originator", &connection).await;
+    let booster: KleahActor = setup_actor("amplifier", &connection).await;
+    let listener: KleahActor = setup_actor("listener", &connection).await;
+    create_follow("listener-amplifier", &listener, &booster, &true, &connection).await
+        .expect("Could not create follow.");
+    let token: String = create_api_token("amplifier", &connection).await
+        .expect("Could not create token.")
+        .token;
+    let listener_token: String = create_api_token("listener", &connection).await
+        .expect("Could not create token.")
+        .token;
+    let keys = generate_keypair().expect("Could not generate keys.");
+    let received: Data<Mutex<Vec<String>>> = Data::new(Mutex::new(Vec::new()));
+    let (remote_host, handle) = start_remote_server(
+        Data::new(Mutex::new(keys.public_key.clone())), 
+        received.clone()
+    );
+    let remote_url: String = format!("http://{}/users/remoteuser", remote_host);
+    let key_id: String = format!("{}#main-key", remote_url);
+    let follower: KleahActor = upsert_remote_actor(
+        &remote_recipient("echo", &remote_host, Some(format!("http://{}/inbox", remote_host))),
+        &connection
+    ).await
+        .expect("Could not cache remote actor.");
+    create_follow(
+        &format!("{}#follow", follower.ap_id.clone().unwrap_or_default()), 
+        &follower, 
+        &booster, 
+        &true, 
+        &connection
+    ).await
+        .expect("Could not create follow.");
+    let note: KleahNote = publish_note("originator", "Worth sharing.", None, None, None, &false, None, &connection).await
+        .expect("Could not publish note.");
+    let private_note: KleahNote = publish_note("originator", "Not for sharing.", None, Some("followers"), None, &false, None, &connection).await
+        .expect("Could not publish note.");
+    create_follow("amplifier-originator", &booster, &_author, &true, &connection).await
+        .expect("Could not create follow.");
+    let note_url: String = format!("https://{}/apub/notes/{}", instance.host, note.note_id);
+    let app = test::init_service(
+        App::new()
+            .app_data(Data::new(AppData{ pool: connection.clone() }))
+            .service(boost_note_service)
+            .service(unboost_note_service)
+            .service(home_timeline_service)
+            .service(outbox_service)
+            .service(shared_inbox_service)
+    ).await;
+    let interact = |path: &str, note_id: &str| {
+        test::TestRequest::post()
+            .uri(path)
+            .set_json(NoteInteractionPayload{
+                api_token: token.clone(),
+                note_id: note_id.to_string()
+            })
+            .to_request()
+    };
+    let _first: serde_json::Value = test::call_and_read_body_json(
+        &app, 
+        interact("/api/post/boost", &note.note_id)
+    ).await;
+    let boosted_twice: serde_json::Value = test::call_and_read_body_json(
+        &app, 
+        interact("/api/post/boost", &note.note_id)
+    ).await;
+    let boost_private = test::call_service(&app, interact("/api/post/boost", &private_note.note_id)).await;
+    let timeline: serde_json::Value = test::call_and_read_body_json(
+        &app,
+        test::TestRequest::get()
+            .uri("/api/timeline/home?limit=1")
+            .insert_header(bearer(&listener_token))
+            .to_request()
+    ).await;
+    let outbox: serde_json::Value = test::call_and_read_body_json(
+        &app,
+        test::TestRequest::get().uri("/apub/amplifier/outbox?page=true").to_request()
+    ).await;
+    let unboosted: serde_json::Value = test::call_and_read_body_json(
+        &app, 
+        interact("/api/post/unboost", &note.note_id)
+    ).await;
+    process_deliveries(&connection).await
+        .expect("Could not send deliveries.");
+    let bodies: Vec<String> = wait_for_deliveries(&received, 2).await;
+    let remote_note_url: String = format!("{}/statuses/1", remote_url);
+    let mut inbound_statuses: Vec<u16> = Vec::new();
+    for (number, target) in [&remote_note_url, &note_url].iter().enumerate() {
+        let announce: String = serde_json::json!({
+            "id": format!("{}/announces/{}", remote_url, number),
+            "type": "Announce",
+            "actor": remote_url,
+            "object": target
+        }).to_string();
+        let resp = test::call_service(
+            &app, 
+            build_signed_request("/inbox", &keys.private_key, &key_id, &announce).to_request()
+        ).await;
+        inbound_statuses.push(resp.status().as_u16());
+    }
+    let fetched: KleahNote = get_note_by_ap_id(&remote_note_url, &connection).await
+        .expect("Could not fetch boosted note.");
+    let remotely_boosted: KleahNote = get_note_by_id(&note.note_id, &connection).await
+        .expect("Could not fetch note.");
+    let undo: String = serde_json::json!({
+        "id": format!("{}/announces/0/undo", remote_url),
+        "type": "Undo",
+        "actor": remote_url,
+        "object": {
+            "id": format!("{}/announces/0", remote_url),
+            "type": "Announce",
+            "actor": remote_url,
+            "object": remote_note_url
+        }
+    }).to_string();
+    let _undone = test::call_service(
+        &app, 
+        build_signed_request("/inbox", &keys.private_key, &key_id, &undo).to_request()
+    ).await;
+    handle.stop(true).await;
+    let remotely_unboosted: KleahNote = get_note_by_id(&fetched.note_id, &connection).await
+        .expect("Could not fetch note.");
+    let notifications: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM notifications WHERE username = $1 AND kind = 'boost' AND note_id = $2")
+        .bind("originator")
+        .bind(&note.note_id)
+        .fetch_one(&connection)
+        .await
+        .expect("Could not count notifications.");
+    let sent: Vec<serde_json::Value> = bodies
+        .iter()
+        .map(|body| serde_json::from_str(body).expect("Could not parse delivered activity."))
+        .collect();
+    let sent_announce: &serde_json::Value = sent
+        .iter()
+        .find(|activity| activity["type"] == "Announce")
+        .expect("No \"Announce\" activity was delivered.");
+    let sent_undo: &serde_json::Value = sent
+        .iter()
+        .find(|activity| activity["type"] == "Undo")
+        .expect("No \"Undo\" activity was delivered.");
+    assert_eq!(boosted_twice["boosts"], 1);
+    assert_eq!(boost_private.status().as_u16(), 400);
+    assert_eq!(timeline["notes"][0]["note_id"], note.note_id);
+    assert_eq!(timeline["notes"][0]["boost"]["username"], "amplifier");
+    assert_eq!(outbox["orderedItems"][0]["type"], "Announce");
+    assert_eq!(outbox["orderedItems"][0]["object"], note_url);
+    assert_eq!(unboosted["boosts"], 0);
+    assert_eq!(sent_announce["type"], "Announce");
+    assert_eq!(sent_announce["object"], note_url);
+    assert_eq!(sent_announce["to"], serde_json::json!(["https://www.w3.org/ns/activitystreams#Public"]));
+    assert_eq!(sent_undo["type"], "Undo");
+    assert_eq!(sent_undo["object"]["id"], sent_announce["id"]);
+    assert!(inbound_statuses.iter().all(|status| *status < 300));
+    assert_eq!(fetched.content, "<p>Boost me.</p>");
+    assert_eq!(remotely_boosted.boost_count, 1);
+    assert_eq!(remotely_unboosted.boost_count, 0);
+    assert_eq!(notifications, 2);
+}
+
+/// The function to test replying to notes,
+/// reading the thread around a note and
+/// serving the replies to a note to other
+/// servers.
+#[actix_web::test]
+pub async fn test_note_threads(){
+    use actix_web::test;
+    let db_url: String = var("KLEAH_DB_URL")
+        .expect("Failed to read environment variable.");
+    let connection = create_connection(&db_url).await
+        .expect("Could not create connection.");
+    let instance: InstanceInformation = setup_instance(&connection).await;
+    let _opener: KleahActor = setup_actor("opener", &connection).await;
+    let _responder: KleahActor = setup_actor("responder", &connection).await;
+    let token: String = create_api_token("responder", &connection).await
+        .expect("Could not create token.")
+        .token;
+    let keys = generate_keypair().expect("Could not generate keys.");
+    let received: Data<Mutex<Vec<String>>> = Data::new(Mutex::new(Vec::new()));
+    let (remote_host, handle) = start_remote_server(
+        Data::new(Mutex::new(keys.public_key.clone())), 
+        received.clone()
+    );
+    let remote_url: String = format!("http://{}/users/remoteuser", remote_host);
+    let key_id: String = format!("{}#main-key", remote_url);
+    let root: KleahNote = publish_note("opener", "Thread start.", None, None, None, &false, None, &connection).await
+        .expect("Could not publish note.");
+    let secret: KleahNote = publish_note("opener", "Not for everyone.", None, Some("followers"), None, &false, None, &connection).await
+        .expect("Could not publish note.");
+    let root_url: String = format!("https://{}/apub/notes/{}", instance.host, root.note_id);
+    let app = test::init_service(
+        App::new()
+            .app_data(Data::new(AppData{ pool: connection.clone() }))
+            .service(create_note_service)
+            .service(note_context_service)
+            .service(apub_note_replies_service)
+            .service(apub_note_service)
+            .service(shared_inbox_service)
+    ).await;
+    let reply_to = |parent: &str| {
+        test::TestRequest::post()
+            .uri("/api/post/create")
+            .set_json(serde_json::json!({ "api_token": token, "content": "A reply.", "in_reply_to": parent }))
+            .to_request()
+    };
+    let reply: serde_json::Value = test::call_and_read_body_json(&app, reply_to(&root.note_id)).await;
+    let hidden_parent = test::call_service(&app, reply_to(&secret.note_id)).await;
+    let hidden_parent_url = test::call_service(
+        &app, 
+        reply_to(&format!("https://{}/apub/notes/{}", instance.host, secret.note_id))
+    ).await;
+    let reply_id: String = reply["note_id"].as_str().unwrap_or_default().to_string();
+    let _nested: KleahNote = publish_note("opener", "Only for followers.", Some(&reply_id), Some("followers"), None, &false, None, &connection).await
+        .expect("Could not publish note.");
+    let remote_reply_url: String = format!("{}/statuses/2", remote_url);
+    let create: String = serde_json::json!({
+        "id": format!("{}/activity", remote_reply_url),
+        "type": "Create",
+        "actor": remote_url,
+        "object": {
+            "id": remote_reply_url,
+            "type": "Note",
+            "attributedTo": remote_url,
+            "content": "<p>Remote reply.</p>",
+            "inReplyTo": root_url,
+            "context": format!("{}/contexts/1", remote_url),
+            "to": ["https://www.w3.org/ns/activitystreams#Public"],
+            "cc": [format!("{}/followers", remote_url)]
+        }
+    }).to_string();
+    let _created = test::call_service(
+        &app, 
+        build_signed_request("/inbox", &keys.private_key, &key_id, &create).to_request()
+    ).await;
+    let remote_note: KleahNote = fetch_remote_note(&format!("{}/statuses/1", remote_url), &connection).await
+        .expect("Could not fetch remote note.");
+    let outgoing: KleahNote = publish_note("responder", "Replying over there.", Some(&note_url(&remote_note)), None, None, &false, None, &connection).await
+        .expect("Could not publish note.");
+    process_deliveries(&connection).await
+        .expect("Could not send deliveries.");
+    let bodies: Vec<String> = wait_for_deliveries(&received, 1).await;
+    handle.stop(true).await;
+    let read_context = |note_id: &str, api_token: Option<&str>| {
+        let mut req = test::TestRequest::get()
+            .uri(&format!("/api/post/{}/context", note_id));
+        if let Some(api_token) = api_token {
+            req = req.insert_header(bearer(api_token));
+        }
+        req.to_request()
+    };
+    let reply_context: serde_json::Value = test::call_and_read_body_json(&app, read_context(&reply_id, None)).await;
+    let own_context: serde_json::Value = test::call_and_read_body_json(&app, read_context(&reply_id, Some(&token))).await;
+    let root_context: serde_json::Value = test::call_and_read_body_json(&app, read_context(&root.note_id, None)).await;
+    let reply_object: serde_json::Value = test::call_and_read_body_json(
+        &app,
+        test::TestRequest::get().uri(&format!("/apub/notes/{}", reply_id)).to_request()
+    ).await;
+    let root_object: serde_json::Value = test::call_and_read_body_json(
+        &app,
+        test::TestRequest::get().uri(&format!("/apub/notes/{}", root.note_id)).to_request()
+    ).await;
+    let replies: serde_json::Value = test::call_and_read_body_json(
+        &app,
+        test::TestRequest::get().uri(&format!("/apub/notes/{}/replies?page=true", root.note_id)).to_request()
+    ).await;
+    let stored_reply: KleahNote = get_note_by_ap_id(&remote_reply_url, &connection).await
+        .expect("Could not fetch note.");
+    let count_replies = |username: &str| {
+        sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM notifications WHERE username = $1 AND kind = 'reply'")
+            .bind(username.to_string())
+            .fetch_one(&connection)
+    };
+    let opener_replies: i64 = count_replies("opener").await
+        .expect("Could not count notifications.");
+    let responder_replies: i64 = count_replies("responder").await
+        .expect("Could not count notifications.");
+    let sent: serde_json::Value = serde_json::from_str(
+        bodies.first().expect("No activity was delivered.")
+    )
+        .expect("Could not parse delivered activity.");
+    let reply_url: String = format!("https://{}/apub/notes/{}", instance.host, reply_id);
+    assert_eq!(reply["in_reply_to"], root_url);
+    assert_eq!(hidden_parent.status().as_u16(), 404);
+    assert_eq!(hidden_parent_url.status().as_u16(), 404);
+    assert_eq!(reply_context["ancestors"].as_array().map(|notes| notes.len()), Some(1));
+    assert_eq!(reply_context["ancestors"][0]["note_id"], root.note_id);
+    assert_eq!(reply_context["descendants"].as_array().map(|notes| notes.len()), Some(0));
+    assert_eq!(own_context["descendants"][0]["content"], "Only for followers.");
+    assert_eq!(root_context["descendants"].as_array().map(|notes| notes.len()), Some(2));
+    assert_eq!(root_context["descendants"][0]["note_id"], reply_id);
+    assert_eq!(root_context["descendants"][1]["content"], "<p>Remote reply.</p>");
+    assert_eq!(reply_object["inReplyTo"], root_url);
+    assert_eq!(reply_object["context"], root_object["context"]);
+    assert_eq!(reply_object["conversation"], root_object["conversation"]);
+    assert_eq!(root_object["replies"], format!("{}/replies", root_url));
+    assert_eq!(stored_reply.conversation, root.conversation);
+    assert_eq!(replies["totalItems"], 2);
+    assert!(replies["orderedItems"].as_array().is_some_and(|items| items.contains(&serde_json::json!(reply_url))));
+    assert!(replies["orderedItems"].as_array().is_some_and(|items| items.contains(&serde_json::json!(remote_reply_url))));
+    assert_eq!(opener_replies, 2);
+    assert_eq!(responder_replies, 1);
+    assert_eq!(sent["object"]["id"], note_url(&outgoing));
+    assert_eq!(sent["object"]["inReplyTo"], note_url(&remote_note));
+    assert!(sent["object"]["cc"].as_array().is_some_and(|cc| cc.contains(&serde_json::json!(remote_url))));
+}
+
+/// The function to test mentioning local
+/// actors and actors on other servers in notes
+/// and receiving mentions from other servers.
+#[actix_web::test]
+pub async fn test_note_mentions(){
+    use actix_web::test;
+    let db_url: String = var("KLEAH_DB_URL")
+        .expect("Failed to read environment variable.");
+    let connection = create_connection(&db_url).await
+        .expect("Could not create connection.");
+    let instance: InstanceInformation = setup_instance(&connection).await;
+    let _herald: KleahActor = setup_actor("herald", &connection).await;
+    let addressee: KleahActor = setup_actor("addressee", &connection).await;
+    let keys = generate_keypair().expect("Could not generate keys.");
+    let received: Data<Mutex<Vec<String>>> = Data::new(Mutex::new(Vec::new()));
+    let (remote_host, handle) = start_remote_server(
+        Data::new(Mutex::new(keys.public_key.clone())), 
+        received.clone()
+    );
+    let remote_url: String = format!("http://{}/users/remoteuser", remote_host);
+    let key_id: String = format!("{}#main-key", remote_url);
+    let found: Vec<(usize, usize, String, Option<String>)> = find_mentions(
+        "Mail herald@example.com, ask @addressee. Or (@remoteuser@Example.org)"
+    );
+    let note: KleahNote = publish_note(
+        "herald", 
+        &format!("Hi @addressee and @remoteuser@{}! Not @nobody or herald@example.com.", remote_host), 
+        None, 
+        Some("direct"), 
+        None, 
+        &false, 
+        None,
+        &connection
+    ).await
+        .expect("Could not publish note.");
+    process_deliveries(&connection).await
+        .expect("Could not send deliveries.");
+    let bodies: Vec<String> = wait_for_deliveries(&received, 1).await;
+    let app = test::init_service(
+        App::new()
+            .app_data(Data::new(AppData{ pool: connection.clone() }))
+            .service(shared_inbox_service)
+    ).await;
+    let addressee_url: String = format!("https://{}/apub/addressee", instance.host);
+    let create: String = serde_json::json!({
+        "id": format!("{}/statuses/3/activity", remote_url),
+        "type": "Create",
+        "actor": remote_url,
+        "object": {
+            "id": format!("{}/statuses/3", remote_url),
+            "type": "Note",
+            "attributedTo": remote_url,
+            "content": "<p>Hello @addressee!</p>",
+            "to": ["https://www.w3.org/ns/activitystreams#Public"],
+            "cc": [format!("{}/followers", remote_url)],
+            "tag": [{ "type": "Mention", "href": addressee_url, "name": "@addressee" }]
+        }
+    }).to_string();
+    let created = test::call_service(
+        &app, 
+        build_signed_request("/inbox", &keys.private_key, &key_id, &create).to_request()
+    ).await;
+    handle.stop(true).await;
+    let recipients: Vec<KleahActor> = get_note_recipients(&note.note_id, &connection).await
+        .expect("Could not fetch recipients.");
+    let visible: bool = note_is_visible_to(&note, &addressee, &connection).await
+        .expect("Could not check visibility.");
+    let mentions: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM notifications WHERE username = $1 AND kind = 'mention'")
+        .bind("addressee")
+        .fetch_one(&connection)
+        .await
+        .expect("Could not count notifications.");
+    let inbound: KleahNote = get_note_by_ap_id(&format!("{}/statuses/3", remote_url), &connection).await
+        .expect("Could not fetch note.");
+    let inbound_visible: bool = note_is_visible_to(&inbound, &addressee, &connection).await
+        .expect("Could not check visibility.");
+    let sent: serde_json::Value = serde_json::from_str(
+        bodies.first().expect("No activity was delivered.")
+    )
+        .expect("Could not parse delivered activity.");
+    let content: String = sent["object"]["content"].as_str().unwrap_or_default().to_string();
+    assert_eq!(found.len(), 2);
+    assert_eq!(found[0].2, "addressee");
+    assert_eq!(found[0].3, None);
+    assert_eq!(found[1].3, Some("example.org".to_string()));
+    assert_eq!(recipients.len(), 2);
+    assert!(visible);
+    assert!(inbound_visible);
+    assert_eq!(mentions, 2);
+    assert!(created.status().is_success());
+    assert_eq!(sent["object"]["to"].as_array().map(|to| to.len()), Some(2));
+    assert!(sent["object"]["to"].as_array().is_some_and(|to| to.contains(&serde_json::json!(addressee_url))));
+    assert!(
+        sent["object"]["tag"].as_array().is_some_and(
+            |tags| tags.contains(
+                &serde_json::json!({
+                    "type": "Mention",
+                    "href": remote_url,
+                    "name": format!("@remoteuser@{}", remote_host)
+                })
+            )
+        )
+    );
+    assert!(content.contains(&format!("<a href=\"{}\" class=\"u-url mention\">@<span>remoteuser</span></a>", remote_url)));
+    assert!(content.contains(&format!("<a href=\"{}\" class=\"u-url mention\">@<span>addressee</span></a>", addressee_url)));
+    assert!(content.contains("Not @nobody or herald@example.com."));
+}
+
+/// The function to test storing the
+/// hashtags of notes, tagging notes with
+/// them and reading the notes carrying
+/// a hashtag.
+#[actix_web::test]
+pub async fn test_note_hashtags(){
+    use actix_web::test;
+    let db_url: String = var("KLEAH_DB_URL")
+        .expect("Failed to read environment variable.");
+    let connection = create_connection(&db_url).await
+        .expect("Could not create connection.");
+    let instance: InstanceInformation = setup_instance(&connection).await;
+    let _tagger: KleahActor = setup_actor("tagger", &connection).await;
+    let keys = generate_keypair().expect("Could not generate keys.");
+    let received: Data<Mutex<Vec<String>>> = Data::new(Mutex::new(Vec::new()));
+    let (remote_host, handle) = start_remote_server(
+        Data::new(Mutex::new(keys.public_key.clone())), 
+        received.clone()
+    );
+    let remote_url: String = format!("http://{}/users/remoteuser", remote_host);
+    let key_id: String = format!("{}#main-key", remote_url);
+    let found: Vec<String> = note_hashtags("#Rust and #rust, #KleahDev! Not a#tag, #123 or &#39;.");
+    let note: KleahNote = publish_note("tagger", "Loving #Rust and #KleahDev! Not a#tag or #123.", None, None, None, &false, None, &connection).await
+        .expect("Could not publish note.");
+    let _private_note: KleahNote = publish_note("tagger", "Quietly #rust.", None, Some("followers"), None, &false, None, &connection).await
+        .expect("Could not publish note.");
+    let app = test::init_service(
+        App::new()
+            .app_data(Data::new(AppData{ pool: connection.clone() }))
+            .service(hashtag_timeline_service)
+            .service(hashtag_collection_service)
+            .service(apub_note_service)
+            .service(shared_inbox_service)
+    ).await;
+    let create: String = serde_json::json!({
+        "id": format!("{}/statuses/4/activity", remote_url),
+        "type": "Create",
+        "actor": remote_url,
+        "object": {
+            "id": format!("{}/statuses/4", remote_url),
+            "type": "Note",
+            "attributedTo": remote_url,
+            "content": "<p>Also <a href=\"https://example.org/tags/rust\">#RUST</a></p>",
+            "to": ["https://www.w3.org/ns/activitystreams#Public"],
+            "cc": [format!("{}/followers", remote_url)],
+            "tag": [{ "type": "Hashtag", "href": "https://example.org/tags/rust", "name": "#RUST" }]
+        }
+    }).to_string();
+    let _created = test::call_service(
+        &app, 
+        build_signed_request("/inbox", &keys.private_key, &key_id, &create).to_request()
+    ).await;
+    handle.stop(true).await;
+    let timeline: serde_json::Value = test::call_and_read_body_json(
+        &app,
+        test::TestRequest::get().uri("/api/timeline/tag/Rust").to_request()
+    ).await;
+    let invalid = test::call_service(
+        &app,
+        test::TestRequest::get().uri("/api/timeline/tag/123").to_request()
+    ).await;
+    let collection: serde_json::Value = test::call_and_read_body_json(
+        &app,
+        test::TestRequest::get().uri("/tags/rust").to_request()
+    ).await;
+    let page: serde_json::Value = test::call_and_read_body_json(
+        &app,
+        test::TestRequest::get().uri("/tags/RUST?page=true").to_request()
+    ).await;
+    let object: serde_json::Value = test::call_and_read_body_json(
+        &app,
+        test::TestRequest::get().uri(&format!("/apub/notes/{}", note.note_id)).to_request()
+    ).await;
+    let tag_url: String = format!("https://{}/tags/rust", instance.host);
+    let note_url: String = format!("https://{}/apub/notes/{}", instance.host, note.note_id);
+    assert_eq!(found, vec!["rust".to_string(), "kleahdev".to_string()]);
+    assert_eq!(timeline["notes"].as_array().map(|notes| notes.len()), Some(2));
+    assert_eq!(timeline["notes"][0]["host"], remote_host);
+    assert_eq!(timeline["notes"][1]["note_id"], note.note_id);
+    assert_eq!(invalid.status().as_u16(), 400);
+    assert_eq!(collection["id"], tag_url);
+    assert_eq!(collection["totalItems"], 1);
+    assert_eq!(page["orderedItems"], serde_json::json!([note_url]));
+    assert_eq!(
+        object["tag"],
+        serde_json::json!([
+            { "type": "Hashtag", "href": tag_url, "name": "#rust" },
+            { "type": "Hashtag", "href": format!("https://{}/tags/kleahdev", instance.host), "name": "#kleahdev" }
+        ])
+    );
+    assert!(
+        object["content"].as_str().is_some_and(
+            |content| content.contains(
+                &format!("<a href=\"{}\" class=\"mention hashtag\" rel=\"tag\">#<span>Rust</span></a>", tag_url)
+            ) && content.contains("Not a#tag or #123.")
+        )
+    );
+}
+
+/// The function to test editing notes,
+/// keeping their prior revisions and sending
+/// and receiving "Update" activities for them.
+#[actix_web::test]
+pub async fn test_note_edits(){
+    use actix_web::test;
+    let db_url: String = var("KLEAH_DB_URL")
+        .expect("Failed to read environment variable.");
+    let connection = create_connection(&db_url).await
+        .expect("Could not create connection.");
+    let instance: InstanceInformation = setup_instance(&connection).await;
+    let reviser: KleahActor = setup_actor("reviser", &connection).await;
+    let _bystander: KleahActor = setup_actor("bystander", &connection).await;
+    let token: String = create_api_token("reviser", &connection).await
+        .expect("Could not create token.")
+        .token;
+    let bystander_token: String = create_api_token("bystander", &connection).await
+        .expect("Could not create token.")
+        .token;
+    let keys = generate_keypair().expect("Could not generate keys.");
+    let received: Data<Mutex<Vec<String>>> = Data::new(Mutex::new(Vec::new()));
+    let (remote_host, handle) = start_remote_server(
+        Data::new(Mutex::new(keys.public_key.clone())), 
+        received.clone()
+    );
+    let remote_url: String = format!("http://{}/users/remoteuser", remote_host);
+    let key_id: String = format!("{}#main-key", remote_url);
+    let follower: KleahActor = upsert_remote_actor(
+        &remote_recipient("reader", &remote_host, Some(format!("http://{}/inbox", remote_host))),
+        &connection
+    ).await
+        .expect("Could not cache remote actor.");
+    create_follow(
+        &format!("{}#follow", follower.ap_id.clone().unwrap_or_default()), 
+        &follower, 
+        &reviser, 
+        &true, 
+        &connection
+    ).await
+        .expect("Could not create follow.");
+    let note: KleahNote = publish_note("reviser", "First draft about #drafts.", None, None, None, &false, None, &connection).await
+        .expect("Could not publish note.");
+    let app = test::init_service(
+        App::new()
+            .app_data(Data::new(AppData{ pool: connection.clone() }))
+            .service(edit_note_service)
+            .service(note_history_service)
+            .service(hashtag_timeline_service)
+            .service(shared_inbox_service)
+    ).await;
+    let edit = |api_token: &str, content: &str, summary: Option<&str>| {
+        test::TestRequest::post()
+            .uri("/api/post/edit")
+            .set_json(NoteEditPayload{
+                api_token: api_token.to_string(),
+                note_id: note.note_id.clone(),
+                content: content.to_string(),
+                summary: summary.map(|summary| summary.to_string()),
+                sensitive: None
+            })
+            .to_request()
+    };
+    let edited: serde_json::Value = test::call_and_read_body_json(
+        &app, 
+        edit(&token, "Final version about #finals.", Some("Ending"))
+    ).await;
+    let foreign = test::call_service(&app, edit(&bystander_token, "Hijacked.", None)).await;
+    let empty = test::call_service(&app, edit(&token, "   ", None)).await;
+    process_deliveries(&connection).await
+        .expect("Could not send deliveries.");
+    let bodies: Vec<String> = wait_for_deliveries(&received, 2).await;
+    let history: serde_json::Value = test::call_and_read_body_json(
+        &app,
+        test::TestRequest::get().uri(&format!("/api/post/{}/history", note.note_id)).to_request()
+    ).await;
+    let _uncovered = test::call_service(
+        &app,
+        test::TestRequest::post()
+            .uri("/api/post/edit")
+            .set_json(NoteEditPayload{
+                api_token: token.clone(),
+                note_id: note.note_id.clone(),
+                content: "Final version about #finals.".to_string(),
+                summary: Some(String::new()),
+                sensitive: Some(false)
+            })
+            .to_request()
+    ).await;
+    process_deliveries(&connection).await
+        .expect("Could not send deliveries.");
+    let uncovered_history: serde_json::Value = test::call_and_read_body_json(
+        &app,
+        test::TestRequest::get().uri(&format!("/api/post/{}/history", note.note_id)).insert_header(bearer(&token)).to_request()
+    ).await;
+    let old_tag: serde_json::Value = test::call_and_read_body_json(
+        &app,
+        test::TestRequest::get().uri("/api/timeline/tag/drafts").to_request()
+    ).await;
+    let new_tag: serde_json::Value = test::call_and_read_body_json(
+        &app,
+        test::TestRequest::get().uri("/api/timeline/tag/finals").to_request()
+    ).await;
+    let remote_note_url: String = format!("{}/statuses/5", remote_url);
+    let inbound = |activity: serde_json::Value| {
+        build_signed_request("/inbox", &keys.private_key, &key_id, &activity.to_string()).to_request()
+    };
+    let remote_note = |content: &str, updated: Option<&str>| {
+        let mut object: serde_json::Value = serde_json::json!({
+            "id": remote_note_url,
+            "type": "Note",
+            "attributedTo": remote_url,
+            "content": content,
+            "published": "2024-01-01T00:00:00Z",
+            "to": ["https://www.w3.org/ns/activitystreams#Public"],
+            "cc": [format!("{}/followers", remote_url)]
+        });
+        if let Some(updated) = updated {
+            object["updated"] = serde_json::json!(updated);
+        }
+        object
+    };
+    let _created = test::call_service(
+        &app,
+        inbound(serde_json::json!({
+            "id": format!("{}/activity", remote_note_url),
+            "type": "Create",
+            "actor": remote_url,
+            "object": remote_note("<p>Remote draft</p>", None)
+        }))
+    ).await;
+    let mut remote_final: serde_json::Value = remote_note(
+        "<p>Remote final</p><script>alert(1)</script>", 
+        Some("2099-01-01T00:00:00Z")
+    );
+    remote_final["summary"] = serde_json::json!("Remote warning");
+    let updated = test::call_service(
+        &app,
+        inbound(serde_json::json!({
+            "id": format!("{}#updates/1", remote_note_url),
+            "type": "Update",
+            "actor": remote_url,
+            "object": remote_final
+        }))
+    ).await;
+    let stale = test::call_service(
+        &app,
+        inbound(serde_json::json!({
+            "id": format!("{}#updates/0", remote_note_url),
+            "type": "Update",
+            "actor": remote_url,
+            "object": remote_note("<p>Stale</p>", Some("2098-01-01T00:00:00Z"))
+        }))
+    ).await;
+    let local_url: String = format!("https://{}/apub/notes/{}", instance.host, note.note_id);
+    let spoofed = test::call_service(
+        &app,
+        inbound(serde_json::json!({
+            "id": format!("{}#updates/2", remote_url),
+            "type": "Update",
+            "actor": remote_url,
+            "object": {
+                "id": local_url,
+                "type": "Note",
+                "attributedTo": remote_url,
+                "content": "<p>Spoofed</p>",
+                "updated": "2099-01-01T00:00:00Z"
+            }
+        }))
+    ).await;
+    handle.stop(true).await;
+    let stored: KleahNote = get_note_by_id(&note.note_id, &connection).await
+        .expect("Could not fetch note.");
+    let remote_stored: KleahNote = get_note_by_ap_id(&remote_note_url, &connection).await
+        .expect("Could not fetch note.");
+    let remote_edits: Vec<KleahNoteEdit> = get_note_edits(&remote_stored.note_id, &connection).await
+        .expect("Could not fetch edits.");
+    let activities: Vec<serde_json::Value> = bodies
+        .iter()
+        .map(|body| serde_json::from_str(body).expect("Could not parse delivered activity."))
+        .collect();
+    let update: &serde_json::Value = activities
+        .iter()
+        .find(|activity| activity["type"] == "Update")
+        .expect("No update was delivered.");
+    assert_eq!(edited["content"], "Final version about #finals.");
+    assert_eq!(edited["summary"], "Ending");
+    assert_eq!(edited["sensitive"], true);
+    assert_eq!(foreign.status().as_u16(), 401);
+    assert_eq!(empty.status().as_u16(), 400);
+    assert_eq!(stored.content, "Final version about #finals.");
+    assert!(stored.updated_at.is_some());
+    assert_eq!(stored.summary, None);
+    assert!(!stored.sensitive);
+    assert_eq!(history["revisions"].as_array().map(|revisions| revisions.len()), Some(2));
+    assert_eq!(history["revisions"][0]["content"], "First draft about #drafts.");
+    assert_eq!(history["revisions"][1]["content"], "Final version about #finals.");
+    assert_eq!(history["revisions"][1]["summary"], "Ending");
+    assert_eq!(uncovered_history["revisions"].as_array().map(|revisions| revisions.len()), Some(3));
+    assert_eq!(uncovered_history["revisions"][0]["summary"], serde_json::Value::Null);
+    assert_eq!(uncovered_history["revisions"][0]["sensitive"], false);
+    assert_eq!(uncovered_history["revisions"][1]["summary"], "Ending");
+    assert_eq!(uncovered_history["revisions"][1]["sensitive"], true);
+    assert_eq!(uncovered_history["revisions"][2]["summary"], serde_json::Value::Null);
+    assert_eq!(uncovered_history["revisions"][2]["sensitive"], false);
+    assert_eq!(old_tag["notes"].as_array().map(|notes| notes.len()), Some(0));
+    assert_eq!(new_tag["notes"][0]["note_id"], note.note_id);
+    assert_eq!(update["object"]["id"], local_url);
+    assert!(update["object"]["updated"].is_string());
+    assert_eq!(update["object"]["summary"], "Ending");
+    assert_eq!(update["object"]["sensitive"], true);
+    assert!(
+        update["object"]["content"].as_str().is_some_and(|content| content.contains("Final version"))
+    );
+    assert!(updated.status().is_success());
+    assert!(stale.status().is_success());
+    assert_eq!(remote_stored.content, "<p>Remote final</p>");
+    assert_eq!(remote_stored.summary, Some("Remote warning".to_string()));
+    assert!(remote_stored.sensitive);
+    assert_eq!(remote_edits.len(), 1);
+    assert_eq!(remote_edits[0].content, "<p>Remote draft</p>");
+    assert_eq!(spoofed.status().as_u16(), 401);
+}
+
+/// The function to test deleting notes,
+/// serving their tombstones and sending and
+/// receiving "Delete" activities for them.
+#[actix_web::test]
+pub async fn test_note_deletions(){
+    use actix_web::test;
+    let db_url: String = var("KLEAH_DB_URL")
+        .expect("Failed to read environment variable.");
+    let connection = create_connection(&db_url).await
+        .expect("Could not create connection.");
+    let instance: InstanceInformation = setup_instance(&connection).await;
+    let retractor: KleahActor = setup_actor("retractor", &connection).await;
+    let _admirer: KleahActor = setup_actor("admirer", &connection).await;
+    let token: String = create_api_token("retractor", &connection).await
+        .expect("Could not create token.")
+        .token;
+    let admirer_token: String = create_api_token("admirer", &connection).await
+        .expect("Could not create token.")
+        .token;
+    let keys = generate_keypair().expect("Could not generate keys.");
+    let received: Data<Mutex<Vec<String>>> = Data::new(Mutex::new(Vec::new()));
+    let (remote_host, handle) = start_remote_server(
+        Data::new(Mutex::new(keys.public_key.clone())), 
+        received.clone()
+    );
+    let remote_url: String = format!("http://{}/users/remoteuser", remote_host);
+    let key_id: String = format!("{}#main-key", remote_url);
+    let follower: KleahActor = upsert_remote_actor(
+        &remote_recipient("watcher", &remote_host, Some(format!("http://{}/inbox", remote_host))),
+        &connection
+    ).await
+        .expect("Could not cache remote actor.");
+    create_follow(
+        &format!("{}#follow", follower.ap_id.clone().unwrap_or_default()), 
+        &follower, 
+        &retractor, 
+        &true, 
+        &connection
+    ).await
+        .expect("Could not create follow.");
+    let note: KleahNote = publish_note(
+        "retractor", 
+        &format!("Regrettable, @remoteuser@{}.", remote_host), 
+        None, 
+        None, 
+        None, 
+        &false, 
+        None,
+        &connection
+    ).await
+        .expect("Could not publish note.");
+    let private_note: KleahNote = publish_note("retractor", "Also regrettable.", None, Some("followers"), None, &false, None, &connection).await
+        .expect("Could not publish note.");
+    let admired: KleahNote = publish_note("admirer", "Here to stay.", None, None, None, &false, None, &connection).await
+        .expect("Could not publish note.");
+    let app = test::init_service(
+        App::new()
+            .app_data(Data::new(AppData{ pool: connection.clone() }))
+            .service(like_note_service)
+            .service(boost_note_service)
+            .service(delete_note_service)
+            .service(apub_note_service)
+            .service(shared_inbox_service)
+    ).await;
+    let interact = |path: &str, api_token: &str, note_id: &str| {
+        test::TestRequest::post()
+            .uri(path)
+            .set_json(serde_json::json!({ "api_token": api_token, "note_id": note_id }))
+            .to_request()
+    };
+    let _liked = test::call_service(&app, interact("/api/post/like", &admirer_token, &note.note_id)).await;
+    let _boosted = test::call_service(&app, interact("/api/post/boost", &admirer_token, &note.note_id)).await;
+    let deleted = test::call_service(&app, interact("/api/post/delete", &token, &note.note_id)).await;
+    let private_deleted = test::call_service(&app, interact("/api/post/delete", &token, &private_note.note_id)).await;
+    process_deliveries(&connection).await
+        .expect("Could not send deliveries.");
+    let bodies: Vec<String> = wait_for_deliveries(&received, 4).await;
+    let tombstone = test::call_service(
+        &app,
+        test::TestRequest::get().uri(&format!("/apub/notes/{}", note.note_id)).to_request()
+    ).await;
+    let tombstone_status: u16 = tombstone.status().as_u16();
+    let tombstone: serde_json::Value = serde_json::from_slice(&test::read_body(tombstone).await)
+        .expect("Could not parse tombstone.");
+    let private_tombstone = test::call_service(
+        &app,
+        test::TestRequest::get().uri(&format!("/apub/notes/{}", private_note.note_id)).to_request()
+    ).await;
+    let remote_note_url: String = format!("{}/statuses/7", remote_url);
+    let inbound = |activity: serde_json::Value| {
+        build_signed_request("/inbox", &keys.private_key, &key_id, &activity.to_string()).to_request()
+    };
+    let _created = test::call_service(
+        &app,
+        inbound(serde_json::json!({
+            "id": format!("{}/activity", remote_note_url),
+            "type": "Create",
+            "actor": remote_url,
+            "object": {
+                "id": remote_note_url,
+                "type": "Note",
+                "attributedTo": remote_url,
+                "content": "<p>Soon gone</p>",
+                "to": ["https://www.w3.org/ns/activitystreams#Public"],
+                "cc": [format!("{}/followers", remote_url)]
+            }
+        }))
+    ).await;
+    let remote_note: KleahNote = get_note_by_ap_id(&remote_note_url, &connection).await
+        .expect("Could not fetch note.");
+    let _remote_liked = test::call_service(&app, interact("/api/post/like", &admirer_token, &remote_note.note_id)).await;
+    let remote_deleted = test::call_service(
+        &app,
+        inbound(serde_json::json!({
+            "id": format!("{}#delete", remote_note_url),
+            "type": "Delete",
+            "actor": remote_url,
+            "object": { "id": remote_note_url, "type": "Tombstone" }
+        }))
+    ).await;
+    let spoofed = test::call_service(
+        &app,
+        inbound(serde_json::json!({
+            "id": format!("{}#delete", remote_url),
+            "type": "Delete",
+            "actor": remote_url,
+            "object": format!("https://{}/apub/notes/{}", instance.host, admired.note_id)
+        }))
+    ).await;
+    handle.stop(true).await;
+    let mut counts: Vec<i64> = Vec::new();
+    for (query, note_id) in [
+        ("SELECT COUNT(*) FROM likes WHERE note_id = $1", &note.note_id),
+        ("SELECT COUNT(*) FROM boosts WHERE note_id = $1", &note.note_id),
+        ("SELECT COUNT(*) FROM notifications WHERE note_id = $1", &note.note_id),
+        ("SELECT COUNT(*) FROM likes WHERE note_id = $1", &remote_note.note_id)
+    ] {
+        counts.push(
+            sqlx::query_scalar(query)
+                .bind(note_id)
+                .fetch_one(&connection)
+                .await
+                .expect("Could not count rows.")
+        );
+    }
+    let note_url: String = format!("https://{}/apub/notes/{}", instance.host, note.note_id);
+    let activities: Vec<serde_json::Value> = bodies
+        .iter()
+        .map(|body| serde_json::from_str(body).expect("Could not parse delivered activity."))
+        .collect();
+    let delete: &serde_json::Value = activities
+        .iter()
+        .find(|activity| activity["type"] == "Delete" && activity["object"]["id"] == note_url)
+        .expect("No deletion was delivered.");
+    let private_url: String = format!("https://{}/apub/notes/{}", instance.host, private_note.note_id);
+    let private_delete: &serde_json::Value = activities
+        .iter()
+        .find(|activity| activity["type"] == "Delete" && activity["object"]["id"] == private_url)
+        .expect("No deletion of the private note was delivered.");
+    assert!(deleted.status().is_success());
+    assert!(get_note_by_id(&note.note_id, &connection).await.is_err());
+    assert_eq!(counts[..3], [0, 0, 0]);
+    assert_eq!(tombstone_status, 410);
+    assert_eq!(tombstone["type"], "Tombstone");
+    assert_eq!(tombstone["id"], note_url);
+    assert_eq!(tombstone["formerType"], "Note");
+    assert!(private_deleted.status().is_success());
+    assert!(get_note_by_id(&private_note.note_id, &connection).await.is_err());
+    assert_eq!(private_tombstone.status().as_u16(), 404);
+    assert_eq!(private_delete["object"]["type"], "Tombstone");
+    assert!(private_delete["to"].as_array().is_some_and(|to| !to.contains(&serde_json::json!("https://www.w3.org/ns/activitystreams#Public"))));
+    assert_eq!(delete["id"], format!("{}#delete", note_url));
+    assert_eq!(delete["object"]["id"], note_url);
+    assert_eq!(delete["object"]["type"], "Tombstone");
+    assert!(delete["cc"].as_array().is_some_and(|cc| cc.contains(&serde_json::json!(remote_url))));
+    assert!(remote_deleted.status().is_success());
+    assert!(get_note_by_ap_id(&remote_note_url, &connection).await.is_err());
+    assert_eq!(counts[3], 0);
+    assert_eq!(spoofed.status().as_u16(), 401);
+    assert!(get_note_by_id(&admired.note_id, &connection).await.is_ok());
+}
+
+/// The function to test publishing and
+/// editing notes with a content warning or
+/// as sensitive, federating both and expanding content
+/// warnings for users who prefer this.
+#[actix_web::test]
+pub async fn test_note_content_warnings(){
+    use actix_web::test;
+    let db_url: String = var("KLEAH_DB_URL")
+        .expect("Failed to read environment variable.");
+    let connection = create_connection(&db_url).await
+        .expect("Could not create connection.");
+    let _instance: InstanceInformation = setup_instance(&connection).await;
+    let cautious: KleahActor = setup_actor("cautious", &connection).await;
+    let token: String = create_api_token("cautious", &connection).await
+        .expect("Could not create token.")
+        .token;
+    let keys = generate_keypair().expect("Could not generate keys.");
+    let received: Data<Mutex<Vec<String>>> = Data::new(Mutex::new(Vec::new()));
+    let (remote_host, handle) = start_remote_server(
+        Data::new(Mutex::new(keys.public_key.clone())), 
+        received.clone()
+    );
+    let remote_url: String = format!("http://{}/users/remoteuser", remote_host);
+    let key_id: String = format!("{}#main-key", remote_url);
+    let follower: KleahActor = upsert_remote_actor(
+        &remote_recipient("spectator", &remote_host, Some(format!("http://{}/inbox", remote_host))),
+        &connection
+    ).await
+        .expect("Could not cache remote actor.");
+    create_follow(
+        &format!("{}#follow", follower.ap_id.clone().unwrap_or_default()), 
+        &follower, 
+        &cautious, 
+        &true, 
+        &connection
+    ).await
+        .expect("Could not create follow.");
+    let app = test::init_service(
+        App::new()
+            .app_data(Data::new(AppData{ pool: connection.clone() }))
+            .service(create_note_service)
+            .service(edit_note_service)
+            .service(get_note_service)
+            .service(update_spoilers_service)
+            .service(local_timeline_service)
+            .service(shared_inbox_service)
+    ).await;
+    let create = |content: &str, summary: Option<&str>, sensitive: Option<bool>| {
+        test::TestRequest::post()
+            .uri("/api/post/create")
+            .set_json(NoteCreatePayload{
+                api_token: token.clone(),
+                content: content.to_string(),
+                in_reply_to: None,
+                visibility: None,
+                summary: summary.map(|summary| summary.to_string()),
+                sensitive,
+                poll: None
+            })
+            .to_request()
+    };
+    let warned: serde_json::Value = test::call_and_read_body_json(
+        &app, 
+        create("The butler did it.", Some(" Spoilers ahead "), None)
+    ).await;
+    let flagged: serde_json::Value = test::call_and_read_body_json(
+        &app, 
+        create("A picture.", Some("   "), Some(true))
+    ).await;
+    let too_long = test::call_service(&app, create("Short.", Some(&"a".repeat(495)), None)).await;
+    process_deliveries(&connection).await
+        .expect("Could not send deliveries.");
+    let bodies: Vec<String> = wait_for_deliveries(&received, 2).await;
+    let remote_note_url: String = format!("{}/statuses/8", remote_url);
+    let inbound: String = serde_json::json!({
+        "id": format!("{}/activity", remote_note_url),
+        "type": "Create",
+        "actor": remote_url,
+        "object": {
+            "id": remote_note_url,
+            "type": "Note",
+            "attributedTo": remote_url,
+            "content": "<p>Dinner tonight</p>",
+            "summary": "food <script>alert(1)</script>",
+            "sensitive": false,
+            "to": ["https://www.w3.org/ns/activitystreams#Public"],
+            "cc": [format!("{}/followers", remote_url)]
+        }
+    }).to_string();
+    let _created = test::call_service(
+        &app, 
+        build_signed_request("/inbox", &keys.private_key, &key_id, &inbound).to_request()
+    ).await;
+    handle.stop(true).await;
+    let note_id: String = warned["note_id"].as_str().unwrap_or_default().to_string();
+    let edit = |note_id: &str, content: &str, summary: Option<&str>, sensitive: Option<bool>| {
+        test::TestRequest::post()
+            .uri("/api/post/edit")
+            .set_json(NoteEditPayload{
+                api_token: token.clone(),
+                note_id: note_id.to_string(),
+                content: content.to_string(),
+                summary: summary.map(|summary| summary.to_string()),
+                sensitive
+            })
+            .to_request()
+    };
+    let kept: serde_json::Value = test::call_and_read_body_json(
+        &app,
+        edit(&note_id, "The gardener did it.", None, None)
+    ).await;
+    let unflagged: serde_json::Value = test::call_and_read_body_json(
+        &app,
+        edit(flagged["note_id"].as_str().unwrap_or_default(), "A picture.", Some(""), Some(false))
+    ).await;
+    let too_long_edit = test::call_service(
+        &app, 
+        edit(&note_id, "Short.", Some(&"a".repeat(495)), None)
+    ).await;
+    let anonymous: serde_json::Value = test::call_and_read_body_json(
+        &app,
+        test::TestRequest::get().uri(&format!("/api/post/{}", note_id)).to_request()
+    ).await;
+    let preferred: serde_json::Value = test::call_and_read_body_json(
+        &app,
+        test::TestRequest::post()
+            .uri("/api/user/edit/spoilers")
+            .set_json(EditSpoilersPayload{ api_token: token.clone(), expand_spoilers: true })
+            .to_request()
+    ).await;
+    let expanded: serde_json::Value = test::call_and_read_body_json(
+        &app,
+        test::TestRequest::get().uri(&format!("/api/post/{}", note_id)).insert_header(bearer(&token)).to_request()
+    ).await;
+    let timeline: serde_json::Value = test::call_and_read_body_json(
+        &app,
+        test::TestRequest::get().uri("/api/timeline/local").insert_header(bearer(&token)).to_request()
+    ).await;
+    let remote_note: KleahNote = get_note_by_ap_id(&remote_note_url, &connection).await
+        .expect("Could not fetch note.");
+    let activities: Vec<serde_json::Value> = bodies
+        .iter()
+        .map(|body| serde_json::from_str(body).expect("Could not parse delivered activity."))
+        .collect();
+    let sent: &serde_json::Value = activities
+        .iter()
+        .find(|activity| activity["object"]["content"].as_str().is_some_and(|content| content.contains("butler")))
+        .expect("No note with a content warning was delivered.");
+    let sent_flagged: &serde_json::Value = activities
+        .iter()
+        .find(|activity| activity["object"]["content"].as_str().is_some_and(|content| content.contains("picture")))
+        .expect("No sensitive note was delivered.");
+    assert_eq!(warned["summary"], "Spoilers ahead");
+    assert_eq!(warned["sensitive"], true);
+    assert_eq!(warned["expanded"], false);
+    assert_eq!(flagged["summary"], serde_json::Value::Null);
+    assert_eq!(flagged["sensitive"], true);
+    assert_eq!(flagged["expanded"], true);
+    assert_eq!(too_long.status().as_u16(), 400);
+    assert_eq!(kept["summary"], "Spoilers ahead");
+    assert_eq!(kept["sensitive"], true);
+    assert_eq!(unflagged["summary"], serde_json::Value::Null);
+    assert_eq!(unflagged["sensitive"], false);
+    assert_eq!(too_long_edit.status().as_u16(), 400);
+    assert_eq!(sent["object"]["summary"], "Spoilers ahead");
+    assert_eq!(sent["object"]["sensitive"], true);
+    assert!(sent_flagged["object"].get("summary").is_none());
+    assert_eq!(sent_flagged["object"]["sensitive"], true);
+    assert_eq!(remote_note.summary, Some("food".to_string()));
+    assert!(remote_note.sensitive);
+    assert_eq!(anonymous["expanded"], false);
+    assert_eq!(preferred["status"], true);
+    assert_eq!(expanded["expanded"], true);
+    assert!(
+        timeline["notes"].as_array().is_some_and(
+            |notes| !notes.is_empty() && notes.iter().all(|note| note["expanded"] == true)
+        )
+    );
+}
+
+/// The function to test publishing polls,
+/// voting in local and remote polls, federating
+/// polls and votes and closing polls that have
+/// ended.
+#[actix_web::test]
+pub async fn test_note_polls(){
+    use actix_web::test;
+    let db_url: String = var("KLEAH_DB_URL")
+        .expect("Failed to read environment variable.");
+    let connection = create_connection(&db_url).await
+        .expect("Could not create connection.");
+    let instance: InstanceInformation = setup_instance(&connection).await;
+    let pollster: KleahActor = setup_actor("pollster", &connection).await;
+    let voter: KleahActor = setup_actor("voter", &connection).await;
+    let _chooser: KleahActor = setup_actor("chooser", &connection).await;
+    let token: String = create_api_token("pollster", &connection).await
+        .expect("Could not create token.")
+        .token;
+    let voter_token: String = create_api_token("voter", &connection).await
+        .expect("Could not create token.")
+        .token;
+    let chooser_token: String = create_api_token("chooser", &connection).await
+        .expect("Could not create token.")
+        .token;
+    let keys = generate_keypair().expect("Could not generate keys.");
+    let received: Data<Mutex<Vec<String>>> = Data::new(Mutex::new(Vec::new()));
+    let (remote_host, handle) = start_remote_server(
+        Data::new(Mutex::new(keys.public_key.clone())), 
+        received.clone()
+    );
+    let remote_url: String = format!("http://{}/users/remoteuser", remote_host);
+    let key_id: String = format!("{}#main-key", remote_url);
+    let follower: KleahActor = upsert_remote_actor(
+        &remote_recipient("canvassed", &remote_host, Some(format!("http://{}/inbox", remote_host))),
+        &connection
+    ).await
+        .expect("Could not cache remote actor.");
+    create_follow(
+        &format!("{}#follow", follower.ap_id.clone().unwrap_or_default()), 
+        &follower, 
+        &pollster, 
+        &true, 
+        &connection
+    ).await
+        .expect("Could not create follow.");
+    let app = test::init_service(
+        App::new()
+            .app_data(Data::new(AppData{ pool: connection.clone() }))
+            .service(create_note_service)
+            .service(vote_poll_service)
+            .service(poll_service)
+            .service(shared_inbox_service)
+    ).await;
+    let create = |options: serde_json::Value, expires_in: i64| {
+        test::TestRequest::post()
+            .uri("/api/post/create")
+            .set_json(serde_json::json!({
+                "api_token": token,
+                "content": "Tea or coffee?",
+                "poll": { "options": options, "expires_in": expires_in }
+            }))
+            .to_request()
+    };
+    let vote = |api_token: &str, note_id: &str, choices: serde_json::Value| {
+        test::TestRequest::post()
+            .uri("/api/post/vote")
+            .set_json(serde_json::json!({ "api_token": api_token, "note_id": note_id, "choices": choices }))
+            .to_request()
+    };
+    let created: serde_json::Value = test::call_and_read_body_json(
+        &app,
+        create(serde_json::json!(["Tea", "Coffee"]), 3600)
+    ).await;
+    let too_few = test::call_service(&app, create(serde_json::json!(["Tea"]), 3600)).await;
+    let duplicated = test::call_service(&app, create(serde_json::json!(["Tea", " Tea "]), 3600)).await;
+    let too_short = test::call_service(&app, create(serde_json::json!(["Tea", "Coffee"]), 10)).await;
+    let note_id: String = created["note_id"].as_str().unwrap_or_default().to_string();
+    let note_url: String = format!("https://{}/apub/notes/{}", instance.host, note_id);
+    let voted: serde_json::Value = test::call_and_read_body_json(
+        &app,
+        vote(&voter_token, &note_id, serde_json::json!([0]))
+    ).await;
+    let repeated = test::call_service(&app, vote(&voter_token, &note_id, serde_json::json!([1]))).await;
+    let own = test::call_service(&app, vote(&token, &note_id, serde_json::json!([0]))).await;
+    let too_many = test::call_service(&app, vote(&chooser_token, &note_id, serde_json::json!([0, 1]))).await;
+    let out_of_range = test::call_service(&app, vote(&chooser_token, &note_id, serde_json::json!([5]))).await;
+    let _chosen = test::call_service(&app, vote(&chooser_token, &note_id, serde_json::json!([1]))).await;
+    let inbound = |activity: serde_json::Value| {
+        build_signed_request("/inbox", &keys.private_key, &key_id, &activity.to_string()).to_request()
+    };
+    let remote_vote = test::call_service(
+        &app,
+        inbound(serde_json::json!({
+            "id": format!("{}#votes/1/activity", remote_url),
+            "type": "Create",
+            "actor": remote_url,
+            "object": {
+                "id": format!("{}#votes/1", remote_url),
+                "type": "Note",
+                "name": "Coffee",
+                "attributedTo": remote_url,
+                "inReplyTo": note_url,
+                "to": [actor_url(&pollster)]
+            }
+        }))
+    ).await;
+    let tallied: serde_json::Value = test::call_and_read_body_json(
+        &app,
+        test::TestRequest::get().uri(&format!("/api/post/{}/poll", note_id)).to_request()
+    ).await;
+    let remote_poll_url: String = format!("{}/statuses/9", remote_url);
+    let _asked = test::call_service(
+        &app,
+        inbound(serde_json::json!({
+            "id": format!("{}/activity", remote_poll_url),
+            "type": "Create",
+            "actor": remote_url,
+            "object": {
+                "id": remote_poll_url,
+                "type": "Question",
+                "attributedTo": remote_url,
+                "content": "<p>Cats or dogs?</p>",
+                "endTime": (Utc::now() + chrono::TimeDelta::days(1)).to_rfc3339(),
+                "votersCount": 4,
+                "oneOf": [
+                    { "type": "Note", "name": "Cats", "replies": { "type": "Collection", "totalItems": 3 } },
+                    { "type": "Note", "name": "Dogs", "replies": { "type": "Collection", "totalItems": 1 } }
+                ],
+                "to": ["https://www.w3.org/ns/activitystreams#Public"],
+                "cc": [format!("{}/followers", remote_url)]
+            }
+        }))
+    ).await;
+    let remote_poll: KleahNote = get_note_by_ap_id(&remote_poll_url, &connection).await
+        .expect("Could not fetch note.");
+    let remote_voted: serde_json::Value = test::call_and_read_body_json(
+        &app,
+        vote(&voter_token, &remote_poll.note_id, serde_json::json!([1]))
+    ).await;
+    let _retallied = test::call_service(
+        &app,
+        inbound(serde_json::json!({
+            "id": format!("{}#updates/1", remote_poll_url),
+            "type": "Update",
+            "actor": remote_url,
+            "object": {
+                "id": remote_poll_url,
+                "type": "Question",
+                "attributedTo": remote_url,
+                "content": "<p>Cats or dogs?</p>",
+                "votersCount": 7,
+                "oneOf": [
+                    { "type": "Note", "name": "Cats", "replies": { "type": "Collection", "totalItems": 5 } },
+                    { "type": "Note", "name": "Dogs", "replies": { "type": "Collection", "totalItems": 2 } }
+                ]
+            }
+        }))
+    ).await;
+    let fetched_poll: KleahNote = fetch_remote_note(&format!("{}/statuses/11", remote_url), &connection).await
+        .expect("Could not fetch remote poll.");
+    let fetched_tallies: Vec<i64> = get_poll_options(&fetched_poll.note_id, &connection).await
+        .expect("Could not fetch poll options.")
+        .iter()
+        .map(|option| option.votes_count)
+        .collect();
+    let remote_tallies: Vec<i64> = get_poll_options(&remote_poll.note_id, &connection).await
+        .expect("Could not fetch poll options.")
+        .iter()
+        .map(|option| option.votes_count)
+        .collect();
+    let remote_edits: Vec<KleahNoteEdit> = get_note_edits(&remote_poll.note_id, &connection).await
+        .expect("Could not fetch edits.");
+    sqlx::query("UPDATE polls SET expires_at = now() - interval '1 second' WHERE note_id = $1")
+        .bind(&note_id)
+        .execute(&connection)
+        .await
+        .expect("Could not end poll.");
+    let closed: usize = process_expired_polls(&connection).await
+        .expect("Could not close polls.");
+    let still_open = test::call_service(&app, vote(&chooser_token, &remote_poll.note_id, serde_json::json!([0]))).await;
+    let ended: serde_json::Value = test::call_and_read_body_json(
+        &app,
+        test::TestRequest::get()
+            .uri(&format!("/api/post/{}/poll", note_id))
+            .insert_header(bearer(&chooser_token))
+            .to_request()
+    ).await;
+    let late_local = test::call_service(&app, vote(&chooser_token, &note_id, serde_json::json!([0]))).await;
+    process_deliveries(&connection).await
+        .expect("Could not send deliveries.");
+    let bodies: Vec<String> = wait_for_deliveries(&received, 4).await;
+    handle.stop(true).await;
+    let notified: Vec<String> = sqlx::query_scalar(
+        "SELECT username FROM notifications WHERE kind = 'poll' AND note_id = $1 ORDER BY username"
+    )
+        .bind(&note_id)
+        .fetch_all(&connection)
+        .await
+        .expect("Could not fetch notifications.");
+    let activities: Vec<serde_json::Value> = bodies
+        .iter()
+        .map(|body| serde_json::from_str(body).expect("Could not parse delivered activity."))
+        .collect();
+    let question: &serde_json::Value = activities
+        .iter()
+        .find(|activity| activity["type"] == "Create" && activity["object"]["id"] == note_url)
+        .expect("No question was delivered.");
+    let outgoing_vote: &serde_json::Value = activities
+        .iter()
+        .find(|activity| activity["type"] == "Create" && activity["object"]["attributedTo"] == actor_url(&voter))
+        .expect("No vote was delivered.");
+    let closing: &serde_json::Value = activities
+        .iter()
+        .find(|activity| activity["type"] == "Update" && activity["object"]["id"] == note_url)
+        .expect("No closed poll was delivered.");
+    assert_eq!(created["has_poll"], true);
+    assert_eq!(too_few.status().as_u16(), 400);
+    assert_eq!(duplicated.status().as_u16(), 400);
+    assert_eq!(too_short.status().as_u16(), 400);
+    assert_eq!(voted["voted"], true);
+    assert_eq!(voted["own_votes"], serde_json::json!([0]));
+    assert_eq!(repeated.status().as_u16(), 400);
+    assert_eq!(own.status().as_u16(), 400);
+    assert_eq!(too_many.status().as_u16(), 400);
+    assert_eq!(out_of_range.status().as_u16(), 400);
+    assert!(remote_vote.status().is_success());
+    assert_eq!(tallied["voters"], 3);
+    assert_eq!(tallied["voted"], false);
+    assert_eq!(tallied["options"][0]["votes"], 1);
+    assert_eq!(tallied["options"][1]["title"], "Coffee");
+    assert_eq!(tallied["options"][1]["votes"], 2);
+    assert!(remote_poll.has_poll);
+    assert_eq!(remote_voted["own_votes"], serde_json::json!([1]));
+    assert_eq!(remote_voted["options"][1]["votes"], 2);
+    assert_eq!(remote_voted["voters"], 5);
+    assert_eq!(remote_tallies, vec![5, 2]);
+    assert!(remote_edits.is_empty());
+    assert!(fetched_poll.has_poll);
+    assert_eq!(fetched_tallies, vec![0, 2]);
+    assert!(closed >= 1);
+    assert!(still_open.status().is_success());
+    assert_eq!(ended["expired"], true);
+    assert_eq!(ended["own_votes"], serde_json::json!([1]));
+    assert_eq!(late_local.status().as_u16(), 400);
+    assert_eq!(notified, vec!["chooser", "pollster", "voter"]);
+    assert_eq!(question["object"]["type"], "Question");
+    assert_eq!(question["object"]["oneOf"][0]["name"], "Tea");
+    assert!(question["object"]["endTime"].is_string());
+    assert_eq!(outgoing_vote["object"]["name"], "Dogs");
+    assert_eq!(outgoing_vote["object"]["inReplyTo"], remote_poll_url);
+    assert_eq!(outgoing_vote["to"], serde_json::json!([remote_url]));
+    assert!(closing["object"]["closed"].is_string());
+    assert_eq!(closing["object"]["oneOf"][1]["replies"]["totalItems"], 2);
+    assert_eq!(closing["object"]["votersCount"], 3);
+}
+
+/// The function to test closing polls
+/// that have ended when one of them cannot
+/// be finished.
+#[actix_web::test]
+pub async fn test_expired_polls(){
+    let db_url: String = var("KLEAH_DB_URL")
+        .expect("Failed to read environment variable.");
+    let connection = create_connection(&db_url).await
+        .expect("Could not create connection.");
+    let instance: InstanceInformation = setup_instance(&connection).await;
+    let _surveyor: KleahActor = setup_actor("surveyor", &connection).await;
+    let finished: KleahNote = publish_note(
+        "surveyor",
+        "Summer or winter?",
+        None,
+        None,
+        None,
+        &false,
+        Some(&PollPayload{ options: vec!["Summer".to_string(), "Winter".to_string()], expires_in: 3600, multiple: None }),
+        &connection
+    ).await
+        .expect("Could not publish note.");
+    let orphaned: KleahNote = create_note(
+        &KleahNote{
+            note_id: generate_id("vanished"),
+            ap_id: None,
+            author_username: "vanished".to_string(),
+            author_host: instance.host.clone(),
+            content: "Left or right?".to_string(),
+            in_reply_to: None,
+            visibility: "public".to_string(),
+            created_at: Utc::now(),
+            updated_at: None,
+            like_count: 0,
+            boost_count: 0,
+            conversation: None,
+            summary: None,
+            sensitive: false,
+            has_poll: true
+        },
+        &connection
+    ).await
+        .expect("Could not store note.");
+    create_poll(
+        &KleahPoll{
+            note_id: orphaned.note_id.clone(),
+            multiple: false,
+            expires_at: Utc::now(),
+            closed_at: None,
+            voters_count: 0
+        },
+        &["Left".to_string(), "Right".to_string()],
+        &[0, 0],
+        &connection
+    ).await
+        .expect("Could not store poll.");
+    sqlx::query("UPDATE polls SET expires_at = now() - interval '1 second' WHERE note_id = $1")
+        .bind(&finished.note_id)
+        .execute(&connection)
+        .await
+        .expect("Could not end poll.");
+    let failed: Result<usize, KleahErr> = process_expired_polls(&connection).await;
+    let repeated: Result<usize, KleahErr> = process_expired_polls(&connection).await;
+    let finished_poll: KleahPoll = get_poll(&finished.note_id, &connection).await
+        .expect("Could not fetch poll.");
+    let orphaned_poll: KleahPoll = get_poll(&orphaned.note_id, &connection).await
+        .expect("Could not fetch poll.");
+    let notified: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM notifications WHERE kind = 'poll' AND note_id = $1"
+    )
+        .bind(&finished.note_id)
+        .fetch_one(&connection)
+        .await
+        .expect("Could not fetch notifications.");
+    sqlx::query("DELETE FROM notes WHERE note_id = $1")
+        .bind(&orphaned.note_id)
+        .execute(&connection)
+        .await
+        .expect("Could not remove note.");
+    let failure: String = failed.err().map(|e| e.to_string()).unwrap_or_default();
+    assert!(failure.contains(&orphaned.note_id));
+    assert!(!failure.contains(&finished.note_id));
+    assert!(repeated.is_err());
+    assert!(finished_poll.closed_at.is_some());
+    assert!(orphaned_poll.closed_at.is_none());
+    assert_eq!(notified, 1);
+}
+
+/// The function to test creating notifications
+/// from local and federated actions, listing,
+/// reading and dismissing them, muting kinds of
+/// them and notifying administrators about
+/// reports.
+#[actix_web::test]
+pub async fn test_notifications(){
+    use actix_web::test;
+    let db_url: String = var("KLEAH_DB_URL")
+        .expect("Failed to read environment variable.");
+    let connection = create_connection(&db_url).await
+        .expect("Could not create connection.");
+    let instance: InstanceInformation = setup_instance(&connection).await;
+    let notified: KleahActor = setup_actor("notified", &connection).await;
+    let _fan: KleahActor = setup_actor("fan", &connection).await;
+    let _moderator: KleahActor = setup_actor("moderator", &connection).await;
+    sqlx::query("UPDATE users SET is_admin = TRUE WHERE username = 'moderator'")
+        .execute(&connection)
+        .await
+        .expect("Could not promote user.");
+    let token: String = create_api_token("notified", &connection).await
+        .expect("Could not create token.")
+        .token;
+    let fan_token: String = create_api_token("fan", &connection).await
+        .expect("Could not create token.")
+        .token;
+    let moderator_token: String = create_api_token("moderator", &connection).await
+        .expect("Could not create token.")
+        .token;
+    let keys = generate_keypair().expect("Could not generate keys.");
+    let received: Data<Mutex<Vec<String>>> = Data::new(Mutex::new(Vec::new()));
+    let (remote_host, handle) = start_remote_server(
+        Data::new(Mutex::new(keys.public_key.clone())), 
+        received.clone()
+    );
+    let remote_url: String = format!("http://{}/users/remoteuser", remote_host);
+    let key_id: String = format!("{}#main-key", remote_url);
+    let note: KleahNote = publish_note("notified", "Notice me.", None, None, None, &false, None, &connection).await
+        .expect("Could not publish note.");
+    let note_url: String = format!("https://{}/apub/notes/{}", instance.host, note.note_id);
+    let app = test::init_service(
+        App::new()
+            .app_data(Data::new(AppData{ pool: connection.clone() }))
+            .service(follow_service)
+            .service(like_note_service)
+            .service(boost_note_service)
+            .service(notifications_service)
+            .service(read_notifications_service)
+            .service(dismiss_notification_service)
+            .service(notification_mutes_service)
+            .service(mute_notifications_service)
+            .service(report_service)
+            .service(get_report_service)
+            .service(shared_inbox_service)
+    ).await;
+    let post = |path: &str, body: serde_json::Value| {
+        test::TestRequest::post()
+            .uri(path)
+            .set_json(body)
+            .to_request()
+    };
+    let inbound = |activity: serde_json::Value| {
+        build_signed_request("/inbox", &keys.private_key, &key_id, &activity.to_string()).to_request()
+    };
+    let _followed = test::call_service(
+        &app,
+        post("/api/user/follow", serde_json::json!({ "api_token": fan_token, "target": actor_url(&notified) }))
+    ).await;
+    update_locked("notified", &true, &connection).await
+        .expect("Could not lock user.");
+    let _requested = test::call_service(
+        &app,
+        inbound(serde_json::json!({
+            "id": format!("{}#follow/1", remote_url),
+            "type": "Follow",
+            "actor": remote_url,
+            "object": actor_url(&notified)
+        }))
+    ).await;
+    let remote_note_url: String = format!("{}/statuses/10", remote_url);
+    let _mentioned = test::call_service(
+        &app,
+        inbound(serde_json::json!({
+            "id": format!("{}/activity", remote_note_url),
+            "type": "Create",
+            "actor": remote_url,
+            "object": {
+                "id": remote_note_url,
+                "type": "Note",
+                "attributedTo": remote_url,
+                "content": "<p>Hey there</p>",
+                "to": [actor_url(&notified)]
+            }
+        }))
+    ).await;
+    let _liked = test::call_service(
+        &app,
+        post("/api/post/like", serde_json::json!({ "api_token": fan_token, "note_id": note.note_id }))
+    ).await;
+    let muted: serde_json::Value = test::call_and_read_body_json(
+        &app,
+        post("/api/notifications/mute", serde_json::json!({ "api_token": token, "kind": "boost", "muted": true }))
+    ).await;
+    let bad_mute = test::call_service(
+        &app,
+        post("/api/notifications/mute", serde_json::json!({ "api_token": token, "kind": "poke", "muted": true }))
+    ).await;
+    let _boosted = test::call_service(
+        &app,
+        post("/api/post/boost", serde_json::json!({ "api_token": fan_token, "note_id": note.note_id }))
+    ).await;
+    let list = |api_token: &str, query: &str| {
+        test::TestRequest::get()
+            .uri(&format!("/api/notifications?{}", query))
+            .insert_header(bearer(api_token))
+            .to_request()
+    };
+    let all: serde_json::Value = test::call_and_read_body_json(&app, list(&token, "")).await;
+    let first_page: serde_json::Value = test::call_and_read_body_json(
+        &app,
+        list(&token, "limit=2")
+    ).await;
+    let second_page: serde_json::Value = test::call_and_read_body_json(
+        &app,
+        list(&token, &format!("limit=2&max_id={}", first_page["next_max_id"].as_str().unwrap_or_default()))
+    ).await;
+    let likes: serde_json::Value = test::call_and_read_body_json(
+        &app,
+        list(&token, "kind=like")
+    ).await;
+    let bad_kind = test::call_service(&app, list(&token, "kind=poke")).await;
+    let anonymous = test::call_service(&app, list("invalid", "")).await;
+    let kinds: Vec<&str> = all["notifications"]
+        .as_array()
+        .map(|notifications| notifications.iter().filter_map(|notification| notification["kind"].as_str()).collect())
+        .unwrap_or_default();
+    let find = |kind: &str| -> String {
+        all["notifications"]
+            .as_array()
+            .and_then(|notifications| notifications.iter().find(|notification| notification["kind"] == kind))
+            .and_then(|notification| notification["notification_id"].as_str())
+            .unwrap_or_default()
+            .to_string()
+    };
+    let _read_one = test::call_service(
+        &app,
+        post("/api/notifications/read", serde_json::json!({ "api_token": token, "notification_id": find("like") }))
+    ).await;
+    let unread: serde_json::Value = test::call_and_read_body_json(
+        &app,
+        list(&token, "unread=true")
+    ).await;
+    let _read_all = test::call_service(
+        &app,
+        post("/api/notifications/read", serde_json::json!({ "api_token": token }))
+    ).await;
+    let all_read: serde_json::Value = test::call_and_read_body_json(&app, list(&token, "")).await;
+    let foreign_dismissal = test::call_service(
+        &app,
+        post("/api/notifications/dismiss", serde_json::json!({ "api_token": fan_token, "notification_id": find("follow") }))
+    ).await;
+    let dismissed = test::call_service(
+        &app,
+        post("/api/notifications/dismiss", serde_json::json!({ "api_token": token, "notification_id": find("follow") }))
+    ).await;
+    let redismissed = test::call_service(
+        &app,
+        post("/api/notifications/dismiss", serde_json::json!({ "api_token": token, "notification_id": find("follow") }))
+    ).await;
+    let remaining: serde_json::Value = test::call_and_read_body_json(&app, list(&token, "")).await;
+    let unmuted: serde_json::Value = test::call_and_read_body_json(
+        &app,
+        post("/api/notifications/mute", serde_json::json!({ "api_token": token, "kind": "boost", "muted": false }))
+    ).await;
+    let mutes: serde_json::Value = test::call_and_read_body_json(
+        &app,
+        test::TestRequest::get()
+            .uri("/api/notifications/mutes")
+            .insert_header(bearer(&token))
+            .to_request()
+    ).await;
+    let report: serde_json::Value = test::call_and_read_body_json(
+        &app,
+        post(
+            "/api/report",
+            serde_json::json!({
+                "api_token": fan_token,
+                "username": "notified",
+                "host": instance.host,
+                "note_id": note.note_id,
+                "comment": "  Rude.  "
+            })
+        )
+    ).await;
+    let self_report = test::call_service(
+        &app,
+        post("/api/report", serde_json::json!({ "api_token": token, "username": "notified", "host": instance.host }))
+    ).await;
+    let flagged = test::call_service(
+        &app,
+        inbound(serde_json::json!({
+            "id": format!("{}#flag/1", remote_url),
+            "type": "Flag",
+            "actor": remote_url,
+            "object": [actor_url(&notified), note_url],
+            "content": "Spam."
+        }))
+    ).await;
+    handle.stop(true).await;
+    let reports: serde_json::Value = test::call_and_read_body_json(
+        &app,
+        list(&moderator_token, "kind=report")
+    ).await;
+    let read_report = |api_token: &str, report_id: &str| {
+        test::TestRequest::get()
+            .uri(&format!("/api/report/{}", report_id))
+            .insert_header(bearer(api_token))
+            .to_request()
+    };
+    let report_id: &str = report["report_id"].as_str().unwrap_or_default();
+    let moderated: serde_json::Value = test::call_and_read_body_json(&app, read_report(&moderator_token, report_id)).await;
+    let snooped = test::call_service(&app, read_report(&fan_token, report_id)).await;
+    let flag_id: &str = reports["notifications"][0]["report_id"].as_str().unwrap_or_default();
+    let flag: serde_json::Value = test::call_and_read_body_json(&app, read_report(&moderator_token, flag_id)).await;
+    assert_eq!(kinds.len(), 4);
+    for kind in ["follow", "follow_request", "mention", "like"] {
+        assert!(kinds.contains(&kind));
+    }
+    assert_eq!(all["unread"], 4);
+    assert_eq!(muted["muted"], serde_json::json!(["boost"]));
+    assert_eq!(bad_mute.status().as_u16(), 400);
+    assert_eq!(first_page["notifications"].as_array().map(|page| page.len()), Some(2));
+    assert_eq!(second_page["notifications"].as_array().map(|page| page.len()), Some(2));
+    assert_ne!(first_page["notifications"][0]["notification_id"], second_page["notifications"][0]["notification_id"]);
+    assert_eq!(likes["notifications"].as_array().map(|page| page.len()), Some(1));
+    assert_eq!(likes["notifications"][0]["actor"], "fan");
+    assert_eq!(likes["notifications"][0]["note_id"], note.note_id);
+    assert_eq!(bad_kind.status().as_u16(), 400);
+    assert_eq!(anonymous.status().as_u16(), 401);
+    assert_eq!(unread["unread"], 3);
+    assert_eq!(unread["notifications"].as_array().map(|page| page.len()), Some(3));
+    assert_eq!(all_read["unread"], 0);
+    assert!(all_read["notifications"].as_array().is_some_and(|page| page.iter().all(|notification| notification["read"] == true)));
+    assert_eq!(foreign_dismissal.status().as_u16(), 404);
+    assert!(dismissed.status().is_success());
+    assert_eq!(redismissed.status().as_u16(), 404);
+    assert_eq!(remaining["notifications"].as_array().map(|page| page.len()), Some(3));
+    assert_eq!(unmuted["muted"], serde_json::json!([]));
+    assert_eq!(mutes["muted"], serde_json::json!([]));
+    assert_eq!(report["comment"], "Rude.");
+    assert_eq!(self_report.status().as_u16(), 400);
+    assert!(flagged.status().is_success());
+    assert_eq!(reports["notifications"].as_array().map(|page| page.len()), Some(2));
+    assert_eq!(reports["notifications"][1]["actor"], "fan");
+    assert_eq!(moderated["target"], "notified");
+    assert_eq!(moderated["note_id"], note.note_id);
+    assert_eq!(snooped.status().as_u16(), 401);
+    assert_eq!(flag["comment"], "Spam.");
+    assert_eq!(flag["reporter"], "remoteuser");
+    assert_eq!(flag["note_id"], note.note_id);
+}
@@ -0,0 +1,275 @@
+/*
+Kleah by Alyx Shang.
+Licensed under the FSL v1.
+*/
+
+/// Importing the structure
+/// representing a pool of
+/// database connections.
+use sqlx::Pool;
+
+/// Importing the structure
+/// representing the UTC
+/// time zone.
+use chrono::Utc;
+
+/// Importing the structure
+/// representing a point in
+/// time.
+use chrono::DateTime;
+
+/// Importing the structure
+/// representing an arbitrary
+/// JSON value.
+use serde_json::Value;
+
+/// Importing the structure
+/// for catching and handling
+/// errors.
+use super::err::KleahErr;
+
+/// Importing the function
+/// to build the URL of a note.
+use super::utils::note_url;
+
+/// Importing the function
+/// to build the base URL of
+/// a host.
+use super::utils::base_url;
+
+/// Importing the structure
+/// representing a Postgres
+/// database.
+use sqlx::postgres::Postgres;
+
+/// Importing the structure
+/// modelling a note.
+use super::models::KleahNote;
+
+/// Importing the function
+/// to read the notes a local
+/// user may see.
+use super::notes::get_viewer;
+
+/// Importing the structure
+/// modelling an actor.
+use super::models::KleahActor;
+
+/// Importing the function
+/// to check whether an actor
+/// may read a note.
+use super::notes::can_view_note;
+
+/// Importing the function
+/// to retrieve the ID of an
+/// ActivityPub object.
+use super::federation::object_id;
+
+/// Importing the function
+/// to retrieve information
+/// about this instance.
+use super::db::get_instance_info;
+
+/// Importing the function
+/// to retrieve a note by
+/// its URL.
+use super::notes::get_note_by_url;
+
+/// Importing the function
+/// to retrieve the replies
+/// to a note.
+use super::db::get_note_descendants;
+
+/// Importing the function
+/// to describe a note for
+/// the API.
+use super::notes::build_note_response;
+
+/// Importing the function
+/// to retrieve a note on
+/// behalf of a reader.
+use super::notes::get_note_for_viewer;
+
+/// Importing the structure
+/// modelling information
+/// about this instance.
+use super::models::InstanceInformation;
+
+/// Importing the function
+/// to find out whether a reader
+/// expands content warnings.
+use super::notes::get_spoiler_preference;
+
+/// Importing the structure
+/// containing the thread
+/// around a note.
+use super::responses::NoteContextResponse;
+
+/// The number of levels of
+/// replies followed up or down
+/// a thread from a single note.
+pub const THREAD_MAX_DEPTH: i32 = 40;
+
+/// The largest number of replies
+/// served in the thread around a
+/// single note.
+pub const THREAD_MAX_DESCENDANTS: i64 = 200;
+
+/// A function to build the ID of
+/// the conversation started by a new
+/// local note. The ID follows the
+/// form used by Mastodon so that other
+/// servers keep threads together. The
+/// ID is returned.
+pub fn new_conversation_id(
+    host: &str,
+    note_id: &str,
+    created_at: &DateTime<Utc>
+) -> String {
+    format!(
+        "tag:{},{}:objectId={}:objectType=Conversation",
+        host,
+        created_at.format("%Y-%m-%d"),
+        note_id
+    )
+}
+
+/// A function to derive the ID of the
+/// conversation a note received from another
+/// server belongs to. Replies to known notes
+/// join the conversation of the note they
+/// reply to, other notes keep the "context"
+/// or "conversation" they were sent with.
+/// The ID is returned if there is one.
+pub fn incoming_conversation(
+    object: &Value,
+    parent: Option<&KleahNote>
+) -> Option<String> {
+    match parent.and_then(|parent| parent.conversation.clone()) {
+        Some(conversation) => Some(conversation),
+        None => object
+            .get("context")
+            .and_then(object_id)
+            .or_else(|| object.get("conversation").and_then(object_id))
+    }
+}
+
+/// A function that attempts to retrieve
+/// the notes a note replies to, up to the
+/// start of its thread or as far as they are
+/// known. Notes the reader may not see are
+/// left out. If the operation is successful,
+/// the notes are returned oldest first. If the
+/// operation fails, an error is returned.
+pub async fn get_note_ancestors(
+    note: &KleahNote,
+    viewer: Option<&KleahActor>,
+    pool: &Pool<Postgres>
+) -> Result<Vec<KleahNote>, KleahErr> {
+    let mut ancestors: Vec<KleahNote> = Vec::new();
+    let mut parent_url: Option<String> = note.in_reply_to.clone();
+    let mut depth: i32 = 0;
+    while let Some(url) = parent_url {
+        if depth >= THREAD_MAX_DEPTH {
+            break;
+        }
+        depth += 1;
+        let parent: KleahNote = match get_note_by_url(&url, pool).await {
+            Ok(parent) => parent,
+            Err(_e) => break
+        };
+        parent_url = parent.in_reply_to.clone();
+        match can_view_note(&parent, viewer, pool).await {
+            Ok(true) => ancestors.push(parent),
+            Ok(false) => {},
+            Err(e) => return Err::<Vec<KleahNote>, KleahErr>(e)
+        };
+    }
+    ancestors.reverse();
+    Ok(ancestors)
+}
+
+/// A function that attempts to retrieve
+/// the replies to a note and the replies to
+/// those replies. Notes the reader may not
+/// see are left out. If the operation is
+/// successful, the notes are returned oldest
+/// first. If the operation fails, an error
+/// is returned.
+pub async fn get_visible_descendants(
+    note: &KleahNote,
+    viewer: Option<&KleahActor>,
+    pool: &Pool<Postgres>
+) -> Result<Vec<KleahNote>, KleahErr> {
+    let instance: InstanceInformation = match get_instance_info(pool).await {
+        Ok(instance) => instance,
+        Err(e) => return Err::<Vec<KleahNote>, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    let descendants: Vec<KleahNote> = match get_note_descendants(
+        &note_url(note),
+        &base_url(&instance.host),
+        THREAD_MAX_DEPTH,
+        THREAD_MAX_DESCENDANTS,
+        pool
+    ).await {
+        Ok(descendants) => descendants,
+        Err(e) => return Err::<Vec<KleahNote>, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    let mut visible: Vec<KleahNote> = Vec::new();
+    for descendant in descendants {
+        match can_view_note(&descendant, viewer, pool).await {
+            Ok(true) => visible.push(descendant),
+            Ok(false) => {},
+            Err(e) => return Err::<Vec<KleahNote>, KleahErr>(e)
+        };
+    }
+    Ok(visible)
+}
+
+/// A function that attempts to read the
+/// thread around a note on behalf of the user
+/// with the given API token or an anonymous
+/// reader. If the operation is successful, an
+/// instance of the `NoteContextResponse` structure
+/// is returned. If the reader may not see the
+/// note or the operation fails, an error is
+/// returned.
+pub async fn read_note_context(
+    note_id: &str,
+    api_token: Option<&str>,
+    pool: &Pool<Postgres>
+) -> Result<NoteContextResponse, KleahErr> {
+    let viewer: Option<KleahActor> = match get_viewer(api_token, pool).await {
+        Ok(viewer) => viewer,
+        Err(e) => return Err::<NoteContextResponse, KleahErr>(e)
+    };
+    let note: KleahNote = match get_note_for_viewer(note_id, viewer.as_ref(), pool).await {
+        Ok(note) => note,
+        Err(e) => return Err::<NoteContextResponse, KleahErr>(e)
+    };
+    let ancestors: Vec<KleahNote> = match get_note_ancestors(&note, viewer.as_ref(), pool).await {
+        Ok(ancestors) => ancestors,
+        Err(e) => return Err::<NoteContextResponse, KleahErr>(e)
+    };
+    let descendants: Vec<KleahNote> = match get_visible_descendants(&note, viewer.as_ref(), pool).await {
+        Ok(descendants) => descendants,
+        Err(e) => return Err::<NoteContextResponse, KleahErr>(e)
+    };
+    let expand_spoilers: bool = get_spoiler_preference(api_token, pool).await;
+    Ok(
+        NoteContextResponse{
+            ancestors: ancestors
+                .iter()
+                .map(|ancestor| build_note_response(ancestor, expand_spoilers))
+                .collect(),
+            descendants: descendants
+                .iter()
+                .map(|descendant| build_note_response(descendant, expand_spoilers))
+                .collect()
+        }
+    )
+}
@@ -0,0 +1,430 @@
+/*
+Kleah by Alyx Shang.
+Licensed under the FSL v1.
+*/
+
+/// Importing the structure
+/// representing a pool of
+/// database connections.
+use sqlx::Pool;
+
+/// Importing the structure
+/// for catching and handling
+/// errors.
+use super::err::KleahErr;
+
+/// Importing the enum
+/// to describe the kind
+/// of error that occurred.
+use super::err::KleahErrType;
+
+/// Importing the structure
+/// representing a Postgres
+/// database.
+use sqlx::postgres::Postgres;
+
+/// Importing the structure
+/// modelling a note.
+use super::models::KleahNote;
+
+/// Importing the function
+/// to read the notes a local
+/// user may see.
+use super::notes::get_viewer;
+
+/// Importing the structure
+/// modelling an actor.
+use super::models::KleahActor;
+
+/// Importing the structure
+/// to look up values
+/// by their keys.
+use std::collections::HashMap;
+
+/// Importing the function
+/// to read a cursor from
+/// a query string.
+use super::utils::parse_cursor;
+
+/// Importing the function
+/// to retrieve notes by
+/// their IDs.
+use super::db::get_notes_by_ids;
+
+/// Importing the function
+/// to build the cursor
+/// pointing at an item.
+use super::utils::format_cursor;
+
+/// Importing the function
+/// to retrieve a page of a
+/// user's home timeline.
+use super::db::get_home_timeline;
+
+/// Importing the function
+/// to retrieve information
+/// about this instance.
+use super::db::get_instance_info;
+
+/// Importing the function
+/// to retrieve a page of the
+/// local timeline.
+use super::db::get_local_timeline;
+
+/// Importing the structure
+/// modelling the query string
+/// for reading a timeline.
+use super::payloads::TimelineQuery;
+
+/// Importing the structure
+/// describing a boost.
+use super::responses::BoostResponse;
+
+/// Importing the function
+/// to describe a note for
+/// the API.
+use super::notes::build_note_response;
+
+/// Importing the function
+/// to retrieve a page of the
+/// federated timeline.
+use super::db::get_federated_timeline;
+
+/// Importing the structure
+/// modelling an entry of
+/// a timeline.
+use super::models::KleahTimelineEntry;
+
+/// Importing the structure
+/// containing a page of a
+/// timeline.
+use super::responses::TimelineResponse;
+
+/// Importing the structure
+/// modelling information
+/// about this instance.
+use super::models::InstanceInformation;
+
+/// Importing the function
+/// to find out whether a reader
+/// expands content warnings.
+use super::notes::get_spoiler_preference;
+
+/// Importing the structure
+/// describing a note on
+/// a timeline.
+use super::responses::TimelineNoteResponse;
+
+/// The number of notes on
+/// a page of a timeline if
+/// no other size is requested.
+pub const TIMELINE_PAGE_SIZE: i64 = 20;
+
+/// The largest number of notes
+/// that can be requested for a
+/// single page of a timeline.
+pub const TIMELINE_MAX_PAGE_SIZE: i64 = 40;
+
+/// The levels of access the administrators
+/// of an instance can grant to the local and
+/// the federated timeline. Timelines are open
+/// to everyone, to logged-in users only, or
+/// disabled.
+pub const TIMELINE_ACCESS_LEVELS: [&str; 3] = ["public", "users", "disabled"];
+
+/// A function to check whether
+/// the given level of access to
+/// a timeline is supported. A
+/// boolean reflecting this is
+/// returned.
+pub fn check_timeline_access(
+    access: &str
+) -> bool {
+    TIMELINE_ACCESS_LEVELS.contains(&access)
+}
+
+/// A function to derive the number
+/// of notes to serve on a page of a
+/// timeline from the size requested.
+/// The size is returned.
+pub fn timeline_page_size(
+    limit: Option<i64>
+) -> i64 {
+    limit
+        .unwrap_or(TIMELINE_PAGE_SIZE)
+        .clamp(1, TIMELINE_MAX_PAGE_SIZE)
+}
+
+/// A function to describe a note
+/// as an entry of a timeline that no
+/// boost put on it. The entry is 
+/// returned.
+pub fn note_entry(
+    note: &KleahNote
+) -> KleahTimelineEntry {
+    KleahTimelineEntry{
+        note_id: note.note_id.clone(),
+        boost_id: None,
+        booster_username: None,
+        booster_host: None,
+        created_at: note.created_at
+    }
+}
+
+/// A function to build the response
+/// describing an entry of a timeline 
+/// with its note. Boosted notes are
+/// attributed to their booster. The
+/// response is returned.
+pub fn build_timeline_note_response(
+    entry: &KleahTimelineEntry,
+    note: &KleahNote,
+    expand_spoilers: bool
+) -> TimelineNoteResponse {
+    let boost: Option<BoostResponse> = match (
+        &entry.boost_id,
+        &entry.booster_username,
+        &entry.booster_host
+    ) {
+        (Some(boost_id), Some(username), Some(host)) => Some(
+            BoostResponse{
+                boost_id: boost_id.clone(),
+                username: username.clone(),
+                host: host.clone(),
+                created_at: entry.created_at.to_rfc3339()
+            }
+        ),
+        _ => None
+    };
+    TimelineNoteResponse{
+        note: build_note_response(note, expand_spoilers),
+        boost
+    }
+}
+
+/// A function to build the cursor
+/// pointing at an entry of a timeline.
+/// Boosts are identified by the ID of
+/// the boost and notes by their own ID.
+/// The cursor is returned as a string.
+pub fn entry_cursor(
+    entry: &KleahTimelineEntry
+) -> String {
+    format_cursor(
+        &entry.created_at,
+        entry.boost_id.as_deref().unwrap_or(&entry.note_id)
+    )
+}
+
+/// A function to build the response
+/// containing a page of a timeline from
+/// its entries and their notes. The 
+/// cursors point past the oldest and the
+/// newest entry on the page. The response
+/// is returned.
+pub fn build_timeline_response(
+    entries: &[KleahTimelineEntry],
+    notes: &[KleahNote],
+    expand_spoilers: bool
+) -> TimelineResponse {
+    let notes: HashMap<&str, &KleahNote> = notes
+        .iter()
+        .map(|note| (note.note_id.as_str(), note))
+        .collect();
+    TimelineResponse{
+        notes: entries
+            .iter()
+            .filter_map(|entry| {
+                notes
+                    .get(entry.note_id.as_str())
+                    .map(|note| build_timeline_note_response(entry, note, expand_spoilers))
+            })
+            .collect(),
+        next_max_id: entries.last().map(entry_cursor),
+        prev_min_id: entries.first().map(entry_cursor)
+    }
+}
+
+/// A function to build the response
+/// containing a page of a timeline that
+/// holds notes only. The response is 
+/// returned.
+pub fn build_note_timeline_response(
+    notes: &[KleahNote],
+    expand_spoilers: bool
+) -> TimelineResponse {
+    let entries: Vec<KleahTimelineEntry> = notes
+        .iter()
+        .map(note_entry)
+        .collect();
+    build_timeline_response(&entries, notes, expand_spoilers)
+}
+
+/// A function that attempts to read
+/// a page of the home timeline of the user
+/// with the given API token. If the operation
+/// is successful, an instance of the
+/// `TimelineResponse` structure is returned.
+/// If the operation fails, an error is returned.
+pub async fn read_home_timeline(
+    api_token: Option<&str>,
+    query: &TimelineQuery,
+    pool: &Pool<Postgres>
+) -> Result<TimelineResponse, KleahErr> {
+    let viewer: KleahActor = match get_viewer(api_token, pool).await {
+        Ok(Some(viewer)) => viewer,
+        Ok(None) => return Err::<TimelineResponse, KleahErr>(
+            KleahErr::with_type(
+                "An API token is required.",
+                KleahErrType::Unauthorized
+            )
+        ),
+        Err(e) => return Err::<TimelineResponse, KleahErr>(e)
+    };
+    let entries: Vec<KleahTimelineEntry> = match get_home_timeline(
+        &viewer,
+        parse_cursor(query.max_id.as_deref()).as_ref(),
+        parse_cursor(query.min_id.as_deref()).as_ref(),
+        timeline_page_size(query.limit),
+        pool
+    ).await {
+        Ok(entries) => entries,
+        Err(e) => return Err::<TimelineResponse, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    let note_ids: Vec<String> = entries
+        .iter()
+        .map(|entry| entry.note_id.clone())
+        .collect();
+    let notes: Vec<KleahNote> = match get_notes_by_ids(&note_ids, pool).await {
+        Ok(notes) => notes,
+        Err(e) => return Err::<TimelineResponse, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    let expand_spoilers: bool = get_spoiler_preference(api_token, pool).await;
+    Ok(build_timeline_response(&entries, &notes, expand_spoilers))
+}
+
+/// A function that attempts to check
+/// whether the reader with the given API token
+/// may read a timeline with the given level of 
+/// access. Disabled timelines are treated as 
+/// nonexistent. If the reader may read the 
+/// timeline, an empty function is returned.
+/// If the reader may not, an error is returned.
+pub async fn ensure_timeline_access(
+    access: &str,
+    api_token: Option<&str>,
+    pool: &Pool<Postgres>
+) -> Result<(), KleahErr> {
+    if access == "disabled" {
+        return Err::<(), KleahErr>(
+            KleahErr::with_type(
+                "This timeline is disabled on this instance.",
+                KleahErrType::NotFound
+            )
+        );
+    }
+    match get_viewer(api_token, pool).await {
+        Ok(Some(_viewer)) => Ok(()),
+        Ok(None) => {
+            if access == "public" {
+                Ok(())
+            }
+            else {
+                Err::<(), KleahErr>(
+                    KleahErr::with_type(
+                        "This timeline is only open to logged-in users.",
+                        KleahErrType::Unauthorized
+                    )
+                )
+            }
+        },
+        Err(e) => Err::<(), KleahErr>(e)
+    }
+}
+
+/// A function that attempts to read
+/// a page of the local timeline, which 
+/// holds the public notes of the users of
+/// this instance. If the operation is successful,
+/// an instance of the `TimelineResponse` structure
+/// is returned. If the operation fails, an error
+/// is returned.
+pub async fn read_local_timeline(
+    api_token: Option<&str>,
+    query: &TimelineQuery,
+    pool: &Pool<Postgres>
+) -> Result<TimelineResponse, KleahErr> {
+    let instance: InstanceInformation = match get_instance_info(pool).await {
+        Ok(instance) => instance,
+        Err(e) => return Err::<TimelineResponse, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    match ensure_timeline_access(
+        &instance.local_timeline,
+        api_token,
+        pool
+    ).await {
+        Ok(_f) => {},
+        Err(e) => return Err::<TimelineResponse, KleahErr>(e)
+    };
+    let notes: Vec<KleahNote> = match get_local_timeline(
+        &instance.host,
+        parse_cursor(query.max_id.as_deref()).as_ref(),
+        parse_cursor(query.min_id.as_deref()).as_ref(),
+        timeline_page_size(query.limit),
+        pool
+    ).await {
+        Ok(notes) => notes,
+        Err(e) => return Err::<TimelineResponse, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    let expand_spoilers: bool = get_spoiler_preference(api_token, pool).await;
+    Ok(build_note_timeline_response(&notes, expand_spoilers))
+}
+
+/// A function that attempts to read
+/// a page of the federated timeline, which 
+/// holds all public notes known to this 
+/// instance. If the operation is successful,
+/// an instance of the `TimelineResponse` structure
+/// is returned. If the operation fails, an error
+/// is returned.
+pub async fn read_federated_timeline(
+    api_token: Option<&str>,
+    query: &TimelineQuery,
+    pool: &Pool<Postgres>
+) -> Result<TimelineResponse, KleahErr> {
+    let instance: InstanceInformation = match get_instance_info(pool).await {
+        Ok(instance) => instance,
+        Err(e) => return Err::<TimelineResponse, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    match ensure_timeline_access(
+        &instance.federated_timeline,
+        api_token,
+        pool
+    ).await {
+        Ok(_f) => {},
+        Err(e) => return Err::<TimelineResponse, KleahErr>(e)
+    };
+    let notes: Vec<KleahNote> = match get_federated_timeline(
+        parse_cursor(query.max_id.as_deref()).as_ref(),
+        parse_cursor(query.min_id.as_deref()).as_ref(),
+        timeline_page_size(query.limit),
+        pool
+    ).await {
+        Ok(notes) => notes,
+        Err(e) => return Err::<TimelineResponse, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    let expand_spoilers: bool = get_spoiler_preference(api_token, pool).await;
+    Ok(build_note_timeline_response(&notes, expand_spoilers))
+}
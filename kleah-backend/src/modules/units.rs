@@ -8,11 +8,21 @@ Licensed under the FSL v1.
 /// database connections.
 use sqlx::Pool;
 
+/// Importing the structure
+/// representing an arbitrary
+/// JSON value.
+use serde_json::Value;
+
 /// Importing the structure that
 /// represents a connection to a 
 /// PostgreSQL database.
 use sqlx::postgres::Postgres;
 
+/// Importing the data structure
+/// modelling data about an ActivityPub
+/// actor in the database.
+use super::models::KleahActor;
+
 /// A data structure to
 /// "store" a pool of
 /// connections to a 
@@ -30,3 +40,36 @@ pub struct KeyPair {
     pub private_key: String,
     pub public_key: String
 }
+
+/// A structure to hold the
+/// values of the headers that
+/// have to be sent with a request
+/// signed according to the HTTP
+/// Signatures draft.
+pub struct SignedHeaders {
+    pub host: String,
+    pub date: String,
+    pub digest: Option<String>,
+    pub signature: String
+}
+
+/// A structure to hold the
+/// parameters of a "Signature"
+/// header received with a
+/// request.
+pub struct ParsedSignature {
+    pub key_id: String,
+    pub algorithm: String,
+    pub headers: Vec<String>,
+    pub signature: String
+}
+
+/// A structure to hold an
+/// ActivityPub activity received
+/// in a request whose signature
+/// has been verified and the actor
+/// that signed the request.
+pub struct SignedActivity {
+    pub actor: KleahActor,
+    pub activity: Value
+}
@@ -22,6 +22,10 @@ use sha2::Digest;
 /// checksum.
 use sha2::Sha256;
 
+/// Importing the structure
+/// to parse URLs.
+use reqwest::Url;
+
 /// Importing this
 /// namespace to use
 /// a type alias.
@@ -37,6 +41,11 @@ use chrono::DateTime;
 /// keypair.
 use openssl::rsa::Rsa;
 
+/// Importing the structure
+/// representing an arbitrary
+/// JSON value.
+use serde_json::Value;
+
 /// Importing the structure
 /// for catching and handling
 /// errors.
@@ -53,11 +62,119 @@ use super::units::KeyPair;
 /// represents a private RSA key.
 use openssl::pkey::Private;
 
+/// Importing the structure
+/// modelling an ActivityPub
+/// actor document.
+use super::apub::ApubActor;
+
+/// Importing the structure
+/// representing an HTTP
+/// request.
+use actix_web::HttpRequest;
+
+/// Importing the structure
+/// for returning a response.
+use actix_web::HttpResponse;
+
 /// Importing the structure that
 /// represents a connection to a 
 /// PostgreSQL database.
 use sqlx::postgres::Postgres;
 
+/// Importing the data structure
+/// modelling data about a Kleah user
+/// in the database.
+use super::models::KleahUser;
+
+/// Importing the data structure
+/// modelling a note in
+/// the database.
+use super::models::KleahNote;
+
+/// Importing the enum
+/// describing the kinds
+/// of errors.
+use super::err::KleahErrType;
+
+/// Importing the data structure
+/// modelling data about an ActivityPub
+/// actor in the database.
+use super::models::KleahActor;
+
+/// Importing the structure
+/// modelling the public key
+/// of an ActivityPub actor.
+use super::apub::ApubPublicKey;
+
+/// Importing the structure
+/// modelling the additional
+/// endpoints of an actor.
+use super::apub::ApubEndpoints;
+
+/// Importing the structure
+/// modelling the position
+/// of an item on a page.
+use super::models::KleahCursor;
+
+/// Importing the function
+/// to retrieve an actor.
+use super::db::get_actor_by_id;
+
+/// Importing the content
+/// type of ActivityPub
+/// documents.
+use super::apub::ACTIVITY_JSON;
+
+/// Importing the structure
+/// modelling an ActivityPub
+/// collection.
+use super::apub::ApubCollection;
+
+/// Importing the function
+/// to retrieve information
+/// about the instance.
+use super::db::get_instance_info;
+
+/// Importing the URI of the
+/// JSON-LD context for public
+/// keys.
+use super::apub::SECURITY_CONTEXT;
+
+/// Importing the structure
+/// modelling an item
+/// of a collection.
+use super::models::CollectionItem;
+
+/// Importing the structure
+/// modelling a single page of
+/// an ActivityPub collection.
+use super::apub::ApubCollectionPage;
+
+/// Importing the structure
+/// modelling the query string
+/// for collection pages.
+use super::payloads::CollectionQuery;
+
+/// Importing the number of
+/// items on a single page
+/// of a collection.
+use super::apub::COLLECTION_PAGE_SIZE;
+
+/// Importing the structure
+/// modelling information
+/// about an instance.
+use super::models::InstanceInformation;
+
+/// Importing the URI of the
+/// JSON-LD context for
+/// ActivityStreams documents.
+use super::apub::ACTIVITYSTREAMS_CONTEXT;
+
+/// Importing the name
+/// of the header carrying
+/// credentials.
+use actix_web::http::header::AUTHORIZATION;
+
 /// A function to check whether
 /// the supplied string is a valid
 /// username. A boolean reflecting
@@ -225,3 +342,463 @@ pub fn validate_invite(
     }
     result
 }
+
+/// A function to check whether anyone
+/// may register on the given instance
+/// without an invite code. A boolean
+/// reflecting this is returned.
+pub fn registrations_open(
+    instance: &InstanceInformation
+) -> bool {
+    !instance.uses_invites
+}
+
+/// A function to read the API token
+/// a request carries as a bearer token
+/// in its "Authorization" header, so that
+/// tokens are not part of URLs. The token
+/// is returned or nothing if the request
+/// carries none.
+pub fn bearer_token(
+    req: &HttpRequest
+) -> Option<String> {
+    let header: &str = req.headers()
+        .get(AUTHORIZATION)?
+        .to_str()
+        .ok()?;
+    let (scheme, token) = header.split_once(' ')?;
+    let token: &str = token.trim();
+    if scheme.eq_ignore_ascii_case("Bearer") && !token.is_empty() {
+        Some(token.to_string())
+    }
+    else {
+        None
+    }
+}
+
+/// A function to build the base URL
+/// of a Kleah instance or another
+/// ActivityPub server from its host.
+/// Local development hosts are served
+/// over plain HTTP, all other hosts
+/// are expected to use HTTPS. The
+/// URL is returned as a string.
+pub fn base_url(
+    host: &str
+) -> String {
+    if host.starts_with("localhost") ||
+       host.starts_with("127.0.0.1")
+    {
+        format!("http://{}", host)
+    }
+    else {
+        format!("https://{}", host)
+    }
+}
+
+/// A function to split a WebFinger
+/// resource of the form `acct:username@host`
+/// into the username and the host. The
+/// "acct:" prefix and a leading "@" are
+/// optional. If the resource is of the
+/// wrong format, `None` is returned.
+pub fn parse_account_resource(
+    resource: &str
+) -> Option<(String, String)> {
+    let account: &str = resource
+        .strip_prefix("acct:")
+        .unwrap_or(resource);
+    let account: &str = account
+        .strip_prefix("@")
+        .unwrap_or(account);
+    let parts: Vec<&str> = account
+        .split("@")
+        .collect::<Vec<&str>>();
+    if parts.len() == 2 &&
+       !parts[0].is_empty() &&
+       !parts[1].is_empty()
+    {
+        Some((parts[0].to_string(), parts[1].to_lowercase()))
+    }
+    else {
+        None
+    }
+}
+
+/// A function to build the ActivityPub
+/// actor document for a local Kleah actor.
+/// All identifiers in the document are
+/// absolute URLs built from the host of the
+/// current instance and the public key is
+/// taken from the user's record.
+pub fn build_actor_document(
+    actor: &KleahActor,
+    user: &KleahUser,
+    host: &str
+) -> ApubActor {
+    let base: String = base_url(host);
+    let actor_url: String = local_actor_url(host, &actor.username);
+    let actor_type: String = match actor.actor_type.as_str() {
+        "Bot" => "Service".to_string(),
+        _ => "Person".to_string()
+    };
+    ApubActor{
+        context: vec![
+            ACTIVITYSTREAMS_CONTEXT.to_string(),
+            SECURITY_CONTEXT.to_string()
+        ],
+        id: actor_url.clone(),
+        actor_type,
+        preferred_username: actor.username.clone(),
+        name: actor.name.clone(),
+        summary: actor.description.clone(),
+        url: format!("{}/@{}", &base, &actor.username),
+        inbox: format!("{}{}", &base, &actor.inbox),
+        outbox: format!("{}{}", &base, &actor.outbox),
+        followers: format!("{}{}", &base, &actor.followers),
+        following: format!("{}{}", &base, &actor.following),
+        liked: format!("{}{}", &base, &actor.liked),
+        endpoints: Some(
+            ApubEndpoints{
+                shared_inbox: Some(format!("{}/inbox", &base))
+            }
+        ),
+        public_key: ApubPublicKey{
+            context: None,
+            id: format!("{}{}", &base, &actor.public_key),
+            owner: actor_url,
+            public_key_pem: user.public_key.clone()
+        }
+    }
+}
+
+/// A function to extract the host
+/// of the given URL. If the URL has
+/// a port, the port is included. If 
+/// the URL cannot be parsed, `None`
+/// is returned.
+pub fn url_host(
+    url: &str
+) -> Option<String> {
+    let parsed: Url = match Url::parse(url){
+        Ok(parsed) => parsed,
+        Err(_e) => return None
+    };
+    let host: &str = parsed.host_str()?;
+    match parsed.port(){
+        Some(port) => Some(format!("{}:{}", host, port)),
+        None => Some(host.to_string())
+    }
+}
+
+/// A function to build the URL of the
+/// actor document of a local Kleah actor
+/// from the host of the current instance
+/// and the actor's username. The URL is
+/// returned as a string.
+pub fn local_actor_url(
+    host: &str,
+    username: &str
+) -> String {
+    format!("{}/apub/{}", base_url(host), username)
+}
+
+/// A function to extract the username
+/// of a local Kleah actor from the URL
+/// of the actor's document. If the URL
+/// does not point to a local actor, `None`
+/// is returned.
+pub fn local_username_from_url(
+    url: &str,
+    host: &str
+) -> Option<String> {
+    let prefix: String = format!("{}/apub/", base_url(host));
+    let username: &str = url.strip_prefix(&prefix)?;
+    if username.is_empty() || username.contains("/") || username.contains("#") {
+        None
+    }
+    else {
+        Some(username.to_string())
+    }
+}
+
+/// A function to retrieve the URL of
+/// the document of any actor in the database.
+/// Actors from other servers have their ID
+/// stored, the URLs of local actors are built
+/// from their host and username.
+pub fn actor_url(
+    actor: &KleahActor
+) -> String {
+    match &actor.ap_id {
+        Some(ap_id) => ap_id.clone(),
+        None => local_actor_url(&actor.host, &actor.username)
+    }
+}
+
+/// A function to generate a new
+/// unique identifier from the given
+/// seed and the current time. The
+/// identifier is returned as a string.
+pub fn generate_id(
+    seed: &str
+) -> String {
+    hash_string(
+        &format!(
+            "{}{}",
+            seed,
+            Utc::now().timestamp_nanos_opt().unwrap_or_default()
+        )
+    ).to_lowercase()
+}
+
+/// A function to build the ActivityPub
+/// document describing a paginated 
+/// collection from the collection's URL
+/// and the total number of its items.
+/// The document is returned.
+pub fn build_collection(
+    url: &str,
+    total_items: i64
+) -> ApubCollection {
+    ApubCollection{
+        context: ACTIVITYSTREAMS_CONTEXT.to_string(),
+        id: url.to_string(),
+        collection_type: "OrderedCollection".to_string(),
+        total_items,
+        first: format!("{}?page=true", url),
+        last: format!("{}?page=true&min_id=0", url)
+    }
+}
+
+/// A function to build the URL of a
+/// page of a collection from the collection's
+/// URL and the cursor the page is requested
+/// with. The cursor is encoded for the query
+/// string. The URL is returned as a string.
+pub fn collection_page_url(
+    url: &str,
+    key: &str,
+    cursor: &str
+) -> String {
+    match Url::parse(url){
+        Ok(mut parsed) => {
+            parsed
+                .query_pairs_mut()
+                .append_pair("page", "true")
+                .append_pair(key, cursor);
+            parsed.to_string()
+        },
+        Err(_e) => format!("{}?page=true&{}={}", url, key, cursor)
+    }
+}
+
+/// A function to build the ActivityPub
+/// document for one page of a collection.
+/// The items are expected to be ordered 
+/// newest first, each with the cursor pointing
+/// at it, and the page is requested with the
+/// given query. Links to the neighbouring pages
+/// use the cursors of the first and last item.
+/// The document is returned.
+pub fn build_collection_page(
+    url: &str,
+    total_items: i64,
+    query: &CollectionQuery,
+    items: Vec<(Value, String)>
+) -> ApubCollectionPage {
+    let id: String = match (&query.max_id, &query.min_id) {
+        (_, Some(min_id)) => collection_page_url(url, "min_id", min_id),
+        (Some(max_id), None) => collection_page_url(url, "max_id", max_id),
+        (None, None) => format!("{}?page=true", url)
+    };
+    let next: Option<String> = match items.last() {
+        Some((_item, cursor)) if items.len() as i64 >= COLLECTION_PAGE_SIZE => Some(
+            collection_page_url(url, "max_id", cursor)
+        ),
+        _ => None
+    };
+    let prev: Option<String> = items.first().map(
+        |(_item, cursor)| collection_page_url(url, "min_id", cursor)
+    );
+    ApubCollectionPage{
+        context: ACTIVITYSTREAMS_CONTEXT.to_string(),
+        id,
+        page_type: "OrderedCollectionPage".to_string(),
+        part_of: url.to_string(),
+        total_items,
+        next,
+        prev,
+        ordered_items: items.into_iter().map(|(item, _cursor)| item).collect()
+    }
+}
+
+/// A function to convert a cursor from
+/// the query string of a page into the
+/// position of an item. Cursors are points
+/// in time in microseconds, followed by an
+/// underscore and the ID of the item. Cursors
+/// without an ID point at the start of their
+/// point in time. If the cursor is invalid,
+/// `None` is returned.
+pub fn parse_cursor(
+    cursor: Option<&str>
+) -> Option<KleahCursor> {
+    let (micros, id): (&str, &str) = match cursor?.split_once('_') {
+        Some((micros, id)) => (micros, id),
+        None => (cursor?, "")
+    };
+    Some(
+        KleahCursor{
+            created_at: DateTime::from_timestamp_micros(micros.parse::<i64>().ok()?)?,
+            id: id.to_string()
+        }
+    )
+}
+
+/// A function to build the cursor
+/// pointing at an item from its creation
+/// time and its ID. The cursor is returned
+/// as a string.
+pub fn format_cursor(
+    created_at: &DateTime<Utc>,
+    id: &str
+) -> String {
+    format!("{}_{}", created_at.timestamp_micros(), id)
+}
+
+/// A function to build the items of a
+/// page of a collection from the rows read
+/// from the database. Each item is returned
+/// together with the cursor pointing at it.
+pub fn collection_items(
+    items: Vec<CollectionItem>
+) -> Vec<(Value, String)> {
+    items
+        .into_iter()
+        .map(|item| (Value::String(item.item_id), format_cursor(&item.created_at, &item.cursor_id)))
+        .collect()
+}
+
+/// A function that attempts to serve a
+/// paginated ActivityPub collection of the
+/// local actor with the given username. The
+/// path of the collection is picked from the
+/// actor, its items are counted and a page of
+/// them is fetched with the given functions. If
+/// the query string requests a page, that page
+/// is served. If the operation is successful, 
+/// an HTTP response is returned containing the
+/// collection or page as a JSON-LD document. If
+/// the actor does not exist, an error is returned.
+pub async fn serve_collection(
+    username: &str,
+    query: &CollectionQuery,
+    collection_path: fn(&KleahActor) -> &str,
+    count_items: impl AsyncFnOnce(&KleahActor) -> Result<i64, KleahErr>,
+    fetch_page: impl AsyncFnOnce(&KleahActor, &str, Option<&KleahCursor>, Option<&KleahCursor>) -> Result<Vec<(Value, String)>, KleahErr>,
+    pool: &Pool<Postgres>
+) -> Result<HttpResponse, KleahErr> {
+    let instance: InstanceInformation = match get_instance_info(pool).await {
+        Ok(instance) => instance,
+        Err(e) => return Err::<HttpResponse, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    let actor: KleahActor = match get_actor_by_id(
+        username,
+        &instance.host,
+        pool
+    ).await {
+        Ok(actor) => actor,
+        Err(_e) => return Err::<HttpResponse, KleahErr>(
+            KleahErr::with_type(
+                "The requested actor does not exist.",
+                KleahErrType::NotFound
+            )
+        )
+    };
+    let base: String = base_url(&instance.host);
+    let url: String = format!("{}{}", base, collection_path(&actor));
+    let total_items: i64 = match count_items(&actor).await {
+        Ok(total_items) => total_items,
+        Err(e) => return Err::<HttpResponse, KleahErr>(
+            KleahErr::new(&e.to_string())
+        )
+    };
+    if !query.page.unwrap_or(false) && query.max_id.is_none() && query.min_id.is_none() {
+        return Ok(
+            HttpResponse::Ok()
+                .content_type(ACTIVITY_JSON)
+                .json(build_collection(&url, total_items))
+        );
+    }
+    let items: Vec<(Value, String)> = match fetch_page(
+        &actor,
+        &base,
+        parse_cursor(query.max_id.as_deref()).as_ref(),
+        parse_cursor(query.min_id.as_deref()).as_ref()
+    ).await {
+        Ok(items) => items,
+        Err(e) => return Err::<HttpResponse, KleahErr>(e)
+    };
+    Ok(
+        HttpResponse::Ok()
+            .content_type(ACTIVITY_JSON)
+            .json(build_collection_page(&url, total_items, query, items))
+    )
+}
+
+/// A function to build the URL of
+/// a note written by a local actor from 
+/// the host of the current instance and 
+/// the note's ID. The URL is returned as
+/// a string.
+pub fn local_note_url(
+    host: &str,
+    note_id: &str
+) -> String {
+    format!("{}/apub/notes/{}", base_url(host), note_id)
+}
+
+/// A function to retrieve the URL of
+/// any note in the database. Notes from
+/// other servers have their ID stored, the 
+/// URLs of local notes are built from their 
+/// author's host and their ID.
+pub fn note_url(
+    note: &KleahNote
+) -> String {
+    match &note.ap_id {
+        Some(ap_id) => ap_id.clone(),
+        None => local_note_url(&note.author_host, &note.note_id)
+    }
+}
+
+/// A function to escape the characters
+/// of the given text that have a special
+/// meaning in HTML. The escaped text is
+/// returned.
+pub fn escape_html(
+    text: &str
+) -> String {
+    text.replace("&", "&amp;")
+        .replace("<", "&lt;")
+        .replace(">", "&gt;")
+        .replace("\"", "&quot;")
+        .replace("'", "&#39;")
+}
+
+/// A function to retrieve the URL of
+/// the followers collection of any actor
+/// in the database. The collections of local
+/// actors are stored as paths and are made
+/// absolute with the actor's host.
+pub fn actor_followers_url(
+    actor: &KleahActor
+) -> String {
+    match &actor.ap_id {
+        Some(_ap_id) => actor.followers.clone(),
+        None => format!("{}{}", base_url(&actor.host), &actor.followers)
+    }
+}